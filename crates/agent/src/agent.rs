@@ -0,0 +1,223 @@
+//! The agent's connection to the daemon: enrolling, registering on an
+//! `AgentStream` and serving it until it drops, then reconnecting.
+
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use ctfjx_common::identity;
+use ctfjx_proto::grpc::{
+    AgentFrame, Heartbeat, Job, JobResult, RegisterAgentRequest, agent_frame::Payload,
+};
+use tokio::{sync::mpsc, time::MissedTickBehavior};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    Error, Executor,
+    config::Config,
+    credentials::{AgentClient, Credentials},
+    stats,
+};
+
+/// Frames buffered towards the daemon
+const OUTBOUND_FRAMES: usize = 32;
+
+pub struct Agent<E> {
+    config: Config,
+    executor: Arc<E>,
+    /// Jobs executing right now, across sessions
+    running: Arc<AtomicUsize>,
+}
+
+impl<E: Executor> Agent<E> {
+    pub fn new(config: Config, executor: E) -> Self {
+        Self {
+            config,
+            executor: Arc::new(executor),
+            running: Arc::default(),
+        }
+    }
+
+    /// Serves the daemon until `shutdown` completes, reconnecting with
+    /// backoff whenever it cannot be reached or the stream drops. Returns
+    /// early only on errors reconnecting does not fix
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+        tokio::pin!(shutdown);
+        let mut backoff = self.config.reconnect;
+        loop {
+            let served = tokio::select! {
+                served = self.connect(&mut backoff) => served,
+                _ = &mut shutdown => return Ok(()),
+            };
+            match served {
+                Ok(()) => tracing::warn!(retry_in = ?backoff, "daemon closed the stream"),
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(e) => tracing::warn!(retry_in = ?backoff, "{e}"),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = &mut shutdown => return Ok(()),
+            }
+            backoff = (backoff * 2).min(self.config.max_reconnect);
+        }
+    }
+
+    /// Enrolls unless the state file holds a credential already, then
+    /// serves one session. `backoff` is reset once registered
+    async fn connect(&self, backoff: &mut Duration) -> Result<(), Error> {
+        let credentials = self.credentials().await?;
+        let client = credentials.connect(&self.config.daemon).await?;
+        self.session(client, &credentials, backoff).await
+    }
+
+    async fn credentials(&self) -> Result<Credentials, Error> {
+        if let Some(credentials) = Credentials::load(&self.config.state)? {
+            return Ok(credentials);
+        }
+        let join_token = self
+            .config
+            .join_token
+            .as_deref()
+            .ok_or(Error::NotEnrolled)?;
+        let credentials = Credentials::enroll(&self.config.daemon, join_token).await?;
+        credentials.save(&self.config.state)?;
+        Ok(credentials)
+    }
+
+    async fn session(
+        &self,
+        mut client: AgentClient,
+        credentials: &Credentials,
+        backoff: &mut Duration,
+    ) -> Result<(), Error> {
+        let agent_id = &credentials.agent_id;
+        let nonce = rand::random::<[u8; 32]>().to_vec();
+        let mut capabilities = self.config.capabilities.clone();
+        capabilities.extend(self.executor.capabilities());
+        capabilities.sort();
+        capabilities.dedup();
+
+        let (tx, outbound) = mpsc::channel(OUTBOUND_FRAMES);
+        send(
+            &tx,
+            Payload::Register(RegisterAgentRequest {
+                agent_id: agent_id.clone(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                capabilities,
+                metadata: self.config.metadata(),
+                nonce: nonce.clone(),
+            }),
+        )
+        .await;
+        let mut inbound = client
+            .agent_stream(ReceiverStream::new(outbound))
+            .await
+            .map_err(Error::Stream)?
+            .into_inner();
+
+        let registered = match inbound.message().await.map_err(Error::Stream)? {
+            Some(AgentFrame {
+                payload: Some(Payload::Registered(registered)),
+            }) => registered,
+            Some(_) => return Err(Error::Protocol("expected `registered` first".to_string())),
+            None => {
+                return Err(Error::Protocol(
+                    "stream closed before registering".to_string(),
+                ));
+            }
+        };
+        if !registered.accepted {
+            return Err(Error::Rejected(registered.message));
+        }
+        identity::verify_proof(
+            &credentials.daemon_public_key()?,
+            agent_id,
+            &nonce,
+            &registered.daemon_signature,
+        )
+        .map_err(|_| Error::BadSignature)?;
+        tracing::info!(agent_id, daemon = %self.config.daemon, "registered");
+        *backoff = self.config.reconnect;
+
+        let mut heartbeat = tokio::time::interval(self.config.heartbeat);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                frame = inbound.message() => match frame.map_err(Error::Stream)? {
+                    Some(AgentFrame { payload: Some(Payload::Job(job)) }) => {
+                        self.execute(job, tx.clone());
+                    }
+                    Some(frame) => tracing::warn!(?frame, "ignoring unexpected frame"),
+                    None => return Ok(()),
+                },
+                _ = heartbeat.tick() => {
+                    let stats = stats::collect(self.running.load(Ordering::Relaxed));
+                    let beat = Payload::Heartbeat(Heartbeat {
+                        agent_id: agent_id.clone(),
+                        time: Some(SystemTime::now().into()),
+                        stats,
+                    });
+                    if !send(&tx, beat).await {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Runs `job` in the background, reporting its result on `tx`. A result
+    /// that finds the session gone is dropped, the daemon hands the job out
+    /// again
+    fn execute(&self, job: Job, tx: mpsc::Sender<AgentFrame>) {
+        let executor = self.executor.clone();
+        let running = self.running.clone();
+        running.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(async move {
+            let job_id = job.job_id.clone();
+            tracing::info!(
+                job_id,
+                r#type = job.r#type,
+                attempt = job.attempt,
+                "executing job"
+            );
+            let result = match executor.execute(job).await {
+                Ok(result_json) => JobResult {
+                    job_id: job_id.clone(),
+                    success: true,
+                    result_json,
+                    ..Default::default()
+                },
+                Err(e) => {
+                    tracing::warn!(job_id, "job failed: {e}");
+                    JobResult {
+                        job_id: job_id.clone(),
+                        message: e.to_string(),
+                        ..Default::default()
+                    }
+                }
+            };
+            running.fetch_sub(1, Ordering::Relaxed);
+            let result = JobResult {
+                finished_at: Some(SystemTime::now().into()),
+                ..result
+            };
+            if !send(&tx, Payload::JobResult(result)).await {
+                tracing::warn!(job_id, "stream dropped before the result was reported");
+            }
+        });
+    }
+}
+
+/// Queues `payload` towards the daemon, false once the session is gone
+async fn send(tx: &mpsc::Sender<AgentFrame>, payload: Payload) -> bool {
+    tx.send(AgentFrame {
+        payload: Some(payload),
+    })
+    .await
+    .is_ok()
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, path::PathBuf, str::FromStr, time::Duration};
+
+use ctfjx_common::env::{EnvError, ResolveEnv, lookup};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+pub const DEFAULT_DAEMON: &str = "http://127.0.0.1:50051";
+pub const DEFAULT_STATE: &str = "ctfjx-agent.json";
+pub const DEFAULT_WORK_DIR: &str = "ctfjx-instances";
+
+/// What instances are run as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RuntimeKind {
+    #[default]
+    Process,
+    /// Only available with the `container` feature
+    Container,
+}
+
+impl FromStr for RuntimeKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "process" => Ok(Self::Process),
+            "container" => Ok(Self::Container),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Validate)]
+pub struct Config {
+    /// `CTFJX_AGENT_DAEMON`, where ctfjxd listens
+    pub daemon: String,
+    /// `CTFJX_AGENT_STATE`, where the credential is kept once enrolled
+    pub state: PathBuf,
+    /// `CTFJX_AGENT_JOIN_TOKEN`, traded for a credential when there is none
+    /// in [`Config::state`] yet
+    pub join_token: Option<String>,
+    /// `CTFJX_AGENT_CAPABILITIES`, comma separated, registered on top of the
+    /// executor's own
+    pub capabilities: Vec<String>,
+    /// `CTFJX_AGENT_LABELS`, comma separated `key=value` pairs registered as
+    /// metadata, e.g. `region=eu-west`. `arch` and `os` are filled in
+    pub labels: HashMap<String, String>,
+    /// `CTFJX_AGENT_RUNTIME`, `process` or `container`
+    pub runtime: RuntimeKind,
+    /// `CTFJX_AGENT_WORK_DIR`, where instances keep their files
+    pub work_dir: PathBuf,
+    /// `CTFJX_AGENT_HEARTBEAT_SECONDS`, well below what the daemon waits
+    /// before it suspects the agent
+    pub heartbeat: Duration,
+    /// Wait before the first reconnect, doubling up to `max_reconnect`
+    pub reconnect: Duration,
+    /// `CTFJX_AGENT_MAX_RECONNECT_SECONDS`
+    pub max_reconnect: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            daemon: DEFAULT_DAEMON.to_string(),
+            state: PathBuf::from(DEFAULT_STATE),
+            join_token: None,
+            capabilities: Vec::new(),
+            labels: HashMap::new(),
+            runtime: RuntimeKind::default(),
+            work_dir: PathBuf::from(DEFAULT_WORK_DIR),
+            heartbeat: Duration::from_secs(10),
+            reconnect: Duration::from_secs(1),
+            max_reconnect: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ResolveEnv for Config {
+    fn populate(&mut self) -> Result<(), EnvError> {
+        if let Ok(daemon) = lookup("CTFJX_AGENT_DAEMON") {
+            self.daemon = daemon.trim().to_string();
+        }
+        if let Ok(state) = lookup("CTFJX_AGENT_STATE") {
+            self.state = PathBuf::from(state.trim());
+        }
+        if let Ok(token) = lookup("CTFJX_AGENT_JOIN_TOKEN") {
+            self.join_token = Some(token.trim().to_string());
+        }
+        if let Ok(capabilities) = lookup("CTFJX_AGENT_CAPABILITIES") {
+            self.capabilities = split_list(&capabilities).map(str::to_string).collect();
+        }
+        if let Ok(labels) = lookup("CTFJX_AGENT_LABELS") {
+            self.labels =
+                parse_labels(&labels).ok_or_else(|| invalid("CTFJX_AGENT_LABELS", "labels"))?;
+        }
+        if let Some(runtime) = lookup_parsed("CTFJX_AGENT_RUNTIME", "runtime")? {
+            self.runtime = runtime;
+        }
+        if let Ok(dir) = lookup("CTFJX_AGENT_WORK_DIR") {
+            self.work_dir = PathBuf::from(dir.trim());
+        }
+        if let Some(secs) = lookup_parsed::<u64>("CTFJX_AGENT_HEARTBEAT_SECONDS", "heartbeat")? {
+            self.heartbeat = Duration::from_secs(secs.max(1));
+        }
+        if let Some(secs) =
+            lookup_parsed::<u64>("CTFJX_AGENT_MAX_RECONNECT_SECONDS", "max_reconnect")?
+        {
+            self.max_reconnect = Duration::from_secs(secs.max(1));
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// The metadata the agent registers with
+    pub fn metadata(&self) -> HashMap<String, String> {
+        let mut metadata = HashMap::from([
+            ("arch".to_string(), std::env::consts::ARCH.to_string()),
+            ("os".to_string(), std::env::consts::OS.to_string()),
+        ]);
+        metadata.extend(self.labels.clone());
+        metadata
+    }
+}
+
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn parse_labels(labels: &str) -> Option<HashMap<String, String>> {
+    split_list(labels)
+        .map(|label| {
+            let (key, value) = label.split_once('=')?;
+            let key = key.trim();
+            (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn invalid(key: &str, field: &'static str) -> EnvError {
+    let mut errors = ValidationErrors::new();
+    errors.add(field, ValidationError::new("parse"));
+    EnvError::Invalid(key.to_string(), errors)
+}
+
+/// Parses `key` when set, keeping the default when it is missing
+fn lookup_parsed<T: FromStr>(key: &str, field: &'static str) -> Result<Option<T>, EnvError> {
+    let Ok(value) = lookup(key) else {
+        return Ok(None);
+    };
+    value
+        .trim()
+        .parse()
+        .map(Some)
+        .map_err(|_| invalid(key, field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_labels() {
+        let labels = parse_labels(" region=eu-west, gpu = yes ,").unwrap();
+        assert_eq!(labels["region"], "eu-west");
+        assert_eq!(labels["gpu"], "yes");
+        assert!(parse_labels("region").is_none());
+        assert!(parse_labels("=eu").is_none());
+
+        let config = Config {
+            labels: HashMap::from([("arch".to_string(), "riscv64".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(config.metadata()["arch"], "riscv64");
+        assert_eq!(config.metadata()["os"], std::env::consts::OS);
+    }
+}
@@ -0,0 +1,148 @@
+//! The agent's enrolled identity, and signing requests with it.
+
+use std::{fs, io, path::Path, sync::Arc, time::SystemTime};
+
+use ctfjx::client::{self, BearerToken};
+use ctfjx_common::identity::Keypair;
+use ctfjx_proto::grpc::{EnrollAgentRequest, service_ctfjx_client::ServiceCtfjxClient};
+use serde::{Deserialize, Serialize};
+use tonic::{
+    Request, Status,
+    service::{Interceptor, interceptor::InterceptedService},
+    transport::{Channel, Endpoint},
+};
+
+use crate::Error;
+
+pub type AgentClient = ServiceCtfjxClient<InterceptedService<Channel, AgentAuth>>;
+
+/// What enrolling left the agent with, kept in the state file
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub agent_id: String,
+    pub credential_id: String,
+    /// Hex seed of the agent's keypair
+    key: String,
+    /// Hex key the daemon signs registrations with
+    daemon_public_key: String,
+}
+
+impl Credentials {
+    pub fn keypair(&self) -> Result<Keypair, Error> {
+        let seed = hex::decode(&self.key)
+            .ok()
+            .and_then(|seed| <[u8; 32]>::try_from(seed).ok())
+            .ok_or(Error::CorruptState("key"))?;
+        Ok(Keypair::from_seed(seed))
+    }
+
+    pub fn daemon_public_key(&self) -> Result<Vec<u8>, Error> {
+        hex::decode(&self.daemon_public_key).map_err(|_| Error::CorruptState("daemon_public_key"))
+    }
+
+    /// The credentials in `path`, `None` before the agent enrolled
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        match fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data)
+                .map(Some)
+                .map_err(|_| Error::CorruptState("json")),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::State(e)),
+        }
+    }
+
+    /// Writes the credentials to `path`, readable by the owner only
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let data = serde_json::to_string_pretty(self).expect("credentials serialize");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        io::Write::write_all(&mut options.open(path)?, data.as_bytes())?;
+        Ok(())
+    }
+
+    /// Trades `join_token` for a credential bound to a fresh keypair
+    pub async fn enroll(daemon: &str, join_token: &str) -> Result<Self, Error> {
+        let key = Keypair::generate();
+        let mut client = client::connect(daemon, BearerToken::default()).await?;
+        let enrolled = client
+            .enroll_agent(EnrollAgentRequest {
+                join_token: join_token.to_string(),
+                public_key: key.public_key().to_vec(),
+            })
+            .await
+            .map_err(Error::Enroll)?
+            .into_inner();
+        let credential = enrolled
+            .credential
+            .ok_or_else(|| Error::Protocol("enrollment returned no credential".to_string()))?;
+        tracing::info!(agent_id = %credential.agent_id, "enrolled");
+        Ok(Self {
+            agent_id: credential.agent_id,
+            credential_id: credential.id,
+            key: hex::encode(key.seed()),
+            daemon_public_key: hex::encode(enrolled.daemon_public_key),
+        })
+    }
+
+    /// Connects to `daemon`, signing every request with the credential
+    pub async fn connect(&self, daemon: &str) -> Result<AgentClient, Error> {
+        let channel = Endpoint::from_shared(daemon.to_string())?.connect().await?;
+        let auth = AgentAuth {
+            key: Arc::new(self.keypair()?),
+            credential_id: self.credential_id.clone(),
+        };
+        Ok(ServiceCtfjxClient::with_interceptor(channel, auth))
+    }
+}
+
+/// Adds a freshly signed agent token to every request, the daemon only
+/// accepts ones issued within a few minutes
+#[derive(Clone)]
+pub struct AgentAuth {
+    key: Arc<Keypair>,
+    credential_id: String,
+}
+
+impl Interceptor for AgentAuth {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = self.key.agent_token(&self.credential_id, SystemTime::now());
+        let value = format!("Bearer {token}")
+            .parse()
+            .map_err(|_| Status::internal("agent token is not a valid header value"))?;
+        request.metadata_mut().insert("authorization", value);
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn survive_a_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert!(Credentials::load(&path).unwrap().is_none());
+
+        let key = Keypair::generate();
+        let credentials = Credentials {
+            agent_id: "agent-1".to_string(),
+            credential_id: "cred-1".to_string(),
+            key: hex::encode(key.seed()),
+            daemon_public_key: hex::encode([7; 32]),
+        };
+        credentials.save(&path).unwrap();
+        let loaded = Credentials::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.agent_id, "agent-1");
+        assert_eq!(loaded.keypair().unwrap().public_key(), key.public_key());
+        assert_eq!(loaded.daemon_public_key().unwrap(), [7; 32]);
+
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(
+            Credentials::load(&path),
+            Err(Error::CorruptState(_))
+        ));
+    }
+}
@@ -0,0 +1,50 @@
+use ctfjx::client::ClientError;
+use ctfjx_common::env::EnvError;
+use thiserror::Error;
+use tonic::{Code, Status};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(#[from] EnvError),
+    #[error("state file: {0}")]
+    State(#[from] std::io::Error),
+    #[error("state file has an invalid {0}")]
+    CorruptState(&'static str),
+    #[error("not enrolled yet and CTFJX_AGENT_JOIN_TOKEN is not set")]
+    NotEnrolled,
+    #[error("client: {0}")]
+    Client(#[from] ClientError),
+    #[error("transport: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("enrollment failed: {}", .0.message())]
+    Enroll(Status),
+    #[error("stream: {}", .0.message())]
+    Stream(Status),
+    #[error("registration rejected: {0}")]
+    Rejected(String),
+    #[error("the daemon's registration signature does not check out")]
+    BadSignature,
+    #[error("protocol: {0}")]
+    Protocol(String),
+}
+
+impl Error {
+    /// Whether reconnecting cannot help, e.g. because the credential was
+    /// revoked
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Self::Client(_) | Self::Transport(_) | Self::Protocol(_) => false,
+            Self::Enroll(status) | Self::Stream(status) => matches!(
+                status.code(),
+                Code::Unauthenticated
+                    | Code::PermissionDenied
+                    | Code::InvalidArgument
+                    | Code::FailedPrecondition
+                    | Code::NotFound
+                    | Code::Unimplemented
+            ),
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+//! What runs the jobs the daemon hands out.
+
+use std::future::Future;
+
+use ctfjx_proto::grpc::Job;
+use thiserror::Error;
+
+/// Why a job failed, reported back as the `JobResult` message
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub struct ExecError(pub String);
+
+impl ExecError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+/// Runs jobs. Jobs are delivered at least once, a job whose result did not
+/// reach the daemon is handed out again, so executing one has to be safe to
+/// repeat
+pub trait Executor: Send + Sync + 'static {
+    /// Capabilities registered on top of the configured ones, e.g. `docker`
+    fn capabilities(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Runs `job`, returning its result as JSON, empty for none
+    fn execute(&self, job: Job) -> impl Future<Output = Result<String, ExecError>> + Send;
+}
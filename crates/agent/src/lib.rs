@@ -0,0 +1,15 @@
+//! `ctfjx-agent` runs on the hosts instances are started on. It enrolls with
+//! ctfjxd once, then holds an `AgentStream` open, executing the jobs pushed
+//! to it and sending heartbeats, and reconnects whenever the stream drops.
+
+pub mod agent;
+pub mod config;
+pub mod credentials;
+pub mod error;
+pub mod executor;
+pub mod runtime;
+pub mod stats;
+
+pub use agent::Agent;
+pub use error::Error;
+pub use executor::{ExecError, Executor};
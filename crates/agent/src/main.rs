@@ -0,0 +1,66 @@
+use ctfjx_agent::{
+    Agent, Error,
+    config::{Config, RuntimeKind},
+    runtime::{ProcessRuntime, Runtime, RuntimeExecutor},
+};
+use ctfjx_common::env::ResolveEnv;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let config = match Config::resolve_and_validate() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("invalid config: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    let served = match config.runtime {
+        RuntimeKind::Process => {
+            let runtime = ProcessRuntime::new(&config.work_dir);
+            serve(config, runtime).await
+        }
+        #[cfg(feature = "container")]
+        RuntimeKind::Container => {
+            let runtime = ctfjx_agent::runtime::ContainerRuntime::new(&config.work_dir);
+            serve(config, runtime).await
+        }
+        #[cfg(not(feature = "container"))]
+        RuntimeKind::Container => {
+            tracing::error!("invalid config: this agent was built without the `container` feature");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = served {
+        tracing::error!("{e}");
+        std::process::exit(1);
+    }
+}
+
+async fn serve(config: Config, runtime: impl Runtime) -> Result<(), Error> {
+    Agent::new(config, RuntimeExecutor(runtime))
+        .run(shutdown_signal())
+        .await
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut term = signal(SignalKind::terminate()).expect("install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+
+    tracing::info!("shutting down");
+}
@@ -0,0 +1,264 @@
+//! Runs instances as containers through the `docker` CLI, or anything
+//! speaking its dialect such as `podman`.
+
+use std::{
+    path::PathBuf,
+    process::{Output, Stdio},
+};
+
+use tokio::process::Command;
+
+use ctfjx_proto::jobs::StartInstanceResult;
+
+use super::{ExecOutput, InstanceSpec, Runtime, RuntimeError, RuntimeStatus, tail_lines};
+
+/// Seconds a container asked to stop has before it is killed
+const STOP_GRACE_SECONDS: &str = "10";
+/// Labels containers so they can be told apart from the host's others
+const INSTANCE_LABEL: &str = "ctfjx.instance";
+
+pub struct ContainerRuntime {
+    cli: String,
+    /// Where flag files are kept to be mounted into containers
+    root: PathBuf,
+}
+
+impl ContainerRuntime {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            cli: "docker".to_string(),
+            root: root.into(),
+        }
+    }
+
+    /// Runs containers with `cli` instead of `docker`
+    pub fn with_cli(mut self, cli: impl Into<String>) -> Self {
+        self.cli = cli.into();
+        self
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<Output, RuntimeError> {
+        Ok(Command::new(&self.cli)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .await?)
+    }
+
+    /// Runs `args`, their stdout when they succeed
+    async fn cli(&self, instance_id: &str, args: &[&str]) -> Result<String, RuntimeError> {
+        let output = self.run(args).await?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if missing(&stderr) {
+            return Err(RuntimeError::NotFound(instance_id.to_string()));
+        }
+        Err(RuntimeError::Failed(format!(
+            "{} {} failed: {}",
+            self.cli,
+            args.first().unwrap_or(&""),
+            stderr.trim()
+        )))
+    }
+
+    /// The host port `port` of the container is published on
+    async fn published(&self, instance_id: &str, port: i32) -> Result<u16, RuntimeError> {
+        if port == 0 {
+            return Ok(0);
+        }
+        let name = name(instance_id);
+        let mapped = self
+            .cli(instance_id, &["port", &name, &format!("{port}/tcp")])
+            .await?;
+        mapped
+            .lines()
+            .find_map(|l| l.rsplit_once(':')?.1.trim().parse().ok())
+            .ok_or_else(|| RuntimeError::Failed(format!("port {port} is not published")))
+    }
+}
+
+impl Runtime for ContainerRuntime {
+    fn capabilities(&self) -> Vec<String> {
+        vec!["docker".to_string()]
+    }
+
+    async fn start(&self, spec: InstanceSpec) -> Result<StartInstanceResult, RuntimeError> {
+        let id = &spec.instance_id;
+        let runtime = &spec.runtime;
+        if runtime.image.is_empty() {
+            return Err(RuntimeError::Invalid(
+                "containers need an image".to_string(),
+            ));
+        }
+        // the CLI would take it for one of its flags, e.g. `--privileged`
+        if runtime.image.starts_with('-') {
+            return Err(RuntimeError::Invalid(format!(
+                "image `{}` is not an image name",
+                runtime.image
+            )));
+        }
+        let container = name(id);
+        match self.status(id).await {
+            Ok(RuntimeStatus::Running) => {
+                let port = self.published(id, runtime.port).await?;
+                return Ok(StartInstanceResult { port });
+            }
+            // an exited container of an earlier attempt is in the way
+            Ok(RuntimeStatus::Exited(_)) => {
+                self.cli(id, &["rm", "-f", &container]).await?;
+            }
+            Err(RuntimeError::NotFound(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        let label = format!("{INSTANCE_LABEL}={id}");
+        let mut args = vec!["run", "-d", "--name", &container, "--label", &label];
+        let mut env: Vec<String> = spec.env.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        env.push(format!("PORT={}", runtime.port));
+        let publish = runtime.port.to_string();
+        if runtime.port != 0 {
+            args.extend(["-p", &publish]);
+        }
+        let mount;
+        if let Some(flag) = &spec.flag {
+            let dir = self.root.join(id);
+            tokio::fs::create_dir_all(&dir).await?;
+            let file = dir.join("flag.txt");
+            tokio::fs::write(&file, flag).await?;
+            env.push(format!("FLAG={flag}"));
+            mount = format!("{}:/flag.txt:ro", std::path::absolute(&file)?.display());
+            args.extend(["-v", &mount]);
+        }
+        for var in &env {
+            args.extend(["-e", var]);
+        }
+        args.push(&runtime.image);
+        args.extend(runtime.command.iter().map(String::as_str));
+        self.cli(id, &args).await?;
+        tracing::info!(instance_id = id, image = runtime.image, "container started");
+
+        let port = self.published(id, runtime.port).await?;
+        Ok(StartInstanceResult { port })
+    }
+
+    async fn stop(&self, instance_id: &str, force: bool) -> Result<(), RuntimeError> {
+        let name = name(instance_id);
+        if force {
+            self.cli(instance_id, &["rm", "-f", &name]).await?;
+        } else {
+            self.cli(instance_id, &["stop", "-t", STOP_GRACE_SECONDS, &name])
+                .await?;
+            self.cli(instance_id, &["rm", &name]).await?;
+        }
+        match tokio::fs::remove_dir_all(self.root.join(instance_id)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        tracing::info!(instance_id, "container stopped");
+        Ok(())
+    }
+
+    async fn status(&self, instance_id: &str) -> Result<RuntimeStatus, RuntimeError> {
+        let state = self
+            .cli(
+                instance_id,
+                &[
+                    "inspect",
+                    "-f",
+                    "{{.State.Running}} {{.State.ExitCode}}",
+                    &name(instance_id),
+                ],
+            )
+            .await?;
+        Ok(match state.trim().split_once(' ') {
+            Some(("true", _)) => RuntimeStatus::Running,
+            Some((_, code)) => RuntimeStatus::Exited(code.parse().ok()),
+            None => RuntimeStatus::Exited(None),
+        })
+    }
+
+    async fn logs(&self, instance_id: &str, tail: usize) -> Result<String, RuntimeError> {
+        let output = self.run(&["logs", &name(instance_id)]).await?;
+        if !output.status.success() {
+            // surfaces the same errors as the other commands
+            self.cli(instance_id, &["inspect", &name(instance_id)])
+                .await?;
+        }
+        // containers print to both, interleaving is lost either way
+        let mut logs = String::from_utf8_lossy(&output.stdout).into_owned();
+        logs.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok(tail_lines(&logs, tail))
+    }
+
+    async fn exec(
+        &self,
+        instance_id: &str,
+        command: &[String],
+    ) -> Result<ExecOutput, RuntimeError> {
+        if command.is_empty() {
+            return Err(RuntimeError::Invalid("exec needs a command".to_string()));
+        }
+        let name = name(instance_id);
+        let mut args = vec!["exec", name.as_str()];
+        args.extend(command.iter().map(String::as_str));
+        let output = self.run(&args).await?;
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        if !output.status.success() && missing(&stderr) {
+            return Err(RuntimeError::NotFound(instance_id.to_string()));
+        }
+        Ok(ExecOutput {
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr,
+        })
+    }
+}
+
+fn name(instance_id: &str) -> String {
+    format!("ctfjx-{instance_id}")
+}
+
+/// Whether the CLI failed because the container does not exist
+fn missing(stderr: &str) -> bool {
+    stderr.to_lowercase().contains("no such container")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ctfjx_proto::grpc::ChallengeRuntime;
+
+    use super::*;
+
+    fn spec(image: &str) -> InstanceSpec {
+        InstanceSpec {
+            instance_id: "i1".to_string(),
+            runtime: ChallengeRuntime {
+                image: image.to_string(),
+                command: vec!["alpine".to_string()],
+                ..Default::default()
+            },
+            env: HashMap::new(),
+            flag: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_images_read_as_flags() {
+        let dir = tempfile::tempdir().unwrap();
+        // never reached, images are checked before the CLI runs
+        let runtime = ContainerRuntime::new(dir.path()).with_cli("/nonexistent/docker");
+        for image in ["", "--privileged", "-v=/:/host"] {
+            assert!(
+                matches!(
+                    runtime.start(spec(image)).await,
+                    Err(RuntimeError::Invalid(_))
+                ),
+                "{image}"
+            );
+        }
+    }
+}
@@ -0,0 +1,199 @@
+//! What instances of challenges run on. [`RuntimeExecutor`] turns the
+//! instance jobs the daemon hands out into calls on a [`Runtime`].
+
+use std::{collections::HashMap, future::Future};
+
+use ctfjx_proto::{
+    grpc::{ChallengeRuntime, Job},
+    jobs::{
+        JOB_INSTANCE_START, JOB_INSTANCE_STOP, StartInstancePayload, StartInstanceResult,
+        StopInstancePayload,
+    },
+};
+use thiserror::Error;
+
+use crate::{ExecError, Executor};
+
+#[cfg(feature = "container")]
+mod container;
+mod process;
+
+#[cfg(feature = "container")]
+pub use container::ContainerRuntime;
+pub use process::ProcessRuntime;
+
+#[derive(Debug, Error)]
+pub enum RuntimeError {
+    #[error("instance `{0}` is not running here")]
+    NotFound(String),
+    #[error("invalid runtime: {0}")]
+    Invalid(String),
+    #[error("{0}")]
+    Failed(String),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// An instance to start
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstanceSpec {
+    pub instance_id: String,
+    pub runtime: ChallengeRuntime,
+    /// The challenge's env with the instance's overrides applied
+    pub env: HashMap<String, String>,
+    pub flag: Option<String>,
+}
+
+impl From<StartInstancePayload> for InstanceSpec {
+    fn from(payload: StartInstancePayload) -> Self {
+        let mut env = payload.runtime.env.clone();
+        env.extend(payload.overrides);
+        Self {
+            instance_id: payload.instance_id,
+            runtime: payload.runtime,
+            env,
+            flag: payload.flag,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeStatus {
+    Running,
+    /// With the exit code, `None` when it was killed by a signal
+    Exited(Option<i32>),
+}
+
+/// What running a command inside an instance printed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecOutput {
+    /// `None` when the command was killed by a signal
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs instances. Instance jobs are delivered at least once, so starting an
+/// instance that runs already hands it back as is, and stopping one that is
+/// gone is left to the caller to shrug off
+pub trait Runtime: Send + Sync + 'static {
+    /// What the agent registers to be placed challenges needing this runtime
+    fn capabilities(&self) -> Vec<String>;
+
+    fn start(
+        &self,
+        spec: InstanceSpec,
+    ) -> impl Future<Output = Result<StartInstanceResult, RuntimeError>> + Send;
+
+    /// Stops and removes the instance, asking it to exit first unless
+    /// `force`d
+    fn stop(
+        &self,
+        instance_id: &str,
+        force: bool,
+    ) -> impl Future<Output = Result<(), RuntimeError>> + Send;
+
+    fn status(
+        &self,
+        instance_id: &str,
+    ) -> impl Future<Output = Result<RuntimeStatus, RuntimeError>> + Send;
+
+    /// The last `tail` lines the instance printed, every line when 0
+    fn logs(
+        &self,
+        instance_id: &str,
+        tail: usize,
+    ) -> impl Future<Output = Result<String, RuntimeError>> + Send;
+
+    /// Runs `command` next to the instance, in its directory and env
+    fn exec(
+        &self,
+        instance_id: &str,
+        command: &[String],
+    ) -> impl Future<Output = Result<ExecOutput, RuntimeError>> + Send;
+}
+
+/// Executes instance jobs on a [`Runtime`]
+#[derive(Debug, Default)]
+pub struct RuntimeExecutor<R>(pub R);
+
+impl<R: Runtime> Executor for RuntimeExecutor<R> {
+    fn capabilities(&self) -> Vec<String> {
+        self.0.capabilities()
+    }
+
+    async fn execute(&self, job: Job) -> Result<String, ExecError> {
+        match job.r#type.as_str() {
+            JOB_INSTANCE_START => {
+                let payload: StartInstancePayload = decode(&job)?;
+                let started = self.0.start(payload.into()).await.map_err(failed)?;
+                Ok(serde_json::to_string(&started).expect("results serialize"))
+            }
+            JOB_INSTANCE_STOP => {
+                let payload: StopInstancePayload = decode(&job)?;
+                match self.0.stop(&payload.instance_id, payload.force).await {
+                    Ok(()) | Err(RuntimeError::NotFound(_)) => Ok(String::new()),
+                    Err(e) => Err(failed(e)),
+                }
+            }
+            other => Err(ExecError::new(format!(
+                "job type `{other}` is not supported by this agent"
+            ))),
+        }
+    }
+}
+
+fn decode<T: serde::de::DeserializeOwned>(job: &Job) -> Result<T, ExecError> {
+    serde_json::from_str(&job.payload_json)
+        .map_err(|e| ExecError::new(format!("invalid `{}` payload: {e}", job.r#type)))
+}
+
+fn failed(e: RuntimeError) -> ExecError {
+    ExecError::new(e.to_string())
+}
+
+/// The last `tail` lines of `output`, all of it when 0
+fn tail_lines(output: &str, tail: usize) -> String {
+    if tail == 0 {
+        return output.to_string();
+    }
+    let lines: Vec<&str> = output.lines().collect();
+    let mut tailed = lines[lines.len().saturating_sub(tail)..].join("\n");
+    if !tailed.is_empty() {
+        tailed.push('\n');
+    }
+    tailed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_win_over_the_challenge_env() {
+        let spec = InstanceSpec::from(StartInstancePayload {
+            instance_id: "i1".to_string(),
+            challenge_id: "c1".to_string(),
+            team_id: "t1".to_string(),
+            overrides: HashMap::from([("LEVEL".to_string(), "hard".to_string())]),
+            flag: None,
+            runtime: ChallengeRuntime {
+                env: HashMap::from([
+                    ("LEVEL".to_string(), "easy".to_string()),
+                    ("MODE".to_string(), "tcp".to_string()),
+                ]),
+                ..Default::default()
+            },
+        });
+        assert_eq!(spec.env["LEVEL"], "hard");
+        assert_eq!(spec.env["MODE"], "tcp");
+    }
+
+    #[test]
+    fn tails_lines() {
+        assert_eq!(tail_lines("a\nb\nc\n", 2), "b\nc\n");
+        assert_eq!(tail_lines("a\nb\nc\n", 0), "a\nb\nc\n");
+        assert_eq!(tail_lines("a\n", 5), "a\n");
+        assert_eq!(tail_lines("", 5), "");
+    }
+}
@@ -0,0 +1,217 @@
+//! Runs instances as plain processes, each in a directory of its own. Needs
+//! nothing but the challenge's program on the host, which makes it the
+//! backend of choice for tests and trusted challenges.
+
+use std::{
+    collections::HashMap,
+    fs,
+    net::{Ipv4Addr, TcpListener},
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::process::{Child, Command};
+
+use ctfjx_proto::jobs::StartInstanceResult;
+
+use super::{ExecOutput, InstanceSpec, Runtime, RuntimeError, RuntimeStatus, tail_lines};
+
+/// What instances print, in their directory
+const OUTPUT_FILE: &str = "output.log";
+const FLAG_FILE: &str = "flag.txt";
+/// How long a process asked to exit has before it is killed
+const STOP_GRACE: Duration = Duration::from_secs(10);
+
+struct Process {
+    child: Child,
+    dir: PathBuf,
+    env: HashMap<String, String>,
+    port: u16,
+}
+
+pub struct ProcessRuntime {
+    root: PathBuf,
+    processes: Mutex<HashMap<String, Arc<tokio::sync::Mutex<Process>>>>,
+}
+
+impl ProcessRuntime {
+    /// Keeps instance directories under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            processes: Mutex::default(),
+        }
+    }
+
+    fn process(&self, instance_id: &str) -> Result<Arc<tokio::sync::Mutex<Process>>, RuntimeError> {
+        self.processes
+            .lock()
+            .get(instance_id)
+            .cloned()
+            .ok_or_else(|| RuntimeError::NotFound(instance_id.to_string()))
+    }
+
+    fn spawn(&self, spec: &InstanceSpec) -> Result<Process, RuntimeError> {
+        let Some((program, args)) = spec.runtime.command.split_first() else {
+            return Err(RuntimeError::Invalid(
+                "processes need a command".to_string(),
+            ));
+        };
+        // ids come from the daemon, yet they name a directory here
+        if spec.instance_id.is_empty()
+            || !spec
+                .instance_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(RuntimeError::Invalid(format!(
+                "instance id `{}` cannot name a directory",
+                spec.instance_id
+            )));
+        }
+
+        let dir = self.root.join(&spec.instance_id);
+        fs::create_dir_all(&dir)?;
+        let mut env = spec.env.clone();
+        let port = free_port()?;
+        env.insert("PORT".to_string(), port.to_string());
+        if let Some(flag) = &spec.flag {
+            fs::write(dir.join(FLAG_FILE), flag)?;
+            env.insert("FLAG".to_string(), flag.clone());
+        }
+
+        let output = fs::File::create(dir.join(OUTPUT_FILE))?;
+        let child = Command::new(program)
+            .args(args)
+            .current_dir(&dir)
+            .envs(&env)
+            .stdin(Stdio::null())
+            .stdout(output.try_clone()?)
+            .stderr(output)
+            .kill_on_drop(true)
+            .spawn()?;
+        tracing::info!(
+            instance_id = spec.instance_id,
+            pid = child.id(),
+            port,
+            "process started"
+        );
+        Ok(Process {
+            child,
+            dir,
+            env,
+            port,
+        })
+    }
+}
+
+impl Runtime for ProcessRuntime {
+    fn capabilities(&self) -> Vec<String> {
+        vec!["process".to_string()]
+    }
+
+    async fn start(&self, spec: InstanceSpec) -> Result<StartInstanceResult, RuntimeError> {
+        if let Ok(process) = self.process(&spec.instance_id) {
+            let mut process = process.lock().await;
+            if process.child.try_wait()?.is_none() {
+                return Ok(StartInstanceResult { port: process.port });
+            }
+        }
+        let process = self.spawn(&spec)?;
+        let port = process.port;
+        self.processes
+            .lock()
+            .insert(spec.instance_id, Arc::new(tokio::sync::Mutex::new(process)));
+        Ok(StartInstanceResult { port })
+    }
+
+    async fn stop(&self, instance_id: &str, force: bool) -> Result<(), RuntimeError> {
+        let process = self
+            .processes
+            .lock()
+            .remove(instance_id)
+            .ok_or_else(|| RuntimeError::NotFound(instance_id.to_string()))?;
+        let mut process = process.lock().await;
+        if !force && terminate(&process.child) {
+            let exited = tokio::time::timeout(STOP_GRACE, process.child.wait()).await;
+            if exited.is_err() {
+                tracing::warn!(instance_id, "process ignored SIGTERM, killing it");
+            }
+        }
+        // a no-op for processes that exited already
+        process.child.kill().await?;
+        remove_dir(&process.dir)?;
+        tracing::info!(instance_id, "process stopped");
+        Ok(())
+    }
+
+    async fn status(&self, instance_id: &str) -> Result<RuntimeStatus, RuntimeError> {
+        let process = self.process(instance_id)?;
+        let mut process = process.lock().await;
+        Ok(match process.child.try_wait()? {
+            None => RuntimeStatus::Running,
+            Some(status) => RuntimeStatus::Exited(status.code()),
+        })
+    }
+
+    async fn logs(&self, instance_id: &str, tail: usize) -> Result<String, RuntimeError> {
+        let dir = self.process(instance_id)?.lock().await.dir.clone();
+        let output = tokio::fs::read(dir.join(OUTPUT_FILE)).await?;
+        Ok(tail_lines(&String::from_utf8_lossy(&output), tail))
+    }
+
+    async fn exec(
+        &self,
+        instance_id: &str,
+        command: &[String],
+    ) -> Result<ExecOutput, RuntimeError> {
+        let Some((program, args)) = command.split_first() else {
+            return Err(RuntimeError::Invalid("exec needs a command".to_string()));
+        };
+        let (dir, env) = {
+            let process = self.process(instance_id)?;
+            let process = process.lock().await;
+            (process.dir.clone(), process.env.clone())
+        };
+        let output = Command::new(program)
+            .args(args)
+            .current_dir(dir)
+            .envs(env)
+            .stdin(Stdio::null())
+            .output()
+            .await?;
+        Ok(ExecOutput {
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// A port nothing listens on right now
+fn free_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?
+        .local_addr()?
+        .port())
+}
+
+/// Asks `child` to exit, false when it cannot be asked
+fn terminate(child: &Child) -> bool {
+    #[cfg(unix)]
+    if let Some(pid) = child.id().and_then(|pid| i32::try_from(pid).ok()) {
+        // SAFETY: signals the child we spawned, which is not reaped yet
+        return unsafe { libc::kill(pid, libc::SIGTERM) } == 0;
+    }
+    let _ = child;
+    false
+}
+
+fn remove_dir(dir: &Path) -> std::io::Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,63 @@
+//! Host stats sent along with heartbeats.
+
+use std::{collections::HashMap, fs};
+
+use ctfjx_proto::jobs::{STAT_CPUS, STAT_JOBS, STAT_LOAD, STAT_MEM_AVAILABLE};
+
+/// Stats of this host, `running` being the jobs currently executing
+pub fn collect(running: usize) -> HashMap<String, String> {
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut stats = HashMap::from([
+        (STAT_CPUS.to_string(), cpus.to_string()),
+        (STAT_JOBS.to_string(), running.to_string()),
+    ]);
+    // the load average per cpu, so hosts of different sizes compare
+    if let Some(load) = fs::read_to_string("/proc/loadavg")
+        .ok()
+        .as_deref()
+        .and_then(parse_loadavg)
+    {
+        stats.insert(STAT_LOAD.to_string(), format!("{:.2}", load / cpus as f64));
+    }
+    if let Some(bytes) = fs::read_to_string("/proc/meminfo")
+        .ok()
+        .as_deref()
+        .and_then(parse_mem_available)
+    {
+        stats.insert(STAT_MEM_AVAILABLE.to_string(), bytes.to_string());
+    }
+    stats
+}
+
+/// The 1 minute load average of `/proc/loadavg`
+fn parse_loadavg(loadavg: &str) -> Option<f64> {
+    loadavg.split_whitespace().next()?.parse().ok()
+}
+
+/// `MemAvailable` of `/proc/meminfo`, in bytes
+fn parse_mem_available(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|l| l.strip_prefix("MemAvailable:"))?;
+    let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_files() {
+        assert_eq!(parse_loadavg("0.52 0.58 0.59 1/467 3081\n"), Some(0.52));
+        assert_eq!(parse_loadavg(""), None);
+        assert_eq!(
+            parse_mem_available("MemTotal: 100 kB\nMemAvailable:    2048 kB\n"),
+            Some(2048 * 1024)
+        );
+        assert_eq!(parse_mem_available("MemTotal: 100 kB\n"), None);
+
+        let stats = collect(3);
+        assert_eq!(stats[STAT_JOBS], "3");
+    }
+}
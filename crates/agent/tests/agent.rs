@@ -0,0 +1,147 @@
+mod util;
+
+use std::time::Duration;
+
+use ctfjx_agent::{Agent, Error, ExecError, Executor, credentials::Credentials};
+use ctfjx_proto::{
+    grpc::{Job, instance_status::State},
+    jobs::STAT_JOBS,
+};
+use ctfjx_storage::{StorageError, Store};
+use tokio::{net::TcpListener, sync::mpsc, sync::oneshot, task::JoinHandle};
+
+/// Hands every job it executes to the test, failing first attempts
+struct Recorder {
+    jobs: mpsc::UnboundedSender<Job>,
+}
+
+impl Executor for Recorder {
+    fn capabilities(&self) -> Vec<String> {
+        vec!["recorder".to_string()]
+    }
+
+    async fn execute(&self, job: Job) -> Result<String, ExecError> {
+        let attempt = job.attempt;
+        self.jobs.send(job).unwrap();
+        if attempt == 1 {
+            return Err(ExecError::new("first attempts fail"));
+        }
+        Ok(r#"{"port":1337}"#.to_string())
+    }
+}
+
+/// Runs an agent with a [`Recorder`] until the returned sender is dropped
+fn run(
+    config: ctfjx_agent::config::Config,
+) -> (
+    mpsc::UnboundedReceiver<Job>,
+    oneshot::Sender<()>,
+    JoinHandle<Result<(), Error>>,
+) {
+    let (jobs, rx) = mpsc::unbounded_channel();
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let agent = Agent::new(config, Recorder { jobs });
+    let handle = tokio::spawn(agent.run(async {
+        let _ = stop.await;
+    }));
+    (rx, shutdown, handle)
+}
+
+async fn next_job(jobs: &mut mpsc::UnboundedReceiver<Job>) -> Job {
+    tokio::time::timeout(Duration::from_secs(5), jobs.recv())
+        .await
+        .expect("no job in time")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn enrolls_and_executes_pushed_jobs() {
+    let backing = util::Backing::default();
+    let mut daemon = util::spawn(&backing).await;
+    let dir = tempfile::tempdir().unwrap();
+    let config = ctfjx_agent::config::Config {
+        join_token: Some(util::join_token(&mut daemon, "agent-1").await),
+        capabilities: vec!["kvm".to_string()],
+        ..util::config(&daemon.url(), dir.path())
+    };
+    let (mut jobs, shutdown, handle) = run(config);
+
+    let store = backing.store.clone();
+    util::eventually(|| store.get_agent("agent-1").is_ok_and(|a| a.online)).await;
+    let agent = backing.store.get_agent("agent-1").unwrap();
+    assert_eq!(agent.capabilities, ["kvm", "recorder"]);
+    assert_eq!(agent.metadata["os"], std::env::consts::OS);
+    assert!(
+        Credentials::load(&dir.path().join("agent.json"))
+            .unwrap()
+            .is_some()
+    );
+
+    let instance_id = util::start_instance(&mut daemon, None).await;
+    let job = next_job(&mut jobs).await;
+    assert_eq!(job.attempt, 1);
+    // the failure was reported, so the job comes back
+    let retried = next_job(&mut jobs).await;
+    assert_eq!(
+        (retried.job_id.as_str(), retried.attempt),
+        (job.job_id.as_str(), 2)
+    );
+    util::eventually(|| matches!(store.get_job(&job.job_id), Err(StorageError::NotFound(..))))
+        .await;
+
+    // heartbeats carry host stats and speak for the agent's instances
+    util::eventually(|| {
+        store
+            .get_agent("agent-1")
+            .is_ok_and(|a| a.stats.contains_key(STAT_JOBS))
+    })
+    .await;
+    let status = util::wait_for_state(&mut daemon, &instance_id, State::Running).await;
+    assert_eq!(status.port, 1337);
+    assert!(status.last_heartbeat.is_some());
+
+    drop(shutdown);
+    handle.await.unwrap().unwrap();
+    util::eventually(|| store.get_agent("agent-1").is_ok_and(|a| !a.online)).await;
+}
+
+#[tokio::test]
+async fn reconnects_with_saved_credentials_once_the_daemon_is_up() {
+    let backing = util::Backing::default();
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let mut daemon = util::spawn(&backing).await;
+        let token = util::join_token(&mut daemon, "agent-1").await;
+        Credentials::enroll(&daemon.url(), &token)
+            .await
+            .unwrap()
+            .save(&dir.path().join("agent.json"))
+            .unwrap();
+    }
+
+    // nothing listens on the port until the agent tried a few times
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let (_jobs, shutdown, handle) = run(util::config(&format!("http://{addr}"), dir.path()));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!handle.is_finished());
+    assert!(!backing.store.get_agent("agent-1").is_ok_and(|a| a.online));
+
+    let _daemon = util::serve(TcpListener::bind(addr).await.unwrap(), &backing).await;
+    let store = backing.store.clone();
+    util::eventually(|| store.get_agent("agent-1").is_ok_and(|a| a.online)).await;
+
+    drop(shutdown);
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn gives_up_without_credentials_or_join_token() {
+    let dir = tempfile::tempdir().unwrap();
+    let (_jobs, _shutdown, handle) = run(util::config("http://127.0.0.1:1", dir.path()));
+    let err = handle.await.unwrap().unwrap_err();
+    assert!(matches!(err, Error::NotEnrolled), "{err}");
+}
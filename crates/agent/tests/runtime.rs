@@ -0,0 +1,216 @@
+mod util;
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use ctfjx_agent::{
+    Agent,
+    runtime::{
+        InstanceSpec, ProcessRuntime, Runtime, RuntimeError, RuntimeExecutor, RuntimeStatus,
+    },
+};
+use ctfjx_proto::grpc::{ChallengeRuntime, StopInstanceRequest, instance_status::State};
+use tokio::sync::oneshot;
+
+fn sh(script: &str) -> ChallengeRuntime {
+    ChallengeRuntime {
+        command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+        ..Default::default()
+    }
+}
+
+fn spec(instance_id: &str, runtime: ChallengeRuntime) -> InstanceSpec {
+    InstanceSpec {
+        instance_id: instance_id.to_string(),
+        env: runtime.env.clone(),
+        runtime,
+        flag: None,
+    }
+}
+
+/// Waits for `runtime` to have printed `lines` lines for `instance_id`
+async fn logged(runtime: &ProcessRuntime, instance_id: &str, lines: usize) -> String {
+    for _ in 0..250 {
+        let logs = runtime.logs(instance_id, 0).await.unwrap();
+        if logs.lines().count() >= lines {
+            return logs;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("instance never printed {lines} lines");
+}
+
+#[tokio::test]
+async fn processes_get_a_dir_env_and_port() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime = ProcessRuntime::new(dir.path());
+    let spec = InstanceSpec {
+        env: HashMap::from([("MODE".to_string(), "tcp".to_string())]),
+        flag: Some("flag{local}".to_string()),
+        ..spec(
+            "i1",
+            sh(r#"echo "listening on $PORT"; echo "$MODE"; exec sleep 30"#),
+        )
+    };
+
+    let started = runtime.start(spec.clone()).await.unwrap();
+    assert_ne!(started.port, 0);
+    // starting a running instance again hands it back
+    assert_eq!(runtime.start(spec).await.unwrap(), started);
+    assert_eq!(runtime.status("i1").await.unwrap(), RuntimeStatus::Running);
+
+    let logs = logged(&runtime, "i1", 2).await;
+    assert_eq!(logs, format!("listening on {}\ntcp\n", started.port));
+    assert_eq!(runtime.logs("i1", 1).await.unwrap(), "tcp\n");
+
+    let cat = runtime
+        .exec("i1", &["cat".to_string(), "flag.txt".to_string()])
+        .await
+        .unwrap();
+    assert_eq!((cat.code, cat.stdout.as_str()), (Some(0), "flag{local}"));
+    let env = runtime
+        .exec("i1", &sh("echo $FLAG $MODE; exit 4").command)
+        .await
+        .unwrap();
+    assert_eq!(
+        (env.code, env.stdout.as_str()),
+        (Some(4), "flag{local} tcp\n")
+    );
+
+    // asked to exit, sleep does well before the grace period is up
+    tokio::time::timeout(Duration::from_secs(5), runtime.stop("i1", false))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!dir.path().join("i1").exists());
+    assert!(matches!(
+        runtime.status("i1").await,
+        Err(RuntimeError::NotFound(_))
+    ));
+    assert!(matches!(
+        runtime.stop("i1", true).await,
+        Err(RuntimeError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn exited_processes_are_started_again() {
+    let dir = tempfile::tempdir().unwrap();
+    let runtime = ProcessRuntime::new(dir.path());
+    let flaky = spec(
+        "i1",
+        sh("test -f restarted && exec sleep 30; touch restarted; exit 3"),
+    );
+
+    runtime.start(flaky.clone()).await.unwrap();
+    util::eventually(|| dir.path().join("i1/restarted").exists()).await;
+    let mut status = runtime.status("i1").await.unwrap();
+    for _ in 0..250 {
+        if status != RuntimeStatus::Running {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        status = runtime.status("i1").await.unwrap();
+    }
+    assert_eq!(status, RuntimeStatus::Exited(Some(3)));
+
+    runtime.start(flaky).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(runtime.status("i1").await.unwrap(), RuntimeStatus::Running);
+    runtime.stop("i1", true).await.unwrap();
+
+    let invalid = runtime
+        .start(spec("i2", ChallengeRuntime::default()))
+        .await
+        .unwrap_err();
+    assert!(matches!(invalid, RuntimeError::Invalid(_)), "{invalid}");
+}
+
+#[tokio::test]
+async fn agents_run_and_stop_instances_on_their_runtime() {
+    let backing = util::Backing::default();
+    let mut daemon = util::spawn(&backing).await;
+    let dir = tempfile::tempdir().unwrap();
+    let work_dir = dir.path().join("instances");
+    let marker = dir.path().join("marker");
+    let config = ctfjx_agent::config::Config {
+        join_token: Some(util::join_token(&mut daemon, "agent-1").await),
+        ..util::config(&daemon.url(), dir.path())
+    };
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let agent = Agent::new(config, RuntimeExecutor(ProcessRuntime::new(&work_dir)));
+    let handle = tokio::spawn(agent.run(async {
+        let _ = stop.await;
+    }));
+
+    let instance_id = util::start_instance(
+        &mut daemon,
+        Some(ChallengeRuntime {
+            env: HashMap::from([("MARKER".to_string(), path(&marker))]),
+            ..sh(r#"echo "$PORT" > "$MARKER"; exec sleep 30"#)
+        }),
+    )
+    .await;
+    // the agent reports the port it handed the instance
+    let status = util::wait_for_state(&mut daemon, &instance_id, State::Running).await;
+    assert_ne!(status.port, 0);
+    assert_eq!(status.node, "agent-1");
+    util::eventually(|| marker.exists()).await;
+    util::eventually(|| {
+        std::fs::read_to_string(&marker).is_ok_and(|port| port.trim() == status.port.to_string())
+    })
+    .await;
+    assert!(work_dir.join(&instance_id).is_dir());
+
+    daemon
+        .client
+        .stop_instance(StopInstanceRequest {
+            instance_id: instance_id.clone(),
+            force: false,
+        })
+        .await
+        .unwrap();
+    util::eventually(|| !work_dir.join(&instance_id).exists()).await;
+
+    drop(shutdown);
+    handle.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn instances_failing_every_start_are_marked_failed() {
+    let backing = util::Backing::default();
+    let mut daemon = util::spawn(&backing).await;
+    let dir = tempfile::tempdir().unwrap();
+    let config = ctfjx_agent::config::Config {
+        join_token: Some(util::join_token(&mut daemon, "agent-1").await),
+        ..util::config(&daemon.url(), dir.path())
+    };
+    let (shutdown, stop) = oneshot::channel::<()>();
+    let runtime = ProcessRuntime::new(dir.path().join("instances"));
+    let agent = Agent::new(config, RuntimeExecutor(runtime));
+    let handle = tokio::spawn(agent.run(async {
+        let _ = stop.await;
+    }));
+
+    let instance_id = util::start_instance(
+        &mut daemon,
+        Some(ChallengeRuntime {
+            command: vec![path(&dir.path().join("missing"))],
+            ..Default::default()
+        }),
+    )
+    .await;
+    let status = util::wait_for_state(&mut daemon, &instance_id, State::Failed).await;
+    assert!(
+        status.message.starts_with("could not start"),
+        "{}",
+        status.message
+    );
+    assert_eq!(status.port, 0);
+
+    drop(shutdown);
+    handle.await.unwrap().unwrap();
+}
+
+fn path(path: &Path) -> String {
+    path.to_str().unwrap().to_string()
+}
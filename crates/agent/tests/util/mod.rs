@@ -0,0 +1,191 @@
+use std::{net::SocketAddr, path::Path, sync::Arc, time::Duration};
+
+use ctfjx::client::{self, BearerToken, Client};
+use ctfjx_agent::config::Config;
+use ctfjx_proto::grpc::{
+    Challenge, ChallengeRuntime, CreateChallengeRequest, CreateJoinTokenRequest, CreateTeamRequest,
+    CreateUserRequest, GetInstanceStatusRequest, InstanceStatus, StartInstanceRequest, Team, User,
+    instance_status::State,
+};
+use ctfjx_storage::MemoryStorage;
+use ctfjxd::{queue::QueueConfig, secret::Secret, server, service::CtfjxService};
+use tokio::{net::TcpListener, sync::oneshot};
+
+/// The admin token test daemons accept
+pub const ADMIN_TOKEN: &str = "admin token of the test daemon";
+
+/// What a daemon keeps across restarts
+#[derive(Clone)]
+pub struct Backing {
+    pub store: Arc<MemoryStorage>,
+    pub secret: Secret,
+}
+
+impl Default for Backing {
+    fn default() -> Self {
+        Self {
+            store: Arc::default(),
+            secret: Secret::random(),
+        }
+    }
+}
+
+pub struct Daemon {
+    /// Authenticated with [`ADMIN_TOKEN`]
+    pub client: Client,
+    pub addr: SocketAddr,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl Daemon {
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+/// Serves a daemon on `listener`, retrying failed jobs quickly
+pub async fn serve(listener: TcpListener, backing: &Backing) -> Daemon {
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let service = CtfjxService::new(backing.store.clone(), &backing.secret)
+        .with_admin_token(ADMIN_TOKEN.parse().unwrap())
+        .with_job_queue(QueueConfig {
+            backoff: Duration::from_millis(20),
+            ..Default::default()
+        });
+    tokio::spawn(server::serve(listener, service, async {
+        let _ = shutdown_rx.await;
+    }));
+
+    let client = client::connect(
+        format!("http://{addr}"),
+        BearerToken::new(Some(ADMIN_TOKEN)).unwrap(),
+    )
+    .await
+    .unwrap();
+    Daemon {
+        client,
+        addr,
+        _shutdown: shutdown_tx,
+    }
+}
+
+/// [`serve`] on an ephemeral port
+pub async fn spawn(backing: &Backing) -> Daemon {
+    serve(TcpListener::bind("127.0.0.1:0").await.unwrap(), backing).await
+}
+
+/// A join token for `agent_id`
+pub async fn join_token(daemon: &mut Daemon, agent_id: &str) -> String {
+    daemon
+        .client
+        .create_join_token(CreateJoinTokenRequest {
+            agent_id: agent_id.to_string(),
+            ttl_seconds: 0,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .token
+}
+
+/// Agent config talking to `daemon`, with state kept in `dir` and short
+/// intervals
+pub fn config(daemon: &str, dir: &Path) -> Config {
+    Config {
+        daemon: daemon.to_string(),
+        state: dir.join("agent.json"),
+        heartbeat: Duration::from_millis(50),
+        reconnect: Duration::from_millis(20),
+        max_reconnect: Duration::from_millis(100),
+        ..Default::default()
+    }
+}
+
+/// Starts an instance of a new challenge run by `runtime` for a new team,
+/// returning its id
+pub async fn start_instance(daemon: &mut Daemon, runtime: Option<ChallengeRuntime>) -> String {
+    let captain_id = daemon
+        .client
+        .create_user(CreateUserRequest {
+            user: Some(User {
+                name: "captain".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    daemon
+        .client
+        .create_team(CreateTeamRequest {
+            team: Some(Team {
+                id: "team-a".to_string(),
+                name: "team-a".to_string(),
+                captain_id,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap();
+    let challenge_id = daemon
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                runtime,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    daemon
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .instance_id
+}
+
+/// Polls the instance's status until it is in `state`
+pub async fn wait_for_state(
+    daemon: &mut Daemon,
+    instance_id: &str,
+    state: State,
+) -> InstanceStatus {
+    let mut status = InstanceStatus::default();
+    for _ in 0..250 {
+        status = daemon
+            .client
+            .get_instance_status(GetInstanceStatusRequest {
+                instance_id: instance_id.to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        if status.state() == state {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("instance never became {state:?}, last {status:?}");
+}
+
+/// Retries `check` until it holds
+pub async fn eventually(mut check: impl FnMut() -> bool) {
+    for _ in 0..250 {
+        if check() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("condition never held");
+}
@@ -0,0 +1,87 @@
+//! A `ServiceCtfjxClient` that authenticates every call with a bearer token.
+
+use ctfjx_common::env::lookup;
+use ctfjx_proto::grpc::service_ctfjx_client::ServiceCtfjxClient;
+use thiserror::Error;
+use tonic::{
+    Request, Status,
+    metadata::{Ascii, MetadataValue},
+    service::{Interceptor, interceptor::InterceptedService},
+    transport::{Channel, Endpoint},
+};
+
+/// Where the token is read from when none is given, `CTFJX_TOKEN_FILE` works too
+pub const TOKEN_ENV: &str = "CTFJX_TOKEN";
+
+pub type Client = ServiceCtfjxClient<InterceptedService<Channel, BearerToken>>;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("token is not a valid header value")]
+    InvalidToken,
+    #[error("transport: {0}")]
+    Transport(#[from] tonic::transport::Error),
+}
+
+/// Adds `authorization: Bearer <token>` to requests, nothing when empty
+#[derive(Debug, Clone, Default)]
+pub struct BearerToken(Option<MetadataValue<Ascii>>);
+
+impl BearerToken {
+    pub fn new(token: Option<&str>) -> Result<Self, ClientError> {
+        token
+            .map(|t| {
+                format!("Bearer {}", t.trim())
+                    .parse()
+                    .map_err(|_| ClientError::InvalidToken)
+            })
+            .transpose()
+            .map(Self)
+    }
+
+    /// The token from [`TOKEN_ENV`], anonymous when it is not set
+    pub fn from_env() -> Result<Self, ClientError> {
+        Self::new(lookup(TOKEN_ENV).ok().as_deref())
+    }
+}
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(value) = &self.0 {
+            request
+                .metadata_mut()
+                .insert("authorization", value.clone());
+        }
+        Ok(request)
+    }
+}
+
+/// Connects to the daemon at `addr`, e.g. `http://127.0.0.1:50051`
+pub async fn connect(addr: impl Into<String>, token: BearerToken) -> Result<Client, ClientError> {
+    let channel = Endpoint::from_shared(addr.into())?.connect().await?;
+    Ok(ServiceCtfjxClient::with_interceptor(channel, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_the_header() {
+        let mut token = BearerToken::new(Some(" ctfjx_abc.def\n")).unwrap();
+        let request = token.call(Request::new(())).unwrap();
+        assert_eq!(
+            request.metadata().get("authorization").unwrap(),
+            "Bearer ctfjx_abc.def"
+        );
+
+        let mut anonymous = BearerToken::default();
+        let request = anonymous.call(Request::new(())).unwrap();
+        assert!(request.metadata().get("authorization").is_none());
+
+        assert!(matches!(
+            BearerToken::new(Some("bad\u{7f}token")),
+            Err(ClientError::InvalidToken)
+        ));
+    }
+}
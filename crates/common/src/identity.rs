@@ -0,0 +1,234 @@
+//! Keypair identities shared by agents and the daemon.
+//!
+//! Agents authenticate with bearer tokens of the form
+//! `ctfjxa_<credential id>.<unix seconds>.<signature>`, an assertion signed by
+//! the key bound to their credential. Assertions are only accepted within
+//! [`MAX_ASSERTION_SKEW`] of the daemon's clock, so agents sign a fresh one
+//! for every connection.
+//!
+//! The daemon signs the nonce an agent sends when registering, letting the
+//! agent check it talks to the daemon it enrolled with.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use thiserror::Error;
+
+pub const AGENT_TOKEN_PREFIX: &str = "ctfjxa_";
+
+/// How far the time of an assertion may be from the verifier's clock
+pub const MAX_ASSERTION_SKEW: Duration = Duration::from_secs(5 * 60);
+
+const ASSERTION_CONTEXT: &[u8] = b"ctfjx agent assertion\0";
+const PROOF_CONTEXT: &[u8] = b"ctfjx daemon proof\0";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum IdentityError {
+    #[error("invalid ed25519 public key")]
+    InvalidKey,
+    #[error("malformed agent token")]
+    Malformed,
+    #[error("signature does not match")]
+    BadSignature,
+    #[error("agent token is too old or from the future")]
+    Stale,
+}
+
+fn unix_secs(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+fn assertion_message(credential_id: &str, issued_at: u64) -> Vec<u8> {
+    [
+        ASSERTION_CONTEXT,
+        credential_id.as_bytes(),
+        b"\0",
+        issued_at.to_string().as_bytes(),
+    ]
+    .concat()
+}
+
+fn proof_message(agent_id: &str, nonce: &[u8]) -> Vec<u8> {
+    [PROOF_CONTEXT, agent_id.as_bytes(), b"\0", nonce].concat()
+}
+
+fn verifying_key(public_key: &[u8]) -> Result<VerifyingKey, IdentityError> {
+    let bytes = public_key
+        .try_into()
+        .map_err(|_| IdentityError::InvalidKey)?;
+    VerifyingKey::from_bytes(bytes).map_err(|_| IdentityError::InvalidKey)
+}
+
+/// Checks `public_key` is a usable ed25519 key
+pub fn validate_public_key(public_key: &[u8]) -> Result<(), IdentityError> {
+    verifying_key(public_key).map(|_| ())
+}
+
+/// An ed25519 keypair
+#[derive(Clone)]
+pub struct Keypair(SigningKey);
+
+impl Keypair {
+    pub fn generate() -> Self {
+        Self(SigningKey::generate(&mut rand::rngs::OsRng))
+    }
+
+    /// The keypair derived from a 32 byte seed, as returned by [`Self::seed`]
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self(SigningKey::from_bytes(&seed))
+    }
+
+    /// The secret seed, to store the keypair
+    pub fn seed(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.0.verifying_key().to_bytes()
+    }
+
+    /// A bearer token asserting the agent holds the key of `credential_id`
+    pub fn agent_token(&self, credential_id: &str, at: SystemTime) -> String {
+        let issued_at = unix_secs(at);
+        let signature = self.0.sign(&assertion_message(credential_id, issued_at));
+        format!(
+            "{AGENT_TOKEN_PREFIX}{credential_id}.{issued_at}.{}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    /// Signs the nonce an agent registered with
+    pub fn prove(&self, agent_id: &str, nonce: &[u8]) -> Vec<u8> {
+        self.0
+            .sign(&proof_message(agent_id, nonce))
+            .to_bytes()
+            .to_vec()
+    }
+}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Keypair")
+            .field(&URL_SAFE_NO_PAD.encode(self.public_key()))
+            .finish()
+    }
+}
+
+/// Checks the daemon's reply to a registration carrying `nonce`
+pub fn verify_proof(
+    daemon_public_key: &[u8],
+    agent_id: &str,
+    nonce: &[u8],
+    signature: &[u8],
+) -> Result<(), IdentityError> {
+    let signature = Signature::from_slice(signature).map_err(|_| IdentityError::BadSignature)?;
+    verifying_key(daemon_public_key)?
+        .verify(&proof_message(agent_id, nonce), &signature)
+        .map_err(|_| IdentityError::BadSignature)
+}
+
+/// A parsed agent bearer token, not verified yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentAssertion {
+    pub credential_id: String,
+    pub issued_at: u64,
+    signature: Signature,
+}
+
+impl AgentAssertion {
+    /// Parses a token, with or without its [`AGENT_TOKEN_PREFIX`]
+    pub fn parse(token: &str) -> Result<Self, IdentityError> {
+        let token = token.strip_prefix(AGENT_TOKEN_PREFIX).unwrap_or(token);
+        let mut parts = token.splitn(3, '.');
+        let (Some(id), Some(issued_at), Some(signature)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(IdentityError::Malformed);
+        };
+        if id.is_empty() {
+            return Err(IdentityError::Malformed);
+        }
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| IdentityError::Malformed)?;
+        Ok(Self {
+            credential_id: id.to_string(),
+            issued_at: issued_at.parse().map_err(|_| IdentityError::Malformed)?,
+            signature: Signature::from_slice(&signature).map_err(|_| IdentityError::Malformed)?,
+        })
+    }
+
+    /// Checks the assertion was signed by `public_key` around `now`
+    pub fn verify(&self, public_key: &[u8], now: SystemTime) -> Result<(), IdentityError> {
+        if unix_secs(now).abs_diff(self.issued_at) > MAX_ASSERTION_SKEW.as_secs() {
+            return Err(IdentityError::Stale);
+        }
+        verifying_key(public_key)?
+            .verify(
+                &assertion_message(&self.credential_id, self.issued_at),
+                &self.signature,
+            )
+            .map_err(|_| IdentityError::BadSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_tokens() {
+        let key = Keypair::generate();
+        let now = SystemTime::now();
+        let token = key.agent_token("cred-1", now);
+        assert!(token.starts_with(AGENT_TOKEN_PREFIX));
+
+        let assertion = AgentAssertion::parse(&token).unwrap();
+        assert_eq!(assertion.credential_id, "cred-1");
+        assertion.verify(&key.public_key(), now).unwrap();
+
+        let other = Keypair::generate();
+        assert_eq!(
+            assertion.verify(&other.public_key(), now),
+            Err(IdentityError::BadSignature)
+        );
+        assert_eq!(
+            assertion.verify(&key.public_key(), now + 2 * MAX_ASSERTION_SKEW),
+            Err(IdentityError::Stale)
+        );
+
+        // the signature covers the credential id
+        let forged = token.replacen("cred-1", "cred-2", 1);
+        let forged = AgentAssertion::parse(&forged).unwrap();
+        assert_eq!(
+            forged.verify(&key.public_key(), now),
+            Err(IdentityError::BadSignature)
+        );
+        assert_eq!(
+            AgentAssertion::parse("ctfjxa_nope"),
+            Err(IdentityError::Malformed)
+        );
+    }
+
+    #[test]
+    fn daemon_proofs() {
+        let daemon = Keypair::from_seed([7; 32]);
+        assert_eq!(
+            Keypair::from_seed(daemon.seed()).public_key(),
+            daemon.public_key()
+        );
+
+        let proof = daemon.prove("agent-1", b"nonce");
+        verify_proof(&daemon.public_key(), "agent-1", b"nonce", &proof).unwrap();
+        assert!(verify_proof(&daemon.public_key(), "agent-2", b"nonce", &proof).is_err());
+        assert!(verify_proof(&daemon.public_key(), "agent-1", b"other", &proof).is_err());
+        assert_eq!(
+            validate_public_key(&[1, 2, 3]),
+            Err(IdentityError::InvalidKey)
+        );
+    }
+}
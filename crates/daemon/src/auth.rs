@@ -0,0 +1,499 @@
+//! Bearer token authentication.
+//!
+//! API tokens look like `ctfjx_<id>.<secret>` and are checked against the
+//! stored hash of their secret. Session tokens, `ctfjxs_<payload>.<mac>`, are
+//! signed by the daemon and only need storage to make sure the API token they
+//! were traded for still stands. The admin token from the config bootstraps
+//! everything else.
+//!
+//! Enrolled agents send `ctfjxa_` assertions signed with the key of their
+//! credential instead, see [`ctfjx_common::identity`]. They act as agents
+//! under their agent id, with no user behind them.
+//!
+//! Callers act with the role of their user, looked up on every request so
+//! role changes apply right away.
+
+use std::{
+    fmt,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ctfjx_common::identity::{AGENT_TOKEN_PREFIX, AgentAssertion};
+use ctfjx_proto::grpc::{ApiToken, user::Role};
+use ctfjx_storage::{Storage, StorageError, TokenRecord, ts_key};
+use hmac::Mac;
+use prost_wkt_types::Timestamp;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tonic::{Request, Status, service::Interceptor};
+
+use crate::{
+    policy,
+    secret::{HmacSha256, Secret},
+    service::{new_id, now},
+};
+
+pub const API_TOKEN_PREFIX: &str = "ctfjx_";
+pub const SESSION_TOKEN_PREFIX: &str = "ctfjxs_";
+pub const JOIN_TOKEN_PREFIX: &str = "ctfjxj_";
+
+/// What a credential may do, each scope implying the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Read,
+    Write,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    /// The broadest of `scopes`, which have to be known ones
+    pub fn parse_all(scopes: &[String]) -> Result<Scope, Status> {
+        let mut max = None;
+        for scope in scopes {
+            max = max.max(Some(scope.parse::<Scope>()?));
+        }
+        max.ok_or_else(|| Status::invalid_argument("at least one scope is required"))
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = Status;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Scope::Read),
+            "write" => Ok(Scope::Write),
+            "admin" => Ok(Scope::Admin),
+            _ => Err(Status::invalid_argument(format!("unknown scope `{s}`"))),
+        }
+    }
+}
+
+/// Who is calling, attached to request extensions by [`Authenticator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    /// Empty for the admin token from the config
+    pub user_id: String,
+    pub role: Role,
+    pub scope: Scope,
+    /// The API token used directly or traded for a session
+    pub token_id: Option<String>,
+    /// The credential an enrolled agent signed its token with
+    pub credential_id: Option<String>,
+}
+
+impl Caller {
+    pub fn has(&self, scope: Scope) -> bool {
+        self.scope >= scope
+    }
+
+    /// Whether the caller is an admin using an admin credential
+    pub fn is_admin(&self) -> bool {
+        policy::effective_role(self.role) == Role::Admin && self.has(Scope::Admin)
+    }
+}
+
+/// What a session token carries, signed
+#[derive(Serialize, Deserialize)]
+struct SessionClaims {
+    #[serde(rename = "u")]
+    user_id: String,
+    #[serde(rename = "s")]
+    scope: Scope,
+    #[serde(rename = "t", default, skip_serializing_if = "Option::is_none")]
+    token_id: Option<String>,
+    /// Expiry, in seconds since the epoch
+    #[serde(rename = "e")]
+    expires_at: i64,
+}
+
+#[derive(Clone)]
+pub struct Authenticator {
+    store: Arc<dyn Storage>,
+    session_key: [u8; 32],
+    admin_token: Option<Secret>,
+}
+
+fn invalid() -> Status {
+    Status::unauthenticated("invalid or expired token")
+}
+
+pub(crate) fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+pub(crate) fn expired(expires_at: Option<&Timestamp>) -> bool {
+    expires_at.is_some_and(|at| ts_key(at) <= ts_key(&now()))
+}
+
+impl Authenticator {
+    pub fn new(store: Arc<dyn Storage>, secret: &Secret) -> Self {
+        Self {
+            store,
+            session_key: secret.derive("session-token"),
+            admin_token: None,
+        }
+    }
+
+    pub fn with_admin_token(mut self, token: Secret) -> Self {
+        self.admin_token = Some(token);
+        self
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.session_key).expect("hmac takes any key size")
+    }
+
+    /// Creates an API token, returning it along with the bearer token to
+    /// hand out once
+    pub fn issue_token(&self, mut token: ApiToken) -> Result<(ApiToken, String), Status> {
+        let secret = hex::encode(rand::random::<[u8; 32]>());
+        token.id = new_id();
+        token.created_at = Some(now());
+        token.revoked_at = None;
+
+        self.store.insert_token(&TokenRecord {
+            token: token.clone(),
+            secret_hash: hash_secret(&secret),
+        })?;
+        let bearer = format!("{API_TOKEN_PREFIX}{}.{secret}", token.id);
+        Ok((token, bearer))
+    }
+
+    /// Signs a session token for `caller`, valid for `ttl`
+    pub fn issue_session(&self, caller: &Caller, ttl: Duration) -> (String, Timestamp) {
+        let now = now();
+        let expires_at = Timestamp {
+            seconds: now.seconds.saturating_add(ttl.as_secs() as i64),
+            nanos: 0,
+        };
+        let claims = serde_json::to_vec(&SessionClaims {
+            user_id: caller.user_id.clone(),
+            scope: caller.scope,
+            token_id: caller.token_id.clone(),
+            expires_at: expires_at.seconds,
+        })
+        .expect("session claims serialize");
+
+        let mut mac = self.mac();
+        mac.update(&claims);
+        let token = format!(
+            "{SESSION_TOKEN_PREFIX}{}.{}",
+            URL_SAFE_NO_PAD.encode(&claims),
+            URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())
+        );
+        (token, expires_at)
+    }
+
+    /// The caller a bearer token stands for
+    pub fn authenticate(&self, token: &str) -> Result<Caller, Status> {
+        if let Some(session) = token.strip_prefix(SESSION_TOKEN_PREFIX) {
+            return self.authenticate_session(session);
+        }
+        if token.starts_with(AGENT_TOKEN_PREFIX) {
+            return self.authenticate_agent(token);
+        }
+        if let Some(api) = token.strip_prefix(API_TOKEN_PREFIX) {
+            let (id, secret) = api.split_once('.').ok_or_else(invalid)?;
+            let token = self.check_api_token(id, Some(secret))?;
+            return Ok(Caller {
+                role: self.role_of(&token.user_id)?,
+                user_id: token.user_id,
+                scope: Scope::parse_all(&token.scopes).map_err(|_| invalid())?,
+                token_id: Some(token.id),
+                credential_id: None,
+            });
+        }
+        match &self.admin_token {
+            Some(admin) if admin.matches(token) => Ok(Caller {
+                user_id: String::new(),
+                role: Role::Admin,
+                scope: Scope::Admin,
+                token_id: None,
+                credential_id: None,
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn authenticate_session(&self, session: &str) -> Result<Caller, Status> {
+        let (claims, tag) = session.split_once('.').ok_or_else(invalid)?;
+        let claims = URL_SAFE_NO_PAD.decode(claims).map_err(|_| invalid())?;
+        let tag = URL_SAFE_NO_PAD.decode(tag).map_err(|_| invalid())?;
+
+        let mut mac = self.mac();
+        mac.update(&claims);
+        mac.verify_slice(&tag).map_err(|_| invalid())?;
+
+        let claims: SessionClaims = serde_json::from_slice(&claims).map_err(|_| invalid())?;
+        if claims.expires_at <= now().seconds {
+            return Err(invalid());
+        }
+        // revoking a token ends the sessions traded for it
+        if let Some(id) = &claims.token_id {
+            self.check_api_token(id, None)?;
+        }
+        Ok(Caller {
+            role: self.role_of(&claims.user_id)?,
+            user_id: claims.user_id,
+            scope: claims.scope,
+            token_id: claims.token_id,
+            credential_id: None,
+        })
+    }
+
+    fn authenticate_agent(&self, token: &str) -> Result<Caller, Status> {
+        let assertion = AgentAssertion::parse(token).map_err(|_| invalid())?;
+        let credential = match self.store.get_agent_credential(&assertion.credential_id) {
+            Ok(credential) => credential,
+            Err(StorageError::NotFound(..)) => return Err(invalid()),
+            Err(e) => return Err(e.into()),
+        };
+        if credential.revoked_at.is_some() {
+            return Err(invalid());
+        }
+        assertion
+            .verify(&credential.public_key, SystemTime::now())
+            .map_err(|_| invalid())?;
+        Ok(Caller {
+            user_id: credential.agent_id,
+            role: Role::Agent,
+            scope: Scope::Write,
+            token_id: None,
+            credential_id: Some(credential.id),
+        })
+    }
+
+    /// The current role of a user, admin for the admin token which has none
+    fn role_of(&self, user_id: &str) -> Result<Role, Status> {
+        if user_id.is_empty() {
+            return Ok(Role::Admin);
+        }
+        match self.store.get_user(user_id) {
+            Ok(user) => Ok(policy::effective_role(user.role())),
+            // tokens outlive deleted users
+            Err(StorageError::NotFound(..)) => Err(invalid()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The stored token, when it is neither revoked nor expired and, if
+    /// given, `secret` is its secret
+    fn check_api_token(&self, id: &str, secret: Option<&str>) -> Result<ApiToken, Status> {
+        let record = match self.store.get_token(id) {
+            Ok(record) => record,
+            Err(StorageError::NotFound(..)) => return Err(invalid()),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(secret) = secret {
+            let hash = hash_secret(secret);
+            if !bool::from(hash.as_bytes().ct_eq(record.secret_hash.as_bytes())) {
+                return Err(invalid());
+            }
+        }
+        if record.token.revoked_at.is_some() || expired(record.token.expires_at.as_ref()) {
+            return Err(invalid());
+        }
+        Ok(record.token)
+    }
+}
+
+impl Interceptor for Authenticator {
+    /// Attaches the [`Caller`] when a bearer token is sent. Requests without
+    /// one go through anonymously, each RPC decides whether that is enough
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(header) = request.metadata().get("authorization") else {
+            return Ok(request);
+        };
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("expected `authorization: Bearer <token>`"))?;
+
+        let caller = self.authenticate(token.trim())?;
+        request.extensions_mut().insert(caller);
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ctfjx_common::identity::Keypair;
+    use ctfjx_proto::grpc::{AgentCredential, User};
+    use ctfjx_storage::{MemoryStorage, Store};
+
+    use super::*;
+
+    fn auth() -> Authenticator {
+        let store = Arc::new(MemoryStorage::default());
+        store
+            .insert_user(&User {
+                id: "u1".to_string(),
+                role: Role::Author as i32,
+                ..Default::default()
+            })
+            .unwrap();
+        Authenticator::new(store, &Secret::random())
+            .with_admin_token("an admin token, long enough".parse().unwrap())
+    }
+
+    fn token(user_id: &str, scope: &str) -> ApiToken {
+        ApiToken {
+            user_id: user_id.to_string(),
+            scopes: vec![scope.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn scopes_imply_lesser_ones() {
+        let scopes = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            Scope::parse_all(&scopes(&["read", "write"])).unwrap(),
+            Scope::Write
+        );
+        assert!(Scope::parse_all(&scopes(&[])).is_err());
+        assert!(Scope::parse_all(&scopes(&["read", "root"])).is_err());
+
+        let caller = Caller {
+            user_id: "u".to_string(),
+            role: Role::Admin,
+            scope: Scope::Write,
+            token_id: None,
+            credential_id: None,
+        };
+        assert!(caller.has(Scope::Read));
+        assert!(!caller.has(Scope::Admin));
+        // admins need an admin credential to act as one
+        assert!(!caller.is_admin());
+    }
+
+    #[test]
+    fn api_tokens() {
+        let auth = auth();
+        let (issued, bearer) = auth.issue_token(token("u1", "write")).unwrap();
+        let caller = auth.authenticate(&bearer).unwrap();
+        assert_eq!(caller.user_id, "u1");
+        assert_eq!(caller.role, Role::Author);
+        assert_eq!(caller.scope, Scope::Write);
+        assert_eq!(caller.token_id.as_deref(), Some(issued.id.as_str()));
+
+        // roles are looked up on every request
+        let mut user = auth.store.get_user("u1").unwrap();
+        user.set_role(Role::Player);
+        auth.store.update_user(&user).unwrap();
+        assert_eq!(auth.authenticate(&bearer).unwrap().role, Role::Player);
+
+        // a tampered secret or an unknown id are rejected alike
+        let mut tampered = bearer.clone();
+        tampered.pop();
+        tampered.push('x');
+        assert!(auth.authenticate(&tampered).is_err());
+        assert!(auth.authenticate("ctfjx_nope.secret").is_err());
+
+        let mut record = auth.store.get_token(&issued.id).unwrap();
+        record.token.revoked_at = Some(now());
+        auth.store.update_token(&record).unwrap();
+        assert!(auth.authenticate(&bearer).is_err());
+    }
+
+    #[test]
+    fn expired_api_tokens() {
+        let auth = auth();
+        let mut expiring = token("u1", "read");
+        expiring.expires_at = Some(Timestamp {
+            seconds: now().seconds - 1,
+            nanos: 0,
+        });
+        let (_, bearer) = auth.issue_token(expiring).unwrap();
+        assert_eq!(
+            auth.authenticate(&bearer).unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+    }
+
+    #[test]
+    fn sessions_follow_their_token() {
+        let auth = auth();
+        let (issued, bearer) = auth.issue_token(token("u1", "read")).unwrap();
+        let caller = auth.authenticate(&bearer).unwrap();
+
+        let (session, _) = auth.issue_session(&caller, Duration::from_secs(60));
+        assert_eq!(auth.authenticate(&session).unwrap(), caller);
+
+        let (expired, _) = auth.issue_session(&caller, Duration::ZERO);
+        assert!(auth.authenticate(&expired).is_err());
+
+        // sessions are signed by this daemon only
+        let other = Authenticator::new(auth.store.clone(), &Secret::random());
+        assert!(other.authenticate(&session).is_err());
+
+        let mut record = auth.store.get_token(&issued.id).unwrap();
+        record.token.revoked_at = Some(now());
+        auth.store.update_token(&record).unwrap();
+        assert!(auth.authenticate(&session).is_err());
+    }
+
+    #[test]
+    fn agent_assertions() {
+        let auth = auth();
+        let key = Keypair::generate();
+        let credential = AgentCredential {
+            id: "c1".to_string(),
+            agent_id: "runner".to_string(),
+            public_key: key.public_key().to_vec(),
+            ..Default::default()
+        };
+        auth.store.insert_agent_credential(&credential).unwrap();
+
+        let token = key.agent_token("c1", SystemTime::now());
+        let caller = auth.authenticate(&token).unwrap();
+        assert_eq!(caller.user_id, "runner");
+        assert_eq!(caller.role, Role::Agent);
+        assert_eq!(caller.credential_id.as_deref(), Some("c1"));
+
+        // signed by another key, or for an unknown credential
+        let other = Keypair::generate();
+        assert!(
+            auth.authenticate(&other.agent_token("c1", SystemTime::now()))
+                .is_err()
+        );
+        assert!(
+            auth.authenticate(&key.agent_token("c2", SystemTime::now()))
+                .is_err()
+        );
+
+        let mut revoked = credential;
+        revoked.revoked_at = Some(now());
+        auth.store.update_agent_credential(&revoked).unwrap();
+        assert!(auth.authenticate(&token).is_err());
+    }
+
+    #[test]
+    fn admin_token() {
+        let auth = auth();
+        let caller = auth.authenticate("an admin token, long enough").unwrap();
+        assert!(caller.has(Scope::Admin));
+        assert!(auth.authenticate("another admin token").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::{net::SocketAddr, str::FromStr, time::Duration};
+
+use ctfjx_common::env::{EnvError, ResolveEnv, lookup};
+use ctfjx_storage::StorageConfig;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::{expiry::ExpiryConfig, liveness::LivenessConfig, queue::QueueConfig, secret::Secret};
+
+pub const DEFAULT_ADDR: &str = "0.0.0.0:50051";
+
+#[derive(Debug, Clone, Validate)]
+pub struct Config {
+    /// `CTFJXD_ADDR`, where the gRPC server listens
+    pub addr: SocketAddr,
+    /// `CTFJXD_STORAGE`, see [`StorageConfig`]
+    pub storage: StorageConfig,
+    /// `CTFJXD_SECRET`, signs page tokens and sessions and derives dynamic
+    /// flags. A random one is used when unset, so nothing signed survives a
+    /// restart, and the daemon refuses to start once dynamic flags exist
+    pub secret: Option<Secret>,
+    /// `CTFJXD_MAX_TEAM_SIZE`, how many members a team may have, 0 for no limit
+    pub max_team_size: usize,
+    /// `CTFJXD_ADMIN_TOKEN`, a bearer token with every scope, for creating
+    /// the first users and their tokens
+    pub admin_token: Option<Secret>,
+    /// `CTFJXD_JOB_LEASE_SECONDS` and `CTFJXD_JOB_MAX_ATTEMPTS`, how long
+    /// agents have to report on a job and how often it is tried
+    pub jobs: QueueConfig,
+    /// `CTFJXD_AGENT_SUSPECT_SECONDS`, `CTFJXD_AGENT_DEAD_SECONDS` and
+    /// `CTFJXD_RESCHEDULE_INSTANCES`, when silent agents are given up on and
+    /// whether their instances move elsewhere
+    pub liveness: LivenessConfig,
+    /// `CTFJXD_INSTANCE_TTL_SECONDS`, `CTFJXD_INSTANCE_MAX_LIFETIME_SECONDS`
+    /// and `CTFJXD_INSTANCE_WARN_SECONDS`, how long instances run and when
+    /// their teams are warned they are about to be stopped
+    pub expiry: ExpiryConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            addr: DEFAULT_ADDR.parse().expect("valid default addr"),
+            storage: StorageConfig::default(),
+            secret: None,
+            max_team_size: 0,
+            admin_token: None,
+            jobs: QueueConfig::default(),
+            liveness: LivenessConfig::default(),
+            expiry: ExpiryConfig::default(),
+        }
+    }
+}
+
+impl ResolveEnv for Config {
+    fn populate(&mut self) -> Result<(), EnvError> {
+        if let Some(addr) = lookup_parsed("CTFJXD_ADDR", "addr")? {
+            self.addr = addr;
+        }
+        if let Some(storage) = lookup_parsed("CTFJXD_STORAGE", "storage")? {
+            self.storage = storage;
+        }
+        if let Some(secret) = lookup_parsed("CTFJXD_SECRET", "secret")? {
+            self.secret = Some(secret);
+        }
+        if let Some(max) = lookup_parsed("CTFJXD_MAX_TEAM_SIZE", "max_team_size")? {
+            self.max_team_size = max;
+        }
+        if let Some(token) = lookup_parsed("CTFJXD_ADMIN_TOKEN", "admin_token")? {
+            self.admin_token = Some(token);
+        }
+        if let Some(secs) = lookup_parsed::<u64>("CTFJXD_JOB_LEASE_SECONDS", "jobs")? {
+            self.jobs.lease = Duration::from_secs(secs.max(1));
+        }
+        if let Some(max) = lookup_parsed::<u32>("CTFJXD_JOB_MAX_ATTEMPTS", "jobs")? {
+            self.jobs.max_attempts = max.max(1);
+        }
+        if let Some(secs) = lookup_parsed::<u64>("CTFJXD_AGENT_SUSPECT_SECONDS", "liveness")? {
+            self.liveness.suspect_after = Duration::from_secs(secs.max(1));
+        }
+        if let Some(secs) = lookup_parsed::<u64>("CTFJXD_AGENT_DEAD_SECONDS", "liveness")? {
+            self.liveness.dead_after = Duration::from_secs(secs.max(1));
+        }
+        // an agent is suspect before it is dead
+        self.liveness.dead_after = self.liveness.dead_after.max(self.liveness.suspect_after);
+        if let Some(reschedule) = lookup_parsed("CTFJXD_RESCHEDULE_INSTANCES", "liveness")? {
+            self.liveness.reschedule = reschedule;
+        }
+        if let Some(secs) = lookup_parsed::<u64>("CTFJXD_INSTANCE_TTL_SECONDS", "expiry")? {
+            self.expiry.default_ttl = Duration::from_secs(secs.max(1));
+        }
+        if let Some(secs) = lookup_parsed::<u64>("CTFJXD_INSTANCE_MAX_LIFETIME_SECONDS", "expiry")?
+        {
+            self.expiry.max_lifetime = Duration::from_secs(secs.max(1));
+        }
+        // instances may always live as long as they are given by default
+        self.expiry.max_lifetime = self.expiry.max_lifetime.max(self.expiry.default_ttl);
+        if let Some(secs) = lookup_parsed::<u64>("CTFJXD_INSTANCE_WARN_SECONDS", "expiry")? {
+            self.expiry.warn_before = Duration::from_secs(secs);
+        }
+        Ok(())
+    }
+}
+
+/// Parses `key` when set, keeping the default when it is missing
+fn lookup_parsed<T: FromStr>(key: &str, field: &'static str) -> Result<Option<T>, EnvError> {
+    let Ok(value) = lookup(key) else {
+        return Ok(None);
+    };
+
+    value.trim().parse().map(Some).map_err(|_| {
+        let mut errors = ValidationErrors::new();
+        errors.add(field, ValidationError::new("parse"));
+        EnvError::Invalid(key.to_string(), errors)
+    })
+}
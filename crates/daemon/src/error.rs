@@ -0,0 +1,19 @@
+use ctfjx_common::env::EnvError;
+use ctfjx_storage::StorageError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(#[from] EnvError),
+    #[error("storage: {0}")]
+    Storage(#[from] StorageError),
+    #[error("transport: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(
+        "CTFJXD_SECRET is not set, but challenge {0} has dynamic flags that a random secret would change on restart"
+    )]
+    SecretRequired(String),
+}
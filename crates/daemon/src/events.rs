@@ -0,0 +1,112 @@
+//! In-process fan out of [`Event`]s to `StreamEvents` subscribers, and of
+//! scoreboard changes to `WatchScoreboard` ones.
+
+use std::{collections::HashMap, sync::Arc};
+
+use ctfjx_proto::grpc::{Event, StreamEventsRequest, event};
+use ctfjx_storage::Instance;
+use tokio::sync::{broadcast, watch};
+
+use crate::service::now;
+
+/// Events a slow subscriber may fall behind by before missing some
+const EVENT_BUFFER_SIZE: usize = 1 << 10;
+
+pub const LABEL_INSTANCE_ID: &str = "instance_id";
+pub const LABEL_CHALLENGE_ID: &str = "challenge_id";
+pub const LABEL_LEVEL: &str = "level";
+pub const LABEL_TEAM_ID: &str = "team_id";
+pub const LABEL_AGENT_ID: &str = "agent_id";
+
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self {
+            tx: broadcast::channel(EVENT_BUFFER_SIZE).0,
+        }
+    }
+}
+
+impl EventBus {
+    /// Publishes an event, dropped when nobody is listening
+    pub fn publish(&self, event: Event) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+}
+
+/// Wakes scoreboard watchers when solves, challenges or scoreboard settings
+/// changed. Watchers recompute the board themselves, so missing a wake up
+/// while busy only merges it with the next one
+#[derive(Clone)]
+pub struct ScoreChanges {
+    tx: Arc<watch::Sender<()>>,
+}
+
+impl Default for ScoreChanges {
+    fn default() -> Self {
+        Self {
+            tx: Arc::new(watch::channel(()).0),
+        }
+    }
+}
+
+impl ScoreChanges {
+    pub fn notify(&self) {
+        self.tx.send_replace(());
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.tx.subscribe()
+    }
+}
+
+/// Builds an event stamped with the current time
+pub fn new_event(
+    kind: event::Type,
+    source: &str,
+    message: impl Into<String>,
+    labels: impl IntoIterator<Item = (&'static str, String)>,
+) -> Event {
+    Event {
+        r#type: kind as i32,
+        source: source.to_string(),
+        message: message.into(),
+        time: Some(now()),
+        labels: labels
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect::<HashMap<_, _>>(),
+    }
+}
+
+/// The event announcing the instance's current state
+pub fn instance_state(instance: &Instance) -> Event {
+    let state = instance.status.state().as_str_name();
+    new_event(
+        event::Type::State,
+        "ctfjxd",
+        format!("instance {} is {}", instance.status.instance_id, state),
+        [
+            (LABEL_INSTANCE_ID, instance.status.instance_id.clone()),
+            (LABEL_CHALLENGE_ID, instance.status.challenge_id.clone()),
+        ],
+    )
+}
+
+/// Whether `event` is one the subscriber asked for, empty fields match anything
+pub fn matches(req: &StreamEventsRequest, event: &Event) -> bool {
+    let label_matches =
+        |key: &str, want: &str| want.is_empty() || event.labels.get(key).is_some_and(|v| v == want);
+
+    label_matches(LABEL_INSTANCE_ID, &req.instance_id)
+        && label_matches(LABEL_CHALLENGE_ID, &req.challenge_id)
+        && label_matches(LABEL_LEVEL, &req.level)
+}
@@ -0,0 +1,116 @@
+//! How long instances live.
+//!
+//! Instances are started with a TTL and stopped once it runs out, a warning
+//! is published [`ExpiryConfig::warn_before`] that. Extending an instance
+//! pushes its expiry back, but never past [`ExpiryConfig::max_lifetime`]
+//! after it was started.
+
+use std::time::{Duration, SystemTime};
+
+use tonic::Status;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryConfig {
+    /// TTL of instances started or extended without one
+    pub default_ttl: Duration,
+    pub max_lifetime: Duration,
+    pub warn_before: Duration,
+    /// How often instances are checked on
+    pub check_every: Duration,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl: Duration::from_secs(60 * 60),
+            max_lifetime: Duration::from_secs(4 * 60 * 60),
+            warn_before: Duration::from_secs(5 * 60),
+            check_every: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ExpiryConfig {
+    /// The TTL granted for `seconds` asked for, the default for 0
+    pub fn ttl(&self, seconds: i64) -> Result<Duration, Status> {
+        let ttl = match u64::try_from(seconds) {
+            Ok(0) => self.default_ttl,
+            Ok(seconds) => Duration::from_secs(seconds),
+            Err(_) => return Err(Status::invalid_argument("ttl must not be negative")),
+        };
+        Ok(ttl.min(self.max_lifetime))
+    }
+
+    /// When an instance started at `started_at` with `ttl` expires
+    pub fn expires_at(&self, started_at: SystemTime, ttl: Duration) -> Result<SystemTime, Status> {
+        after(started_at, ttl)
+    }
+
+    /// `expires_at` pushed back by `by`, as far as the lifetime of an
+    /// instance started at `started_at` allows. `None` when it is used up
+    pub fn extend(
+        &self,
+        started_at: SystemTime,
+        expires_at: SystemTime,
+        by: Duration,
+    ) -> Result<Option<SystemTime>, Status> {
+        let extended = after(expires_at, by)?.min(after(started_at, self.max_lifetime)?);
+        Ok((extended > expires_at).then_some(extended))
+    }
+
+    /// When the team of an instance expiring at `expires_at` is warned
+    pub fn warn_at(&self, expires_at: SystemTime) -> SystemTime {
+        expires_at
+            .checked_sub(self.warn_before)
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    }
+}
+
+/// `at` pushed back by `by`, refused when that is past what time can hold
+fn after(at: SystemTime, by: Duration) -> Result<SystemTime, Status> {
+    at.checked_add(by)
+        .ok_or_else(|| Status::invalid_argument("ttl reaches too far into the future"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifetimes_are_capped() {
+        let config = ExpiryConfig::default();
+        assert_eq!(config.ttl(0).unwrap(), config.default_ttl);
+        assert_eq!(config.ttl(60).unwrap(), Duration::from_secs(60));
+        assert_eq!(config.ttl(i64::MAX).unwrap(), config.max_lifetime);
+        assert!(config.ttl(-1).is_err());
+
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let hour = Duration::from_secs(60 * 60);
+        assert_eq!(
+            config.extend(t0, t0 + hour, hour).unwrap(),
+            Some(t0 + 2 * hour)
+        );
+        // the last extension only gets what is left of the lifetime
+        assert_eq!(
+            config.extend(t0, t0 + 3 * hour, 2 * hour).unwrap(),
+            Some(t0 + 4 * hour)
+        );
+        assert_eq!(config.extend(t0, t0 + 4 * hour, hour).unwrap(), None);
+
+        assert_eq!(config.warn_at(t0 + hour), t0 + hour - config.warn_before);
+    }
+
+    #[test]
+    fn lifetimes_past_the_end_of_time_are_refused() {
+        let config = ExpiryConfig {
+            max_lifetime: Duration::from_secs(u64::MAX),
+            ..Default::default()
+        };
+        let t0 = SystemTime::now();
+        let ttl = config.ttl(i64::MAX).unwrap();
+        let err = config.expires_at(t0, ttl).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        let err = config.extend(t0, t0 + config.default_ttl, ttl).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,248 @@
+//! Checking submitted flags against a challenge's [`FlagSpec`]s.
+
+use std::{collections::HashMap, sync::Arc};
+
+use ctfjx_proto::grpc::{Challenge, FlagSpec, flag_spec::Kind};
+use hmac::Mac;
+use parking_lot::Mutex;
+use regex::{Regex, RegexBuilder};
+use subtle::ConstantTimeEq;
+use tonic::Status;
+
+use crate::secret::{HmacSha256, Secret};
+
+/// Longest flag accepted in a submission
+pub const MAX_FLAG_LEN: usize = 1024;
+/// Prefix of dynamic flags whose spec does not name one
+pub const DEFAULT_DYNAMIC_PREFIX: &str = "flag";
+
+/// Compiled size a regex flag may take
+const REGEX_SIZE_LIMIT: usize = 1 << 16;
+
+/// Compiled regex flags kept before the cache starts over
+const REGEX_CACHE_SIZE: usize = 1024;
+
+/// Regex flags by challenge and pattern, `None` for ones that do not compile
+type RegexCache = HashMap<(String, String), Option<Regex>>;
+
+#[derive(Clone)]
+pub struct FlagChecker {
+    key: [u8; 32],
+    regexes: Arc<Mutex<RegexCache>>,
+}
+
+impl FlagChecker {
+    pub fn new(secret: &Secret) -> Self {
+        Self {
+            key: secret.derive("dynamic-flag"),
+            regexes: Arc::default(),
+        }
+    }
+
+    /// The flag `team_id` has to submit for a dynamic flag of `challenge_id`
+    pub fn dynamic_flag(&self, prefix: &str, challenge_id: &str, team_id: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("hmac takes any key size");
+        mac.update(challenge_id.as_bytes());
+        mac.update(&[0]);
+        mac.update(team_id.as_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        let prefix = if prefix.is_empty() {
+            DEFAULT_DYNAMIC_PREFIX
+        } else {
+            prefix
+        };
+        format!("{prefix}{{{}}}", hex::encode(&digest[..16]))
+    }
+
+    /// Whether `submitted` is an accepted flag for `team_id`
+    pub fn matches(
+        &self,
+        spec: &FlagSpec,
+        challenge_id: &str,
+        team_id: &str,
+        submitted: &str,
+    ) -> bool {
+        match spec.kind() {
+            Kind::Unspecified => false,
+            Kind::Static => ct_eq(&spec.value, submitted),
+            Kind::CaseInsensitive => ct_eq(&spec.value.to_lowercase(), &submitted.to_lowercase()),
+            Kind::Regex => self
+                .regex(challenge_id, &spec.value)
+                .is_some_and(|re| re.is_match(submitted)),
+            Kind::Dynamic => ct_eq(
+                &self.dynamic_flag(&spec.value, challenge_id, team_id),
+                submitted,
+            ),
+        }
+    }
+
+    /// The flag `team_id` is handed for `challenge`, if it has a dynamic one
+    pub fn team_flag(&self, challenge: &Challenge, team_id: &str) -> Option<String> {
+        dynamic_spec(challenge).map(|spec| self.dynamic_flag(&spec.value, &challenge.id, team_id))
+    }
+
+    /// Which of `teams` `submitted` is the dynamic flag of, a sign the
+    /// submitter got it from them
+    pub fn shared_from<'a>(
+        &self,
+        challenge: &Challenge,
+        submitted: &str,
+        teams: impl IntoIterator<Item = &'a str>,
+    ) -> Option<String> {
+        let spec = dynamic_spec(challenge)?;
+        teams
+            .into_iter()
+            .find(|team| self.matches(spec, &challenge.id, team, submitted))
+            .map(str::to_string)
+    }
+
+    /// The compiled regex flag `pattern` of `challenge_id`, compiling it on first use
+    fn regex(&self, challenge_id: &str, pattern: &str) -> Option<Regex> {
+        let key = (challenge_id.to_string(), pattern.to_string());
+        if let Some(re) = self.regexes.lock().get(&key) {
+            return re.clone();
+        }
+
+        let re = compile(pattern).ok();
+        let mut regexes = self.regexes.lock();
+        // patterns of edited or deleted challenges are never looked up again
+        if regexes.len() >= REGEX_CACHE_SIZE {
+            regexes.clear();
+        }
+        regexes.insert(key, re.clone());
+        re
+    }
+}
+
+/// The challenge's dynamic flag spec, the first one when there are several
+pub fn dynamic_spec(challenge: &Challenge) -> Option<&FlagSpec> {
+    challenge.flags.iter().find(|f| f.kind() == Kind::Dynamic)
+}
+
+fn ct_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
+
+/// Compiles a regex flag so it has to match the whole submission
+fn compile(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(&format!("^(?:{pattern})$"))
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+}
+
+/// Rejects specs that could never be matched
+pub fn validate(spec: &FlagSpec) -> Result<(), Status> {
+    match spec.kind() {
+        Kind::Unspecified => Err(Status::invalid_argument("flag kind is required")),
+        Kind::Static | Kind::CaseInsensitive if spec.value.is_empty() => {
+            Err(Status::invalid_argument("static flags need a value"))
+        }
+        Kind::Regex => compile(&spec.value)
+            .map(|_| ())
+            .map_err(|e| Status::invalid_argument(format!("invalid flag regex: {e}"))),
+        Kind::Dynamic if spec.value.contains(['{', '}']) => Err(Status::invalid_argument(
+            "dynamic flag prefix must not contain braces",
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(kind: Kind, value: &str) -> FlagSpec {
+        FlagSpec {
+            kind: kind as i32,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn matches_each_kind() {
+        let checker = FlagChecker::new(&Secret::random());
+        let check = |spec: &FlagSpec, flag: &str| checker.matches(spec, "c1", "t1", flag);
+
+        let s = spec(Kind::Static, "flag{Exact}");
+        assert!(check(&s, "flag{Exact}"));
+        assert!(!check(&s, "flag{exact}"));
+
+        let s = spec(Kind::CaseInsensitive, "flag{Exact}");
+        assert!(check(&s, "FLAG{exact}"));
+
+        let s = spec(Kind::Regex, r"flag\{[0-9]+\}");
+        assert!(check(&s, "flag{1337}"));
+        assert!(!check(&s, "xflag{1337}"));
+        assert!(!check(&s, "flag{1337}x"));
+
+        assert!(!check(&spec(Kind::Unspecified, ""), ""));
+    }
+
+    #[test]
+    fn regex_flags_compile_once_per_challenge() {
+        let checker = FlagChecker::new(&Secret::random());
+        let s = spec(Kind::Regex, r"flag\{[0-9]+\}");
+
+        assert!(checker.matches(&s, "c1", "t1", "flag{1}"));
+        assert!(!checker.matches(&s, "c1", "t2", "flag{x}"));
+        assert_eq!(checker.regexes.lock().len(), 1);
+
+        assert!(checker.matches(&s, "c2", "t1", "flag{2}"));
+        assert_eq!(checker.regexes.lock().len(), 2);
+    }
+
+    #[test]
+    fn dynamic_flags_differ_per_team() {
+        let checker = FlagChecker::new(&Secret::random());
+        let s = spec(Kind::Dynamic, "ctf");
+
+        let t1 = checker.dynamic_flag("ctf", "c1", "t1");
+        assert!(t1.starts_with("ctf{") && t1.ends_with('}'));
+        assert!(checker.matches(&s, "c1", "t1", &t1));
+        assert!(!checker.matches(&s, "c1", "t2", &t1));
+        assert!(!checker.matches(&s, "c2", "t1", &t1));
+
+        // another secret derives other flags
+        let other = FlagChecker::new(&Secret::random());
+        assert_ne!(other.dynamic_flag("ctf", "c1", "t1"), t1);
+        assert!(checker.dynamic_flag("", "c1", "t1").starts_with("flag{"));
+    }
+
+    #[test]
+    fn finds_the_team_a_flag_belongs_to() {
+        let checker = FlagChecker::new(&Secret::random());
+        let challenge = Challenge {
+            id: "c1".to_string(),
+            flags: vec![spec(Kind::Static, "flag{static}"), spec(Kind::Dynamic, "")],
+            ..Default::default()
+        };
+
+        let flag = checker.team_flag(&challenge, "t2").unwrap();
+        assert_eq!(
+            checker.shared_from(&challenge, &flag, ["t1", "t2", "t3"]),
+            Some("t2".to_string())
+        );
+        assert_eq!(
+            checker.shared_from(&challenge, "flag{static}", ["t1"]),
+            None
+        );
+
+        let static_only = Challenge {
+            flags: vec![spec(Kind::Static, "flag{static}")],
+            ..challenge
+        };
+        assert_eq!(checker.team_flag(&static_only, "t1"), None);
+    }
+
+    #[test]
+    fn validates_specs() {
+        assert!(validate(&spec(Kind::Static, "x")).is_ok());
+        assert!(validate(&spec(Kind::Static, "")).is_err());
+        assert!(validate(&spec(Kind::Regex, "(")).is_err());
+        assert!(validate(&spec(Kind::Regex, "a{100000}")).is_err());
+        assert!(validate(&spec(Kind::Dynamic, "")).is_ok());
+        assert!(validate(&spec(Kind::Dynamic, "a{")).is_err());
+        assert!(validate(&spec(Kind::Unspecified, "x")).is_err());
+    }
+}
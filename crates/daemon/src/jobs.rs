@@ -0,0 +1,158 @@
+//! Jobs the daemon hands to agents, carried as JSON in `Job.payload_json`.
+//! The payloads live in [`ctfjx_proto::jobs`], agents decode them too.
+
+use ctfjx_proto::grpc::{Job, instance_status::State};
+use ctfjx_storage::{Instance, StorageError, Store};
+use serde::Serialize;
+use tonic::Status;
+
+use crate::service::{new_id, now};
+
+pub use ctfjx_proto::jobs::*;
+
+/// Builds a new job of `kind` around `payload`
+pub fn new_job(kind: &str, payload: &impl Serialize) -> Job {
+    Job {
+        job_id: new_id(),
+        r#type: kind.to_string(),
+        payload_json: serde_json::to_string(payload).expect("job payloads serialize"),
+        created_at: Some(now()),
+        ..Default::default()
+    }
+}
+
+/// How a job ended for good
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome<'a> {
+    /// Reported by `agent_id`, with the job's `result_json`
+    Succeeded {
+        agent_id: &'a str,
+        result_json: &'a str,
+    },
+    /// Out of attempts, with the last error
+    Died(&'a str),
+}
+
+/// The payload of a start job, `None` for other jobs
+fn start_payload(job: &Job) -> Option<StartInstancePayload> {
+    if job.r#type != JOB_INSTANCE_START {
+        return None;
+    }
+    serde_json::from_str(&job.payload_json).ok()
+}
+
+/// The instance a start or stop job is about
+pub fn instance_id(job: &Job) -> Option<String> {
+    match job.r#type.as_str() {
+        JOB_INSTANCE_START => start_payload(job).map(|p| p.instance_id),
+        JOB_INSTANCE_STOP => serde_json::from_str::<StopInstancePayload>(&job.payload_json)
+            .ok()
+            .map(|p| p.instance_id),
+        _ => None,
+    }
+}
+
+/// Whether `job` starts an instance that is gone or no longer starting, e.g.
+/// one stopped while an earlier attempt failed
+pub fn obsolete(tx: &dyn Store, job: &Job) -> Result<bool, Status> {
+    let Some(payload) = start_payload(job) else {
+        return Ok(false);
+    };
+    match tx.get_instance(&payload.instance_id) {
+        Ok(instance) => Ok(instance.status.state() != State::Starting),
+        Err(StorageError::NotFound(..)) => Ok(true),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Takes the start jobs of an instance being stopped out of the queue,
+/// within the transaction stopping it. Jobs an agent holds stay, their agent
+/// reports on them before it is handed the stop job. Returns the agent that
+/// may have started the instance and has to stop it, if any
+pub fn withdraw_start(tx: &dyn Store, instance: &Instance) -> Result<Option<String>, Status> {
+    let instance_id = &instance.status.instance_id;
+    let mut queued_start = false;
+    let mut started_on = None;
+    for queued in tx.list_jobs()? {
+        if start_payload(&queued.job).is_none_or(|p| &p.instance_id != instance_id) {
+            continue;
+        }
+        queued_start = true;
+        if queued.leased_to.is_some() {
+            started_on = queued.leased_to;
+            continue;
+        }
+        tx.delete_job(&queued.job.job_id)?;
+        // an agent held it before, see `claim`
+        if queued.job.attempt > 0 {
+            started_on = started_on.or(queued.agent_id);
+        }
+    }
+
+    // the start job settled, on the instance's node
+    if !queued_start && !instance.status.node.is_empty() {
+        started_on = Some(instance.status.node.clone());
+    }
+    Ok(started_on)
+}
+
+/// Records `agent_id` taking the start job of an instance, within the
+/// transaction leasing the job. Returns whether the job has to stay with that
+/// agent from now on, which may have started the instance by the time it
+/// lets go of the job
+pub fn claim(tx: &dyn Store, job: &Job, agent_id: &str) -> Result<bool, Status> {
+    let Some(payload) = start_payload(job) else {
+        return Ok(false);
+    };
+    let mut instance = match tx.get_instance(&payload.instance_id) {
+        Ok(instance) => instance,
+        Err(StorageError::NotFound(..)) => return Ok(true),
+        Err(e) => return Err(e.into()),
+    };
+    if instance.status.state() == State::Starting && instance.status.node != agent_id {
+        instance.status.node = agent_id.to_string();
+        instance.status.message = "waiting for the agent".to_string();
+        tx.update_instance(&instance)?;
+    }
+    Ok(true)
+}
+
+/// Applies the outcome of `job` to the instance it started, within the
+/// transaction settling the job. Returns the instance when it changed, not
+/// touching ones that moved on from starting, or to another agent, meanwhile
+pub fn settle(tx: &dyn Store, job: &Job, outcome: Outcome<'_>) -> Result<Option<Instance>, Status> {
+    let Some(payload) = start_payload(job) else {
+        return Ok(None);
+    };
+    let mut instance = match tx.get_instance(&payload.instance_id) {
+        Ok(instance) => instance,
+        Err(StorageError::NotFound(..)) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if instance.status.state() != State::Starting {
+        return Ok(None);
+    }
+
+    match outcome {
+        Outcome::Succeeded { agent_id, .. } if instance.status.node != agent_id => {
+            return Ok(None);
+        }
+        Outcome::Succeeded { result_json, .. } => {
+            // the instance runs either way, it is only its port that is lost
+            let started =
+                serde_json::from_str::<StartInstanceResult>(result_json).unwrap_or_else(|e| {
+                    tracing::warn!(job_id = job.job_id, error = %e, "invalid start result");
+                    StartInstanceResult::default()
+                });
+            instance.status.set_state(State::Running);
+            instance.status.port = i32::from(started.port);
+            instance.status.message = "running".to_string();
+        }
+        Outcome::Died(error) => {
+            instance.status.set_state(State::Failed);
+            instance.status.message = format!("could not start: {error}");
+        }
+    }
+    tx.update_instance(&instance)?;
+    Ok(Some(instance))
+}
@@ -0,0 +1,19 @@
+pub mod auth;
+pub mod config;
+pub mod error;
+pub mod events;
+pub mod expiry;
+pub mod flags;
+pub mod jobs;
+pub mod liveness;
+pub mod mask;
+pub mod paging;
+pub mod policy;
+pub mod queue;
+pub mod scheduler;
+pub mod scoring;
+pub mod secret;
+pub mod server;
+pub mod service;
+
+pub use error::Error;
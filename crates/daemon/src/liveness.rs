@@ -0,0 +1,82 @@
+//! When agents that stopped sending heartbeats are given up on.
+//!
+//! An agent is heard from whenever it registers, polls for jobs or sends a
+//! heartbeat. Silent for [`LivenessConfig::suspect_after`] it is suspect and
+//! no longer placed on, silent for [`LivenessConfig::dead_after`] it is dead
+//! and its instances are failed, or moved to healthy agents when
+//! [`LivenessConfig::reschedule`] is set.
+
+use std::time::{Duration, SystemTime};
+
+use ctfjx_storage::{Agent, Liveness};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LivenessConfig {
+    pub suspect_after: Duration,
+    pub dead_after: Duration,
+    /// How often agents are checked on
+    pub check_every: Duration,
+    /// Whether instances of dead agents are started again elsewhere
+    pub reschedule: bool,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            suspect_after: Duration::from_secs(30),
+            dead_after: Duration::from_secs(90),
+            check_every: Duration::from_secs(5),
+            reschedule: false,
+        }
+    }
+}
+
+impl LivenessConfig {
+    /// What `agent` should be at `now`, judging by when it was last seen
+    pub fn assess(&self, agent: &Agent, now: SystemTime) -> Liveness {
+        let Some(last_seen) = agent.last_seen else {
+            return Liveness::Dead;
+        };
+        let silent = SystemTime::try_from(last_seen)
+            .ok()
+            .and_then(|at| now.duration_since(at).ok())
+            .unwrap_or_default();
+        if silent >= self.dead_after {
+            Liveness::Dead
+        } else if silent >= self.suspect_after {
+            Liveness::Suspect
+        } else {
+            Liveness::Healthy
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silence_makes_agents_suspect_then_dead() {
+        let config = LivenessConfig::default();
+        let t0 = SystemTime::now();
+        let agent = Agent {
+            last_seen: Some(t0.into()),
+            ..Default::default()
+        };
+        assert_eq!(config.assess(&agent, t0), Liveness::Healthy);
+        // clocks going backwards do not count as silence
+        assert_eq!(
+            config.assess(&agent, t0 - Duration::from_secs(60)),
+            Liveness::Healthy
+        );
+        assert_eq!(
+            config.assess(&agent, t0 + Duration::from_secs(30)),
+            Liveness::Suspect
+        );
+        assert_eq!(
+            config.assess(&agent, t0 + Duration::from_secs(90)),
+            Liveness::Dead
+        );
+        assert_eq!(config.assess(&Agent::default(), t0), Liveness::Dead);
+    }
+}
@@ -1,2 +1,40 @@
+use ctfjx_common::env::ResolveEnv;
+use ctfjxd::{config::Config, server};
+use tracing_subscriber::EnvFilter;
+
 #[tokio::main]
-async fn main() {}
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+
+    let config = match Config::resolve_and_validate() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("invalid config: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = server::run(config, shutdown_signal()).await {
+        tracing::error!("{e}");
+        std::process::exit(1);
+    }
+}
+
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut term = signal(SignalKind::terminate()).expect("install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+
+    tracing::info!("shutting down");
+}
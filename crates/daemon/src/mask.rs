@@ -0,0 +1,216 @@
+//! `google.protobuf.FieldMask` support for partial challenge updates.
+
+use ctfjx_proto::grpc::Challenge;
+use tonic::Status;
+
+/// A field of [`Challenge`] a mask may name
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Path {
+    Name,
+    Description,
+    Owner,
+    Difficulty,
+    Tags,
+    /// The whole map is replaced
+    Metadata,
+    /// A single key is set, or removed when the patch does not have it
+    MetadataKey(String),
+    Points,
+    Scoring,
+    Requirements,
+    Runtime,
+    /// Only ever replaced when named, see [`ALL`]
+    Flags,
+}
+
+/// What an empty mask replaces. `flags` is left out, challenges are handed
+/// out with their flags redacted and sending one back must not wipe them
+const ALL: &[Path] = &[
+    Path::Name,
+    Path::Description,
+    Path::Owner,
+    Path::Difficulty,
+    Path::Tags,
+    Path::Metadata,
+    Path::Points,
+    Path::Scoring,
+    Path::Requirements,
+    Path::Runtime,
+];
+
+/// Which fields of a patch an update applies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChallengeMask(Vec<Path>);
+
+impl ChallengeMask {
+    /// Parses mask paths, no paths or `*` meaning every updatable field but
+    /// `flags`
+    pub fn parse(paths: &[String]) -> Result<Self, Status> {
+        if paths.is_empty() || paths.iter().any(|p| p == "*") {
+            return Ok(Self(ALL.to_vec()));
+        }
+        paths
+            .iter()
+            .map(|p| parse_path(p))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Copies the masked fields of `patch` onto `target`
+    pub fn apply(&self, target: &mut Challenge, patch: &Challenge) {
+        for path in &self.0 {
+            match path {
+                Path::Name => target.name.clone_from(&patch.name),
+                Path::Description => target.description.clone_from(&patch.description),
+                Path::Owner => target.owner.clone_from(&patch.owner),
+                Path::Difficulty => target.difficulty.clone_from(&patch.difficulty),
+                Path::Tags => target.tags.clone_from(&patch.tags),
+                Path::Metadata => target.metadata.clone_from(&patch.metadata),
+                Path::Points => target.points = patch.points,
+                Path::Scoring => target.scoring.clone_from(&patch.scoring),
+                Path::Requirements => target.requirements.clone_from(&patch.requirements),
+                Path::Runtime => target.runtime.clone_from(&patch.runtime),
+                Path::Flags => target.flags.clone_from(&patch.flags),
+                Path::MetadataKey(key) => match patch.metadata.get(key) {
+                    Some(value) => {
+                        target.metadata.insert(key.clone(), value.clone());
+                    }
+                    None => {
+                        target.metadata.remove(key);
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn parse_path(path: &str) -> Result<Path, Status> {
+    let parsed = match path.split_once('.') {
+        None => match path {
+            "name" => Path::Name,
+            "description" => Path::Description,
+            "owner" => Path::Owner,
+            "difficulty" => Path::Difficulty,
+            "tags" => Path::Tags,
+            "metadata" => Path::Metadata,
+            "points" => Path::Points,
+            "scoring" => Path::Scoring,
+            "requirements" => Path::Requirements,
+            "runtime" => Path::Runtime,
+            "flags" => Path::Flags,
+            "id" | "created_at" | "updated_at" => {
+                return Err(Status::invalid_argument(format!(
+                    "`{path}` cannot be updated"
+                )));
+            }
+            _ => return Err(unknown(path)),
+        },
+        Some(("metadata", key)) => {
+            // keys that are not plain identifiers are quoted in backticks
+            let key = key
+                .strip_prefix('`')
+                .and_then(|k| k.strip_suffix('`'))
+                .unwrap_or(key);
+            if key.is_empty() {
+                return Err(unknown(path));
+            }
+            Path::MetadataKey(key.to_string())
+        }
+        Some(_) => return Err(unknown(path)),
+    };
+    Ok(parsed)
+}
+
+fn unknown(path: &str) -> Status {
+    Status::invalid_argument(format!("unknown update_mask path `{path}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ctfjx_proto::grpc::{FlagSpec, flag_spec};
+
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    fn stored() -> Challenge {
+        Challenge {
+            id: "c1".to_string(),
+            name: "old".to_string(),
+            tags: vec!["web".to_string()],
+            metadata: HashMap::from([
+                ("author".to_string(), "a".to_string()),
+                ("a.b".to_string(), "dotted".to_string()),
+                ("keep".to_string(), "me".to_string()),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn applies_only_masked_paths() {
+        let patch = Challenge {
+            name: "ignored".to_string(),
+            tags: vec!["pwn".to_string()],
+            metadata: HashMap::from([("author".to_string(), "b".to_string())]),
+            ..Default::default()
+        };
+        let mut target = stored();
+        ChallengeMask::parse(&paths(&["tags", "metadata.author", "metadata.`a.b`"]))
+            .unwrap()
+            .apply(&mut target, &patch);
+
+        assert_eq!(target.name, "old");
+        assert_eq!(target.tags, ["pwn"]);
+        assert_eq!(
+            target.metadata,
+            HashMap::from([
+                ("author".to_string(), "b".to_string()),
+                ("keep".to_string(), "me".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_mask_replaces_everything_but_flags() {
+        let flag = FlagSpec {
+            kind: flag_spec::Kind::Static as i32,
+            value: "flag{x}".to_string(),
+        };
+        let patch = Challenge {
+            id: "c1".to_string(),
+            name: "new".to_string(),
+            ..Default::default()
+        };
+        for mask in [paths(&[]), paths(&["*"])] {
+            let mut target = stored();
+            target.flags = vec![flag.clone()];
+            ChallengeMask::parse(&mask)
+                .unwrap()
+                .apply(&mut target, &patch);
+            assert_eq!(target.flags, std::slice::from_ref(&flag));
+            target.flags.clear();
+            assert_eq!(target, patch);
+        }
+
+        let mut target = stored();
+        target.flags = vec![flag];
+        ChallengeMask::parse(&paths(&["flags"]))
+            .unwrap()
+            .apply(&mut target, &patch);
+        assert!(target.flags.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_paths() {
+        for bad in ["score", "id", "updated_at", "metadata.", "tags.0", "name.x"] {
+            let err = ChallengeMask::parse(&paths(&["name", bad])).unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument, "{bad}");
+            assert!(err.message().contains(bad), "{bad}: {}", err.message());
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Keyset pagination for List RPCs.
+//!
+//! A page token is `base64url(payload || hmac)`, the payload naming the list
+//! it belongs to, the key of the last record handed out and a hash of the
+//! filter it was issued for. Tokens are opaque to clients, tampering with one
+//! or reusing it with another filter or list is rejected.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ctfjx_storage::Page;
+use hmac::Mac;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tonic::Status;
+
+use crate::secret::{HmacSha256, Secret};
+
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+const MAC_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    /// Which list the token continues
+    #[serde(rename = "l")]
+    list: String,
+    /// Key of the last record of the previous page
+    #[serde(rename = "a")]
+    after: String,
+    /// Hash of the filter the token was issued for
+    #[serde(rename = "f")]
+    filter: String,
+}
+
+fn filter_hash(filter: &str) -> String {
+    hex::encode(&Sha256::digest(filter.trim().as_bytes())[..16])
+}
+
+#[derive(Clone)]
+pub struct PageTokens {
+    key: [u8; 32],
+}
+
+impl PageTokens {
+    pub fn new(secret: &Secret) -> Self {
+        Self {
+            key: secret.derive("page-token"),
+        }
+    }
+
+    fn mac(&self) -> HmacSha256 {
+        HmacSha256::new_from_slice(&self.key).expect("hmac takes any key size")
+    }
+
+    /// A token continuing `list` after `after`, for requests using `filter`
+    pub fn encode(&self, list: &str, after: &str, filter: &str) -> String {
+        let payload = serde_json::to_vec(&Payload {
+            list: list.to_string(),
+            after: after.to_string(),
+            filter: filter_hash(filter),
+        })
+        .expect("page token payloads serialize");
+
+        let mut mac = self.mac();
+        mac.update(&payload);
+        let mut token = payload;
+        token.extend_from_slice(&mac.finalize().into_bytes());
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// The key to continue after, `None` for the first page
+    pub fn decode(&self, list: &str, token: &str, filter: &str) -> Result<Option<String>, Status> {
+        if token.is_empty() {
+            return Ok(None);
+        }
+        let invalid = || Status::invalid_argument("invalid page token");
+
+        let raw = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let split = raw.len().checked_sub(MAC_LEN).ok_or_else(invalid)?;
+        let (payload, tag) = raw.split_at(split);
+
+        let mut mac = self.mac();
+        mac.update(payload);
+        mac.verify_slice(tag).map_err(|_| invalid())?;
+
+        let payload: Payload = serde_json::from_slice(payload).map_err(|_| invalid())?;
+        if payload.list != list {
+            return Err(invalid());
+        }
+        if payload.filter != filter_hash(filter) {
+            return Err(Status::invalid_argument(
+                "page token was issued for a different filter",
+            ));
+        }
+        Ok(Some(payload.after))
+    }
+
+    /// The page a request asks for, fetching one extra record to tell
+    /// whether another page follows
+    pub fn page(
+        &self,
+        list: &str,
+        page_size: i32,
+        token: &str,
+        filter: &str,
+    ) -> Result<(Page, usize), Status> {
+        let size = page_size_or_default(page_size)?;
+        let after = self.decode(list, token, filter)?;
+        Ok((
+            Page {
+                after,
+                limit: size + 1,
+            },
+            size,
+        ))
+    }
+
+    /// Trims `records` down to `size`, returning the token for the next page
+    /// when there is one
+    pub fn finish<T>(
+        &self,
+        list: &str,
+        records: &mut Vec<T>,
+        size: usize,
+        filter: &str,
+        key: impl Fn(&T) -> &str,
+    ) -> String {
+        if records.len() <= size {
+            return String::new();
+        }
+        records.truncate(size);
+        records
+            .last()
+            .map(|last| self.encode(list, key(last), filter))
+            .unwrap_or_default()
+    }
+}
+
+/// `page_size` from a request, zero picks the default and large sizes are capped
+pub fn page_size_or_default(page_size: i32) -> Result<usize, Status> {
+    match page_size {
+        n if n < 0 => Err(Status::invalid_argument("page_size must not be negative")),
+        0 => Ok(DEFAULT_PAGE_SIZE),
+        n => Ok((n as usize).min(MAX_PAGE_SIZE)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> PageTokens {
+        PageTokens::new(&Secret::random())
+    }
+
+    #[test]
+    fn roundtrip() {
+        let t = tokens();
+        let token = t.encode("challenges", "abc", "tags:web");
+        assert_eq!(
+            t.decode("challenges", &token, " tags:web ").unwrap(),
+            Some("abc".to_string())
+        );
+        assert_eq!(t.decode("challenges", "", "").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_misuse() {
+        let t = tokens();
+        let token = t.encode("challenges", "abc", "tags:web");
+
+        let err = t.decode("challenges", &token, "tags:pwn").unwrap_err();
+        assert!(err.message().contains("different filter"));
+        assert!(t.decode("instances", &token, "tags:web").is_err());
+        assert!(tokens().decode("challenges", &token, "tags:web").is_err());
+        assert!(t.decode("challenges", "not a token", "").is_err());
+
+        let mut raw = URL_SAFE_NO_PAD.decode(&token).unwrap();
+        raw[8] ^= 1;
+        let tampered = URL_SAFE_NO_PAD.encode(raw);
+        assert!(t.decode("challenges", &tampered, "tags:web").is_err());
+    }
+
+    #[test]
+    fn page_sizes() {
+        assert_eq!(page_size_or_default(0).unwrap(), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size_or_default(7).unwrap(), 7);
+        assert_eq!(page_size_or_default(i32::MAX).unwrap(), MAX_PAGE_SIZE);
+        assert!(page_size_or_default(-1).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! Who may call which RPC.
+//!
+//! Every method of `ServiceCtfjx` has a [`Policy`] in [`Method::policy`]:
+//! whether anonymous callers get in, the scope the credential needs, the
+//! roles allowed and which of those roles are limited to what they own.
+//! Ownership depends on the request, handlers check it through
+//! [`Access::check_owner`] once they know the resource.
+
+use std::fmt;
+
+use ctfjx_proto::grpc::user::Role;
+use tonic::Status;
+
+use crate::auth::{Caller, Scope};
+
+/// Every role, for methods anyone may call
+const ANYONE: &[Role] = &[Role::Admin, Role::Author, Role::Player, Role::Agent];
+/// People, as opposed to agents
+const USERS: &[Role] = &[Role::Admin, Role::Author, Role::Player];
+const STAFF: &[Role] = &[Role::Admin, Role::Author];
+const PLAYERS: &[Role] = &[Role::Admin, Role::Player];
+const AGENTS: &[Role] = &[Role::Admin, Role::Agent];
+/// Agents only, which still need an enrolled credential on top of the role
+const ENROLLED: &[Role] = &[Role::Agent];
+const ADMINS: &[Role] = &[Role::Admin];
+
+/// The role a user acts with, players being the default
+pub fn effective_role(role: Role) -> Role {
+    match role {
+        Role::Unspecified => Role::Player,
+        role => role,
+    }
+}
+
+pub fn role_name(role: Role) -> &'static str {
+    match effective_role(role) {
+        Role::Unspecified | Role::Player => "player",
+        Role::Author => "author",
+        Role::Admin => "admin",
+        Role::Agent => "agent",
+    }
+}
+
+/// The broadest scope a token of a `role` user may carry
+pub fn max_scope(role: Role) -> Scope {
+    match effective_role(role) {
+        Role::Admin => Scope::Admin,
+        _ => Scope::Write,
+    }
+}
+
+/// What calling a method takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// Whether callers without a token get in
+    pub anonymous: bool,
+    pub scope: Scope,
+    pub roles: &'static [Role],
+    /// Roles among `roles` that may only act on what they own
+    pub owned: &'static [Role],
+}
+
+const fn public() -> Policy {
+    Policy {
+        anonymous: true,
+        scope: Scope::Read,
+        roles: ANYONE,
+        owned: &[],
+    }
+}
+
+const fn allow(scope: Scope, roles: &'static [Role]) -> Policy {
+    Policy {
+        anonymous: false,
+        scope,
+        roles,
+        owned: &[],
+    }
+}
+
+const fn own(scope: Scope, roles: &'static [Role], owned: &'static [Role]) -> Policy {
+    Policy {
+        anonymous: false,
+        scope,
+        roles,
+        owned,
+    }
+}
+
+macro_rules! methods {
+    ($($method:ident),* $(,)?) => {
+        /// A method of `ServiceCtfjx`
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Method {
+            $($method),*
+        }
+
+        impl Method {
+            pub const ALL: &[Method] = &[$(Method::$method),*];
+
+            /// The name of the RPC in the proto
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Method::$method => stringify!($method)),*
+                }
+            }
+        }
+    };
+}
+
+methods! {
+    Ping,
+    CreateChallenge,
+    GetChallenge,
+    UpdateChallenge,
+    DeleteChallenge,
+    ListChallenges,
+    CreateUser,
+    GetUser,
+    CreateTeam,
+    GetTeam,
+    ListTeams,
+    JoinTeam,
+    LeaveTeam,
+    TransferCaptain,
+    ResetInviteCode,
+    CreateToken,
+    ListTokens,
+    RevokeToken,
+    CreateSession,
+    SubmitFlag,
+    InvalidateSolve,
+    GetScoreboard,
+    WatchScoreboard,
+    FreezeScoreboard,
+    UnfreezeScoreboard,
+    SetTeamDivision,
+    StartInstance,
+    StopInstance,
+    GetInstanceStatus,
+    ExtendInstance,
+    CreateJoinToken,
+    EnrollAgent,
+    RotateAgentKey,
+    ListAgentCredentials,
+    RevokeAgentCredential,
+    RegisterAgent,
+    AssignJob,
+    ReportJobResult,
+    SendHeartbeat,
+    ListDeadJobs,
+    StreamEvents,
+    AgentStream,
+}
+
+impl Method {
+    /// The permission table
+    pub const fn policy(self) -> Policy {
+        use Method::*;
+        use Scope::{Admin, Read, Write};
+
+        match self {
+            // agents enroll with a join token rather than a bearer token
+            Ping | GetScoreboard | WatchScoreboard | EnrollAgent => public(),
+
+            // authors own the challenges they create
+            CreateChallenge | UpdateChallenge | DeleteChallenge => {
+                own(Write, STAFF, &[Role::Author])
+            }
+            GetChallenge | ListChallenges => allow(Read, USERS),
+
+            CreateUser => allow(Admin, ADMINS),
+            GetUser | GetTeam | ListTeams => allow(Read, USERS),
+            // players act for themselves, or as the captain of their team
+            CreateTeam | JoinTeam | LeaveTeam | TransferCaptain | ResetInviteCode => {
+                own(Write, PLAYERS, &[Role::Player])
+            }
+
+            // only admins manage the tokens of other users
+            CreateToken | RevokeToken => own(Write, USERS, &[Role::Author, Role::Player]),
+            ListTokens => own(Read, USERS, &[Role::Author, Role::Player]),
+            CreateSession => allow(Read, USERS),
+
+            // players submit and run instances for their own team
+            SubmitFlag | StartInstance | StopInstance | ExtendInstance => {
+                own(Write, PLAYERS, &[Role::Player])
+            }
+            GetInstanceStatus => own(Read, USERS, &[Role::Player]),
+            InvalidateSolve | FreezeScoreboard | UnfreezeScoreboard | SetTeamDivision => {
+                allow(Admin, ADMINS)
+            }
+
+            CreateJoinToken | ListAgentCredentials | RevokeAgentCredential => allow(Admin, ADMINS),
+            RotateAgentKey => allow(Write, ENROLLED),
+            // agents act under their own agent_id only, registering and
+            // streaming as the agent itself
+            RegisterAgent | AgentStream => own(Write, ENROLLED, ENROLLED),
+            AssignJob | ReportJobResult | SendHeartbeat => own(Write, AGENTS, &[Role::Agent]),
+            ListDeadJobs => allow(Admin, ADMINS),
+            StreamEvents => allow(Read, STAFF),
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What [`authorize`] let through
+#[derive(Debug, Clone)]
+pub struct Access {
+    caller: Option<Caller>,
+    /// Whether the caller may only act on what it owns
+    restricted: bool,
+}
+
+impl Access {
+    /// The caller, `None` when anonymous
+    pub fn caller(&self) -> Option<&Caller> {
+        self.caller.as_ref()
+    }
+
+    /// The caller of a method that needs one
+    pub fn required_caller(&self) -> Result<&Caller, Status> {
+        self.caller()
+            .ok_or_else(|| Status::unauthenticated("a bearer token is required"))
+    }
+
+    /// Whether the caller may see secrets such as flags
+    pub fn is_admin(&self) -> bool {
+        self.caller.as_ref().is_some_and(Caller::is_admin)
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.restricted
+    }
+
+    /// Whether `owner` is the calling user
+    pub fn owns(&self, owner: &str) -> bool {
+        self.caller
+            .as_ref()
+            .is_some_and(|c| !c.user_id.is_empty() && c.user_id == owner)
+    }
+
+    /// Lets restricted callers through only when `owns` holds for their
+    /// user id
+    pub fn check_owner(&self, owns: impl FnOnce(&str) -> bool) -> Result<(), Status> {
+        match &self.caller {
+            Some(caller) if self.restricted && !owns(&caller.user_id) => {
+                Err(Status::permission_denied(format!(
+                    "{}s may only act on what they own",
+                    role_name(caller.role)
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Checks `caller` against the policy of `method`
+pub fn authorize(caller: Option<&Caller>, method: Method) -> Result<Access, Status> {
+    let policy = method.policy();
+    let Some(caller) = caller else {
+        if policy.anonymous {
+            return Ok(Access {
+                caller: None,
+                restricted: false,
+            });
+        }
+        return Err(Status::unauthenticated("a bearer token is required"));
+    };
+
+    let role = effective_role(caller.role);
+    if !policy.roles.contains(&role) {
+        return Err(Status::permission_denied(format!(
+            "{}s may not call {method}",
+            role_name(role)
+        )));
+    }
+    if !caller.has(policy.scope) {
+        return Err(Status::permission_denied(format!(
+            "{method} requires the `{}` scope",
+            policy.scope
+        )));
+    }
+    Ok(Access {
+        caller: Some(caller.clone()),
+        restricted: policy.owned.contains(&role),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use regex::Regex;
+
+    use super::*;
+
+    fn caller(role: Role, scope: Scope) -> Caller {
+        Caller {
+            user_id: "u1".to_string(),
+            role,
+            scope,
+            token_id: None,
+            credential_id: None,
+        }
+    }
+
+    /// What each role gets out of each method with the broadest token it
+    /// may hold: `A`llowed, `O`wn resources only or `-` denied, for admins,
+    /// authors, players and agents in that order
+    const EXPECTED: &[(Method, bool, Scope, &str)] = {
+        use Method::*;
+        use Scope::{Admin, Read, Write};
+        &[
+            (Ping, true, Read, "AAAA"),
+            (CreateChallenge, false, Write, "AO--"),
+            (GetChallenge, false, Read, "AAA-"),
+            (UpdateChallenge, false, Write, "AO--"),
+            (DeleteChallenge, false, Write, "AO--"),
+            (ListChallenges, false, Read, "AAA-"),
+            (CreateUser, false, Admin, "A---"),
+            (GetUser, false, Read, "AAA-"),
+            (CreateTeam, false, Write, "A-O-"),
+            (GetTeam, false, Read, "AAA-"),
+            (ListTeams, false, Read, "AAA-"),
+            (JoinTeam, false, Write, "A-O-"),
+            (LeaveTeam, false, Write, "A-O-"),
+            (TransferCaptain, false, Write, "A-O-"),
+            (ResetInviteCode, false, Write, "A-O-"),
+            (CreateToken, false, Write, "AOO-"),
+            (ListTokens, false, Read, "AOO-"),
+            (RevokeToken, false, Write, "AOO-"),
+            (CreateSession, false, Read, "AAA-"),
+            (SubmitFlag, false, Write, "A-O-"),
+            (InvalidateSolve, false, Admin, "A---"),
+            (GetScoreboard, true, Read, "AAAA"),
+            (WatchScoreboard, true, Read, "AAAA"),
+            (FreezeScoreboard, false, Admin, "A---"),
+            (UnfreezeScoreboard, false, Admin, "A---"),
+            (SetTeamDivision, false, Admin, "A---"),
+            (StartInstance, false, Write, "A-O-"),
+            (StopInstance, false, Write, "A-O-"),
+            (GetInstanceStatus, false, Read, "AAO-"),
+            (ExtendInstance, false, Write, "A-O-"),
+            (CreateJoinToken, false, Admin, "A---"),
+            (EnrollAgent, true, Read, "AAAA"),
+            (RotateAgentKey, false, Write, "---A"),
+            (ListAgentCredentials, false, Admin, "A---"),
+            (RevokeAgentCredential, false, Admin, "A---"),
+            (RegisterAgent, false, Write, "---O"),
+            (AssignJob, false, Write, "A--O"),
+            (ReportJobResult, false, Write, "A--O"),
+            (SendHeartbeat, false, Write, "A--O"),
+            (ListDeadJobs, false, Admin, "A---"),
+            (StreamEvents, false, Read, "AA--"),
+            (AgentStream, false, Write, "---O"),
+        ]
+    };
+
+    #[test]
+    fn every_rpc_has_a_policy() {
+        let proto = include_str!("../../proto/proto/ctfjx.proto");
+        let rpcs: BTreeSet<_> = Regex::new(r"rpc (\w+)\(")
+            .unwrap()
+            .captures_iter(proto)
+            .map(|c| c.get(1).unwrap().as_str())
+            .collect();
+        let methods: BTreeSet<_> = Method::ALL.iter().map(|m| m.name()).collect();
+        assert_eq!(rpcs, methods);
+
+        let expected: Vec<_> = EXPECTED.iter().map(|(m, ..)| *m).collect();
+        assert_eq!(expected, Method::ALL);
+    }
+
+    #[test]
+    fn methods_follow_the_table() {
+        let roles = [Role::Admin, Role::Author, Role::Player, Role::Agent];
+        for &(method, anonymous, scope, grants) in EXPECTED {
+            assert_eq!(
+                authorize(None, method).is_ok(),
+                anonymous,
+                "{method} anonymously"
+            );
+            for (role, grant) in roles.iter().zip(grants.chars()) {
+                let got = authorize(Some(&caller(*role, max_scope(*role))), method);
+                let got = match got {
+                    Ok(access) if access.is_restricted() => 'O',
+                    Ok(_) => 'A',
+                    Err(e) => {
+                        assert_eq!(e.code(), tonic::Code::PermissionDenied);
+                        '-'
+                    }
+                };
+                assert_eq!(got, grant, "{method} as {}", role_name(*role));
+            }
+
+            // the scope is needed on top of the role
+            assert_eq!(method.policy().scope, scope, "{method}");
+            if scope > Scope::Read {
+                let weaker = if scope == Scope::Admin {
+                    Scope::Write
+                } else {
+                    Scope::Read
+                };
+                assert!(
+                    authorize(Some(&caller(Role::Admin, weaker)), method).is_err(),
+                    "{method} with a `{weaker}` token"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn restricted_callers_act_on_their_own() {
+        let author = caller(Role::Author, Scope::Write);
+        let access = authorize(Some(&author), Method::UpdateChallenge).unwrap();
+        assert!(access.check_owner(|u| u == "u1").is_ok());
+        assert_eq!(
+            access.check_owner(|u| u == "u2").unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+
+        let admin = caller(Role::Admin, Scope::Admin);
+        let access = authorize(Some(&admin), Method::UpdateChallenge).unwrap();
+        assert!(access.is_admin());
+        assert!(access.check_owner(|u| u == "u2").is_ok());
+
+        // unset roles are players
+        let nobody = caller(Role::Unspecified, Scope::Write);
+        assert!(authorize(Some(&nobody), Method::SubmitFlag).is_ok());
+        assert!(authorize(Some(&nobody), Method::CreateChallenge).is_err());
+    }
+}
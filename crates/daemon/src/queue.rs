@@ -0,0 +1,678 @@
+//! The durable job queue agents pull from.
+//!
+//! Handing a job out leases it to the agent for [`QueueConfig::lease`]. A
+//! successful `JobResult` removes the job, a failed one or a lapsed lease
+//! counts as a failed attempt: the job is offered again after an exponential
+//! backoff, and moved to the dead-letter list once it used up
+//! [`QueueConfig::max_attempts`]. Lapsed leases are noticed whenever the queue
+//! is read, so nothing has to run in the background.
+//!
+//! Agents holding an `AgentStream` are woken through [`JobQueue::subscribe`]
+//! whenever a job may have become available. Leasing a start job binds its
+//! instance to the agent, see [`jobs::claim`], and settling it for good
+//! carries its outcome over to the instance, see [`jobs::settle`].
+
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use ctfjx_proto::grpc::{DeadJob, Job, JobResult};
+use ctfjx_storage::{Agent, Instance, QueuedJob, Storage, Store, ts_key};
+use prost_wkt_types::Timestamp;
+use tokio::sync::watch;
+use tonic::Status;
+
+use crate::{
+    events::{self, EventBus},
+    jobs::{self, Outcome},
+    scheduler,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueConfig {
+    /// How long an agent has to report on a job
+    pub lease: Duration,
+    /// Attempts before a job is dead-lettered, at least 1
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling with every failed attempt
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            lease: Duration::from_secs(5 * 60),
+            max_attempts: 5,
+            backoff: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+impl QueueConfig {
+    /// Wait before handing out a job again after its `attempt`th failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(16);
+        self.backoff
+            .saturating_mul(1 << doublings)
+            .min(self.max_backoff)
+    }
+}
+
+#[derive(Clone)]
+pub struct JobQueue {
+    store: Arc<dyn Storage>,
+    config: QueueConfig,
+    wake: Arc<watch::Sender<()>>,
+    events: EventBus,
+}
+
+/// `job` as queued before its first attempt
+fn fresh(job: Job, agent_id: Option<String>, requirements: Vec<String>) -> QueuedJob {
+    QueuedJob {
+        job: Job {
+            attempt: 0,
+            lease_expires_at: None,
+            ..job
+        },
+        agent_id,
+        requirements,
+        ..Default::default()
+    }
+}
+
+/// Whether `at` is set and no later than `now`
+fn passed(at: Option<&Timestamp>, now: &Timestamp) -> bool {
+    at.is_some_and(|at| ts_key(at) <= ts_key(now))
+}
+
+impl JobQueue {
+    /// A queue announcing the instances its jobs settled on `events`
+    pub fn new(store: Arc<dyn Storage>, events: EventBus, config: QueueConfig) -> Self {
+        Self {
+            store,
+            config,
+            wake: Arc::new(watch::channel(()).0),
+            events,
+        }
+    }
+
+    pub fn config(&self) -> &QueueConfig {
+        &self.config
+    }
+
+    /// Queues `job` for `agent_id`, or any agent when `None`
+    pub fn enqueue(&self, job: Job, agent_id: Option<String>) -> Result<(), Status> {
+        self.store.enqueue_job(&fresh(job, agent_id, Vec::new()))?;
+        self.wake();
+        Ok(())
+    }
+
+    /// Queues `job` for the first agent to meet all of `requirements`
+    pub fn enqueue_unplaced(&self, job: Job, requirements: Vec<String>) -> Result<(), Status> {
+        self.store.enqueue_job(&fresh(job, None, requirements))?;
+        self.wake();
+        Ok(())
+    }
+
+    /// Queues `job` within `tx`, for `agent_id` or else the first agent to
+    /// meet all of `requirements`. Agents are only told with [`Self::wake`],
+    /// once `tx` committed
+    pub fn enqueue_in(
+        &self,
+        tx: &dyn Store,
+        job: Job,
+        agent_id: Option<String>,
+        requirements: Vec<String>,
+    ) -> Result<(), Status> {
+        Ok(tx.enqueue_job(&fresh(job, agent_id, requirements))?)
+    }
+
+    /// Tells agents jobs may have become available
+    pub fn wake(&self) {
+        self.wake.send_replace(());
+    }
+
+    /// Changes whenever jobs were queued or went back to the queue. Jobs
+    /// backing off become available without a wake up
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.wake.subscribe()
+    }
+
+    /// Leases up to `max` jobs to `agent`, oldest first
+    pub fn lease(&self, agent: &Agent, max: usize) -> Result<Vec<Job>, Status> {
+        self.lease_at(agent, max, SystemTime::now())
+    }
+
+    fn lease_at(&self, agent: &Agent, max: usize, at: SystemTime) -> Result<Vec<Job>, Status> {
+        let agent_id = agent.agent_id.as_str();
+        let (leased, settled) = self.store.atomically(|tx| {
+            let mut settled = self.reap_in(tx, at)?;
+            let now = Timestamp::from(at);
+            let queued_jobs = tx.list_jobs()?;
+            // agents run their jobs side by side, stopping an instance waits
+            // for the agent starting it to report back
+            let mut starting: HashSet<String> = queued_jobs
+                .iter()
+                .filter(|q| q.leased_to.is_some() && q.job.r#type == jobs::JOB_INSTANCE_START)
+                .filter_map(|q| jobs::instance_id(&q.job))
+                .collect();
+            let mut leased = Vec::new();
+            for mut queued in queued_jobs {
+                if leased.len() >= max {
+                    break;
+                }
+                if queued.leased_to.is_none() && jobs::obsolete(tx, &queued.job)? {
+                    tx.delete_job(&queued.job.job_id)?;
+                    continue;
+                }
+                // e.g. queued before `max_attempts` was lowered
+                if queued.leased_to.is_none() && self.exhausted(&queued) {
+                    settled.extend(self.fail(tx, queued, "out of attempts", at)?);
+                    continue;
+                }
+                let waiting = queued.job.r#type == jobs::JOB_INSTANCE_STOP
+                    && jobs::instance_id(&queued.job).is_some_and(|id| starting.contains(&id));
+                let eligible = !waiting
+                    && queued.leased_to.is_none()
+                    && queued.agent_id.as_deref().is_none_or(|id| id == agent_id)
+                    && queued
+                        .requirements
+                        .iter()
+                        .all(|r| scheduler::satisfies(agent, r))
+                    && queued
+                        .not_before
+                        .as_ref()
+                        .is_none_or(|at| ts_key(at) <= ts_key(&now));
+                if !eligible {
+                    continue;
+                }
+                // whoever took an unplaced start job first may have started
+                // the instance, retries go to the same agent
+                if queued.agent_id.is_none() && jobs::claim(tx, &queued.job, agent_id)? {
+                    queued.agent_id = Some(agent_id.to_string());
+                }
+                if queued.job.r#type == jobs::JOB_INSTANCE_START {
+                    starting.extend(jobs::instance_id(&queued.job));
+                }
+                queued.leased_to = Some(agent_id.to_string());
+                queued.not_before = None;
+                queued.job.attempt += 1;
+                queued.job.lease_expires_at = Some((at + self.config.lease).into());
+                tx.update_job(&queued)?;
+                leased.push(queued.job);
+            }
+            Ok::<_, Status>((leased, settled))
+        })?;
+        self.announce(settled);
+        Ok(leased)
+    }
+
+    /// Settles the job `agent_id` holds with what it reported
+    pub fn complete(&self, agent_id: &str, result: &JobResult) -> Result<(), Status> {
+        self.complete_at(agent_id, result, SystemTime::now())
+    }
+
+    fn complete_at(
+        &self,
+        agent_id: &str,
+        result: &JobResult,
+        at: SystemTime,
+    ) -> Result<(), Status> {
+        let settled = self.store.atomically(|tx| {
+            let queued = tx.get_job(&result.job_id)?;
+            // a late result still counts while nobody else took the job
+            if queued.leased_to.as_deref() != Some(agent_id) {
+                return Err(Status::failed_precondition(format!(
+                    "job `{}` is not leased to `{agent_id}`",
+                    result.job_id
+                )));
+            }
+            if result.success {
+                tx.delete_job(&result.job_id)?;
+                let outcome = Outcome::Succeeded {
+                    agent_id,
+                    result_json: &result.result_json,
+                };
+                return jobs::settle(tx, &queued.job, outcome);
+            }
+            let error = match result.message.trim() {
+                "" => "job failed",
+                message => message,
+            };
+            self.fail(tx, queued, error, at)
+        })?;
+        self.announce(settled);
+        Ok(())
+    }
+
+    /// Puts the jobs leased to `agent_id` back in the queue, for an agent that
+    /// went away. The attempts they were on still count, jobs on their last
+    /// one are dead-lettered
+    pub fn release(&self, agent_id: &str) -> Result<(), Status> {
+        let at = SystemTime::now();
+        let (released, settled) = self.store.atomically(|tx| {
+            let mut released = 0;
+            let mut settled = Vec::new();
+            for queued in tx.list_jobs()? {
+                if queued.leased_to.as_deref() != Some(agent_id) {
+                    continue;
+                }
+                settled.extend(self.unlease(tx, queued, "agent disconnected", at)?);
+                released += 1;
+            }
+            Ok::<_, Status>((released, settled))
+        })?;
+        self.announce(settled);
+        if released > 0 {
+            tracing::info!(agent_id, released, "leased jobs released");
+            self.wake();
+        }
+        Ok(())
+    }
+
+    /// Gives up on an agent that is gone for good: jobs only it could take
+    /// are dead-lettered, the ones it merely held go back to the queue. The
+    /// agent's instances are left to the caller to fail or move
+    pub fn abandon(&self, agent_id: &str, error: &str) -> Result<(), Status> {
+        let at = SystemTime::now();
+        let settled = self.store.atomically(|tx| {
+            let mut settled = Vec::new();
+            for mut queued in tx.list_jobs()? {
+                if queued.agent_id.as_deref() == Some(agent_id) {
+                    tx.delete_job(&queued.job.job_id)?;
+                    queued.job.lease_expires_at = None;
+                    tx.insert_dead_job(&DeadJob {
+                        attempts: queued.job.attempt,
+                        job: Some(queued.job),
+                        agent_id: agent_id.to_string(),
+                        last_error: error.to_string(),
+                        died_at: Some(at.into()),
+                    })?;
+                } else if queued.leased_to.as_deref() == Some(agent_id) {
+                    settled.extend(self.unlease(tx, queued, error, at)?);
+                }
+            }
+            Ok::<_, Status>(settled)
+        })?;
+        self.announce(settled);
+        self.wake();
+        Ok(())
+    }
+
+    /// Retries or dead-letters jobs whose lease lapsed
+    pub fn reap(&self) -> Result<(), Status> {
+        let settled = self
+            .store
+            .atomically(|tx| self.reap_in(tx, SystemTime::now()))?;
+        self.announce(settled);
+        Ok(())
+    }
+
+    /// Returns the instances of the jobs it dead-lettered that changed
+    fn reap_in(&self, tx: &dyn Store, at: SystemTime) -> Result<Vec<Instance>, Status> {
+        let now = Timestamp::from(at);
+        let mut settled = Vec::new();
+        for queued in tx.list_jobs()? {
+            if queued.leased_to.is_some() && passed(queued.job.lease_expires_at.as_ref(), &now) {
+                settled.extend(self.fail(tx, queued, "lease expired", at)?);
+            }
+        }
+        Ok(settled)
+    }
+
+    /// Takes the job back from the agent it is leased to, to be handed out
+    /// again right away. Dead-letters it instead when it has no attempts left
+    fn unlease(
+        &self,
+        tx: &dyn Store,
+        mut queued: QueuedJob,
+        error: &str,
+        at: SystemTime,
+    ) -> Result<Option<Instance>, Status> {
+        if self.exhausted(&queued) {
+            return self.fail(tx, queued, error, at);
+        }
+        queued.leased_to = None;
+        queued.job.lease_expires_at = None;
+        queued.last_error = error.to_string();
+        tx.update_job(&queued)?;
+        Ok(None)
+    }
+
+    /// Whether the job used up all of its attempts
+    fn exhausted(&self, queued: &QueuedJob) -> bool {
+        u32::try_from(queued.job.attempt).unwrap_or(0) >= self.config.max_attempts
+    }
+
+    /// Counts a failed attempt, dead-lettering the job once it has none left.
+    /// Returns the job's instance when dead-lettering changed it
+    fn fail(
+        &self,
+        tx: &dyn Store,
+        mut queued: QueuedJob,
+        error: &str,
+        at: SystemTime,
+    ) -> Result<Option<Instance>, Status> {
+        let attempts = u32::try_from(queued.job.attempt).unwrap_or(0);
+        if self.exhausted(&queued) {
+            tracing::warn!(job_id = %queued.job.job_id, attempts, error, "job dead-lettered");
+            tx.delete_job(&queued.job.job_id)?;
+            queued.job.lease_expires_at = None;
+            let settled = jobs::settle(tx, &queued.job, Outcome::Died(error))?;
+            tx.insert_dead_job(&DeadJob {
+                job: Some(queued.job),
+                agent_id: queued.agent_id.unwrap_or_default(),
+                attempts: attempts as i32,
+                last_error: error.to_string(),
+                died_at: Some(at.into()),
+            })?;
+            return Ok(settled);
+        }
+
+        queued.leased_to = None;
+        queued.job.lease_expires_at = None;
+        queued.not_before = Some((at + self.config.backoff(attempts)).into());
+        queued.last_error = error.to_string();
+        tx.update_job(&queued)?;
+        Ok(None)
+    }
+
+    /// Publishes the state of instances settled jobs changed, once committed
+    fn announce(&self, settled: impl IntoIterator<Item = Instance>) {
+        for instance in settled {
+            self.events.publish(events::instance_state(&instance));
+        }
+    }
+
+    /// Jobs that ran out of attempts, meant for `agent_id` or all of them
+    pub fn dead_jobs(&self, agent_id: Option<&str>) -> Result<Vec<DeadJob>, Status> {
+        self.reap()?;
+        Ok(self.store.list_dead_jobs(agent_id)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ctfjx_proto::grpc::{InstanceStatus, instance_status::State};
+    use ctfjx_storage::MemoryStorage;
+
+    use super::*;
+    use crate::jobs::{JOB_INSTANCE_START, StartInstancePayload, StartInstanceResult};
+
+    fn queue() -> JobQueue {
+        JobQueue::new(
+            Arc::new(MemoryStorage::default()),
+            EventBus::default(),
+            QueueConfig {
+                lease: Duration::from_secs(60),
+                max_attempts: 2,
+                backoff: Duration::from_secs(10),
+                max_backoff: Duration::from_secs(15),
+            },
+        )
+    }
+
+    fn job(id: &str) -> Job {
+        Job {
+            job_id: id.to_string(),
+            r#type: "test".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn agent(id: &str) -> Agent {
+        Agent {
+            agent_id: id.to_string(),
+            capabilities: vec!["docker".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn result(id: &str, success: bool) -> JobResult {
+        JobResult {
+            job_id: id.to_string(),
+            success,
+            message: "it broke".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_a_cap() {
+        let config = queue().config;
+        assert_eq!(config.backoff(1), Duration::from_secs(10));
+        assert_eq!(config.backoff(2), Duration::from_secs(15));
+        assert_eq!(config.backoff(u32::MAX), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn leased_jobs_are_hidden_until_settled() {
+        let queue = queue();
+        let t0 = SystemTime::now();
+        queue.enqueue(job("a"), None).unwrap();
+        queue.enqueue(job("b"), Some("other".to_string())).unwrap();
+
+        let leased = queue.lease_at(&agent("me"), 10, t0).unwrap();
+        assert_eq!(leased.len(), 1);
+        assert_eq!(leased[0].attempt, 1);
+        assert!(queue.lease_at(&agent("me"), 10, t0).unwrap().is_empty());
+
+        // only the holder reports on a job
+        let err = queue
+            .complete_at("other", &result("a", true), t0)
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+        queue.complete_at("me", &result("a", true), t0).unwrap();
+        assert!(queue.store.get_job("a").is_err());
+    }
+
+    #[test]
+    fn unplaced_jobs_wait_for_an_eligible_agent() {
+        let queue = queue();
+        let t0 = SystemTime::now();
+        queue
+            .enqueue_unplaced(job("a"), vec!["kvm".to_string()])
+            .unwrap();
+        queue
+            .enqueue_unplaced(job("b"), vec!["docker".to_string()])
+            .unwrap();
+
+        let leased = queue.lease_at(&agent("me"), 10, t0).unwrap();
+        assert_eq!(leased.len(), 1);
+        assert_eq!(leased[0].job_id, "b");
+
+        let mut kvm = agent("kvm");
+        kvm.capabilities = vec!["kvm".to_string()];
+        let leased = queue.lease_at(&kvm, 10, t0).unwrap();
+        assert_eq!(leased[0].job_id, "a");
+    }
+
+    #[test]
+    fn released_jobs_are_handed_out_again() {
+        let queue = queue();
+        let mut wake = queue.subscribe();
+        let t0 = SystemTime::now();
+        queue.enqueue(job("a"), None).unwrap();
+        assert!(wake.has_changed().unwrap());
+        wake.mark_unchanged();
+
+        queue.lease_at(&agent("me"), 10, t0).unwrap();
+        queue.release("other").unwrap();
+        assert!(!wake.has_changed().unwrap());
+        queue.release("me").unwrap();
+        assert!(wake.has_changed().unwrap());
+
+        let leased = queue.lease_at(&agent("you"), 10, t0).unwrap();
+        assert_eq!(leased[0].attempt, 2);
+    }
+
+    #[test]
+    fn released_jobs_are_dead_lettered_out_of_attempts() {
+        let queue = queue();
+        let t0 = SystemTime::now();
+        queue.enqueue(job("a"), None).unwrap();
+
+        let max = queue.config.max_attempts;
+        for attempt in 1..=max + 1 {
+            let leased = queue.lease_at(&agent("me"), 1, t0).unwrap();
+            if attempt <= max {
+                assert_eq!(leased[0].attempt, attempt as i32);
+            } else {
+                assert!(leased.is_empty());
+            }
+            queue.release("me").unwrap();
+        }
+        let dead = queue.store.list_dead_jobs(None).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, max as i32);
+        assert_eq!(dead[0].last_error, "agent disconnected");
+        assert!(queue.store.list_jobs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn abandoned_agents_leave_their_jobs_behind() {
+        let queue = queue();
+        let t0 = SystemTime::now();
+        queue.enqueue(job("mine"), Some("me".to_string())).unwrap();
+        queue.enqueue(job("any"), None).unwrap();
+        queue.lease_at(&agent("me"), 10, t0).unwrap();
+
+        queue.abandon("me", "agent is dead").unwrap();
+        let dead = queue.store.list_dead_jobs(Some("me")).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].job.as_ref().unwrap().job_id, "mine");
+        assert_eq!(dead[0].last_error, "agent is dead");
+        let leased = queue.lease_at(&agent("you"), 10, t0).unwrap();
+        assert_eq!(leased.len(), 1);
+        assert_eq!(leased[0].job_id, "any");
+    }
+
+    #[test]
+    fn failures_are_retried_then_dead_lettered() {
+        let queue = queue();
+        let t0 = SystemTime::now();
+        queue.enqueue(job("a"), None).unwrap();
+
+        queue.lease_at(&agent("me"), 1, t0).unwrap();
+        queue.complete_at("me", &result("a", false), t0).unwrap();
+        // backing off
+        assert!(queue.lease_at(&agent("me"), 1, t0).unwrap().is_empty());
+
+        let t1 = t0 + Duration::from_secs(10);
+        let retried = queue.lease_at(&agent("me"), 1, t1).unwrap();
+        assert_eq!(retried[0].attempt, 2);
+
+        // the lease lapses on the last attempt
+        let t2 = t1 + Duration::from_secs(61);
+        assert!(queue.lease_at(&agent("me"), 1, t2).unwrap().is_empty());
+        let dead = queue.store.list_dead_jobs(None).unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].attempts, 2);
+        assert_eq!(dead[0].last_error, "lease expired");
+        assert!(queue.store.list_jobs().unwrap().is_empty());
+    }
+
+    fn start_job(id: &str, instance_id: &str) -> Job {
+        Job {
+            r#type: JOB_INSTANCE_START.to_string(),
+            payload_json: serde_json::to_string(&StartInstancePayload {
+                instance_id: instance_id.to_string(),
+                challenge_id: "c1".to_string(),
+                team_id: "t1".to_string(),
+                overrides: Default::default(),
+                flag: None,
+                runtime: Default::default(),
+            })
+            .unwrap(),
+            ..job(id)
+        }
+    }
+
+    fn starting(queue: &JobQueue, instance_id: &str) {
+        starting_on(queue, instance_id, "me");
+    }
+
+    fn starting_on(queue: &JobQueue, instance_id: &str, node: &str) {
+        let status = InstanceStatus {
+            instance_id: instance_id.to_string(),
+            state: State::Starting as i32,
+            node: node.to_string(),
+            ..Default::default()
+        };
+        queue
+            .store
+            .insert_instance(&Instance {
+                status,
+                team_id: "t1".to_string(),
+                overrides: Default::default(),
+                started_at: None,
+                stopped_at: None,
+                expiry_warned: None,
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn start_jobs_settle_their_instance() {
+        let queue = queue();
+        let mut events = queue.events.subscribe();
+        let t0 = SystemTime::now();
+        starting(&queue, "up");
+        starting(&queue, "down");
+        queue.enqueue(start_job("a", "up"), None).unwrap();
+        queue.enqueue(start_job("b", "down"), None).unwrap();
+
+        queue.lease_at(&agent("me"), 2, t0).unwrap();
+        let done = JobResult {
+            result_json: serde_json::to_string(&StartInstanceResult { port: 31337 }).unwrap(),
+            ..result("a", true)
+        };
+        queue.complete_at("me", &done, t0).unwrap();
+        let up = queue.store.get_instance("up").unwrap().status;
+        assert_eq!((up.state(), up.port), (State::Running, 31337));
+        assert!(
+            events
+                .try_recv()
+                .unwrap()
+                .message
+                .ends_with("STATE_RUNNING")
+        );
+
+        // out of attempts
+        queue.complete_at("me", &result("b", false), t0).unwrap();
+        let t1 = t0 + Duration::from_secs(10);
+        queue.lease_at(&agent("me"), 1, t1).unwrap();
+        queue.complete_at("me", &result("b", false), t1).unwrap();
+        let down = queue.store.get_instance("down").unwrap().status;
+        assert_eq!(down.state(), State::Failed);
+        assert_eq!(down.message, "could not start: it broke");
+        assert!(events.try_recv().unwrap().message.ends_with("STATE_FAILED"));
+    }
+
+    #[test]
+    fn unplaced_start_jobs_stay_with_their_first_agent() {
+        let queue = queue();
+        let t0 = SystemTime::now();
+        starting_on(&queue, "up", "");
+        queue
+            .enqueue_unplaced(start_job("a", "up"), vec!["docker".to_string()])
+            .unwrap();
+
+        queue.lease_at(&agent("me"), 1, t0).unwrap();
+        assert_eq!(queue.store.get_instance("up").unwrap().status.node, "me");
+
+        // it may have started the instance before going away
+        queue.release("me").unwrap();
+        assert!(queue.lease_at(&agent("you"), 1, t0).unwrap().is_empty());
+        let leased = queue.lease_at(&agent("me"), 1, t0).unwrap();
+        assert_eq!(leased[0].attempt, 2);
+
+        queue.complete_at("me", &result("a", true), t0).unwrap();
+        let up = queue.store.get_instance("up").unwrap().status;
+        assert_eq!((up.state(), up.node.as_str()), (State::Running, "me"));
+    }
+}
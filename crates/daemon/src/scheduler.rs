@@ -0,0 +1,220 @@
+//! Picks the agent an instance runs on.
+//!
+//! A challenge's requirements are matched against what agents registered: a
+//! plain requirement names a capability, `key=value` a metadata label. Among
+//! the online, healthy agents meeting all of them, the one reporting the
+//! lowest load in its heartbeat wins, then the one running the fewest
+//! instances.
+
+use std::cmp::Ordering;
+
+use ctfjx_proto::grpc::instance_status::State;
+use ctfjx_storage::{Agent, Instance, Liveness};
+use tonic::Status;
+
+use crate::jobs::STAT_LOAD;
+
+/// Most agents named when explaining why none is eligible
+const MAX_EXPLAINED: usize = 5;
+
+/// Rejects requirements no agent could ever meet
+pub fn validate(requirements: &[String]) -> Result<(), Status> {
+    for requirement in requirements {
+        let bad = match requirement.split_once('=') {
+            Some((key, _)) => key.is_empty(),
+            None => requirement.is_empty(),
+        };
+        if bad || requirement.chars().any(char::is_whitespace) {
+            return Err(Status::invalid_argument(format!(
+                "invalid requirement `{requirement}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `agent` meets `requirement`
+pub fn satisfies(agent: &Agent, requirement: &str) -> bool {
+    match requirement.split_once('=') {
+        Some((key, value)) => agent.metadata.get(key).is_some_and(|v| v == value),
+        None => agent.capabilities.iter().any(|c| c == requirement),
+    }
+}
+
+/// Checks `agent` meets every requirement, naming the ones it does not
+pub fn check(agent: &Agent, requirements: &[String]) -> Result<(), String> {
+    let missing: Vec<_> = requirements
+        .iter()
+        .filter(|r| !satisfies(agent, r))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(format!("{} lacks {}", agent.agent_id, missing.join(", ")))
+}
+
+/// The load the agent last reported, 0 when it did not
+pub fn load(agent: &Agent) -> f64 {
+    agent
+        .stats
+        .get(STAT_LOAD)
+        .and_then(|l| l.trim().parse::<f64>().ok())
+        .filter(|l| l.is_finite())
+        .unwrap_or(0.0)
+}
+
+/// Instances placed on `agent_id` that have not stopped
+fn running(instances: &[Instance], agent_id: &str) -> usize {
+    instances
+        .iter()
+        .filter(|i| i.status.node == agent_id)
+        .filter(|i| matches!(i.status.state(), State::Starting | State::Running))
+        .count()
+}
+
+/// The least loaded of `agents` meeting `requirements`, or why there is none
+pub fn place<'a>(
+    agents: &'a [Agent],
+    instances: &[Instance],
+    requirements: &[String],
+) -> Result<&'a Agent, String> {
+    if agents.is_empty() {
+        return Err("no agent is registered".to_string());
+    }
+
+    let mut reasons = Vec::new();
+    let mut best: Option<(&Agent, f64, usize)> = None;
+    for agent in agents {
+        if !agent.online {
+            reasons.push(format!("{} is offline", agent.agent_id));
+            continue;
+        }
+        if agent.liveness != Liveness::Healthy {
+            reasons.push(format!("{} is {}", agent.agent_id, agent.liveness.as_str()));
+            continue;
+        }
+        if let Err(reason) = check(agent, requirements) {
+            reasons.push(reason);
+            continue;
+        }
+        let candidate = (agent, load(agent), running(instances, &agent.agent_id));
+        let better = best.is_none_or(|(_, load, running)| match candidate.1.total_cmp(&load) {
+            Ordering::Equal => candidate.2 < running,
+            ordering => ordering == Ordering::Less,
+        });
+        if better {
+            best = Some(candidate);
+        }
+    }
+    if let Some((agent, ..)) = best {
+        return Ok(agent);
+    }
+
+    let more = reasons.len().saturating_sub(MAX_EXPLAINED);
+    reasons.truncate(MAX_EXPLAINED);
+    let mut message = format!("no agent is eligible: {}", reasons.join("; "));
+    if more > 0 {
+        message.push_str(&format!(" and {more} more"));
+    }
+    Err(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ctfjx_proto::grpc::InstanceStatus;
+
+    use super::*;
+
+    fn agent(id: &str, capabilities: &[&str], load: Option<&str>) -> Agent {
+        Agent {
+            agent_id: id.to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            metadata: HashMap::from([("arch".to_string(), "amd64".to_string())]),
+            stats: load
+                .map(|l| HashMap::from([(STAT_LOAD.to_string(), l.to_string())]))
+                .unwrap_or_default(),
+            online: true,
+            ..Default::default()
+        }
+    }
+
+    fn instance(node: &str, state: State) -> Instance {
+        Instance {
+            status: InstanceStatus {
+                node: node.to_string(),
+                state: state as i32,
+                ..Default::default()
+            },
+            team_id: String::new(),
+            overrides: HashMap::new(),
+            started_at: None,
+            stopped_at: None,
+            expiry_warned: None,
+        }
+    }
+
+    fn reqs(requirements: &[&str]) -> Vec<String> {
+        requirements.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_capabilities_and_labels() {
+        let a = agent("a", &["docker"], None);
+        assert!(satisfies(&a, "docker"));
+        assert!(!satisfies(&a, "kvm"));
+        assert!(satisfies(&a, "arch=amd64"));
+        assert!(!satisfies(&a, "arch=arm64"));
+        assert!(!satisfies(&a, "region=eu"));
+        assert_eq!(
+            check(&a, &reqs(&["docker", "kvm", "arch=arm64"])).unwrap_err(),
+            "a lacks kvm, arch=arm64"
+        );
+    }
+
+    #[test]
+    fn validates() {
+        assert!(validate(&reqs(&["docker", "region=", "arch=amd64"])).is_ok());
+        assert!(validate(&reqs(&[""])).is_err());
+        assert!(validate(&reqs(&["=amd64"])).is_err());
+        assert!(validate(&reqs(&["two words"])).is_err());
+    }
+
+    #[test]
+    fn prefers_the_least_loaded() {
+        let agents = [
+            agent("a", &["docker"], Some("0.9")),
+            agent("b", &["docker"], Some("0.2")),
+            agent("c", &[], Some("0")),
+        ];
+        let picked = place(&agents, &[], &reqs(&["docker"])).unwrap();
+        assert_eq!(picked.agent_id, "b");
+
+        // equal loads are broken by running instances, stopped ones not counting
+        let agents = [agent("a", &[], None), agent("b", &[], Some("garbage"))];
+        let instances = [
+            instance("a", State::Running),
+            instance("b", State::Stopped),
+            instance("b", State::Failed),
+        ];
+        assert_eq!(place(&agents, &instances, &[]).unwrap().agent_id, "b");
+    }
+
+    #[test]
+    fn explains_why_none_is_eligible() {
+        assert_eq!(place(&[], &[], &[]).unwrap_err(), "no agent is registered");
+
+        let mut agents: Vec<_> = (0..7).map(|i| agent(&format!("a{i}"), &[], None)).collect();
+        agents[1].online = false;
+        agents[1].capabilities = vec!["kvm".to_string()];
+        agents[2].liveness = Liveness::Suspect;
+        agents[2].capabilities = vec!["kvm".to_string()];
+        assert_eq!(
+            place(&agents, &[], &reqs(&["kvm"])).unwrap_err(),
+            "no agent is eligible: a0 lacks kvm; a1 is offline; a2 is suspect; \
+             a3 lacks kvm; a4 lacks kvm and 2 more"
+        );
+    }
+}
@@ -0,0 +1,432 @@
+//! Challenge values and team standings, computed from the recorded solves.
+//!
+//! Nothing here is stored: the board is derived from solves every time, so
+//! invalidating a solve is reflected the next time it is computed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ctfjx_proto::grpc::{
+    Challenge, ChallengeValue, Scoreboard, ScoreboardDelta, ScoreboardEntry, Scoring,
+    scoring::Function,
+};
+use ctfjx_storage::{Solve, ts_key};
+use prost_wkt_types::Timestamp;
+use tonic::Status;
+
+/// Rejects scoring settings that could not produce a sane value
+pub fn validate(scoring: &Scoring) -> Result<(), Status> {
+    if scoring.first_blood_bonus.iter().any(|b| *b < 0) {
+        return Err(Status::invalid_argument(
+            "first blood bonuses must not be negative",
+        ));
+    }
+    if scoring.function() == Function::Unspecified {
+        return Ok(());
+    }
+    if scoring.minimum < 0 || scoring.initial < scoring.minimum {
+        return Err(Status::invalid_argument(
+            "scoring needs 0 <= minimum <= initial",
+        ));
+    }
+    if scoring.decay <= 0 {
+        return Err(Status::invalid_argument("scoring decay must be positive"));
+    }
+    Ok(())
+}
+
+/// What each solver of `challenge` is awarded once it has `solves` solves
+pub fn value(challenge: &Challenge, solves: usize) -> i64 {
+    let Some(scoring) = challenge
+        .scoring
+        .as_ref()
+        .filter(|s| s.function() != Function::Unspecified)
+    else {
+        return i64::from(challenge.points);
+    };
+
+    let initial = i64::from(scoring.initial);
+    let minimum = i64::from(scoring.minimum);
+    let decay = i64::from(scoring.decay.max(1));
+    // the first solver gets the full value
+    let n = solves.saturating_sub(1) as i64;
+
+    let value = match scoring.function() {
+        Function::Unspecified => unreachable!("filtered above"),
+        Function::Linear => initial.saturating_sub(decay.saturating_mul(n)),
+        Function::Logarithmic => {
+            // CTFd's "logarithmic" decay, a parabola hitting `minimum` at `decay`
+            let drop = (initial - minimum) as f64 / (decay * decay) as f64 * (n * n) as f64;
+            (initial as f64 - drop).ceil() as i64
+        }
+    };
+    value.max(minimum)
+}
+
+/// Bonus for the `place`-th solver of `challenge`, counting from zero
+fn first_blood_bonus(challenge: &Challenge, place: usize) -> i64 {
+    challenge
+        .scoring
+        .as_ref()
+        .and_then(|s| s.first_blood_bonus.get(place))
+        .map_or(0, |b| i64::from(*b))
+}
+
+#[derive(Default)]
+struct Standing {
+    score: i64,
+    solves: i32,
+    last_solve_at: Option<Timestamp>,
+}
+
+/// Ranks every team with a solve. Higher scores rank first, ties go to the
+/// team whose last solve came earlier, then to the lower team id so the
+/// order is the same on every call.
+///
+/// `solves` has to be ordered by solve time, as storage lists them.
+pub fn scoreboard(
+    challenges: &[Challenge],
+    solves: &[Solve],
+) -> (Vec<ScoreboardEntry>, Vec<ChallengeValue>) {
+    let challenges: HashMap<&str, &Challenge> =
+        challenges.iter().map(|c| (c.id.as_str(), c)).collect();
+
+    let mut by_challenge: HashMap<&str, Vec<&Solve>> = HashMap::new();
+    for solve in solves {
+        if challenges.contains_key(solve.challenge_id.as_str()) {
+            by_challenge
+                .entry(&solve.challenge_id)
+                .or_default()
+                .push(solve);
+        }
+    }
+
+    let mut values: Vec<ChallengeValue> = challenges
+        .values()
+        .map(|c| {
+            let solves = by_challenge.get(c.id.as_str()).map_or(0, Vec::len);
+            ChallengeValue {
+                challenge_id: c.id.clone(),
+                value: value(c, solves),
+                solves: solves as i32,
+            }
+        })
+        .collect();
+    values.sort_by(|a, b| a.challenge_id.cmp(&b.challenge_id));
+
+    let mut standings: HashMap<&str, Standing> = HashMap::new();
+    for (challenge_id, solves) in &by_challenge {
+        let challenge = challenges[challenge_id];
+        let value = value(challenge, solves.len());
+        for (place, solve) in solves.iter().enumerate() {
+            let standing = standings.entry(&solve.team_id).or_default();
+            standing.score += value + first_blood_bonus(challenge, place);
+            standing.solves += 1;
+            if standing
+                .last_solve_at
+                .is_none_or(|last| ts_key(&last) < ts_key(&solve.solved_at))
+            {
+                standing.last_solve_at = Some(solve.solved_at);
+            }
+        }
+    }
+
+    let mut standings: Vec<_> = standings.into_iter().collect();
+    standings.sort_by(|(team_a, a), (team_b, b)| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| {
+                let last = |s: &Standing| s.last_solve_at.as_ref().map(ts_key);
+                last(a).cmp(&last(b))
+            })
+            .then_with(|| team_a.cmp(team_b))
+    });
+
+    let entries = standings
+        .into_iter()
+        .enumerate()
+        .map(|(i, (team_id, s))| ScoreboardEntry {
+            rank: i as i32 + 1,
+            team_id: team_id.to_string(),
+            score: s.score,
+            solves: s.solves,
+            last_solve_at: s.last_solve_at,
+            division: String::new(),
+        })
+        .collect();
+
+    (entries, values)
+}
+
+/// Tags entries with their team's division and, when `division` is set, only
+/// keeps and re-ranks that division's teams
+pub fn rank_division(
+    entries: Vec<ScoreboardEntry>,
+    divisions: &BTreeMap<String, String>,
+    division: &str,
+) -> Vec<ScoreboardEntry> {
+    entries
+        .into_iter()
+        .map(|mut e| {
+            e.division = divisions.get(&e.team_id).cloned().unwrap_or_default();
+            e
+        })
+        .filter(|e| division.is_empty() || e.division == division)
+        .enumerate()
+        .map(|(i, mut e)| {
+            e.rank = i as i32 + 1;
+            e
+        })
+        .collect()
+}
+
+/// What changed from `prev` to `next`, `None` when nothing did. Without a
+/// previous board the delta holds all of `next`
+pub fn diff(prev: Option<&Scoreboard>, next: &Scoreboard) -> Option<ScoreboardDelta> {
+    let first = prev.is_none();
+    let empty = Scoreboard::default();
+    let prev = prev.unwrap_or(&empty);
+
+    let old_entries: HashMap<&str, &ScoreboardEntry> = prev
+        .entries
+        .iter()
+        .map(|e| (e.team_id.as_str(), e))
+        .collect();
+    let old_values: HashMap<&str, &ChallengeValue> = prev
+        .challenges
+        .iter()
+        .map(|c| (c.challenge_id.as_str(), c))
+        .collect();
+
+    let delta = ScoreboardDelta {
+        entries: next
+            .entries
+            .iter()
+            .filter(|e| old_entries.get(e.team_id.as_str()) != Some(e))
+            .cloned()
+            .collect(),
+        removed_team_ids: removed(&prev.entries, &next.entries, |e| &e.team_id),
+        challenges: next
+            .challenges
+            .iter()
+            .filter(|c| old_values.get(c.challenge_id.as_str()) != Some(c))
+            .cloned()
+            .collect(),
+        removed_challenge_ids: removed(&prev.challenges, &next.challenges, |c| &c.challenge_id),
+        frozen_at: next.frozen_at,
+    };
+
+    let unchanged = delta.entries.is_empty()
+        && delta.removed_team_ids.is_empty()
+        && delta.challenges.is_empty()
+        && delta.removed_challenge_ids.is_empty()
+        && prev.frozen_at == next.frozen_at;
+    (first || !unchanged).then_some(delta)
+}
+
+/// Keys of `prev` that are not in `next`
+fn removed<T>(prev: &[T], next: &[T], key: impl Fn(&T) -> &String) -> Vec<String> {
+    prev.iter()
+        .map(&key)
+        .filter(|k| !next.iter().any(|n| key(n) == *k))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dynamic(function: Function, bonus: Vec<i32>) -> Challenge {
+        Challenge {
+            id: "c".to_string(),
+            scoring: Some(Scoring {
+                function: function as i32,
+                initial: 500,
+                minimum: 100,
+                decay: 10,
+                first_blood_bonus: bonus,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn solve(challenge_id: &str, team_id: &str, secs: i64) -> Solve {
+        Solve {
+            challenge_id: challenge_id.to_string(),
+            team_id: team_id.to_string(),
+            submission_id: String::new(),
+            solved_at: Timestamp {
+                seconds: secs,
+                nanos: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn static_value() {
+        let c = Challenge {
+            points: 300,
+            ..Default::default()
+        };
+        assert_eq!(value(&c, 0), 300);
+        assert_eq!(value(&c, 50), 300);
+    }
+
+    #[test]
+    fn linear_decay() {
+        let c = dynamic(Function::Linear, vec![]);
+        assert_eq!(value(&c, 0), 500);
+        assert_eq!(value(&c, 1), 500);
+        assert_eq!(value(&c, 2), 490);
+        assert_eq!(value(&c, 41), 100);
+        assert_eq!(value(&c, 1000), 100);
+    }
+
+    #[test]
+    fn logarithmic_decay() {
+        let c = dynamic(Function::Logarithmic, vec![]);
+        assert_eq!(value(&c, 1), 500);
+        // CTFd: ceil(((100 - 500) / 10^2) * 5^2 + 500)
+        assert_eq!(value(&c, 6), 400);
+        assert_eq!(value(&c, 11), 100);
+        assert_eq!(value(&c, 50), 100);
+
+        let mut values: Vec<_> = (1..=11).map(|n| value(&c, n)).collect();
+        let sorted = {
+            let mut v = values.clone();
+            v.sort_by(|a, b| b.cmp(a));
+            v
+        };
+        assert_eq!(values, sorted);
+        values.dedup();
+        assert!(values.len() > 5);
+    }
+
+    #[test]
+    fn ranks_with_bonuses_and_ties() {
+        let mut a = dynamic(Function::Linear, vec![50, 20]);
+        a.id = "a".to_string();
+        let b = Challenge {
+            id: "b".to_string(),
+            points: 100,
+            ..Default::default()
+        };
+        let solves = [
+            solve("a", "t1", 10),
+            solve("a", "t2", 20),
+            solve("a", "t3", 30),
+            solve("b", "t3", 40),
+            solve("b", "t4", 50),
+            // solves of deleted challenges are ignored
+            solve("gone", "t4", 60),
+        ];
+
+        let (entries, values) = scoreboard(&[a, b], &solves);
+        // a is worth 480 after three solves
+        assert_eq!(values[0].value, 480);
+        assert_eq!(values[1].value, 100);
+
+        let board: Vec<_> = entries
+            .iter()
+            .map(|e| (e.rank, e.team_id.as_str(), e.score))
+            .collect();
+        assert_eq!(
+            board,
+            [
+                (1, "t3", 580),
+                (2, "t1", 530),
+                (3, "t2", 500),
+                (4, "t4", 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_earlier_team() {
+        let c = |id: &str| Challenge {
+            id: id.to_string(),
+            points: 100,
+            ..Default::default()
+        };
+        let solves = [solve("a", "late", 20), solve("b", "early", 10)];
+        let (entries, _) = scoreboard(&[c("a"), c("b")], &solves);
+        assert_eq!(entries[0].team_id, "early");
+        assert_eq!(entries[1].team_id, "late");
+    }
+
+    #[test]
+    fn divisions_are_ranked_apart() {
+        let entry = |rank, team_id: &str| ScoreboardEntry {
+            rank,
+            team_id: team_id.to_string(),
+            ..Default::default()
+        };
+        let divisions = BTreeMap::from([
+            ("t2".to_string(), "students".to_string()),
+            ("t3".to_string(), "students".to_string()),
+        ]);
+        let entries = vec![entry(1, "t1"), entry(2, "t2"), entry(3, "t3")];
+
+        let all = rank_division(entries.clone(), &divisions, "");
+        assert_eq!(all[1].rank, 2);
+        assert_eq!(all[1].division, "students");
+        assert_eq!(all[0].division, "");
+
+        let students: Vec<_> = rank_division(entries, &divisions, "students")
+            .into_iter()
+            .map(|e| (e.rank, e.team_id))
+            .collect();
+        assert_eq!(students, [(1, "t2".to_string()), (2, "t3".to_string())]);
+    }
+
+    #[test]
+    fn diffs_boards() {
+        let entry = |rank, team_id: &str, score| ScoreboardEntry {
+            rank,
+            team_id: team_id.to_string(),
+            score,
+            ..Default::default()
+        };
+        let before = Scoreboard {
+            entries: vec![entry(1, "a", 200), entry(2, "b", 100), entry(3, "c", 50)],
+            ..Default::default()
+        };
+
+        let first = diff(None, &before).unwrap();
+        assert_eq!(first.entries, before.entries);
+        assert!(diff(Some(&before), &before).is_none());
+        // the first delta is sent even for an empty board
+        assert!(diff(None, &Scoreboard::default()).is_some());
+
+        let after = Scoreboard {
+            entries: vec![entry(1, "b", 300), entry(2, "a", 200)],
+            ..Default::default()
+        };
+        let delta = diff(Some(&before), &after).unwrap();
+        assert_eq!(delta.entries, [entry(1, "b", 300), entry(2, "a", 200)]);
+        assert_eq!(delta.removed_team_ids, ["c"]);
+
+        let frozen = Scoreboard {
+            frozen_at: Some(Timestamp::default()),
+            ..after.clone()
+        };
+        let delta = diff(Some(&after), &frozen).unwrap();
+        assert!(delta.entries.is_empty());
+        assert!(delta.frozen_at.is_some());
+    }
+
+    #[test]
+    fn validates() {
+        let mut s = dynamic(Function::Linear, vec![]).scoring.unwrap();
+        assert!(validate(&s).is_ok());
+        s.minimum = 600;
+        assert!(validate(&s).is_err());
+        s.minimum = 100;
+        s.decay = 0;
+        assert!(validate(&s).is_err());
+        s.function = Function::Unspecified as i32;
+        assert!(validate(&s).is_ok());
+        s.first_blood_bonus = vec![-1];
+        assert!(validate(&s).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! The daemon's long lived secret, everything it signs uses a key derived
+//! from it.
+
+use std::{fmt, str::FromStr};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+pub type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret([u8; 32]);
+
+impl Secret {
+    /// A fresh secret, anything signed with it is void after a restart
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// A key for one `purpose`, so a signature made for one use is never
+    /// accepted by another
+    pub fn derive(&self, purpose: &str) -> [u8; 32] {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("hmac takes any key size");
+        mac.update(purpose.as_bytes());
+        mac.finalize().into_bytes().into()
+    }
+
+    /// Whether `passphrase` is the one this secret was parsed from
+    pub fn matches(&self, passphrase: &str) -> bool {
+        let digest: [u8; 32] = Sha256::digest(passphrase.as_bytes()).into();
+        digest.ct_eq(&self.0).into()
+    }
+}
+
+impl FromStr for Secret {
+    type Err = String;
+
+    /// Any passphrase works, it is hashed down to the key size
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() < 16 {
+            return Err("secret must be at least 16 characters".to_string());
+        }
+        Ok(Self(Sha256::digest(s.as_bytes()).into()))
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
@@ -0,0 +1,101 @@
+use std::future::Future;
+
+use ctfjx_proto::grpc::service_ctfjx_server::ServiceCtfjxServer;
+use ctfjx_storage::{Page, Storage};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+use crate::{Error, config::Config, flags, secret::Secret, service::CtfjxService};
+
+/// Serves `service` on an already bound listener until `shutdown` resolves
+pub async fn serve(
+    listener: TcpListener,
+    service: CtfjxService,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), Error> {
+    let auth = service.authenticator();
+    let watcher = tokio::spawn(service.clone().watch_agents());
+    let reaper = tokio::spawn(service.clone().reap_instances());
+    let served = Server::builder()
+        .add_service(ServiceCtfjxServer::with_interceptor(service, auth))
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+        .await;
+    watcher.abort();
+    reaper.abort();
+    served?;
+    Ok(())
+}
+
+/// Opens storage and serves on `config.addr` until `shutdown` resolves
+pub async fn run(config: Config, shutdown: impl Future<Output = ()>) -> Result<(), Error> {
+    let store = ctfjx_storage::open(&config.storage)?;
+    let secret = resolve_secret(config.secret, store.as_ref())?;
+    let listener = TcpListener::bind(config.addr).await?;
+    tracing::info!(addr = %listener.local_addr()?, "ctfjxd listening");
+
+    let mut service = CtfjxService::new(store, &secret)
+        .with_max_team_size(config.max_team_size)
+        .with_job_queue(config.jobs)
+        .with_liveness(config.liveness)
+        .with_expiry(config.expiry);
+    match config.admin_token {
+        Some(token) => service = service.with_admin_token(token),
+        None => tracing::warn!("CTFJXD_ADMIN_TOKEN is not set, no token can be created"),
+    }
+    serve(listener, service, shutdown).await
+}
+
+/// The configured secret, or a random one as long as no challenge hands out
+/// dynamic flags derived from it
+fn resolve_secret(secret: Option<Secret>, store: &dyn Storage) -> Result<Secret, Error> {
+    if let Some(secret) = secret {
+        return Ok(secret);
+    }
+
+    let challenges = store.list_challenges(None, &Page::ALL)?;
+    if let Some(challenge) = challenges.iter().find(|c| flags::dynamic_spec(c).is_some()) {
+        return Err(Error::SecretRequired(challenge.id.clone()));
+    }
+    tracing::warn!(
+        "CTFJXD_SECRET is not set, dynamic flags, sessions, page tokens and the daemon identity will not survive a restart"
+    );
+    Ok(Secret::random())
+}
+
+#[cfg(test)]
+mod tests {
+    use ctfjx_proto::grpc::{Challenge, FlagSpec, flag_spec::Kind};
+    use ctfjx_storage::{MemoryStorage, Store};
+
+    use super::*;
+
+    fn challenge(id: &str, kind: Kind) -> Challenge {
+        Challenge {
+            id: id.to_string(),
+            flags: vec![FlagSpec {
+                kind: kind as i32,
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn dynamic_flags_need_a_configured_secret() {
+        let store = MemoryStorage::default();
+        store
+            .insert_challenge(&challenge("static", Kind::Static))
+            .unwrap();
+        assert!(resolve_secret(None, &store).is_ok());
+
+        store
+            .insert_challenge(&challenge("dynamic", Kind::Dynamic))
+            .unwrap();
+        assert!(matches!(
+            resolve_secret(None, &store),
+            Err(Error::SecretRequired(id)) if id == "dynamic"
+        ));
+        assert!(resolve_secret(Some(Secret::random()), &store).is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use ctfjx_proto::grpc::{
+    AssignJobRequest, AssignJobResponse, Heartbeat, ListDeadJobsRequest, ListDeadJobsResponse,
+    RegisterAgentRequest, RegisterAgentResponse, ReportJobResultRequest, instance_status::State,
+};
+use ctfjx_storage::{Agent, Instance, Liveness, StorageError};
+use tonic::Status;
+
+use crate::{
+    auth::Caller,
+    policy::Access,
+    service::{CtfjxService, now},
+};
+
+/// Most jobs handed out by a single `AssignJob`
+pub const MAX_JOBS_PER_ASSIGN: usize = 32;
+
+/// The caller, when it is an agent signing with its enrolled credential
+pub(super) fn enrolled(access: &Access) -> Result<&Caller, Status> {
+    let caller = access.required_caller()?;
+    if caller.credential_id.is_none() {
+        return Err(Status::permission_denied(
+            "agents authenticate with their enrolled credential",
+        ));
+    }
+    Ok(caller)
+}
+
+/// Records the agent and signs its nonce with the daemon's key
+pub(super) fn register(
+    svc: &CtfjxService,
+    req: RegisterAgentRequest,
+    access: &Access,
+) -> Result<RegisterAgentResponse, Status> {
+    if req.agent_id.trim().is_empty() {
+        return Err(Status::invalid_argument("agent_id is required"));
+    }
+    enrolled(access)?;
+    access.check_owner(|agent_id| req.agent_id == agent_id)?;
+
+    let daemon_signature = svc.identity.prove(&req.agent_id, &req.nonce);
+    let ts = now();
+    let registered_at = match svc.store.get_agent(&req.agent_id) {
+        Ok(agent) => agent.registered_at,
+        Err(StorageError::NotFound(..)) => Some(ts),
+        Err(e) => return Err(e.into()),
+    };
+    svc.store.put_agent(&Agent {
+        agent_id: req.agent_id,
+        version: req.version,
+        capabilities: req.capabilities,
+        metadata: req.metadata,
+        registered_at,
+        last_seen: Some(ts),
+        online: true,
+        ..Default::default()
+    })?;
+
+    Ok(RegisterAgentResponse {
+        accepted: true,
+        message: "registered".to_string(),
+        daemon_signature,
+    })
+}
+
+/// The registered agent, marked as seen just now
+fn seen(svc: &CtfjxService, agent_id: &str) -> Result<Agent, Status> {
+    let mut agent = svc.store.get_agent(agent_id).map_err(|e| match e {
+        StorageError::NotFound(..) => Status::failed_precondition("agent is not registered"),
+        e => e.into(),
+    })?;
+    agent.last_seen = Some(now());
+    if agent.liveness != Liveness::Healthy {
+        tracing::info!(agent_id, was = agent.liveness.as_str(), "agent is back");
+        agent.liveness = Liveness::Healthy;
+    }
+    Ok(agent)
+}
+
+pub(super) fn assign_job(
+    svc: &CtfjxService,
+    req: AssignJobRequest,
+    access: &Access,
+) -> Result<AssignJobResponse, Status> {
+    access.check_owner(|user| req.agent_id == user)?;
+    let agent = seen(svc, &req.agent_id)?;
+    svc.store.put_agent(&agent)?;
+
+    let max = usize::try_from(req.max_jobs)
+        .unwrap_or(0)
+        .clamp(1, MAX_JOBS_PER_ASSIGN);
+    Ok(AssignJobResponse {
+        jobs: svc.jobs.lease(&agent, max)?,
+    })
+}
+
+pub(super) fn heartbeat(svc: &CtfjxService, req: Heartbeat, access: &Access) -> Result<(), Status> {
+    access.check_owner(|user| req.agent_id == user)?;
+    record_heartbeat(svc, req)
+}
+
+/// Keeps the stats the scheduler balances by, and marks the agent's
+/// instances as heard from
+pub(super) fn record_heartbeat(svc: &CtfjxService, req: Heartbeat) -> Result<(), Status> {
+    let mut agent = seen(svc, &req.agent_id)?;
+    agent.stats = req.stats;
+    agent.last_heartbeat = agent.last_seen;
+    svc.store.put_agent(&agent)?;
+
+    let runs = |instance: &Instance| {
+        instance.status.node == agent.agent_id
+            && matches!(instance.status.state(), State::Starting | State::Running)
+    };
+    for listed in svc.store.list_instances(None)? {
+        if !runs(&listed) {
+            continue;
+        }
+        // stopping or extending the instance meanwhile must not be undone
+        svc.store.atomically(|tx| {
+            let mut instance = tx.get_instance(&listed.status.instance_id)?;
+            if runs(&instance) {
+                instance.status.last_heartbeat = agent.last_heartbeat;
+                tx.update_instance(&instance)?;
+            }
+            Ok::<_, Status>(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Marks the agent offline, putting the jobs it held back in the queue
+pub(super) fn disconnect(svc: &CtfjxService, agent_id: &str) -> Result<(), Status> {
+    // a heartbeat landing meanwhile must not be overwritten with stale stats
+    svc.store.atomically(|tx| {
+        let mut agent = tx.get_agent(agent_id)?;
+        agent.online = false;
+        tx.put_agent(&agent)?;
+        Ok::<_, Status>(())
+    })?;
+    svc.jobs.release(agent_id)?;
+    tracing::info!(agent_id, "agent went offline");
+    Ok(())
+}
+
+pub(super) fn report_result(
+    svc: &CtfjxService,
+    req: ReportJobResultRequest,
+    access: &Access,
+) -> Result<(), Status> {
+    access.check_owner(|user| req.agent_id == user)?;
+    let result = req
+        .result
+        .ok_or_else(|| Status::invalid_argument("result is required"))?;
+    svc.jobs.complete(&req.agent_id, &result)
+}
+
+pub(super) fn list_dead_jobs(
+    svc: &CtfjxService,
+    req: ListDeadJobsRequest,
+) -> Result<ListDeadJobsResponse, Status> {
+    let agent_id = req.agent_id.trim();
+    Ok(ListDeadJobsResponse {
+        jobs: svc
+            .jobs
+            .dead_jobs((!agent_id.is_empty()).then_some(agent_id))?,
+    })
+}
@@ -0,0 +1,180 @@
+use ctfjx_proto::grpc::{
+    Challenge, ChallengeRuntime, CreateChallengeRequest, CreateChallengeResponse,
+    DeleteChallengeRequest, GetChallengeRequest, ListChallengesRequest, ListChallengesResponse,
+    UpdateChallengeRequest,
+};
+use ctfjx_storage::Filter;
+use tonic::Status;
+
+use crate::{
+    flags,
+    mask::ChallengeMask,
+    policy::Access,
+    scheduler, scoring,
+    service::{CtfjxService, new_id, now},
+};
+
+/// Names challenge listings in page tokens
+const LIST: &str = "challenges";
+/// Longest filter accepted, in bytes
+const MAX_FILTER_LEN: usize = 4096;
+
+fn validate(challenge: &Challenge) -> Result<(), Status> {
+    if challenge.name.trim().is_empty() {
+        return Err(Status::invalid_argument("challenge name is required"));
+    }
+    if challenge.points < 0 {
+        return Err(Status::invalid_argument("points must not be negative"));
+    }
+    if let Some(s) = &challenge.scoring {
+        scoring::validate(s)?;
+    }
+    scheduler::validate(&challenge.requirements)?;
+    if let Some(runtime) = &challenge.runtime {
+        validate_runtime(runtime)?;
+    }
+    challenge.flags.iter().try_for_each(flags::validate)
+}
+
+fn validate_runtime(runtime: &ChallengeRuntime) -> Result<(), Status> {
+    if runtime.image.is_empty() && runtime.command.is_empty() {
+        return Err(Status::invalid_argument(
+            "runtime needs an image or a command",
+        ));
+    }
+    // container CLIs would take it for one of their flags
+    if runtime.image.starts_with('-') {
+        return Err(Status::invalid_argument(format!(
+            "runtime image `{}` is not an image name",
+            runtime.image
+        )));
+    }
+    if !(0..=i32::from(u16::MAX)).contains(&runtime.port) {
+        return Err(Status::invalid_argument("runtime port is out of range"));
+    }
+    if let Some(key) = runtime
+        .env
+        .keys()
+        .find(|k| k.is_empty() || k.contains(['=', '\0']))
+    {
+        return Err(Status::invalid_argument(format!(
+            "runtime env name `{key}` is invalid"
+        )));
+    }
+    Ok(())
+}
+
+/// Strips what only admins and the challenge's owner may see
+fn redact(mut challenge: Challenge, access: &Access) -> Challenge {
+    if !access.is_admin() && !access.owns(&challenge.owner) {
+        challenge.flags.clear();
+        challenge.runtime = None;
+    }
+    challenge
+}
+
+pub(super) fn create(
+    svc: &CtfjxService,
+    req: CreateChallengeRequest,
+    access: &Access,
+) -> Result<CreateChallengeResponse, Status> {
+    let mut challenge = req
+        .challenge
+        .ok_or(Status::invalid_argument("challenge is required"))?;
+    validate(&challenge)?;
+    // authors own what they write
+    if access.is_restricted() {
+        challenge.owner = access.required_caller()?.user_id.clone();
+    }
+
+    if challenge.id.is_empty() {
+        challenge.id = new_id();
+    }
+    let ts = now();
+    challenge.created_at = Some(ts);
+    challenge.updated_at = Some(ts);
+
+    svc.store.insert_challenge(&challenge)?;
+    svc.scores.notify();
+    Ok(CreateChallengeResponse {
+        id: challenge.id,
+        message: "challenge created".to_string(),
+    })
+}
+
+pub(super) fn get(
+    svc: &CtfjxService,
+    req: GetChallengeRequest,
+    access: &Access,
+) -> Result<Challenge, Status> {
+    Ok(redact(svc.store.get_challenge(&req.id)?, access))
+}
+
+pub(super) fn update(
+    svc: &CtfjxService,
+    req: UpdateChallengeRequest,
+    access: &Access,
+) -> Result<Challenge, Status> {
+    let patch = req
+        .challenge
+        .ok_or(Status::invalid_argument("challenge is required"))?;
+    let mask = ChallengeMask::parse(&req.update_mask.unwrap_or_default().paths)?;
+
+    let updated = svc.store.atomically(|tx| {
+        let mut next = tx.get_challenge(&patch.id)?;
+        // authors update their own challenges and cannot hand them over
+        access.check_owner(|user| next.owner == user)?;
+        mask.apply(&mut next, &patch);
+        access.check_owner(|user| next.owner == user)?;
+        validate(&next)?;
+
+        // `updated_at` is the version the client last read, the store rejects
+        // the update when someone else wrote in between
+        next.updated_at = patch.updated_at;
+        Ok::<_, Status>(redact(tx.update_challenge(&next)?, access))
+    })?;
+    svc.scores.notify();
+    Ok(updated)
+}
+
+pub(super) fn delete(
+    svc: &CtfjxService,
+    req: DeleteChallengeRequest,
+    access: &Access,
+) -> Result<(), Status> {
+    svc.store.atomically(|tx| {
+        let challenge = tx.get_challenge(&req.id)?;
+        access.check_owner(|user| challenge.owner == user)?;
+        tx.delete_challenge(&req.id)?;
+        Ok::<_, Status>(())
+    })?;
+    svc.scores.notify();
+    Ok(())
+}
+
+pub(super) fn list(
+    svc: &CtfjxService,
+    req: ListChallengesRequest,
+    access: &Access,
+) -> Result<ListChallengesResponse, Status> {
+    if req.filter.len() > MAX_FILTER_LEN {
+        return Err(Status::invalid_argument(format!(
+            "filter is longer than {MAX_FILTER_LEN} bytes"
+        )));
+    }
+    let filter = Filter::parse(&req.filter)
+        .map_err(|e| Status::invalid_argument(format!("invalid filter: {e}")))?;
+    let (page, size) = svc
+        .pages
+        .page(LIST, req.page_size, &req.page_token, &req.filter)?;
+
+    let mut challenges = svc.store.list_challenges(filter.as_ref(), &page)?;
+    let next_page_token = svc
+        .pages
+        .finish(LIST, &mut challenges, size, &req.filter, |c| &c.id);
+
+    Ok(ListChallengesResponse {
+        challenges: challenges.into_iter().map(|c| redact(c, access)).collect(),
+        next_page_token,
+    })
+}
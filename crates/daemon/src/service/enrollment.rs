@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use ctfjx_common::identity;
+use ctfjx_proto::grpc::{
+    AgentCredential, CreateJoinTokenRequest, EnrollAgentRequest, EnrollAgentResponse, JoinToken,
+    ListAgentCredentialsRequest, ListAgentCredentialsResponse, RevokeAgentCredentialRequest,
+    RotateAgentKeyRequest,
+};
+use ctfjx_storage::{JoinTokenRecord, StorageError, Store};
+use prost_wkt_types::Timestamp;
+use subtle::ConstantTimeEq;
+use tonic::Status;
+
+use crate::{
+    auth::{JOIN_TOKEN_PREFIX, expired, hash_secret},
+    policy::Access,
+    service::{CtfjxService, new_id, now},
+};
+
+const DEFAULT_JOIN_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_JOIN_TOKEN_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+fn invalid_join_token() -> Status {
+    Status::unauthenticated("invalid, used or expired join token")
+}
+
+fn check_public_key(public_key: &[u8]) -> Result<(), Status> {
+    identity::validate_public_key(public_key)
+        .map_err(|e| Status::invalid_argument(format!("public_key: {e}")))
+}
+
+fn credential_response(svc: &CtfjxService, credential: AgentCredential) -> EnrollAgentResponse {
+    EnrollAgentResponse {
+        credential: Some(credential),
+        daemon_public_key: svc.identity.public_key().to_vec(),
+    }
+}
+
+/// Binds `agent_id` to `public_key` under a fresh credential
+fn insert_credential(
+    tx: &dyn Store,
+    agent_id: String,
+    public_key: Vec<u8>,
+) -> Result<AgentCredential, Status> {
+    let credential = AgentCredential {
+        id: new_id(),
+        agent_id,
+        public_key,
+        created_at: Some(now()),
+        revoked_at: None,
+    };
+    tx.insert_agent_credential(&credential)?;
+    Ok(credential)
+}
+
+pub(super) fn create_join_token(
+    svc: &CtfjxService,
+    req: CreateJoinTokenRequest,
+) -> Result<JoinToken, Status> {
+    let ttl = match req.ttl_seconds {
+        n if n < 0 => return Err(Status::invalid_argument("ttl_seconds must not be negative")),
+        0 => DEFAULT_JOIN_TOKEN_TTL,
+        n => Duration::from_secs(n as u64).min(MAX_JOIN_TOKEN_TTL),
+    };
+    let agent_id = match req.agent_id.trim() {
+        "" => new_id(),
+        id => id.to_string(),
+    };
+
+    let created_at = now();
+    let token = JoinToken {
+        id: new_id(),
+        agent_id,
+        token: String::new(),
+        expires_at: Some(Timestamp {
+            seconds: created_at.seconds.saturating_add(ttl.as_secs() as i64),
+            nanos: created_at.nanos,
+        }),
+        created_at: Some(created_at),
+        used_at: None,
+    };
+    let secret = hex::encode(rand::random::<[u8; 32]>());
+    svc.store.insert_join_token(&JoinTokenRecord {
+        token: token.clone(),
+        secret_hash: hash_secret(&secret),
+    })?;
+
+    Ok(JoinToken {
+        token: format!("{JOIN_TOKEN_PREFIX}{}.{secret}", token.id),
+        ..token
+    })
+}
+
+/// Trades a join token for a credential, the token can only be used once
+pub(super) fn enroll(
+    svc: &CtfjxService,
+    req: EnrollAgentRequest,
+) -> Result<EnrollAgentResponse, Status> {
+    check_public_key(&req.public_key)?;
+    let (id, secret) = req
+        .join_token
+        .trim()
+        .strip_prefix(JOIN_TOKEN_PREFIX)
+        .and_then(|t| t.split_once('.'))
+        .ok_or_else(invalid_join_token)?;
+
+    let credential = svc.store.atomically(|tx| {
+        let mut record = match tx.get_join_token(id) {
+            Ok(record) => record,
+            Err(StorageError::NotFound(..)) => return Err(invalid_join_token()),
+            Err(e) => return Err(e.into()),
+        };
+        let hash = hash_secret(secret);
+        if !bool::from(hash.as_bytes().ct_eq(record.secret_hash.as_bytes()))
+            || record.token.used_at.is_some()
+            || expired(record.token.expires_at.as_ref())
+        {
+            return Err(invalid_join_token());
+        }
+        record.token.used_at = Some(now());
+        tx.update_join_token(&record)?;
+        insert_credential(tx, record.token.agent_id, req.public_key)
+    })?;
+    tracing::info!(agent_id = %credential.agent_id, credential_id = %credential.id, "agent enrolled");
+    Ok(credential_response(svc, credential))
+}
+
+/// Moves the calling agent to a new key, its current credential stops working
+pub(super) fn rotate(
+    svc: &CtfjxService,
+    req: RotateAgentKeyRequest,
+    access: &Access,
+) -> Result<EnrollAgentResponse, Status> {
+    check_public_key(&req.public_key)?;
+    let caller = access.required_caller()?;
+    let current = caller
+        .credential_id
+        .as_deref()
+        .ok_or_else(|| Status::permission_denied("only enrolled agents rotate their key"))?;
+
+    let credential = svc.store.atomically(|tx| {
+        let mut old = tx.get_agent_credential(current)?;
+        if old.revoked_at.is_some() {
+            return Err(Status::unauthenticated("credential was revoked"));
+        }
+        old.revoked_at = Some(now());
+        tx.update_agent_credential(&old)?;
+        insert_credential(tx, old.agent_id, req.public_key)
+    })?;
+    Ok(credential_response(svc, credential))
+}
+
+pub(super) fn list(
+    svc: &CtfjxService,
+    req: ListAgentCredentialsRequest,
+) -> Result<ListAgentCredentialsResponse, Status> {
+    let agent_id = req.agent_id.trim();
+    Ok(ListAgentCredentialsResponse {
+        credentials: svc
+            .store
+            .list_agent_credentials((!agent_id.is_empty()).then_some(agent_id))?,
+    })
+}
+
+pub(super) fn revoke(svc: &CtfjxService, req: RevokeAgentCredentialRequest) -> Result<(), Status> {
+    svc.store.atomically(|tx| {
+        let mut credential = tx.get_agent_credential(&req.id)?;
+        if credential.revoked_at.is_none() {
+            credential.revoked_at = Some(now());
+            tx.update_agent_credential(&credential)?;
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,27 @@
+use ctfjx_proto::grpc::StreamEventsRequest;
+use futures_util::StreamExt;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+use tonic::Status;
+
+use crate::{
+    events,
+    service::{BoxStream, CtfjxService},
+};
+
+pub(super) fn stream(
+    svc: &CtfjxService,
+    req: StreamEventsRequest,
+) -> Result<BoxStream<ctfjx_proto::grpc::Event>, Status> {
+    let stream = BroadcastStream::new(svc.events.subscribe()).filter_map(move |res| {
+        let item = match res {
+            Ok(event) if events::matches(&req, &event) => Some(Ok(event)),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(n)) => Some(Err(Status::data_loss(format!(
+                "subscriber fell behind, {n} events dropped"
+            )))),
+        };
+        async move { item }
+    });
+
+    Ok(Box::pin(stream))
+}
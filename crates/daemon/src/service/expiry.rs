@@ -0,0 +1,92 @@
+//! Stopping instances that ran out their TTL, see [`crate::expiry`].
+
+use std::time::{Duration, SystemTime};
+
+use ctfjx_proto::grpc::{event, instance_status::State};
+use ctfjx_storage::Instance;
+use tonic::Status;
+
+use crate::{
+    events::{LABEL_CHALLENGE_ID, LABEL_INSTANCE_ID, LABEL_LEVEL, LABEL_TEAM_ID, new_event},
+    service::{CtfjxService, instance},
+};
+
+/// When the instance expires, `None` for stopped ones and those started
+/// before instances had a TTL, which live on
+fn expiry(instance: &Instance) -> Option<SystemTime> {
+    if !matches!(instance.status.state(), State::Starting | State::Running) {
+        return None;
+    }
+    instance
+        .status
+        .expires_at
+        .and_then(|t| SystemTime::try_from(t).ok())
+}
+
+/// Stops the instances expired at `at`, warning the teams of those about to
+pub(super) fn sweep(svc: &CtfjxService, at: SystemTime) -> Result<(), Status> {
+    for listed in svc.store.list_instances(None)? {
+        let Some(expires_at) = expiry(&listed) else {
+            continue;
+        };
+        let id = &listed.status.instance_id;
+        if expires_at <= at {
+            // checked again as stored, the instance may have been extended
+            let expired = |i: &Instance| expiry(i).is_some_and(|e| e <= at);
+            if instance::halt(svc, id, false, "expired", expired)?.is_some() {
+                tracing::info!(instance_id = id, "instance expired");
+            }
+        } else if svc.expiry.warn_at(expires_at) <= at
+            && listed.expiry_warned != listed.status.expires_at
+        {
+            warn(svc, id, at)?;
+        }
+    }
+    Ok(())
+}
+
+/// Warns the instance's team it expires soon, once per expiry
+fn warn(svc: &CtfjxService, instance_id: &str, at: SystemTime) -> Result<(), Status> {
+    let warned = svc.store.atomically(|tx| {
+        let mut instance = tx.get_instance(instance_id)?;
+        let due = expiry(&instance)
+            .filter(|&e| e > at && svc.expiry.warn_at(e) <= at)
+            .filter(|_| instance.expiry_warned != instance.status.expires_at);
+        let Some(expires_at) = due else {
+            return Ok::<_, Status>(None);
+        };
+        instance.expiry_warned = instance.status.expires_at;
+        tx.update_instance(&instance)?;
+        Ok(Some((
+            instance,
+            expires_at.duration_since(at).unwrap_or_default(),
+        )))
+    })?;
+    let Some((instance, left)) = warned else {
+        return Ok(());
+    };
+
+    svc.events.publish(new_event(
+        event::Type::Log,
+        "ctfjxd",
+        format!(
+            "instance {instance_id} expires in {}, extend it to keep it running",
+            minutes(left)
+        ),
+        [
+            (LABEL_LEVEL, "warn".to_string()),
+            (LABEL_INSTANCE_ID, instance_id.to_string()),
+            (LABEL_CHALLENGE_ID, instance.status.challenge_id),
+            (LABEL_TEAM_ID, instance.team_id),
+        ],
+    ));
+    Ok(())
+}
+
+/// `left`, rounded up to whole minutes
+fn minutes(left: Duration) -> String {
+    match left.as_secs().div_ceil(60) {
+        0 | 1 => "less than a minute".to_string(),
+        minutes => format!("{minutes} minutes"),
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+
+use ctfjx_proto::grpc::{Challenge, SubmitFlagRequest, SubmitFlagResponse, event};
+use ctfjx_storage::{Solve, StorageError, Submission};
+use tonic::Status;
+
+use crate::{
+    events::{LABEL_CHALLENGE_ID, LABEL_LEVEL, LABEL_TEAM_ID, new_event},
+    flags::{self, MAX_FLAG_LEN},
+    policy::Access,
+    service::{CtfjxService, new_id, now},
+};
+
+pub(super) fn submit(
+    svc: &CtfjxService,
+    req: SubmitFlagRequest,
+    access: &Access,
+) -> Result<SubmitFlagResponse, Status> {
+    if req.team_id.trim().is_empty() {
+        return Err(Status::invalid_argument("team_id is required"));
+    }
+    let flag = req.flag.trim();
+    if flag.is_empty() {
+        return Err(Status::invalid_argument("flag is required"));
+    }
+    if flag.len() > MAX_FLAG_LEN {
+        return Err(Status::invalid_argument("flag is too long"));
+    }
+
+    let challenge = svc.store.get_challenge(&req.challenge_id)?;
+    let team = svc.store.get_team(&req.team_id)?;
+    access.check_owner(|user| team.member_ids.iter().any(|m| m == user))?;
+    if challenge.flags.is_empty() {
+        return Err(Status::failed_precondition("challenge has no flags"));
+    }
+    let correct = challenge
+        .flags
+        .iter()
+        .any(|spec| svc.flags.matches(spec, &challenge.id, &req.team_id, flag));
+    let shared_from = if correct {
+        None
+    } else {
+        shared_from(svc, &challenge, &req.team_id, flag)?
+    };
+
+    let submission = Submission {
+        id: new_id(),
+        challenge_id: challenge.id.clone(),
+        team_id: req.team_id.clone(),
+        flag: flag.to_string(),
+        correct,
+        submitted_at: now(),
+        shared_from: shared_from.clone(),
+    };
+    let (solve, already_solved) = svc.store.atomically(|tx| {
+        tx.insert_submission(&submission)?;
+        if !correct {
+            return Ok::<_, Status>((None, false));
+        }
+
+        match tx.get_solve(&submission.challenge_id, &submission.team_id) {
+            Ok(solve) => return Ok((Some(solve), true)),
+            Err(StorageError::NotFound(..)) => {}
+            Err(e) => return Err(e.into()),
+        }
+        let solve = Solve {
+            challenge_id: submission.challenge_id.clone(),
+            team_id: submission.team_id.clone(),
+            submission_id: submission.id.clone(),
+            solved_at: submission.submitted_at,
+        };
+        tx.insert_solve(&solve)?;
+        Ok((Some(solve), false))
+    })?;
+
+    let message = match (&solve, already_solved) {
+        (None, _) => "incorrect flag",
+        (Some(_), true) => "challenge already solved",
+        (Some(_), false) => "correct flag",
+    };
+    if let Some(source) = shared_from {
+        svc.events.publish(new_event(
+            event::Type::Log,
+            "ctfjxd",
+            format!(
+                "team {} submitted the flag of team {source} for {}, suspected sharing",
+                req.team_id, challenge.id
+            ),
+            [
+                (LABEL_LEVEL, "warn".to_string()),
+                (LABEL_CHALLENGE_ID, challenge.id.clone()),
+                (LABEL_TEAM_ID, req.team_id.clone()),
+            ],
+        ));
+    }
+    if solve.is_some() && !already_solved {
+        svc.scores.notify();
+        svc.events.publish(new_event(
+            event::Type::Log,
+            "ctfjxd",
+            format!("team {} solved {}", req.team_id, challenge.id),
+            [(LABEL_CHALLENGE_ID, challenge.id.clone())],
+        ));
+    }
+
+    Ok(SubmitFlagResponse {
+        correct,
+        already_solved,
+        message: message.to_string(),
+        solved_at: solve.map(|s| s.solved_at),
+    })
+}
+
+/// The team a wrong flag was dynamically issued to, looking at every team
+/// that was handed one through an instance of the challenge
+fn shared_from(
+    svc: &CtfjxService,
+    challenge: &Challenge,
+    team_id: &str,
+    flag: &str,
+) -> Result<Option<String>, Status> {
+    if flags::dynamic_spec(challenge).is_none() {
+        return Ok(None);
+    }
+    let instances = svc.store.list_instances(Some(&challenge.id))?;
+    let teams: BTreeSet<&str> = instances
+        .iter()
+        .map(|i| i.team_id.as_str())
+        .filter(|owner| *owner != team_id)
+        .collect();
+    Ok(svc.flags.shared_from(challenge, flag, teams))
+}
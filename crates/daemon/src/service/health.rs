@@ -0,0 +1,12 @@
+use ctfjx_proto::grpc::{PingRequest, PingResponse};
+use tonic::Status;
+
+use crate::service::{CtfjxService, VERSION, now};
+
+pub(super) fn ping(_svc: &CtfjxService, _req: PingRequest) -> Result<PingResponse, Status> {
+    Ok(PingResponse {
+        message: "pong".to_string(),
+        server_time: Some(now()),
+        version: VERSION.to_string(),
+    })
+}
@@ -0,0 +1,286 @@
+use std::time::SystemTime;
+
+use ctfjx_proto::grpc::{
+    Challenge, ExtendInstanceRequest, GetInstanceStatusRequest, InstanceStatus,
+    StartInstanceRequest, StartInstanceResponse, StopInstanceRequest, StopInstanceResponse,
+    instance_status::State,
+};
+use ctfjx_storage::{Instance, Store};
+use tonic::Status;
+
+use crate::{
+    events,
+    jobs::{self, StartInstancePayload, StopInstancePayload},
+    policy::Access,
+    scheduler,
+    service::{CtfjxService, new_id, now},
+};
+
+/// Lets restricted callers at their own team's instances only
+fn check_team(svc: &CtfjxService, access: &Access, team_id: &str) -> Result<(), Status> {
+    if access.is_restricted() {
+        let team = svc.store.get_team(team_id)?;
+        access.check_owner(|user| team.member_ids.iter().any(|m| m == user))?;
+    }
+    Ok(())
+}
+
+/// Publishes the instance's current state
+pub(super) fn publish_state(svc: &CtfjxService, instance: &Instance) {
+    svc.events.publish(events::instance_state(instance));
+}
+
+/// The agent to run `challenge` on, `pinned` when set, or why none is
+/// eligible right now
+fn place(
+    svc: &CtfjxService,
+    challenge: &Challenge,
+    pinned: &str,
+) -> Result<Result<String, String>, Status> {
+    if !pinned.is_empty() {
+        let agent = svc.store.get_agent(pinned)?;
+        scheduler::check(&agent, &challenge.requirements).map_err(Status::failed_precondition)?;
+        return Ok(Ok(agent.agent_id));
+    }
+    let agents = svc.store.list_agents()?;
+    let instances = svc.store.list_instances(None)?;
+    Ok(
+        scheduler::place(&agents, &instances, &challenge.requirements)
+            .map(|agent| agent.agent_id.clone()),
+    )
+}
+
+/// Queues the job starting `instance` within `tx`, for its node or, without
+/// one, the first agent to meet the challenge's requirements
+fn queue_start(
+    svc: &CtfjxService,
+    tx: &dyn Store,
+    challenge: &Challenge,
+    instance: &Instance,
+) -> Result<(), Status> {
+    // the flag only travels in the job, it is derived again when checking
+    // submissions and never stored with the instance
+    let job = jobs::new_job(
+        jobs::JOB_INSTANCE_START,
+        &StartInstancePayload {
+            instance_id: instance.status.instance_id.clone(),
+            flag: svc.flags.team_flag(challenge, &instance.team_id),
+            challenge_id: challenge.id.clone(),
+            team_id: instance.team_id.clone(),
+            overrides: instance.overrides.clone(),
+            runtime: challenge.runtime.clone().unwrap_or_default(),
+        },
+    );
+    if instance.status.node.is_empty() {
+        svc.jobs
+            .enqueue_in(tx, job, None, challenge.requirements.clone())
+    } else {
+        let node = instance.status.node.clone();
+        svc.jobs.enqueue_in(tx, job, Some(node), Vec::new())
+    }
+}
+
+/// Starts a failed instance again on another agent, when one is eligible.
+/// Leaves it be when it was stopped or moved meanwhile
+pub(super) fn reschedule(svc: &CtfjxService, instance: &Instance) -> Result<(), Status> {
+    let challenge = svc.store.get_challenge(&instance.status.challenge_id)?;
+    let placed = place(svc, &challenge, "")?;
+    let from = &instance.status.node;
+    let moved = svc.store.atomically(|tx| {
+        let mut instance = tx.get_instance(&instance.status.instance_id)?;
+        if instance.status.state() != State::Failed || &instance.status.node != from {
+            return Ok::<_, Status>(None);
+        }
+        match &placed {
+            Ok(agent_id) => {
+                instance.status.set_state(State::Starting);
+                instance.status.message = format!("rescheduled from {from}");
+                instance.status.node = agent_id.clone();
+                instance.status.last_heartbeat = None;
+                instance.status.port = 0;
+                tx.update_instance(&instance)?;
+                queue_start(svc, tx, &challenge, &instance)?;
+            }
+            Err(why) => {
+                instance.status.message =
+                    format!("{}, not rescheduled: {why}", instance.status.message);
+                tx.update_instance(&instance)?;
+            }
+        }
+        Ok(Some(instance))
+    })?;
+    if let (Some(moved), Ok(_)) = (moved, placed) {
+        svc.jobs.wake();
+        publish_state(svc, &moved);
+    }
+    Ok(())
+}
+
+pub(super) fn start(
+    svc: &CtfjxService,
+    req: StartInstanceRequest,
+    access: &Access,
+) -> Result<StartInstanceResponse, Status> {
+    if req.team_id.is_empty() {
+        return Err(Status::invalid_argument("team_id is required"));
+    }
+    let challenge = svc.store.get_challenge(&req.challenge_id)?;
+    let team = svc.store.get_team(&req.team_id)?;
+    access.check_owner(|user| team.member_ids.iter().any(|m| m == user))?;
+    let ttl = svc.expiry.ttl(req.ttl_seconds)?;
+    let (node, message) = match place(svc, &challenge, &req.agent_id)? {
+        Ok(agent_id) => (agent_id, "waiting for the agent".to_string()),
+        // the job waits in the queue for an agent that meets the requirements
+        Err(why) => (String::new(), why),
+    };
+
+    let started_at = SystemTime::now();
+    let expires_at = Some(svc.expiry.expires_at(started_at, ttl)?.into());
+    let started_at = Some(started_at.into());
+    let instance = Instance {
+        status: InstanceStatus {
+            instance_id: new_id(),
+            challenge_id: challenge.id.clone(),
+            state: State::Starting as i32,
+            node,
+            message,
+            last_heartbeat: None,
+            expires_at,
+            port: 0,
+        },
+        team_id: team.id,
+        overrides: req.overrides,
+        started_at,
+        stopped_at: None,
+        expiry_warned: None,
+    };
+    svc.store.atomically(|tx| {
+        tx.insert_instance(&instance)?;
+        queue_start(svc, tx, &challenge, &instance)
+    })?;
+    svc.jobs.wake();
+    publish_state(svc, &instance);
+
+    Ok(StartInstanceResponse {
+        instance_id: instance.status.instance_id,
+        node: instance.status.node,
+        message: instance.status.message,
+        started_at,
+        expires_at,
+    })
+}
+
+pub(super) fn stop(
+    svc: &CtfjxService,
+    req: StopInstanceRequest,
+    access: &Access,
+) -> Result<StopInstanceResponse, Status> {
+    let instance = svc.store.get_instance(&req.instance_id)?;
+    check_team(svc, access, &instance.team_id)?;
+    let stopped = halt(svc, &req.instance_id, req.force, "stop requested", |i| {
+        i.status.state() != State::Stopped
+    })?
+    .ok_or_else(|| Status::failed_precondition("instance is already stopped"))?;
+
+    Ok(StopInstanceResponse {
+        instance_id: req.instance_id,
+        message: stopped.status.message,
+        stopped_at: stopped.stopped_at,
+    })
+}
+
+/// Marks the instance stopped, when `halting` still holds for it as stored.
+/// Its start job is withdrawn when no agent took it yet, otherwise the agent
+/// that may have started the instance is sent a job stopping it. `None` when
+/// `halting` did not hold
+pub(super) fn halt(
+    svc: &CtfjxService,
+    instance_id: &str,
+    force: bool,
+    message: &str,
+    halting: impl FnOnce(&Instance) -> bool,
+) -> Result<Option<Instance>, Status> {
+    let halted = svc.store.atomically(|tx| {
+        let mut instance = tx.get_instance(instance_id)?;
+        if !halting(&instance) {
+            return Ok::<_, Status>(None);
+        }
+        instance.status.set_state(State::Stopped);
+        instance.status.message = message.to_string();
+        instance.stopped_at = Some(now());
+        tx.update_instance(&instance)?;
+
+        let Some(agent_id) = jobs::withdraw_start(tx, &instance)? else {
+            return Ok(Some((instance, false)));
+        };
+        let job = jobs::new_job(
+            jobs::JOB_INSTANCE_STOP,
+            &StopInstancePayload {
+                instance_id: instance_id.to_string(),
+                force,
+            },
+        );
+        svc.jobs.enqueue_in(tx, job, Some(agent_id), Vec::new())?;
+        Ok(Some((instance, true)))
+    })?;
+    let Some((instance, queued)) = halted else {
+        return Ok(None);
+    };
+
+    if queued {
+        svc.jobs.wake();
+    }
+    publish_state(svc, &instance);
+    Ok(Some(instance))
+}
+
+pub(super) fn extend(
+    svc: &CtfjxService,
+    req: ExtendInstanceRequest,
+    access: &Access,
+) -> Result<InstanceStatus, Status> {
+    let by = svc.expiry.ttl(req.seconds)?;
+    let instance = svc.store.get_instance(&req.instance_id)?;
+    check_team(svc, access, &instance.team_id)?;
+
+    svc.store.atomically(|tx| {
+        let mut instance = tx.get_instance(&req.instance_id)?;
+        if !matches!(instance.status.state(), State::Starting | State::Running) {
+            return Err(Status::failed_precondition("instance is not running"));
+        }
+        let (Some(started_at), Some(expires_at)) = (
+            instance
+                .started_at
+                .and_then(|t| SystemTime::try_from(t).ok()),
+            instance
+                .status
+                .expires_at
+                .and_then(|t| SystemTime::try_from(t).ok()),
+        ) else {
+            return Err(Status::failed_precondition("instance does not expire"));
+        };
+        let extended = svc
+            .expiry
+            .extend(started_at, expires_at, by)?
+            .ok_or_else(|| {
+                Status::failed_precondition(format!(
+                    "instance reached its maximum lifetime of {}s",
+                    svc.expiry.max_lifetime.as_secs()
+                ))
+            })?;
+
+        instance.status.expires_at = Some(extended.into());
+        tx.update_instance(&instance)?;
+        Ok(instance.status)
+    })
+}
+
+pub(super) fn status(
+    svc: &CtfjxService,
+    req: GetInstanceStatusRequest,
+    access: &Access,
+) -> Result<InstanceStatus, Status> {
+    let instance = svc.store.get_instance(&req.instance_id)?;
+    check_team(svc, access, &instance.team_id)?;
+    Ok(instance.status)
+}
@@ -0,0 +1,100 @@
+//! Acting on agents that went silent, see [`crate::liveness`].
+
+use std::time::SystemTime;
+
+use ctfjx_proto::grpc::{event, instance_status::State};
+use ctfjx_storage::{Instance, Liveness};
+use tonic::Status;
+
+use crate::{
+    events::{LABEL_AGENT_ID, LABEL_LEVEL, new_event},
+    service::{CtfjxService, instance},
+};
+
+/// Moves every agent silent for too long to a worse state, failing the
+/// instances of the ones found dead
+pub(super) fn sweep(svc: &CtfjxService, at: SystemTime) -> Result<(), Status> {
+    for agent in svc.store.list_agents()? {
+        // hearing from an agent makes it healthy again, the sweep only ever
+        // gives up on them
+        let worsened = svc.store.atomically(|tx| {
+            let mut agent = tx.get_agent(&agent.agent_id)?;
+            let liveness = svc.liveness.assess(&agent, at);
+            if liveness <= agent.liveness {
+                return Ok::<_, Status>(None);
+            }
+            agent.liveness = liveness;
+            tx.put_agent(&agent)?;
+            Ok(Some(liveness))
+        })?;
+        match worsened {
+            Some(Liveness::Suspect) => suspect(svc, &agent.agent_id),
+            Some(Liveness::Dead) => dead(svc, &agent.agent_id)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn publish(svc: &CtfjxService, agent_id: &str, level: &str, message: String) {
+    svc.events.publish(new_event(
+        event::Type::Log,
+        "ctfjxd",
+        message,
+        [
+            (LABEL_LEVEL, level.to_string()),
+            (LABEL_AGENT_ID, agent_id.to_string()),
+        ],
+    ));
+}
+
+fn suspect(svc: &CtfjxService, agent_id: &str) {
+    tracing::warn!(agent_id, "agent missed its heartbeats");
+    publish(
+        svc,
+        agent_id,
+        "warn",
+        format!("agent {agent_id} missed its heartbeats, no longer placing instances on it"),
+    );
+}
+
+/// Fails the agent's instances, moving them elsewhere when configured to
+fn dead(svc: &CtfjxService, agent_id: &str) -> Result<(), Status> {
+    tracing::error!(agent_id, "agent is dead");
+    publish(
+        svc,
+        agent_id,
+        "error",
+        format!("agent {agent_id} stopped sending heartbeats"),
+    );
+    svc.jobs.abandon(agent_id, "agent is dead")?;
+
+    let runs = |instance: &Instance| {
+        instance.status.node == agent_id
+            && matches!(instance.status.state(), State::Starting | State::Running)
+    };
+    for listed in svc.store.list_instances(None)? {
+        if !runs(&listed) {
+            continue;
+        }
+        // instances stopped meanwhile stay stopped
+        let lost = svc.store.atomically(|tx| {
+            let mut instance = tx.get_instance(&listed.status.instance_id)?;
+            if !runs(&instance) {
+                return Ok::<_, Status>(None);
+            }
+            instance.status.set_state(State::Failed);
+            instance.status.message = format!("agent {agent_id} stopped sending heartbeats");
+            tx.update_instance(&instance)?;
+            Ok(Some(instance))
+        })?;
+        let Some(lost) = lost else {
+            continue;
+        };
+        instance::publish_state(svc, &lost);
+        if svc.liveness.reschedule {
+            instance::reschedule(svc, &lost)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,483 @@
+//! `ServiceCtfjx` implementation, one module per group of RPCs.
+
+use std::{pin::Pin, sync::Arc, time::SystemTime};
+
+use ctfjx_common::identity::Keypair;
+use ctfjx_proto::grpc::{service_ctfjx_server::ServiceCtfjx, *};
+use futures_util::Stream;
+use prost_wkt_types::Timestamp;
+use tonic::{Request, Response, Status, Streaming};
+
+use ctfjx_storage::Storage;
+
+use crate::{
+    auth::{Authenticator, Caller},
+    events::{EventBus, ScoreChanges},
+    expiry::ExpiryConfig,
+    flags::FlagChecker,
+    liveness::LivenessConfig,
+    paging::PageTokens,
+    policy::{self, Access, Method},
+    queue::{JobQueue, QueueConfig},
+    secret::Secret,
+};
+
+mod agent;
+mod challenge;
+mod enrollment;
+mod events;
+mod expiry;
+mod flag;
+mod health;
+mod instance;
+mod liveness;
+mod scoreboard;
+mod session;
+mod team;
+mod token;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+pub fn now() -> Timestamp {
+    SystemTime::now().into()
+}
+
+pub fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Checks the caller of `request` against the permission table
+fn authorize<T>(request: &Request<T>, method: Method) -> Result<Access, Status> {
+    policy::authorize(request.extensions().get::<Caller>(), method)
+}
+
+#[derive(Clone)]
+pub struct CtfjxService {
+    pub(crate) store: Arc<dyn Storage>,
+    pub(crate) events: EventBus,
+    pub(crate) pages: PageTokens,
+    pub(crate) flags: FlagChecker,
+    pub(crate) scores: ScoreChanges,
+    pub(crate) auth: Authenticator,
+    /// Signs `RegisterAgent` replies, so agents know who they talk to
+    pub(crate) identity: Keypair,
+    pub(crate) jobs: JobQueue,
+    pub(crate) sessions: session::Sessions,
+    pub(crate) boards: scoreboard::Boards,
+    pub(crate) liveness: LivenessConfig,
+    pub(crate) expiry: ExpiryConfig,
+    /// 0 for no limit
+    pub(crate) max_team_size: usize,
+}
+
+impl CtfjxService {
+    pub fn new(store: Arc<dyn Storage>, secret: &Secret) -> Self {
+        let events = EventBus::default();
+        Self {
+            store: store.clone(),
+            events: events.clone(),
+            pages: PageTokens::new(secret),
+            flags: FlagChecker::new(secret),
+            scores: ScoreChanges::default(),
+            auth: Authenticator::new(store.clone(), secret),
+            identity: Keypair::from_seed(secret.derive("agent-identity")),
+            jobs: JobQueue::new(store.clone(), events, QueueConfig::default()),
+            sessions: session::Sessions::default(),
+            boards: scoreboard::Boards::default(),
+            liveness: LivenessConfig::default(),
+            expiry: ExpiryConfig::default(),
+            max_team_size: 0,
+        }
+    }
+
+    pub fn with_max_team_size(mut self, max: usize) -> Self {
+        self.max_team_size = max;
+        self
+    }
+
+    pub fn with_job_queue(mut self, config: QueueConfig) -> Self {
+        self.jobs = JobQueue::new(self.store.clone(), self.events.clone(), config);
+        self
+    }
+
+    pub fn with_liveness(mut self, config: LivenessConfig) -> Self {
+        self.liveness = config;
+        self
+    }
+
+    pub fn with_expiry(mut self, config: ExpiryConfig) -> Self {
+        self.expiry = config;
+        self
+    }
+
+    /// Lets `token` in with every scope, to bootstrap users and their tokens
+    pub fn with_admin_token(mut self, token: Secret) -> Self {
+        self.auth = self.auth.with_admin_token(token);
+        self
+    }
+
+    /// The interceptor authenticating requests to this service
+    pub fn authenticator(&self) -> Authenticator {
+        self.auth.clone()
+    }
+
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Checks on agents every [`LivenessConfig::check_every`], forever
+    pub async fn watch_agents(self) {
+        let mut tick = tokio::time::interval(self.liveness.check_every);
+        loop {
+            tick.tick().await;
+            if let Err(e) = liveness::sweep(&self, SystemTime::now()) {
+                tracing::warn!(error = %e, "checking on agents failed");
+            }
+        }
+    }
+
+    /// Stops expired instances every [`ExpiryConfig::check_every`], forever
+    pub async fn reap_instances(self) {
+        let mut tick = tokio::time::interval(self.expiry.check_every);
+        loop {
+            tick.tick().await;
+            if let Err(e) = expiry::sweep(&self, SystemTime::now()) {
+                tracing::warn!(error = %e, "reaping instances failed");
+            }
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ServiceCtfjx for CtfjxService {
+    async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+        authorize(&request, Method::Ping)?;
+        health::ping(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn create_challenge(
+        &self,
+        request: Request<CreateChallengeRequest>,
+    ) -> Result<Response<CreateChallengeResponse>, Status> {
+        let access = authorize(&request, Method::CreateChallenge)?;
+        challenge::create(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn get_challenge(
+        &self,
+        request: Request<GetChallengeRequest>,
+    ) -> Result<Response<Challenge>, Status> {
+        let access = authorize(&request, Method::GetChallenge)?;
+        challenge::get(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn update_challenge(
+        &self,
+        request: Request<UpdateChallengeRequest>,
+    ) -> Result<Response<Challenge>, Status> {
+        let access = authorize(&request, Method::UpdateChallenge)?;
+        challenge::update(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn delete_challenge(
+        &self,
+        request: Request<DeleteChallengeRequest>,
+    ) -> Result<Response<()>, Status> {
+        let access = authorize(&request, Method::DeleteChallenge)?;
+        challenge::delete(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn list_challenges(
+        &self,
+        request: Request<ListChallengesRequest>,
+    ) -> Result<Response<ListChallengesResponse>, Status> {
+        let access = authorize(&request, Method::ListChallenges)?;
+        challenge::list(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn create_user(
+        &self,
+        request: Request<CreateUserRequest>,
+    ) -> Result<Response<User>, Status> {
+        authorize(&request, Method::CreateUser)?;
+        team::create_user(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn get_user(&self, request: Request<GetUserRequest>) -> Result<Response<User>, Status> {
+        authorize(&request, Method::GetUser)?;
+        team::get_user(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn create_team(
+        &self,
+        request: Request<CreateTeamRequest>,
+    ) -> Result<Response<Team>, Status> {
+        let access = authorize(&request, Method::CreateTeam)?;
+        team::create(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn get_team(&self, request: Request<GetTeamRequest>) -> Result<Response<Team>, Status> {
+        let access = authorize(&request, Method::GetTeam)?;
+        team::get(self, request.into_inner(), access.is_admin()).map(Response::new)
+    }
+
+    async fn list_teams(
+        &self,
+        request: Request<ListTeamsRequest>,
+    ) -> Result<Response<ListTeamsResponse>, Status> {
+        let access = authorize(&request, Method::ListTeams)?;
+        team::list(self, request.into_inner(), access.is_admin()).map(Response::new)
+    }
+
+    async fn join_team(&self, request: Request<JoinTeamRequest>) -> Result<Response<Team>, Status> {
+        let access = authorize(&request, Method::JoinTeam)?;
+        team::join(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn leave_team(&self, request: Request<LeaveTeamRequest>) -> Result<Response<()>, Status> {
+        let access = authorize(&request, Method::LeaveTeam)?;
+        team::leave(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn transfer_captain(
+        &self,
+        request: Request<TransferCaptainRequest>,
+    ) -> Result<Response<Team>, Status> {
+        let access = authorize(&request, Method::TransferCaptain)?;
+        team::transfer_captain(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn reset_invite_code(
+        &self,
+        request: Request<ResetInviteCodeRequest>,
+    ) -> Result<Response<Team>, Status> {
+        let access = authorize(&request, Method::ResetInviteCode)?;
+        team::reset_invite_code(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn create_token(
+        &self,
+        request: Request<CreateTokenRequest>,
+    ) -> Result<Response<CreateTokenResponse>, Status> {
+        let access = authorize(&request, Method::CreateToken)?;
+        token::create(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn list_tokens(
+        &self,
+        request: Request<ListTokensRequest>,
+    ) -> Result<Response<ListTokensResponse>, Status> {
+        let access = authorize(&request, Method::ListTokens)?;
+        token::list(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn revoke_token(
+        &self,
+        request: Request<RevokeTokenRequest>,
+    ) -> Result<Response<()>, Status> {
+        let access = authorize(&request, Method::RevokeToken)?;
+        token::revoke(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn create_session(
+        &self,
+        request: Request<CreateSessionRequest>,
+    ) -> Result<Response<Session>, Status> {
+        let access = authorize(&request, Method::CreateSession)?;
+        token::create_session(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn submit_flag(
+        &self,
+        request: Request<SubmitFlagRequest>,
+    ) -> Result<Response<SubmitFlagResponse>, Status> {
+        let access = authorize(&request, Method::SubmitFlag)?;
+        flag::submit(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn invalidate_solve(
+        &self,
+        request: Request<InvalidateSolveRequest>,
+    ) -> Result<Response<()>, Status> {
+        authorize(&request, Method::InvalidateSolve)?;
+        scoreboard::invalidate(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn get_scoreboard(
+        &self,
+        request: Request<GetScoreboardRequest>,
+    ) -> Result<Response<Scoreboard>, Status> {
+        let access = authorize(&request, Method::GetScoreboard)?;
+        scoreboard::get(self, request.into_inner(), access.is_admin()).map(Response::new)
+    }
+
+    type WatchScoreboardStream = BoxStream<ScoreboardDelta>;
+
+    async fn watch_scoreboard(
+        &self,
+        request: Request<WatchScoreboardRequest>,
+    ) -> Result<Response<Self::WatchScoreboardStream>, Status> {
+        let access = authorize(&request, Method::WatchScoreboard)?;
+        scoreboard::watch(self, request.into_inner(), access.is_admin()).map(Response::new)
+    }
+
+    async fn freeze_scoreboard(
+        &self,
+        request: Request<FreezeScoreboardRequest>,
+    ) -> Result<Response<()>, Status> {
+        authorize(&request, Method::FreezeScoreboard)?;
+        scoreboard::freeze(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn unfreeze_scoreboard(
+        &self,
+        request: Request<UnfreezeScoreboardRequest>,
+    ) -> Result<Response<()>, Status> {
+        authorize(&request, Method::UnfreezeScoreboard)?;
+        scoreboard::unfreeze(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn set_team_division(
+        &self,
+        request: Request<SetTeamDivisionRequest>,
+    ) -> Result<Response<()>, Status> {
+        authorize(&request, Method::SetTeamDivision)?;
+        scoreboard::set_division(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn start_instance(
+        &self,
+        request: Request<StartInstanceRequest>,
+    ) -> Result<Response<StartInstanceResponse>, Status> {
+        let access = authorize(&request, Method::StartInstance)?;
+        instance::start(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn stop_instance(
+        &self,
+        request: Request<StopInstanceRequest>,
+    ) -> Result<Response<StopInstanceResponse>, Status> {
+        let access = authorize(&request, Method::StopInstance)?;
+        instance::stop(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn get_instance_status(
+        &self,
+        request: Request<GetInstanceStatusRequest>,
+    ) -> Result<Response<InstanceStatus>, Status> {
+        let access = authorize(&request, Method::GetInstanceStatus)?;
+        instance::status(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn extend_instance(
+        &self,
+        request: Request<ExtendInstanceRequest>,
+    ) -> Result<Response<InstanceStatus>, Status> {
+        let access = authorize(&request, Method::ExtendInstance)?;
+        instance::extend(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn create_join_token(
+        &self,
+        request: Request<CreateJoinTokenRequest>,
+    ) -> Result<Response<JoinToken>, Status> {
+        authorize(&request, Method::CreateJoinToken)?;
+        enrollment::create_join_token(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn enroll_agent(
+        &self,
+        request: Request<EnrollAgentRequest>,
+    ) -> Result<Response<EnrollAgentResponse>, Status> {
+        authorize(&request, Method::EnrollAgent)?;
+        enrollment::enroll(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn rotate_agent_key(
+        &self,
+        request: Request<RotateAgentKeyRequest>,
+    ) -> Result<Response<EnrollAgentResponse>, Status> {
+        let access = authorize(&request, Method::RotateAgentKey)?;
+        enrollment::rotate(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn list_agent_credentials(
+        &self,
+        request: Request<ListAgentCredentialsRequest>,
+    ) -> Result<Response<ListAgentCredentialsResponse>, Status> {
+        authorize(&request, Method::ListAgentCredentials)?;
+        enrollment::list(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn revoke_agent_credential(
+        &self,
+        request: Request<RevokeAgentCredentialRequest>,
+    ) -> Result<Response<()>, Status> {
+        authorize(&request, Method::RevokeAgentCredential)?;
+        enrollment::revoke(self, request.into_inner()).map(Response::new)
+    }
+
+    async fn register_agent(
+        &self,
+        request: Request<RegisterAgentRequest>,
+    ) -> Result<Response<RegisterAgentResponse>, Status> {
+        let access = authorize(&request, Method::RegisterAgent)?;
+        agent::register(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn assign_job(
+        &self,
+        request: Request<AssignJobRequest>,
+    ) -> Result<Response<AssignJobResponse>, Status> {
+        let access = authorize(&request, Method::AssignJob)?;
+        agent::assign_job(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn report_job_result(
+        &self,
+        request: Request<ReportJobResultRequest>,
+    ) -> Result<Response<()>, Status> {
+        let access = authorize(&request, Method::ReportJobResult)?;
+        agent::report_result(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn send_heartbeat(&self, request: Request<Heartbeat>) -> Result<Response<()>, Status> {
+        let access = authorize(&request, Method::SendHeartbeat)?;
+        agent::heartbeat(self, request.into_inner(), &access).map(Response::new)
+    }
+
+    async fn list_dead_jobs(
+        &self,
+        request: Request<ListDeadJobsRequest>,
+    ) -> Result<Response<ListDeadJobsResponse>, Status> {
+        authorize(&request, Method::ListDeadJobs)?;
+        agent::list_dead_jobs(self, request.into_inner()).map(Response::new)
+    }
+
+    type StreamEventsStream = BoxStream<Event>;
+
+    async fn stream_events(
+        &self,
+        request: Request<StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        authorize(&request, Method::StreamEvents)?;
+        events::stream(self, request.into_inner()).map(Response::new)
+    }
+
+    type AgentStreamStream = BoxStream<AgentFrame>;
+
+    async fn agent_stream(
+        &self,
+        request: Request<Streaming<AgentFrame>>,
+    ) -> Result<Response<Self::AgentStreamStream>, Status> {
+        let access = authorize(&request, Method::AgentStream)?;
+        agent::enrolled(&access)?;
+        Ok(Response::new(session::open(
+            self,
+            request.into_inner(),
+            access,
+        )))
+    }
+}
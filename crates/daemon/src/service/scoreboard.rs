@@ -0,0 +1,277 @@
+use std::{collections::HashMap, future, sync::Arc, time::SystemTime};
+
+use ctfjx_proto::grpc::{
+    FreezeScoreboardRequest, GetScoreboardRequest, InvalidateSolveRequest, Scoreboard,
+    ScoreboardDelta, SetTeamDivisionRequest, UnfreezeScoreboardRequest, WatchScoreboardRequest,
+    event,
+};
+use ctfjx_storage::{Page, ts_key};
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+use tonic::Status;
+
+use crate::{
+    events::{LABEL_CHALLENGE_ID, LABEL_TEAM_ID, new_event},
+    paging::page_size_or_default,
+    scoring,
+    service::{BoxStream, CtfjxService, now},
+};
+
+/// Names scoreboard listings in page tokens
+const LIST: &str = "scoreboard";
+
+/// A board as computed for one view, shared by everybody watching it
+type Shared = Result<Arc<Scoreboard>, Status>;
+
+/// Whose board a watcher sees, a division through admin or public eyes
+type ViewKey = (String, bool);
+
+/// Hands the latest board of a view to its watchers, `None` until computed
+type Publisher = Arc<watch::Sender<Option<Shared>>>;
+
+/// The boards being watched. Each view is recomputed once per change by a
+/// task of its own, which stops with the last watcher of the view. Watchers
+/// only diff the shared board against what they sent last
+#[derive(Default, Clone)]
+pub struct Boards {
+    views: Arc<Mutex<HashMap<ViewKey, Publisher>>>,
+}
+
+impl Boards {
+    fn subscribe(
+        &self,
+        svc: &CtfjxService,
+        division: &str,
+        admin: bool,
+    ) -> watch::Receiver<Option<Shared>> {
+        let key = (division.to_string(), admin);
+        let mut views = self.views.lock();
+        if let Some(tx) = views.get(&key) {
+            return tx.subscribe();
+        }
+
+        let (tx, rx) = watch::channel(None);
+        let tx = Arc::new(tx);
+        views.insert(key.clone(), tx.clone());
+        tokio::spawn(recompute(svc.clone(), key, tx));
+        rx
+    }
+}
+
+/// Recomputes the board of `key` on every score change, and when a
+/// scheduled freeze starts hiding solves, as long as anybody watches it
+async fn recompute(svc: CtfjxService, key: ViewKey, tx: Publisher) {
+    let (division, admin) = &key;
+    let mut changes = svc.scores.subscribe();
+    loop {
+        changes.mark_unchanged();
+        let freezes_in = match view(&svc, division, *admin) {
+            Ok((board, freezes_at)) => {
+                tx.send_replace(Some(Ok(Arc::new(board))));
+                freezes_at.map(|at| at.duration_since(SystemTime::now()).unwrap_or_default())
+            }
+            Err(e) => {
+                tx.send_replace(Some(Err(e)));
+                None
+            }
+        };
+        let freeze = async {
+            match freezes_in {
+                Some(after) => tokio::time::sleep(after).await,
+                None => future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = changes.changed() => {}
+            () = freeze => {}
+            () = tx.closed() => {
+                // somebody may have subscribed since, under the same lock
+                let mut views = svc.boards.views.lock();
+                if tx.receiver_count() == 0 {
+                    views.remove(&key);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// The board of `division` as the caller may see it, and when a scheduled
+/// freeze is going to change it. Admins always see the live board, everybody
+/// else stops seeing solves once it is frozen
+fn view(
+    svc: &CtfjxService,
+    division: &str,
+    admin: bool,
+) -> Result<(Scoreboard, Option<SystemTime>), Status> {
+    let (challenges, mut solves, teams, settings) = svc.store.atomically(|tx| {
+        Ok::<_, Status>((
+            tx.list_challenges(None, &Page::ALL)?,
+            tx.list_solves(None)?,
+            tx.list_teams(&Page::ALL)?,
+            tx.get_scoreboard_settings()?,
+        ))
+    })?;
+
+    let now = now();
+    let frozen_at = settings
+        .freeze_at
+        .filter(|at| !admin && ts_key(at) <= ts_key(&now));
+    let freezes_at = settings
+        .freeze_at
+        .filter(|at| !admin && ts_key(at) > ts_key(&now))
+        .and_then(|at| SystemTime::try_from(at).ok());
+    if let Some(at) = &frozen_at {
+        solves.retain(|s| ts_key(&s.solved_at) < ts_key(at));
+    }
+
+    let divisions = teams
+        .into_iter()
+        .filter(|t| !t.division.is_empty())
+        .map(|t| (t.id, t.division))
+        .collect();
+    let (entries, challenges) = scoring::scoreboard(&challenges, &solves);
+    let board = Scoreboard {
+        entries: scoring::rank_division(entries, &divisions, division),
+        challenges,
+        next_page_token: String::new(),
+        frozen_at,
+    };
+    Ok((board, freezes_at))
+}
+
+pub(super) fn get(
+    svc: &CtfjxService,
+    req: GetScoreboardRequest,
+    admin: bool,
+) -> Result<Scoreboard, Status> {
+    let size = page_size_or_default(req.page_size)?;
+    let after = match svc.pages.decode(LIST, &req.page_token, &req.division)? {
+        Some(rank) => rank
+            .parse::<i32>()
+            .map_err(|_| Status::invalid_argument("invalid page token"))?,
+        None => 0,
+    };
+
+    let (mut board, _) = view(svc, &req.division, admin)?;
+    board.entries.retain(|e| e.rank > after);
+    if board.entries.len() > size {
+        board.entries.truncate(size);
+        if let Some(last) = board.entries.last() {
+            board.next_page_token = svc
+                .pages
+                .encode(LIST, &last.rank.to_string(), &req.division);
+        }
+    }
+    Ok(board)
+}
+
+/// Streams the board of a division, whole at first and then as deltas
+pub(super) fn watch(
+    svc: &CtfjxService,
+    req: WatchScoreboardRequest,
+    admin: bool,
+) -> Result<BoxStream<ScoreboardDelta>, Status> {
+    let boards = svc.boards.subscribe(svc, &req.division, admin);
+    let mut prev: Option<Arc<Scoreboard>> = None;
+    let stream = WatchStream::new(boards).filter_map(move |shared| {
+        let item = match shared {
+            // not computed yet
+            None => None,
+            Some(Ok(board)) => {
+                let delta = scoring::diff(prev.as_deref(), &board);
+                prev = Some(board);
+                delta.map(Ok)
+            }
+            Some(Err(e)) => Some(Err(e)),
+        };
+        async move { item }
+    });
+
+    Ok(Box::pin(stream))
+}
+
+pub(super) fn freeze(svc: &CtfjxService, req: FreezeScoreboardRequest) -> Result<(), Status> {
+    let freeze_at = req.freeze_at.unwrap_or_else(now);
+    svc.store.atomically(|tx| {
+        let mut settings = tx.get_scoreboard_settings()?;
+        settings.freeze_at = Some(freeze_at);
+        Ok::<_, Status>(tx.put_scoreboard_settings(&settings)?)
+    })?;
+    svc.scores.notify();
+
+    svc.events.publish(new_event(
+        event::Type::Log,
+        "ctfjxd",
+        format!("scoreboard frozen at {freeze_at}"),
+        [],
+    ));
+    Ok(())
+}
+
+/// Lifts the freeze, public views catch up with every solve it hid
+pub(super) fn unfreeze(svc: &CtfjxService, _req: UnfreezeScoreboardRequest) -> Result<(), Status> {
+    let was_frozen = svc.store.atomically(|tx| {
+        let mut settings = tx.get_scoreboard_settings()?;
+        let was_frozen = settings.freeze_at.take().is_some();
+        tx.put_scoreboard_settings(&settings)?;
+        Ok::<_, Status>(was_frozen)
+    })?;
+    if !was_frozen {
+        return Ok(());
+    }
+    svc.scores.notify();
+
+    svc.events.publish(new_event(
+        event::Type::Log,
+        "ctfjxd",
+        "scoreboard unfrozen",
+        [],
+    ));
+    Ok(())
+}
+
+pub(super) fn set_division(svc: &CtfjxService, req: SetTeamDivisionRequest) -> Result<(), Status> {
+    if req.team_id.is_empty() {
+        return Err(Status::invalid_argument("team_id is required"));
+    }
+    svc.store.atomically(|tx| {
+        let mut team = tx.get_team(&req.team_id)?;
+        req.division.trim().clone_into(&mut team.division);
+        Ok::<_, Status>(tx.update_team(&team)?)
+    })?;
+    svc.scores.notify();
+    Ok(())
+}
+
+/// Removes a solve, the board is recomputed without it from then on
+pub(super) fn invalidate(svc: &CtfjxService, req: InvalidateSolveRequest) -> Result<(), Status> {
+    if req.challenge_id.is_empty() || req.team_id.is_empty() {
+        return Err(Status::invalid_argument(
+            "challenge_id and team_id are required",
+        ));
+    }
+    svc.store.delete_solve(&req.challenge_id, &req.team_id)?;
+    svc.scores.notify();
+
+    let reason = match req.reason.trim() {
+        "" => String::new(),
+        reason => format!(": {reason}"),
+    };
+    svc.events.publish(new_event(
+        event::Type::Log,
+        "ctfjxd",
+        format!(
+            "solve of {} by team {} invalidated{reason}",
+            req.challenge_id, req.team_id
+        ),
+        [
+            (LABEL_CHALLENGE_ID, req.challenge_id),
+            (LABEL_TEAM_ID, req.team_id),
+        ],
+    ));
+    Ok(())
+}
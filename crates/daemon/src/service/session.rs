@@ -0,0 +1,176 @@
+//! `AgentStream`, the control channel agents hold open instead of polling.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use ctfjx_proto::grpc::{AgentFrame, agent_frame::Payload};
+use parking_lot::Mutex;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Status, Streaming};
+
+use crate::{
+    policy::Access,
+    service::{BoxStream, CtfjxService, agent},
+};
+
+/// How often jobs that finished backing off are looked for
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Frames queued for a slow agent before pushing jobs waits
+const OUTBOX_SIZE: usize = 16;
+
+type Outbox = mpsc::Sender<Result<AgentFrame, Status>>;
+
+/// The open stream of each agent. A reconnecting agent replaces its previous
+/// stream, which no longer takes the agent offline once it drops
+#[derive(Default, Clone)]
+pub struct Sessions {
+    current: Arc<Mutex<HashMap<String, u64>>>,
+    next: Arc<AtomicU64>,
+}
+
+impl Sessions {
+    fn open(&self, agent_id: &str) -> u64 {
+        let id = self.next.fetch_add(1, Ordering::Relaxed);
+        self.current.lock().insert(agent_id.to_string(), id);
+        id
+    }
+
+    /// Whether session `id` was still the agent's current one
+    fn close(&self, agent_id: &str, id: u64) -> bool {
+        let mut current = self.current.lock();
+        if current.get(agent_id) != Some(&id) {
+            return false;
+        }
+        current.remove(agent_id);
+        true
+    }
+}
+
+fn frame(payload: Payload) -> Result<AgentFrame, Status> {
+    Ok(AgentFrame {
+        payload: Some(payload),
+    })
+}
+
+pub(super) fn open(
+    svc: &CtfjxService,
+    inbound: Streaming<AgentFrame>,
+    access: Access,
+) -> BoxStream<AgentFrame> {
+    let (tx, rx) = mpsc::channel(OUTBOX_SIZE);
+    let svc = svc.clone();
+    tokio::spawn(async move {
+        if let Err(status) = run(&svc, inbound, &access, &tx).await {
+            let _ = tx.send(Err(status)).await;
+        }
+    });
+    Box::pin(ReceiverStream::new(rx))
+}
+
+/// Registers the agent from the first frame and serves it until either side
+/// hangs up
+async fn run(
+    svc: &CtfjxService,
+    mut inbound: Streaming<AgentFrame>,
+    access: &Access,
+    tx: &Outbox,
+) -> Result<(), Status> {
+    let register = match inbound.message().await? {
+        Some(AgentFrame {
+            payload: Some(Payload::Register(register)),
+        }) => register,
+        None => return Ok(()),
+        Some(_) => {
+            return Err(Status::failed_precondition(
+                "the first frame must be `register`",
+            ));
+        }
+    };
+    let agent_id = register.agent_id.clone();
+    let registered = agent::register(svc, register, access)?;
+    let session = svc.sessions.open(&agent_id);
+    tracing::info!(agent_id, "agent connected");
+
+    let result = match tx.send(frame(Payload::Registered(registered))).await {
+        Ok(()) => serve(svc, &agent_id, inbound, tx).await,
+        Err(_) => Ok(()),
+    };
+    if svc.sessions.close(&agent_id, session)
+        && let Err(e) = agent::disconnect(svc, &agent_id)
+    {
+        tracing::warn!(agent_id, error = %e, "failed to take agent offline");
+    }
+    result
+}
+
+async fn serve(
+    svc: &CtfjxService,
+    agent_id: &str,
+    mut inbound: Streaming<AgentFrame>,
+    tx: &Outbox,
+) -> Result<(), Status> {
+    let mut wake = svc.jobs.subscribe();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        if !push(svc, agent_id, tx).await? {
+            return Ok(());
+        }
+        tokio::select! {
+            message = inbound.message() => match message? {
+                Some(frame) => handle(svc, agent_id, frame)?,
+                None => return Ok(()),
+            },
+            _ = wake.changed() => {}
+            _ = poll.tick() => {}
+            () = tx.closed() => return Ok(()),
+        }
+    }
+}
+
+/// Sends the agent the jobs it may take, false once it stopped listening
+async fn push(svc: &CtfjxService, agent_id: &str, tx: &Outbox) -> Result<bool, Status> {
+    let agent = svc.store.get_agent(agent_id)?;
+    for job in svc.jobs.lease(&agent, agent::MAX_JOBS_PER_ASSIGN)? {
+        if tx.send(frame(Payload::Job(job))).await.is_err() {
+            // the lease lapses, or the job is released once the stream drops
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn handle(svc: &CtfjxService, agent_id: &str, frame: AgentFrame) -> Result<(), Status> {
+    match frame.payload {
+        Some(Payload::Heartbeat(mut heartbeat)) => {
+            if heartbeat.agent_id.is_empty() {
+                heartbeat.agent_id = agent_id.to_string();
+            } else if heartbeat.agent_id != agent_id {
+                return Err(Status::permission_denied(
+                    "heartbeats are for the registered agent only",
+                ));
+            }
+            agent::record_heartbeat(svc, heartbeat)
+        }
+        Some(Payload::JobResult(result)) => {
+            // a stale result does not end the stream, the job was settled or
+            // handed to someone else meanwhile
+            if let Err(e) = svc.jobs.complete(agent_id, &result) {
+                tracing::warn!(agent_id, job_id = %result.job_id, error = %e, "job result ignored");
+            }
+            Ok(())
+        }
+        Some(Payload::Register(_)) => Err(Status::failed_precondition("already registered")),
+        Some(Payload::Job(_) | Payload::JobResponse(_) | Payload::Registered(_)) => Err(
+            Status::invalid_argument("only the daemon sends jobs and registrations"),
+        ),
+        None => Err(Status::invalid_argument("empty frame")),
+    }
+}
@@ -0,0 +1,223 @@
+use ctfjx_proto::grpc::{
+    CreateTeamRequest, CreateUserRequest, GetTeamRequest, GetUserRequest, JoinTeamRequest,
+    LeaveTeamRequest, ListTeamsRequest, ListTeamsResponse, ResetInviteCodeRequest, Team,
+    TransferCaptainRequest, User,
+};
+use ctfjx_storage::StorageError;
+use tonic::Status;
+
+use crate::{
+    policy::{Access, effective_role},
+    service::{CtfjxService, new_id, now},
+};
+
+/// Names team listings in page tokens
+const LIST: &str = "teams";
+
+/// Bytes of randomness in an invite code
+const INVITE_CODE_LEN: usize = 12;
+
+fn new_invite_code() -> String {
+    hex::encode(rand::random::<[u8; INVITE_CODE_LEN]>())
+}
+
+/// Strips what only admins may see
+fn redact(mut team: Team, admin: bool) -> Team {
+    if !admin {
+        team.invite_code.clear();
+    }
+    team
+}
+
+fn required(field: &str, value: &str) -> Result<(), Status> {
+    if value.trim().is_empty() {
+        return Err(Status::invalid_argument(format!("{field} is required")));
+    }
+    Ok(())
+}
+
+pub(super) fn create_user(svc: &CtfjxService, req: CreateUserRequest) -> Result<User, Status> {
+    let mut user = req
+        .user
+        .ok_or(Status::invalid_argument("user is required"))?;
+    required("user name", &user.name)?;
+
+    if user.id.is_empty() {
+        user.id = new_id();
+    }
+    user.set_role(effective_role(user.role()));
+    // users join teams through `JoinTeam` and `CreateTeam` only
+    user.team_id.clear();
+    user.created_at = Some(now());
+
+    svc.store.insert_user(&user)?;
+    Ok(user)
+}
+
+pub(super) fn get_user(svc: &CtfjxService, req: GetUserRequest) -> Result<User, Status> {
+    Ok(svc.store.get_user(&req.id)?)
+}
+
+/// Creates a team around its captain, the only call returning the invite
+/// code besides [`reset_invite_code`]
+pub(super) fn create(
+    svc: &CtfjxService,
+    req: CreateTeamRequest,
+    access: &Access,
+) -> Result<Team, Status> {
+    let mut team = req
+        .team
+        .ok_or(Status::invalid_argument("team is required"))?;
+    required("team name", &team.name)?;
+    required("captain_id", &team.captain_id)?;
+    // players found teams for themselves
+    access.check_owner(|user| team.captain_id == user)?;
+
+    if team.id.is_empty() {
+        team.id = new_id();
+    }
+    team.name = team.name.trim().to_string();
+    team.division = team.division.trim().to_string();
+    team.member_ids = vec![team.captain_id.clone()];
+    team.invite_code = new_invite_code();
+    team.created_at = Some(now());
+
+    svc.store.atomically(|tx| {
+        let mut captain = tx.get_user(&team.captain_id)?;
+        if !captain.team_id.is_empty() {
+            return Err(Status::failed_precondition("captain is already in a team"));
+        }
+        captain.team_id = team.id.clone();
+        tx.insert_team(&team)?;
+        tx.update_user(&captain)?;
+        Ok(())
+    })?;
+    svc.scores.notify();
+    Ok(team)
+}
+
+pub(super) fn get(svc: &CtfjxService, req: GetTeamRequest, admin: bool) -> Result<Team, Status> {
+    Ok(redact(svc.store.get_team(&req.id)?, admin))
+}
+
+pub(super) fn list(
+    svc: &CtfjxService,
+    req: ListTeamsRequest,
+    admin: bool,
+) -> Result<ListTeamsResponse, Status> {
+    let (page, size) = svc.pages.page(LIST, req.page_size, &req.page_token, "")?;
+    let mut teams = svc.store.list_teams(&page)?;
+    let next_page_token = svc.pages.finish(LIST, &mut teams, size, "", |t| &t.id);
+
+    Ok(ListTeamsResponse {
+        teams: teams.into_iter().map(|t| redact(t, admin)).collect(),
+        next_page_token,
+    })
+}
+
+pub(super) fn join(
+    svc: &CtfjxService,
+    req: JoinTeamRequest,
+    access: &Access,
+) -> Result<Team, Status> {
+    required("invite_code", &req.invite_code)?;
+    access.check_owner(|user| req.user_id == user)?;
+
+    let team = svc.store.atomically(|tx| {
+        let mut user = tx.get_user(&req.user_id)?;
+        if !user.team_id.is_empty() {
+            return Err(Status::failed_precondition("user is already in a team"));
+        }
+        let mut team = match tx.get_team_by_invite(req.invite_code.trim()) {
+            Ok(team) => team,
+            // the code is a secret, it is not echoed back
+            Err(StorageError::NotFound(..)) => {
+                return Err(Status::not_found("invalid invite code"));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if svc.max_team_size > 0 && team.member_ids.len() >= svc.max_team_size {
+            return Err(Status::failed_precondition(format!(
+                "team is full, teams have at most {} members",
+                svc.max_team_size
+            )));
+        }
+
+        team.member_ids.push(user.id.clone());
+        if team.captain_id.is_empty() {
+            team.captain_id = user.id.clone();
+        }
+        user.team_id = team.id.clone();
+        tx.update_team(&team)?;
+        tx.update_user(&user)?;
+        Ok(team)
+    })?;
+    Ok(redact(team, access.is_admin()))
+}
+
+/// Takes a user out of their team. The team and its solves stay when the
+/// last member leaves
+pub(super) fn leave(
+    svc: &CtfjxService,
+    req: LeaveTeamRequest,
+    access: &Access,
+) -> Result<(), Status> {
+    access.check_owner(|user| req.user_id == user)?;
+    svc.store.atomically(|tx| {
+        let mut user = tx.get_user(&req.user_id)?;
+        if user.team_id.is_empty() {
+            return Err(Status::failed_precondition("user is not in a team"));
+        }
+        let mut team = tx.get_team(&user.team_id)?;
+        if team.captain_id == user.id {
+            if team.member_ids.len() > 1 {
+                return Err(Status::failed_precondition(
+                    "the captain has to transfer the team before leaving",
+                ));
+            }
+            team.captain_id.clear();
+        }
+
+        team.member_ids.retain(|id| *id != user.id);
+        user.team_id.clear();
+        tx.update_team(&team)?;
+        tx.update_user(&user)?;
+        Ok(())
+    })
+}
+
+pub(super) fn transfer_captain(
+    svc: &CtfjxService,
+    req: TransferCaptainRequest,
+    access: &Access,
+) -> Result<Team, Status> {
+    let team = svc.store.atomically(|tx| {
+        let mut team = tx.get_team(&req.team_id)?;
+        access.check_owner(|user| team.captain_id == user)?;
+        if !team.member_ids.contains(&req.user_id) {
+            return Err(Status::failed_precondition(format!(
+                "user `{}` is not a member of the team",
+                req.user_id
+            )));
+        }
+        team.captain_id = req.user_id.clone();
+        tx.update_team(&team)?;
+        Ok(team)
+    })?;
+    Ok(redact(team, access.is_admin()))
+}
+
+/// Captains hand the new code to their team, it is returned to them too
+pub(super) fn reset_invite_code(
+    svc: &CtfjxService,
+    req: ResetInviteCodeRequest,
+    access: &Access,
+) -> Result<Team, Status> {
+    svc.store.atomically(|tx| {
+        let mut team = tx.get_team(&req.team_id)?;
+        access.check_owner(|user| team.captain_id == user)?;
+        team.invite_code = new_invite_code();
+        tx.update_team(&team)?;
+        Ok(team)
+    })
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use ctfjx_proto::grpc::{
+    ApiToken, CreateSessionRequest, CreateTokenRequest, CreateTokenResponse, ListTokensRequest,
+    ListTokensResponse, RevokeTokenRequest, Session,
+};
+use ctfjx_storage::ts_key;
+use tonic::Status;
+
+use crate::{
+    auth::Scope,
+    policy::{self, Access},
+    service::{CtfjxService, now},
+};
+
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+const MAX_SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The user a request targets, the caller's own unless an admin names
+/// someone else
+fn target_user(access: &Access, user_id: &str) -> Result<String, Status> {
+    let caller = access.required_caller()?;
+    if user_id.is_empty() || user_id == caller.user_id {
+        return Ok(caller.user_id.clone());
+    }
+    if access.is_restricted() || !caller.has(Scope::Admin) {
+        return Err(Status::permission_denied(
+            "only admins manage the tokens of other users",
+        ));
+    }
+    Ok(user_id.to_string())
+}
+
+pub(super) fn create(
+    svc: &CtfjxService,
+    req: CreateTokenRequest,
+    access: &Access,
+) -> Result<CreateTokenResponse, Status> {
+    if req.name.trim().is_empty() {
+        return Err(Status::invalid_argument("token name is required"));
+    }
+    let caller = access.required_caller()?;
+    let user_id = target_user(access, &req.user_id)?;
+    if user_id.is_empty() {
+        return Err(Status::invalid_argument("user_id is required"));
+    }
+    let user = svc.store.get_user(&user_id)?;
+
+    let scope = Scope::parse_all(&req.scopes)?;
+    if scope > caller.scope {
+        return Err(Status::permission_denied(format!(
+            "cannot grant `{scope}` with a `{}` credential",
+            caller.scope
+        )));
+    }
+    if scope > policy::max_scope(user.role()) {
+        return Err(Status::invalid_argument(format!(
+            "{}s cannot hold `{scope}` tokens",
+            policy::role_name(user.role())
+        )));
+    }
+    if req
+        .expires_at
+        .is_some_and(|at| ts_key(&at) <= ts_key(&now()))
+    {
+        return Err(Status::invalid_argument("expires_at is in the past"));
+    }
+
+    let (token, secret) = svc.auth.issue_token(ApiToken {
+        name: req.name.trim().to_string(),
+        user_id,
+        scopes: vec![scope.to_string()],
+        expires_at: req.expires_at,
+        ..Default::default()
+    })?;
+    Ok(CreateTokenResponse {
+        token: Some(token),
+        secret,
+    })
+}
+
+pub(super) fn list(
+    svc: &CtfjxService,
+    req: ListTokensRequest,
+    access: &Access,
+) -> Result<ListTokensResponse, Status> {
+    let user_id = target_user(access, &req.user_id)?;
+    // only the admin token has no user, it sees every token
+    let tokens = svc
+        .store
+        .list_tokens((!user_id.is_empty()).then_some(user_id.as_str()))?;
+    Ok(ListTokensResponse {
+        tokens: tokens.into_iter().map(|t| t.token).collect(),
+    })
+}
+
+/// Revokes a token and every session traded for it
+pub(super) fn revoke(
+    svc: &CtfjxService,
+    req: RevokeTokenRequest,
+    access: &Access,
+) -> Result<(), Status> {
+    svc.store.atomically(|tx| {
+        let mut record = tx.get_token(&req.id)?;
+        if target_user(access, &record.token.user_id).is_err() {
+            // other users' tokens are not acknowledged to exist
+            return Err(Status::not_found(format!("token `{}` not found", req.id)));
+        }
+        if record.token.revoked_at.is_none() {
+            record.token.revoked_at = Some(now());
+            tx.update_token(&record)?;
+        }
+        Ok(())
+    })
+}
+
+pub(super) fn create_session(
+    svc: &CtfjxService,
+    req: CreateSessionRequest,
+    access: &Access,
+) -> Result<Session, Status> {
+    let caller = access.required_caller()?;
+    let ttl = match req.ttl_seconds {
+        n if n < 0 => return Err(Status::invalid_argument("ttl_seconds must not be negative")),
+        0 => DEFAULT_SESSION_TTL,
+        n => Duration::from_secs(n as u64).min(MAX_SESSION_TTL),
+    };
+    let (token, expires_at) = svc.auth.issue_session(caller, ttl);
+    Ok(Session {
+        token,
+        expires_at: Some(expires_at),
+    })
+}
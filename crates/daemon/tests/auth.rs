@@ -0,0 +1,170 @@
+mod util;
+
+use ctfjx_proto::grpc::*;
+use tonic::Code;
+
+#[tokio::test]
+async fn anonymous_callers_only_reach_public_rpcs() {
+    let srv = util::spawn().await;
+    let mut anonymous = srv.connect(None).await;
+
+    let err = anonymous
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    anonymous.ping(PingRequest::default()).await.unwrap();
+    anonymous
+        .get_scoreboard(GetScoreboardRequest::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn unknown_tokens_are_rejected() {
+    let srv = util::spawn().await;
+    for token in ["garbage", "ctfjx_nope.00", "ctfjxs_e30.AAAA"] {
+        let err = srv
+            .connect(Some(token))
+            .await
+            .ping(PingRequest::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated, "{token}");
+    }
+}
+
+#[tokio::test]
+async fn scopes_limit_what_tokens_do() {
+    let mut srv = util::spawn().await;
+    let alice = util::user(&mut srv, "alice").await;
+    let read = util::token(&mut srv, &alice, "read").await;
+    let mut client = srv.connect(Some(&read)).await;
+
+    client
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap();
+    let err = client
+        .create_team(CreateTeamRequest {
+            team: Some(Team {
+                name: "alpha".to_string(),
+                captain_id: alice.clone(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // nor can a token hand out more than it holds
+    let err = client
+        .create_token(CreateTokenRequest {
+            name: "escalated".to_string(),
+            scopes: vec!["admin".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn tokens_are_listed_and_revoked_by_their_owner() {
+    let mut srv = util::spawn().await;
+    let alice = util::user(&mut srv, "alice").await;
+    let bob = util::user(&mut srv, "bob").await;
+    let write = util::token(&mut srv, &alice, "write").await;
+    util::token(&mut srv, &bob, "read").await;
+    let mut client = srv.connect(Some(&write)).await;
+
+    let created = client
+        .create_token(CreateTokenRequest {
+            name: "ci".to_string(),
+            scopes: vec!["read".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let token = created.token.unwrap();
+    assert_eq!(token.user_id, alice);
+    assert!(created.secret.starts_with("ctfjx_"));
+
+    let listed = client
+        .list_tokens(ListTokensRequest::default())
+        .await
+        .unwrap()
+        .into_inner()
+        .tokens;
+    assert_eq!(listed.len(), 2);
+    assert!(listed.iter().all(|t| t.user_id == alice));
+    let err = client
+        .list_tokens(ListTokensRequest {
+            user_id: bob.clone(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // secrets are only shown once
+    let mut ci = srv.connect(Some(&created.secret)).await;
+    ci.ping(PingRequest::default()).await.unwrap();
+    client
+        .revoke_token(RevokeTokenRequest {
+            id: token.id.clone(),
+        })
+        .await
+        .unwrap();
+    let err = ci.ping(PingRequest::default()).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let listed = srv
+        .client
+        .list_tokens(ListTokensRequest { user_id: alice })
+        .await
+        .unwrap()
+        .into_inner()
+        .tokens;
+    let revoked = listed.iter().find(|t| t.id == token.id).unwrap();
+    assert!(revoked.revoked_at.is_some());
+}
+
+#[tokio::test]
+async fn sessions_last_as_long_as_their_token() {
+    let mut srv = util::spawn().await;
+    let alice = util::user(&mut srv, "alice").await;
+    let secret = util::token(&mut srv, &alice, "write").await;
+    let mut client = srv.connect(Some(&secret)).await;
+
+    let session = client
+        .create_session(CreateSessionRequest { ttl_seconds: 60 })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(session.expires_at.is_some());
+    let mut browser = srv.connect(Some(&session.token)).await;
+    browser
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap();
+
+    let id = client
+        .list_tokens(ListTokensRequest::default())
+        .await
+        .unwrap()
+        .into_inner()
+        .tokens
+        .remove(0)
+        .id;
+    srv.client
+        .revoke_token(RevokeTokenRequest { id })
+        .await
+        .unwrap();
+    let err = browser
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+}
@@ -0,0 +1,361 @@
+mod util;
+
+use std::collections::HashMap;
+
+use ctfjx_proto::grpc::*;
+use prost_wkt_types::FieldMask;
+use tonic::Code;
+
+fn challenge(name: &str) -> Challenge {
+    Challenge {
+        name: name.to_string(),
+        difficulty: "easy".to_string(),
+        tags: vec!["web".to_string()],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn ping_reports_version() {
+    let mut srv = util::spawn().await;
+
+    let resp = srv
+        .client
+        .ping(PingRequest {
+            client: "test".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(resp.message, "pong");
+    assert!(resp.server_time.is_some());
+    assert!(!resp.version.is_empty());
+}
+
+#[tokio::test]
+async fn challenge_crud() {
+    let mut srv = util::spawn().await;
+
+    let id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(challenge("warmup")),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+
+    let got = srv
+        .client
+        .get_challenge(GetChallengeRequest { id: id.clone() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(got.name, "warmup");
+    assert!(got.created_at.is_some());
+
+    let mut update = got.clone();
+    update.description = "now with a description".to_string();
+    let updated = srv
+        .client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(update),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(updated.description, "now with a description");
+    assert_eq!(updated.created_at, got.created_at);
+
+    let list = srv
+        .client
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(list.challenges, vec![updated]);
+
+    srv.client
+        .delete_challenge(DeleteChallengeRequest { id: id.clone() })
+        .await
+        .unwrap();
+    let err = srv
+        .client
+        .get_challenge(GetChallengeRequest { id })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_and_duplicates() {
+    let mut srv = util::spawn().await;
+
+    let err = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(challenge(" ")),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    for runtime in [
+        ChallengeRuntime::default(),
+        ChallengeRuntime {
+            image: "--privileged".to_string(),
+            command: vec!["alpine".to_string()],
+            ..Default::default()
+        },
+        ChallengeRuntime {
+            image: "-v=/:/host".to_string(),
+            ..Default::default()
+        },
+        ChallengeRuntime {
+            image: "alpine".to_string(),
+            port: 70000,
+            ..Default::default()
+        },
+        ChallengeRuntime {
+            image: "alpine".to_string(),
+            env: [("A=B".to_string(), "c".to_string())].into(),
+            ..Default::default()
+        },
+    ] {
+        let err = srv
+            .client
+            .create_challenge(CreateChallengeRequest {
+                challenge: Some(Challenge {
+                    runtime: Some(runtime),
+                    ..challenge("bad runtime")
+                }),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    let mut dup = challenge("dup");
+    dup.id = "fixed".to_string();
+    srv.client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(dup.clone()),
+        })
+        .await
+        .unwrap();
+    let err = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(dup),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::AlreadyExists);
+}
+
+#[tokio::test]
+async fn stale_updates_are_aborted() {
+    let mut srv = util::spawn().await;
+
+    let id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(challenge("race")),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    let read = srv
+        .client
+        .get_challenge(GetChallengeRequest { id })
+        .await
+        .unwrap()
+        .into_inner();
+
+    srv.client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(read.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let err = srv
+        .client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(read),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Aborted);
+}
+
+#[tokio::test]
+async fn list_filters_challenges() {
+    let mut srv = util::spawn().await;
+
+    for (name, difficulty) in [("easy-one", "easy"), ("hard-one", "hard")] {
+        let mut c = challenge(name);
+        c.difficulty = difficulty.to_string();
+        srv.client
+            .create_challenge(CreateChallengeRequest { challenge: Some(c) })
+            .await
+            .unwrap();
+    }
+
+    let list = srv
+        .client
+        .list_challenges(ListChallengesRequest {
+            filter: r#"difficulty = "hard" AND tags:web"#.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let names: Vec<_> = list.challenges.into_iter().map(|c| c.name).collect();
+    assert_eq!(names, ["hard-one"]);
+
+    let err = srv
+        .client
+        .list_challenges(ListChallengesRequest {
+            filter: "difficulty = hard AND".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("column 22"), "{}", err.message());
+}
+
+#[tokio::test]
+async fn list_rejects_hostile_filters() {
+    let mut srv = util::spawn().await;
+
+    let nested = format!("{}name = x{}", "(".repeat(100), ")".repeat(100));
+    let long = format!("{}name = x{}", "(".repeat(100_000), ")".repeat(100_000));
+    for (filter, message) in [(nested, "nests too deeply"), (long, "longer than")] {
+        let err = srv
+            .client
+            .list_challenges(ListChallengesRequest {
+                filter,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(err.message().contains(message), "{}", err.message());
+    }
+}
+
+#[tokio::test]
+async fn list_pages_through_challenges() {
+    let mut srv = util::spawn().await;
+
+    for i in 0..5 {
+        let mut c = challenge(&format!("c{i}"));
+        c.id = format!("c{i}");
+        srv.client
+            .create_challenge(CreateChallengeRequest { challenge: Some(c) })
+            .await
+            .unwrap();
+    }
+
+    let mut ids = Vec::new();
+    let mut page_token = String::new();
+    loop {
+        let resp = srv
+            .client
+            .list_challenges(ListChallengesRequest {
+                page_size: 2,
+                page_token,
+                filter: "id != c2".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(resp.challenges.len() <= 2);
+        ids.extend(resp.challenges.into_iter().map(|c| c.id));
+        if resp.next_page_token.is_empty() {
+            break;
+        }
+        page_token = resp.next_page_token;
+    }
+    assert_eq!(ids, ["c0", "c1", "c3", "c4"]);
+
+    let first = srv
+        .client
+        .list_challenges(ListChallengesRequest {
+            page_size: 2,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let err = srv
+        .client
+        .list_challenges(ListChallengesRequest {
+            page_size: 2,
+            page_token: first.next_page_token,
+            filter: "tags:web".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn update_applies_field_mask() {
+    let mut srv = util::spawn().await;
+
+    let mut c = challenge("masked");
+    c.metadata = HashMap::from([
+        ("author".to_string(), "a".to_string()),
+        ("license".to_string(), "mit".to_string()),
+    ]);
+    let id = srv
+        .client
+        .create_challenge(CreateChallengeRequest { challenge: Some(c) })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+
+    let updated = srv
+        .client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(Challenge {
+                id: id.clone(),
+                tags: vec!["web".to_string(), "easy".to_string()],
+                metadata: HashMap::from([("author".to_string(), "b".to_string())]),
+                ..Default::default()
+            }),
+            update_mask: Some(FieldMask {
+                paths: vec!["tags".to_string(), "metadata.author".to_string()],
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(updated.name, "masked");
+    assert_eq!(updated.difficulty, "easy");
+    assert_eq!(updated.tags, ["web", "easy"]);
+    assert_eq!(updated.metadata["author"], "b");
+    assert_eq!(updated.metadata["license"], "mit");
+
+    let err = srv
+        .client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(Challenge {
+                id,
+                ..Default::default()
+            }),
+            update_mask: Some(FieldMask {
+                paths: vec!["score".to_string()],
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
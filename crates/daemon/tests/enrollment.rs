@@ -0,0 +1,149 @@
+mod util;
+
+use std::time::SystemTime;
+
+use ctfjx_common::identity::{self, Keypair};
+use ctfjx_proto::grpc::*;
+use tonic::Code;
+
+#[tokio::test]
+async fn join_tokens_are_used_once() {
+    let mut srv = util::spawn().await;
+    let join = srv
+        .client
+        .create_join_token(CreateJoinTokenRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(join.token.starts_with("ctfjxj_"));
+    assert!(!join.agent_id.is_empty());
+    assert!(join.expires_at.is_some());
+
+    let mut anonymous = srv.connect(None).await;
+    let enroll = |token: &str| EnrollAgentRequest {
+        join_token: token.to_string(),
+        public_key: Keypair::generate().public_key().to_vec(),
+    };
+    let enrolled = anonymous
+        .enroll_agent(enroll(&join.token))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(enrolled.credential.unwrap().agent_id, join.agent_id);
+
+    let err = anonymous
+        .enroll_agent(enroll(&join.token))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let err = anonymous
+        .enroll_agent(enroll("ctfjxj_nope.secret"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let err = anonymous
+        .enroll_agent(EnrollAgentRequest {
+            join_token: join.token,
+            public_key: vec![1, 2, 3],
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    // only admins hand out join tokens
+    let err = anonymous
+        .create_join_token(CreateJoinTokenRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn registration_is_signed_by_the_daemon() {
+    let mut srv = util::spawn().await;
+    let mut agent = util::enroll(&mut srv, "runner-1").await;
+
+    let nonce = b"a fresh nonce".to_vec();
+    let registered = agent
+        .client
+        .register_agent(RegisterAgentRequest {
+            agent_id: "runner-1".to_string(),
+            nonce: nonce.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(registered.accepted);
+    identity::verify_proof(
+        &agent.daemon_public_key,
+        "runner-1",
+        &nonce,
+        &registered.daemon_signature,
+    )
+    .unwrap();
+
+    // admins may not register in an agent's stead
+    let err = srv
+        .client
+        .register_agent(RegisterAgentRequest {
+            agent_id: "runner-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn keys_are_rotated_and_revoked() {
+    let mut srv = util::spawn().await;
+    let mut agent = util::enroll(&mut srv, "runner-1").await;
+
+    let key = Keypair::generate();
+    let rotated = agent
+        .client
+        .rotate_agent_key(RotateAgentKeyRequest {
+            public_key: key.public_key().to_vec(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .credential
+        .unwrap();
+    assert_eq!(rotated.agent_id, "runner-1");
+
+    // the old key stops working right away
+    let err = agent.client.ping(PingRequest::default()).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let mut client = srv
+        .connect(Some(&key.agent_token(&rotated.id, SystemTime::now())))
+        .await;
+    client.ping(PingRequest::default()).await.unwrap();
+
+    let credentials = srv
+        .client
+        .list_agent_credentials(ListAgentCredentialsRequest {
+            agent_id: "runner-1".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .credentials;
+    assert_eq!(credentials.len(), 2);
+    assert_eq!(
+        credentials
+            .iter()
+            .filter(|c| c.revoked_at.is_none())
+            .count(),
+        1
+    );
+
+    srv.client
+        .revoke_agent_credential(RevokeAgentCredentialRequest { id: rotated.id })
+        .await
+        .unwrap();
+    let err = client.ping(PingRequest::default()).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+}
@@ -0,0 +1,189 @@
+mod util;
+
+use std::time::Duration;
+
+use ctfjx_proto::grpc::{instance_status::State, *};
+use ctfjxd::expiry::ExpiryConfig;
+use prost_wkt_types::Timestamp;
+use tokio_stream::StreamExt;
+use tonic::Code;
+
+async fn challenge(srv: &mut util::TestServer) -> String {
+    srv.client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id
+}
+
+fn lifetime(started_at: Option<Timestamp>, expires_at: Option<Timestamp>) -> i64 {
+    expires_at.unwrap().seconds - started_at.unwrap().seconds
+}
+
+#[tokio::test]
+async fn expired_instances_are_stopped_after_a_warning() {
+    let mut srv = util::spawn_with(|svc| {
+        svc.with_expiry(ExpiryConfig {
+            default_ttl: Duration::from_millis(500),
+            max_lifetime: Duration::from_secs(1),
+            warn_before: Duration::from_millis(300),
+            check_every: Duration::from_millis(25),
+        })
+    })
+    .await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let mut agent = util::enroll(&mut srv, "agent-1").await.client;
+    agent
+        .register_agent(RegisterAgentRequest {
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let challenge_id = challenge(&mut srv).await;
+    let mut warnings = srv
+        .client
+        .stream_events(StreamEventsRequest {
+            challenge_id: challenge_id.clone(),
+            level: "warn".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let started = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(started.expires_at.is_some());
+
+    let warning = tokio::time::timeout(Duration::from_secs(5), warnings.next())
+        .await
+        .expect("no warning in time")
+        .unwrap()
+        .unwrap();
+    assert_eq!(warning.labels["instance_id"], started.instance_id);
+    assert_eq!(warning.labels["team_id"], "team-a");
+    assert!(
+        warning.message.contains("expires in"),
+        "{}",
+        warning.message
+    );
+
+    let mut status = InstanceStatus::default();
+    for _ in 0..100 {
+        status = srv
+            .client
+            .get_instance_status(GetInstanceStatusRequest {
+                instance_id: started.instance_id.clone(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        if status.state() == State::Stopped {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert_eq!(status.state(), State::Stopped);
+    assert_eq!(status.message, "expired");
+
+    let jobs = agent
+        .assign_job(AssignJobRequest {
+            agent_id: "agent-1".to_string(),
+            max_jobs: 4,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    // no agent took the start job, so there is nothing to stop
+    assert!(jobs.is_empty(), "{jobs:?}");
+
+    // the team was warned once, not on every check
+    let again = tokio::time::timeout(Duration::from_millis(200), warnings.next()).await;
+    assert!(again.is_err());
+}
+
+#[tokio::test]
+async fn extensions_stop_at_the_maximum_lifetime() {
+    let mut srv = util::spawn_with(|svc| {
+        svc.with_expiry(ExpiryConfig {
+            default_ttl: Duration::from_secs(60),
+            max_lifetime: Duration::from_secs(90),
+            ..Default::default()
+        })
+    })
+    .await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let challenge_id = challenge(&mut srv).await;
+    let start = |ttl_seconds: i64| StartInstanceRequest {
+        challenge_id: challenge_id.clone(),
+        team_id: "team-a".to_string(),
+        ttl_seconds,
+        ..Default::default()
+    };
+
+    let err = srv.client.start_instance(start(-1)).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    let capped = srv
+        .client
+        .start_instance(start(3600))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(lifetime(capped.started_at, capped.expires_at), 90);
+
+    let started = srv
+        .client
+        .start_instance(start(0))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(lifetime(started.started_at, started.expires_at), 60);
+    let extend = |seconds: i64| ExtendInstanceRequest {
+        instance_id: started.instance_id.clone(),
+        seconds,
+    };
+    let extended = srv
+        .client
+        .extend_instance(extend(20))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(lifetime(started.started_at, extended.expires_at), 80);
+    // the default TTL is more than what is left
+    let extended = srv
+        .client
+        .extend_instance(extend(0))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(lifetime(started.started_at, extended.expires_at), 90);
+    let err = srv.client.extend_instance(extend(10)).await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    srv.client
+        .stop_instance(StopInstanceRequest {
+            instance_id: started.instance_id.clone(),
+            force: false,
+        })
+        .await
+        .unwrap();
+    let err = srv.client.extend_instance(extend(10)).await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+}
@@ -0,0 +1,261 @@
+mod util;
+
+use ctfjx_proto::grpc::{flag_spec::Kind, *};
+use ctfjxd::jobs::StartInstancePayload;
+use prost_wkt_types::FieldMask;
+use tokio_stream::StreamExt;
+use tonic::Code;
+
+fn flag(kind: Kind, value: &str) -> FlagSpec {
+    FlagSpec {
+        kind: kind as i32,
+        value: value.to_string(),
+    }
+}
+
+async fn create(srv: &mut util::TestServer, flags: Vec<FlagSpec>) -> String {
+    srv.client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "flagged".to_string(),
+                points: 100,
+                flags,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id
+}
+
+async fn submit(
+    srv: &mut util::TestServer,
+    challenge_id: &str,
+    team_id: &str,
+    flag: &str,
+) -> SubmitFlagResponse {
+    srv.client
+        .submit_flag(SubmitFlagRequest {
+            challenge_id: challenge_id.to_string(),
+            team_id: team_id.to_string(),
+            flag: flag.to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+#[tokio::test]
+async fn submissions_solve_once() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a", "team-b"]).await;
+    let id = create(
+        &mut srv,
+        vec![
+            flag(Kind::Static, "flag{one}"),
+            flag(Kind::Regex, r"flag\{two-[0-9]+\}"),
+        ],
+    )
+    .await;
+
+    let wrong = submit(&mut srv, &id, "team-a", "flag{nope}").await;
+    assert!(!wrong.correct);
+    assert!(wrong.solved_at.is_none());
+
+    let right = submit(&mut srv, &id, "team-a", " flag{two-42} ").await;
+    assert!(right.correct && !right.already_solved);
+    assert!(right.solved_at.is_some());
+
+    let again = submit(&mut srv, &id, "team-a", "flag{one}").await;
+    assert!(again.correct && again.already_solved);
+    assert_eq!(again.solved_at, right.solved_at);
+
+    let other = submit(&mut srv, &id, "team-b", "flag{one}").await;
+    assert!(other.correct && !other.already_solved);
+}
+
+#[tokio::test]
+async fn flags_are_only_returned_to_admins() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a", "team-b"]).await;
+    let id = create(&mut srv, vec![flag(Kind::Static, "flag{secret}")]).await;
+    let player = util::user(&mut srv, "player").await;
+    let token = util::token(&mut srv, &player, "read").await;
+    let mut client = srv.connect(Some(&token)).await;
+
+    let got = client
+        .get_challenge(GetChallengeRequest { id: id.clone() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(got.flags.is_empty());
+    assert_eq!(got.points, 100);
+
+    let listed = client
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(listed.challenges.iter().all(|c| c.flags.is_empty()));
+
+    // sending a redacted challenge back keeps its flags
+    let updated = srv
+        .client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(got),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(updated.flags, vec![flag(Kind::Static, "flag{secret}")]);
+    assert!(
+        submit(&mut srv, &id, "team-a", "flag{secret}")
+            .await
+            .correct
+    );
+
+    // unless flags are named explicitly
+    srv.client
+        .update_challenge(UpdateChallengeRequest {
+            challenge: Some(Challenge {
+                id: id.clone(),
+                flags: vec![flag(Kind::CaseInsensitive, "flag{rotated}")],
+                ..Default::default()
+            }),
+            update_mask: Some(FieldMask {
+                paths: vec!["flags".to_string()],
+            }),
+        })
+        .await
+        .unwrap();
+    assert!(
+        !submit(&mut srv, &id, "team-b", "flag{secret}")
+            .await
+            .correct
+    );
+    assert!(
+        submit(&mut srv, &id, "team-b", "FLAG{ROTATED}")
+            .await
+            .correct
+    );
+}
+
+#[tokio::test]
+async fn rejects_bad_flags_and_submissions() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+
+    let err = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "bad".to_string(),
+                flags: vec![flag(Kind::Regex, "flag{(")],
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+
+    let no_flags = create(&mut srv, vec![]).await;
+    let err = srv
+        .client
+        .submit_flag(SubmitFlagRequest {
+            challenge_id: no_flags,
+            team_id: "team-a".to_string(),
+            flag: "flag{x}".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    let id = create(&mut srv, vec![flag(Kind::Static, "flag{x}")]).await;
+    for (team_id, flag) in [("", "flag{x}"), ("team-a", "  ")] {
+        let err = srv
+            .client
+            .submit_flag(SubmitFlagRequest {
+                challenge_id: id.clone(),
+                team_id: team_id.to_string(),
+                flag: flag.to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+}
+
+#[tokio::test]
+async fn dynamic_flags_are_per_team() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a", "team-b", "team-c"]).await;
+    let id = create(&mut srv, vec![flag(Kind::Dynamic, "ctf")]).await;
+    util::enroll(&mut srv, "agent-1")
+        .await
+        .client
+        .register_agent(RegisterAgentRequest {
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let mut team_flags = Vec::new();
+    for team in ["team-a", "team-b"] {
+        srv.client
+            .start_instance(StartInstanceRequest {
+                challenge_id: id.clone(),
+                team_id: team.to_string(),
+                agent_id: "agent-1".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let jobs = srv
+            .client
+            .assign_job(AssignJobRequest {
+                agent_id: "agent-1".to_string(),
+                max_jobs: 1,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .jobs;
+        let payload: StartInstancePayload = serde_json::from_str(&jobs[0].payload_json).unwrap();
+        let flag = payload.flag.unwrap();
+        assert!(flag.starts_with("ctf{"));
+        team_flags.push(flag);
+    }
+    assert_ne!(team_flags[0], team_flags[1]);
+
+    let mut events = srv
+        .client
+        .stream_events(StreamEventsRequest {
+            challenge_id: id.clone(),
+            level: "warn".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    // team b hands its flag to team c
+    let shared = submit(&mut srv, &id, "team-c", &team_flags[1]).await;
+    assert!(!shared.correct);
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(event.labels["team_id"], "team-c");
+    assert!(event.message.contains("team-b"), "{}", event.message);
+
+    assert!(
+        !submit(&mut srv, &id, "team-a", &team_flags[1])
+            .await
+            .correct
+    );
+    assert!(
+        submit(&mut srv, &id, "team-a", &team_flags[0])
+            .await
+            .correct
+    );
+}
@@ -0,0 +1,228 @@
+mod util;
+
+use std::time::Duration;
+
+use ctfjx_proto::grpc::{instance_status::State, *};
+use ctfjxd::jobs::{JOB_INSTANCE_START, JOB_INSTANCE_STOP, StartInstancePayload};
+use tokio_stream::StreamExt;
+use tonic::Code;
+
+async fn setup(srv: &mut util::TestServer) -> String {
+    util::enroll(srv, "agent-1")
+        .await
+        .client
+        .register_agent(RegisterAgentRequest {
+            agent_id: "agent-1".to_string(),
+            version: "0.1.0".to_string(),
+            capabilities: vec!["process".to_string()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    srv.client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                runtime: Some(ChallengeRuntime {
+                    command: vec!["./pwn".to_string()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id
+}
+
+#[tokio::test]
+async fn start_queues_job_for_agent() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let challenge_id = setup(&mut srv).await;
+
+    let started = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id: challenge_id.clone(),
+            team_id: "team-a".to_string(),
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(started.node, "agent-1");
+
+    let status = srv
+        .client
+        .get_instance_status(GetInstanceStatusRequest {
+            instance_id: started.instance_id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(status.state(), State::Starting);
+
+    let jobs = srv
+        .client
+        .assign_job(AssignJobRequest {
+            agent_id: "agent-1".to_string(),
+            max_jobs: 4,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].r#type, JOB_INSTANCE_START);
+    let payload: StartInstancePayload = serde_json::from_str(&jobs[0].payload_json).unwrap();
+    assert_eq!(payload.instance_id, started.instance_id);
+    assert_eq!(payload.challenge_id, challenge_id);
+    assert_eq!(payload.runtime.command, ["./pwn"]);
+
+    // jobs are handed out once
+    let again = srv
+        .client
+        .assign_job(AssignJobRequest {
+            agent_id: "agent-1".to_string(),
+            max_jobs: 4,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(again.jobs.is_empty());
+
+    srv.client
+        .stop_instance(StopInstanceRequest {
+            instance_id: started.instance_id.clone(),
+            force: false,
+        })
+        .await
+        .unwrap();
+    let client = srv.client.clone();
+    let assign = async || {
+        client
+            .clone()
+            .assign_job(AssignJobRequest {
+                agent_id: "agent-1".to_string(),
+                max_jobs: 0,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .jobs
+    };
+    // the agent reports on starting the instance before it is told to stop it
+    assert!(assign().await.is_empty());
+    srv.client
+        .report_job_result(ReportJobResultRequest {
+            agent_id: "agent-1".to_string(),
+            result: Some(JobResult {
+                job_id: jobs[0].job_id.clone(),
+                success: true,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap();
+    let jobs = assign().await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].r#type, JOB_INSTANCE_STOP);
+
+    let err = srv
+        .client
+        .stop_instance(StopInstanceRequest {
+            instance_id: started.instance_id,
+            force: false,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+}
+
+#[tokio::test]
+async fn start_requires_known_challenge_and_agent() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let challenge_id = setup(&mut srv).await;
+
+    let err = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id: "missing".to_string(),
+            team_id: "team-a".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            agent_id: "ghost".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = srv
+        .client
+        .assign_job(AssignJobRequest {
+            agent_id: "ghost".to_string(),
+            max_jobs: 1,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+}
+
+#[tokio::test]
+async fn events_are_filtered_per_subscriber() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let challenge_id = setup(&mut srv).await;
+
+    let mut other = srv
+        .client
+        .stream_events(StreamEventsRequest {
+            challenge_id: "other".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let mut events = srv
+        .client
+        .stream_events(StreamEventsRequest {
+            challenge_id: challenge_id.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let started = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(event.r#type(), event::Type::State);
+    assert_eq!(event.labels["instance_id"], started.instance_id);
+
+    let nothing = tokio::time::timeout(Duration::from_millis(100), other.next()).await;
+    assert!(nothing.is_err());
+}
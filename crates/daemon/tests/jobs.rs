@@ -0,0 +1,144 @@
+mod util;
+
+use ctfjx_proto::grpc::*;
+use ctfjxd::queue::QueueConfig;
+use tonic::Code;
+
+/// Starts an instance of a new challenge, queuing a job for `agent-1`
+async fn start(srv: &mut util::TestServer) {
+    let challenge_id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    srv.client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+}
+
+fn assign() -> AssignJobRequest {
+    AssignJobRequest {
+        agent_id: "agent-1".to_string(),
+        max_jobs: 8,
+    }
+}
+
+fn report(job_id: &str, success: bool) -> ReportJobResultRequest {
+    ReportJobResultRequest {
+        agent_id: "agent-1".to_string(),
+        result: Some(JobResult {
+            job_id: job_id.to_string(),
+            success,
+            message: "image not found".to_string(),
+            ..Default::default()
+        }),
+    }
+}
+
+#[tokio::test]
+async fn reported_jobs_leave_the_queue() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let mut agent = util::enroll(&mut srv, "agent-1").await.client;
+    agent
+        .register_agent(RegisterAgentRequest {
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    start(&mut srv).await;
+
+    let jobs = agent.assign_job(assign()).await.unwrap().into_inner().jobs;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].attempt, 1);
+    assert!(jobs[0].lease_expires_at.is_some());
+    // leased jobs are not handed out twice
+    assert!(
+        agent
+            .assign_job(assign())
+            .await
+            .unwrap()
+            .into_inner()
+            .jobs
+            .is_empty()
+    );
+
+    agent
+        .report_job_result(report(&jobs[0].job_id, true))
+        .await
+        .unwrap();
+    let err = agent
+        .report_job_result(report(&jobs[0].job_id, true))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn failed_jobs_are_dead_lettered() {
+    let mut srv = util::spawn_with(|svc| {
+        svc.with_job_queue(QueueConfig {
+            max_attempts: 1,
+            ..Default::default()
+        })
+    })
+    .await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let mut agent = util::enroll(&mut srv, "agent-1").await.client;
+    agent
+        .register_agent(RegisterAgentRequest {
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    start(&mut srv).await;
+
+    let job = agent.assign_job(assign()).await.unwrap().into_inner().jobs[0].clone();
+    agent
+        .report_job_result(report(&job.job_id, false))
+        .await
+        .unwrap();
+    assert!(
+        agent
+            .assign_job(assign())
+            .await
+            .unwrap()
+            .into_inner()
+            .jobs
+            .is_empty()
+    );
+
+    let dead = srv
+        .client
+        .list_dead_jobs(ListDeadJobsRequest::default())
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].job.as_ref().unwrap().job_id, job.job_id);
+    assert_eq!(dead[0].agent_id, "agent-1");
+    assert_eq!(dead[0].last_error, "image not found");
+
+    // only admins look at dead jobs
+    let err = agent
+        .list_dead_jobs(ListDeadJobsRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
@@ -0,0 +1,162 @@
+mod util;
+
+use std::time::Duration;
+
+use ctfjx::client::Client;
+use ctfjx_proto::grpc::{instance_status::State, *};
+use ctfjxd::liveness::LivenessConfig;
+use tokio_stream::StreamExt;
+
+fn config(reschedule: bool) -> LivenessConfig {
+    LivenessConfig {
+        suspect_after: Duration::from_millis(300),
+        dead_after: Duration::from_millis(600),
+        check_every: Duration::from_millis(25),
+        reschedule,
+    }
+}
+
+async fn register(srv: &mut util::TestServer, agent_id: &str) -> Client {
+    let mut client = util::enroll(srv, agent_id).await.client;
+    client
+        .register_agent(RegisterAgentRequest {
+            agent_id: agent_id.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    client
+}
+
+/// Keeps `agent_id` alive until the test ends
+fn keep_alive(mut client: Client, agent_id: &str) {
+    let agent_id = agent_id.to_string();
+    tokio::spawn(async move {
+        loop {
+            let heartbeat = Heartbeat {
+                agent_id: agent_id.clone(),
+                ..Default::default()
+            };
+            if client.send_heartbeat(heartbeat).await.is_err() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+    });
+}
+
+/// Starts an instance pinned to `agent_id`, subscribed to its events
+async fn start(srv: &mut util::TestServer, agent_id: &str) -> (String, tonic::Streaming<Event>) {
+    let challenge_id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    let events = srv
+        .client
+        .stream_events(StreamEventsRequest {
+            challenge_id: challenge_id.clone(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let instance_id = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            agent_id: agent_id.to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .instance_id;
+    (instance_id, events)
+}
+
+async fn next_state(events: &mut tonic::Streaming<Event>) -> String {
+    let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+        .await
+        .expect("no event in time")
+        .unwrap()
+        .unwrap();
+    assert_eq!(event.r#type(), event::Type::State);
+    event.message
+}
+
+async fn status(srv: &mut util::TestServer, instance_id: &str) -> InstanceStatus {
+    srv.client
+        .get_instance_status(GetInstanceStatusRequest {
+            instance_id: instance_id.to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+#[tokio::test]
+async fn instances_of_dead_agents_fail() {
+    let mut srv = util::spawn_with(|svc| svc.with_liveness(config(false))).await;
+    util::teams(&mut srv, &["team-a"]).await;
+    register(&mut srv, "silent").await;
+
+    let (instance_id, mut events) = start(&mut srv, "silent").await;
+    assert!(next_state(&mut events).await.ends_with("STATE_STARTING"));
+    assert!(next_state(&mut events).await.ends_with("STATE_FAILED"));
+
+    let failed = status(&mut srv, &instance_id).await;
+    assert_eq!(failed.state(), State::Failed);
+    assert_eq!(failed.message, "agent silent stopped sending heartbeats");
+
+    // the job only it could take is given up on as well
+    let dead = srv
+        .client
+        .list_dead_jobs(ListDeadJobsRequest {
+            agent_id: "silent".to_string(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert_eq!(dead.len(), 1);
+}
+
+#[tokio::test]
+async fn instances_of_dead_agents_move_to_healthy_ones() {
+    let mut srv = util::spawn_with(|svc| svc.with_liveness(config(true))).await;
+    util::teams(&mut srv, &["team-a"]).await;
+    register(&mut srv, "silent").await;
+    let alive = register(&mut srv, "alive").await;
+    keep_alive(alive, "alive");
+
+    let (instance_id, mut events) = start(&mut srv, "silent").await;
+    assert!(next_state(&mut events).await.ends_with("STATE_STARTING"));
+    assert!(next_state(&mut events).await.ends_with("STATE_FAILED"));
+    assert!(next_state(&mut events).await.ends_with("STATE_STARTING"));
+
+    let moved = status(&mut srv, &instance_id).await;
+    assert_eq!(moved.state(), State::Starting);
+    assert_eq!(moved.node, "alive");
+    assert_eq!(moved.message, "rescheduled from silent");
+
+    let jobs = srv
+        .client
+        .assign_job(AssignJobRequest {
+            agent_id: "alive".to_string(),
+            max_jobs: 4,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert_eq!(jobs.len(), 1);
+}
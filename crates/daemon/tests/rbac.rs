@@ -0,0 +1,229 @@
+mod util;
+
+use ctfjx_proto::grpc::{user::Role, *};
+use prost_wkt_types::FieldMask;
+use tonic::Code;
+
+fn challenge(name: &str, owner: &str) -> Challenge {
+    Challenge {
+        name: name.to_string(),
+        owner: owner.to_string(),
+        points: 100,
+        flags: vec![FlagSpec {
+            kind: flag_spec::Kind::Static as i32,
+            value: "flag{rbac}".to_string(),
+        }],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn players_cannot_write_challenges_or_take_jobs() {
+    let mut srv = util::spawn().await;
+    let (_, mut player) = util::login(&mut srv, "player", Role::Player).await;
+
+    let err = player
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(challenge("nope", "")),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let err = player
+        .assign_job(AssignJobRequest {
+            agent_id: "player".to_string(),
+            max_jobs: 1,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let err = player
+        .freeze_scoreboard(FreezeScoreboardRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn agents_only_act_as_themselves() {
+    let mut srv = util::spawn().await;
+    let id = "runner-1".to_string();
+    let mut agent = util::enroll(&mut srv, &id).await.client;
+
+    let register = |agent_id: &str| RegisterAgentRequest {
+        agent_id: agent_id.to_string(),
+        ..Default::default()
+    };
+    agent.register_agent(register(&id)).await.unwrap();
+    let err = agent
+        .register_agent(register("runner-2"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // agent users need an enrolled credential to register
+    let (other, mut unenrolled) = util::login(&mut srv, "runner-2", Role::Agent).await;
+    let err = unenrolled
+        .register_agent(register(&other))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    agent
+        .assign_job(AssignJobRequest {
+            agent_id: id,
+            max_jobs: 1,
+        })
+        .await
+        .unwrap();
+    let err = agent
+        .assign_job(AssignJobRequest {
+            agent_id: "runner-2".to_string(),
+            max_jobs: 1,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // and nothing meant for people
+    let err = agent
+        .list_challenges(ListChallengesRequest::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn authors_manage_the_challenges_they_own() {
+    let mut srv = util::spawn().await;
+    let (alice, mut author) = util::login(&mut srv, "alice", Role::Author).await;
+    let (_, mut other) = util::login(&mut srv, "bob", Role::Author).await;
+
+    // whatever owner they name, authors own what they create
+    let id = author
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                runtime: Some(ChallengeRuntime {
+                    image: "pwn:latest".to_string(),
+                    ..Default::default()
+                }),
+                ..challenge("mine", "bob")
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    let got = author
+        .get_challenge(GetChallengeRequest { id: id.clone() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(got.owner, alice);
+    assert_eq!(got.flags.len(), 1);
+    assert_eq!(got.runtime.unwrap().image, "pwn:latest");
+
+    let rename = |name: &str, owner: &str| UpdateChallengeRequest {
+        challenge: Some(Challenge {
+            id: id.clone(),
+            name: name.to_string(),
+            owner: owner.to_string(),
+            ..Default::default()
+        }),
+        update_mask: Some(FieldMask {
+            paths: vec!["name".to_string(), "owner".to_string()],
+        }),
+    };
+    author
+        .update_challenge(rename("renamed", &alice))
+        .await
+        .unwrap();
+
+    let err = other
+        .update_challenge(rename("stolen", "bob"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    let err = other
+        .delete_challenge(DeleteChallengeRequest { id: id.clone() })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    // nor do they see flags or runtimes of challenges they do not own
+    let seen = other
+        .get_challenge(GetChallengeRequest { id: id.clone() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(seen.flags.is_empty());
+    assert!(seen.runtime.is_none());
+
+    let err = author
+        .update_challenge(rename("given away", "bob"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // admins may do either
+    srv.client
+        .update_challenge(rename("handed over", "bob"))
+        .await
+        .unwrap();
+    other
+        .delete_challenge(DeleteChallengeRequest { id })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn players_act_for_themselves_and_their_team() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["rivals"]).await;
+    let (carol, mut player) = util::login(&mut srv, "carol", Role::Player).await;
+    let dave = util::user(&mut srv, "dave").await;
+
+    let team = |captain_id: &str| CreateTeamRequest {
+        team: Some(Team {
+            name: format!("{captain_id}'s team"),
+            captain_id: captain_id.to_string(),
+            ..Default::default()
+        }),
+    };
+    let err = player.create_team(team(&dave)).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    let created = player.create_team(team(&carol)).await.unwrap().into_inner();
+
+    let err = player
+        .join_team(JoinTeamRequest {
+            user_id: dave,
+            invite_code: created.invite_code,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(challenge("shared", "")),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    let submit = |team_id: &str| SubmitFlagRequest {
+        challenge_id: id.clone(),
+        team_id: team_id.to_string(),
+        flag: "flag{rbac}".to_string(),
+    };
+    let err = player.submit_flag(submit("rivals")).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+    let solved = player
+        .submit_flag(submit(&created.id))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(solved.correct);
+}
@@ -0,0 +1,170 @@
+mod util;
+
+use std::collections::HashMap;
+
+use ctfjx::client::Client;
+use ctfjx_proto::{grpc::*, jobs::STAT_LOAD};
+use tonic::Code;
+
+/// Enrolls and registers an agent, returning its client
+async fn agent(
+    srv: &mut util::TestServer,
+    agent_id: &str,
+    capabilities: &[&str],
+    arch: &str,
+) -> Client {
+    let mut client = util::enroll(srv, agent_id).await.client;
+    client
+        .register_agent(RegisterAgentRequest {
+            agent_id: agent_id.to_string(),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+            metadata: HashMap::from([("arch".to_string(), arch.to_string())]),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    client
+}
+
+async fn heartbeat(client: &mut Client, agent_id: &str, load: &str) {
+    client
+        .send_heartbeat(Heartbeat {
+            agent_id: agent_id.to_string(),
+            stats: HashMap::from([(STAT_LOAD.to_string(), load.to_string())]),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+}
+
+async fn challenge(srv: &mut util::TestServer, requirements: &[&str]) -> String {
+    srv.client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                requirements: requirements.iter().map(|r| r.to_string()).collect(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id
+}
+
+fn start(challenge_id: &str, agent_id: &str) -> StartInstanceRequest {
+    StartInstanceRequest {
+        challenge_id: challenge_id.to_string(),
+        team_id: "team-a".to_string(),
+        agent_id: agent_id.to_string(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn instances_go_to_the_least_loaded_eligible_agent() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let mut small = agent(&mut srv, "small", &["docker"], "amd64").await;
+    let mut big = agent(&mut srv, "big", &["docker", "kvm"], "amd64").await;
+    heartbeat(&mut small, "small", "0.1").await;
+    heartbeat(&mut big, "big", "0.8").await;
+
+    let docker = challenge(&mut srv, &["docker", "arch=amd64"]).await;
+    let started = srv
+        .client
+        .start_instance(start(&docker, ""))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(started.node, "small");
+
+    let kvm = challenge(&mut srv, &["kvm"]).await;
+    let started = srv
+        .client
+        .start_instance(start(&kvm, ""))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(started.node, "big");
+
+    // pinning skips balancing but not the requirements
+    let started = srv
+        .client
+        .start_instance(start(&docker, "big"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(started.node, "big");
+    let err = srv
+        .client
+        .start_instance(start(&kvm, "small"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+    assert_eq!(err.message(), "small lacks kvm");
+
+    // agents only report their own heartbeat
+    let err = small
+        .send_heartbeat(Heartbeat {
+            agent_id: "big".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn instances_wait_for_an_eligible_agent() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    agent(&mut srv, "x86", &["docker"], "amd64").await;
+
+    let arm = challenge(&mut srv, &["docker", "arch=arm64"]).await;
+    let started = srv
+        .client
+        .start_instance(start(&arm, ""))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(started.node.is_empty());
+    assert_eq!(
+        started.message,
+        "no agent is eligible: x86 lacks arch=arm64"
+    );
+
+    // the job is left for the first agent meeting the requirements
+    let assign = |agent_id: &str| AssignJobRequest {
+        agent_id: agent_id.to_string(),
+        max_jobs: 4,
+    };
+    let jobs = srv.client.assign_job(assign("x86")).await.unwrap();
+    assert!(jobs.into_inner().jobs.is_empty());
+
+    let mut pi = agent(&mut srv, "pi", &["docker"], "arm64").await;
+    let jobs = pi.assign_job(assign("pi")).await.unwrap().into_inner().jobs;
+    assert_eq!(jobs.len(), 1);
+    let status = srv
+        .client
+        .get_instance_status(GetInstanceStatusRequest {
+            instance_id: started.instance_id,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(status.node, "pi");
+
+    let err = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "bad".to_string(),
+                requirements: vec!["=arm64".to_string()],
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,424 @@
+mod util;
+
+use std::time::{Duration, SystemTime};
+
+use ctfjx_proto::grpc::{flag_spec::Kind, scoring::Function, *};
+use tokio_stream::StreamExt;
+use tonic::Code;
+
+async fn create(srv: &mut util::TestServer, name: &str, scoring: Option<Scoring>) -> String {
+    srv.client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: name.to_string(),
+                points: 100,
+                flags: vec![FlagSpec {
+                    kind: Kind::Static as i32,
+                    value: format!("flag{{{name}}}"),
+                }],
+                scoring,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id
+}
+
+async fn solve(srv: &mut util::TestServer, challenge_id: &str, name: &str, team_id: &str) {
+    let res = srv
+        .client
+        .submit_flag(SubmitFlagRequest {
+            challenge_id: challenge_id.to_string(),
+            team_id: team_id.to_string(),
+            flag: format!("flag{{{name}}}"),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(res.correct);
+}
+
+/// The public board, as an anonymous visitor sees it
+async fn board(srv: &mut util::TestServer) -> Scoreboard {
+    srv.connect(None)
+        .await
+        .get_scoreboard(GetScoreboardRequest::default())
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+fn standings(board: &Scoreboard) -> Vec<(i32, &str, i64)> {
+    standings_of(&board.entries)
+}
+
+fn standings_of(entries: &[ScoreboardEntry]) -> Vec<(i32, &str, i64)> {
+    entries
+        .iter()
+        .map(|e| (e.rank, e.team_id.as_str(), e.score))
+        .collect()
+}
+
+#[tokio::test]
+async fn values_decay_with_solves() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["alpha", "bravo", "charlie"]).await;
+    let dynamic = create(
+        &mut srv,
+        "dynamic",
+        Some(Scoring {
+            function: Function::Linear as i32,
+            initial: 500,
+            minimum: 100,
+            decay: 50,
+            first_blood_bonus: vec![30],
+        }),
+    )
+    .await;
+    let fixed = create(&mut srv, "fixed", None).await;
+
+    solve(&mut srv, &dynamic, "dynamic", "alpha").await;
+    solve(&mut srv, &fixed, "fixed", "bravo").await;
+    solve(&mut srv, &dynamic, "dynamic", "bravo").await;
+    solve(&mut srv, &dynamic, "dynamic", "charlie").await;
+
+    let board = board(&mut srv).await;
+    let value = |id: &str| {
+        board
+            .challenges
+            .iter()
+            .find(|c| c.challenge_id == id)
+            .map(|c| (c.value, c.solves))
+            .unwrap()
+    };
+    assert_eq!(value(&dynamic), (400, 3));
+    assert_eq!(value(&fixed), (100, 1));
+    // everybody's solve is worth the decayed value, alpha keeps the bonus
+    assert_eq!(
+        standings(&board),
+        [(1, "bravo", 500), (2, "alpha", 430), (3, "charlie", 400)]
+    );
+}
+
+#[tokio::test]
+async fn ties_rank_earlier_teams_first() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["slow", "fast"]).await;
+    let a = create(&mut srv, "a", None).await;
+    let b = create(&mut srv, "b", None).await;
+
+    solve(&mut srv, &b, "b", "slow").await;
+    solve(&mut srv, &a, "a", "fast").await;
+    solve(&mut srv, &b, "b", "fast").await;
+    solve(&mut srv, &a, "a", "slow").await;
+
+    assert_eq!(
+        standings(&board(&mut srv).await),
+        [(1, "fast", 200), (2, "slow", 200)]
+    );
+}
+
+#[tokio::test]
+async fn invalidated_solves_are_not_counted() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["alpha", "cheater"]).await;
+    let id = create(
+        &mut srv,
+        "dynamic",
+        Some(Scoring {
+            function: Function::Logarithmic as i32,
+            initial: 500,
+            minimum: 100,
+            decay: 2,
+            first_blood_bonus: vec![],
+        }),
+    )
+    .await;
+    solve(&mut srv, &id, "dynamic", "alpha").await;
+    solve(&mut srv, &id, "dynamic", "cheater").await;
+    assert_eq!(
+        standings(&board(&mut srv).await),
+        [(1, "alpha", 400), (2, "cheater", 400)]
+    );
+
+    srv.client
+        .invalidate_solve(InvalidateSolveRequest {
+            challenge_id: id.clone(),
+            team_id: "cheater".to_string(),
+            reason: "shared flag".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(standings(&board(&mut srv).await), [(1, "alpha", 500)]);
+
+    let err = srv
+        .client
+        .invalidate_solve(InvalidateSolveRequest {
+            challenge_id: id,
+            team_id: "cheater".to_string(),
+            reason: String::new(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn rejects_bad_scoring() {
+    let mut srv = util::spawn().await;
+    let err = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "bad".to_string(),
+                scoring: Some(Scoring {
+                    function: Function::Linear as i32,
+                    initial: 100,
+                    minimum: 500,
+                    decay: 1,
+                    first_blood_bonus: vec![],
+                }),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+async fn set_division(srv: &mut util::TestServer, team_id: &str, division: &str) {
+    srv.client
+        .set_team_division(SetTeamDivisionRequest {
+            team_id: team_id.to_string(),
+            division: division.to_string(),
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn pages_through_a_division() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["t1", "t2", "t3", "t4", "t5"]).await;
+    let id = create(&mut srv, "a", None).await;
+    for team in ["t1", "t2", "t3", "t4", "t5"] {
+        solve(&mut srv, &id, "a", team).await;
+    }
+    for team in ["t2", "t3", "t5"] {
+        set_division(&mut srv, team, "students").await;
+    }
+
+    let mut seen = Vec::new();
+    let mut page_token = String::new();
+    loop {
+        let page = srv
+            .client
+            .get_scoreboard(GetScoreboardRequest {
+                division: "students".to_string(),
+                page_size: 2,
+                page_token,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        seen.extend(page.entries.into_iter().map(|e| (e.rank, e.team_id)));
+        if page.next_page_token.is_empty() {
+            break;
+        }
+        page_token = page.next_page_token;
+    }
+    let want = [(1, "t2"), (2, "t3"), (3, "t5")].map(|(r, t)| (r, t.to_string()));
+    assert_eq!(seen, want);
+
+    // tokens are bound to their division
+    let first = srv
+        .client
+        .get_scoreboard(GetScoreboardRequest {
+            division: "students".to_string(),
+            page_size: 1,
+            page_token: String::new(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let err = srv
+        .client
+        .get_scoreboard(GetScoreboardRequest {
+            division: String::new(),
+            page_size: 1,
+            page_token: first.next_page_token,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn watchers_get_deltas() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["alpha", "bravo"]).await;
+    let a = create(&mut srv, "a", None).await;
+    let b = create(&mut srv, "b", None).await;
+    solve(&mut srv, &a, "a", "alpha").await;
+
+    let mut stream = srv
+        .client
+        .watch_scoreboard(WatchScoreboardRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let mut next = async || {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("a delta in time")
+            .unwrap()
+            .unwrap()
+    };
+
+    let first = next().await;
+    assert_eq!(first.entries.len(), 1);
+    assert_eq!(first.challenges.len(), 2);
+
+    solve(&mut srv, &b, "b", "bravo").await;
+    let delta = next().await;
+    assert_eq!(delta.entries.len(), 1);
+    assert_eq!(delta.entries[0].team_id, "bravo");
+    assert_eq!(delta.challenges.len(), 1);
+
+    // overtaking moves both teams
+    solve(&mut srv, &a, "a", "bravo").await;
+    let delta = next().await;
+    let ranks: Vec<_> = delta
+        .entries
+        .iter()
+        .map(|e| (e.rank, e.team_id.as_str()))
+        .collect();
+    assert_eq!(ranks, [(1, "bravo"), (2, "alpha")]);
+}
+
+#[tokio::test]
+async fn watchers_of_one_view_share_its_board() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["alpha"]).await;
+    let a = create(&mut srv, "a", None).await;
+
+    let public = srv.connect(None).await;
+    let watch = async || {
+        public
+            .clone()
+            .watch_scoreboard(WatchScoreboardRequest::default())
+            .await
+            .unwrap()
+            .into_inner()
+    };
+    let mut first = watch().await;
+    let mut second = watch().await;
+    let next = async |stream: &mut tonic::Streaming<ScoreboardDelta>| {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("a delta in time")
+            .unwrap()
+            .unwrap()
+    };
+    assert_eq!(next(&mut first).await.challenges.len(), 1);
+    assert_eq!(next(&mut second).await.challenges.len(), 1);
+
+    // one watcher leaving keeps the board going for the other
+    drop(first);
+    solve(&mut srv, &a, "a", "alpha").await;
+    assert_eq!(next(&mut second).await.entries[0].team_id, "alpha");
+
+    // and a watcher joining later starts from the whole board
+    let mut third = watch().await;
+    let whole = next(&mut third).await;
+    assert_eq!(standings_of(&whole.entries), [(1, "alpha", 100)]);
+}
+
+#[tokio::test]
+async fn scheduled_freezes_reach_watchers() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["alpha"]).await;
+    create(&mut srv, "a", None).await;
+
+    let mut stream = srv
+        .connect(None)
+        .await
+        .watch_scoreboard(WatchScoreboardRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    let mut next = async || {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("a delta in time")
+            .unwrap()
+            .unwrap()
+    };
+    assert!(next().await.frozen_at.is_none());
+
+    let freeze_at = SystemTime::now() + Duration::from_millis(500);
+    srv.client
+        .freeze_scoreboard(FreezeScoreboardRequest {
+            freeze_at: Some(freeze_at.into()),
+        })
+        .await
+        .unwrap();
+    let delta = next().await;
+    assert_eq!(delta.frozen_at, Some(freeze_at.into()));
+    assert!(SystemTime::now() >= freeze_at);
+}
+
+#[tokio::test]
+async fn frozen_boards_hide_later_solves() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["alpha", "bravo"]).await;
+    let a = create(&mut srv, "a", None).await;
+    let b = create(&mut srv, "b", None).await;
+    solve(&mut srv, &a, "a", "alpha").await;
+
+    srv.client
+        .freeze_scoreboard(FreezeScoreboardRequest { freeze_at: None })
+        .await
+        .unwrap();
+    solve(&mut srv, &b, "b", "bravo").await;
+    solve(&mut srv, &a, "a", "bravo").await;
+
+    let frozen = board(&mut srv).await;
+    assert!(frozen.frozen_at.is_some());
+    assert_eq!(standings(&frozen), [(1, "alpha", 100)]);
+
+    // admins keep seeing the live board
+    let live = srv
+        .client
+        .get_scoreboard(GetScoreboardRequest::default())
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(live.frozen_at.is_none());
+    assert_eq!(live.entries.len(), 2);
+
+    // a freeze in the future leaves the board live until then
+    srv.client
+        .freeze_scoreboard(FreezeScoreboardRequest {
+            freeze_at: Some((SystemTime::now() + Duration::from_secs(3600)).into()),
+        })
+        .await
+        .unwrap();
+    let live = board(&mut srv).await;
+    assert!(live.frozen_at.is_none());
+    assert_eq!(live.entries.len(), 2);
+
+    srv.client
+        .freeze_scoreboard(FreezeScoreboardRequest { freeze_at: None })
+        .await
+        .unwrap();
+    srv.client
+        .unfreeze_scoreboard(UnfreezeScoreboardRequest {})
+        .await
+        .unwrap();
+    let revealed = board(&mut srv).await;
+    assert!(revealed.frozen_at.is_none());
+    assert_eq!(standings(&revealed), [(1, "bravo", 200), (2, "alpha", 100)]);
+}
@@ -0,0 +1,243 @@
+mod util;
+
+use std::time::Duration;
+
+use ctfjx::client::Client;
+use ctfjx_common::identity;
+use ctfjx_proto::{
+    grpc::{agent_frame::Payload, *},
+    jobs::STAT_LOAD,
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Code, Streaming};
+
+/// Both ends of an `AgentStream`
+struct Session {
+    tx: mpsc::Sender<AgentFrame>,
+    rx: Streaming<AgentFrame>,
+}
+
+impl Session {
+    async fn open(client: &mut Client) -> Self {
+        let (tx, outbound) = mpsc::channel(8);
+        let rx = client
+            .agent_stream(ReceiverStream::new(outbound))
+            .await
+            .unwrap()
+            .into_inner();
+        Self { tx, rx }
+    }
+
+    async fn send(&self, payload: Payload) {
+        self.tx
+            .send(AgentFrame {
+                payload: Some(payload),
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn recv(&mut self) -> Result<Payload, tonic::Status> {
+        let frame = tokio::time::timeout(Duration::from_secs(5), self.rx.message())
+            .await
+            .expect("no frame in time")?;
+        Ok(frame.unwrap().payload.unwrap())
+    }
+
+    async fn job(&mut self) -> Job {
+        match self.recv().await.unwrap() {
+            Payload::Job(job) => job,
+            other => panic!("expected a job, got {other:?}"),
+        }
+    }
+}
+
+fn register(agent_id: &str) -> Payload {
+    Payload::Register(RegisterAgentRequest {
+        agent_id: agent_id.to_string(),
+        nonce: b"nonce".to_vec(),
+        ..Default::default()
+    })
+}
+
+async fn start(srv: &mut util::TestServer) -> String {
+    let challenge_id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "pwn".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+    srv.client
+        .start_instance(StartInstanceRequest {
+            challenge_id,
+            team_id: "team-a".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .instance_id
+}
+
+/// Retries `check` until it holds, the daemon handles frames on its own time
+async fn eventually<F: Future<Output = bool>>(mut check: impl FnMut() -> F) {
+    for _ in 0..100 {
+        if check().await {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("condition never held");
+}
+
+#[tokio::test]
+async fn jobs_are_pushed_to_connected_agents() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let mut agent = util::enroll(&mut srv, "agent-1").await;
+    let mut session = Session::open(&mut agent.client).await;
+
+    session.send(register("agent-1")).await;
+    let Payload::Registered(registered) = session.recv().await.unwrap() else {
+        panic!("expected the registration to be answered");
+    };
+    identity::verify_proof(
+        &agent.daemon_public_key,
+        "agent-1",
+        b"nonce",
+        &registered.daemon_signature,
+    )
+    .unwrap();
+
+    let instance_id = start(&mut srv).await;
+    let job = session.job().await;
+    assert_eq!(job.attempt, 1);
+
+    session
+        .send(Payload::Heartbeat(Heartbeat {
+            stats: [(STAT_LOAD.to_string(), "0.5".to_string())].into(),
+            ..Default::default()
+        }))
+        .await;
+    eventually(|| {
+        let mut client = srv.client.clone();
+        let instance_id = instance_id.clone();
+        async move {
+            client
+                .get_instance_status(GetInstanceStatusRequest { instance_id })
+                .await
+                .unwrap()
+                .into_inner()
+                .last_heartbeat
+                .is_some()
+        }
+    })
+    .await;
+
+    session
+        .send(Payload::JobResult(JobResult {
+            job_id: job.job_id.clone(),
+            success: true,
+            ..Default::default()
+        }))
+        .await;
+
+    // heartbeats only speak for the registered agent
+    session
+        .send(Payload::Heartbeat(Heartbeat {
+            agent_id: "agent-2".to_string(),
+            ..Default::default()
+        }))
+        .await;
+    let err = session.recv().await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    // the stream ended, yet the settled job is not handed out again
+    let jobs = srv
+        .client
+        .assign_job(AssignJobRequest {
+            agent_id: "agent-1".to_string(),
+            max_jobs: 4,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert!(jobs.is_empty());
+}
+
+#[tokio::test]
+async fn dropped_streams_release_their_jobs() {
+    let mut srv = util::spawn().await;
+    util::teams(&mut srv, &["team-a"]).await;
+    let mut agent = util::enroll(&mut srv, "agent-1").await;
+    let mut session = Session::open(&mut agent.client).await;
+    session.send(register("agent-1")).await;
+    session.recv().await.unwrap();
+
+    start(&mut srv).await;
+    let job = session.job().await;
+    drop(session);
+
+    // the agent went offline, nothing is placed on it
+    let client = srv.client.clone();
+    eventually(|| {
+        let mut client = client.clone();
+        async move {
+            let challenge_id = client
+                .create_challenge(CreateChallengeRequest {
+                    challenge: Some(Challenge {
+                        name: "web".to_string(),
+                        ..Default::default()
+                    }),
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .id;
+            client
+                .start_instance(StartInstanceRequest {
+                    challenge_id,
+                    team_id: "team-a".to_string(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .message
+                == "no agent is eligible: agent-1 is offline"
+        }
+    })
+    .await;
+
+    // and what it held is handed out again, as the next attempt
+    let mut session = Session::open(&mut agent.client).await;
+    session.send(register("agent-1")).await;
+    session.recv().await.unwrap();
+    let again = session.job().await;
+    assert_eq!(again.job_id, job.job_id);
+    assert_eq!(again.attempt, 2);
+}
+
+#[tokio::test]
+async fn streams_open_with_a_registration() {
+    let mut srv = util::spawn().await;
+    let mut agent = util::enroll(&mut srv, "agent-1").await;
+    let mut session = Session::open(&mut agent.client).await;
+    session.send(Payload::Heartbeat(Heartbeat::default())).await;
+    let err = session.recv().await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    // an agent registers as itself only
+    let mut session = Session::open(&mut agent.client).await;
+    session.send(register("agent-2")).await;
+    let err = session.recv().await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+}
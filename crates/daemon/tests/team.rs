@@ -0,0 +1,247 @@
+mod util;
+
+use ctfjx_proto::grpc::*;
+use tonic::Code;
+
+async fn create_team(srv: &mut util::TestServer, name: &str, captain_id: &str) -> Team {
+    srv.client
+        .create_team(CreateTeamRequest {
+            team: Some(Team {
+                name: name.to_string(),
+                captain_id: captain_id.to_string(),
+                division: " open ".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+}
+
+async fn join(srv: &mut util::TestServer, user_id: &str, invite_code: &str) -> Result<Team, Code> {
+    srv.client
+        .join_team(JoinTeamRequest {
+            user_id: user_id.to_string(),
+            invite_code: invite_code.to_string(),
+        })
+        .await
+        .map(|r| r.into_inner())
+        .map_err(|e| e.code())
+}
+
+async fn leave(srv: &mut util::TestServer, user_id: &str) -> Result<(), Code> {
+    srv.client
+        .leave_team(LeaveTeamRequest {
+            user_id: user_id.to_string(),
+        })
+        .await
+        .map(|_| ())
+        .map_err(|e| e.code())
+}
+
+#[tokio::test]
+async fn members_join_with_the_invite_code() {
+    let mut srv = util::spawn().await;
+    let alice = util::user(&mut srv, "alice").await;
+    let bob = util::user(&mut srv, "bob").await;
+
+    let team = create_team(&mut srv, "alpha", &alice).await;
+    assert_eq!(team.member_ids, std::slice::from_ref(&alice));
+    assert_eq!(team.division, "open");
+    assert!(!team.invite_code.is_empty());
+
+    assert_eq!(join(&mut srv, &bob, "wrong").await, Err(Code::NotFound));
+    let token = util::token(&mut srv, &bob, "write").await;
+    let joined = srv
+        .connect(Some(&token))
+        .await
+        .join_team(JoinTeamRequest {
+            user_id: bob.clone(),
+            invite_code: team.invite_code.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(joined.member_ids, [alice.clone(), bob.clone()]);
+    assert_eq!(joined.captain_id, alice);
+    // invite codes are only handed out to players on creation and reset
+    assert!(joined.invite_code.is_empty());
+
+    let user = srv
+        .client
+        .get_user(GetUserRequest { id: bob.clone() })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(user.team_id, team.id);
+    assert_eq!(
+        join(&mut srv, &bob, &team.invite_code).await,
+        Err(Code::FailedPrecondition)
+    );
+
+    // resetting the code locks out the old one
+    let carol = util::user(&mut srv, "carol").await;
+    let reset = srv
+        .client
+        .reset_invite_code(ResetInviteCodeRequest {
+            team_id: team.id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        join(&mut srv, &carol, &team.invite_code).await,
+        Err(Code::NotFound)
+    );
+    join(&mut srv, &carol, &reset.invite_code).await.unwrap();
+}
+
+#[tokio::test]
+async fn captains_transfer_before_leaving() {
+    let mut srv = util::spawn().await;
+    let alice = util::user(&mut srv, "alice").await;
+    let bob = util::user(&mut srv, "bob").await;
+    let team = create_team(&mut srv, "alpha", &alice).await;
+    join(&mut srv, &bob, &team.invite_code).await.unwrap();
+
+    assert_eq!(leave(&mut srv, &alice).await, Err(Code::FailedPrecondition));
+
+    let stranger = util::user(&mut srv, "stranger").await;
+    let err = srv
+        .client
+        .transfer_captain(TransferCaptainRequest {
+            team_id: team.id.clone(),
+            user_id: stranger,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    let team = srv
+        .client
+        .transfer_captain(TransferCaptainRequest {
+            team_id: team.id.clone(),
+            user_id: bob.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(team.captain_id, bob);
+
+    leave(&mut srv, &alice).await.unwrap();
+    assert_eq!(leave(&mut srv, &alice).await, Err(Code::FailedPrecondition));
+    // the last member may leave, the team stays behind
+    leave(&mut srv, &bob).await.unwrap();
+    let team = srv
+        .client
+        .get_team(GetTeamRequest {
+            id: team.id.clone(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(team.member_ids.is_empty());
+    assert!(team.captain_id.is_empty());
+}
+
+#[tokio::test]
+async fn teams_have_a_size_limit() {
+    let mut srv = util::spawn_with(|svc| svc.with_max_team_size(2)).await;
+    let captain = util::user(&mut srv, "captain").await;
+    let team = create_team(&mut srv, "alpha", &captain).await;
+
+    let second = util::user(&mut srv, "second").await;
+    join(&mut srv, &second, &team.invite_code).await.unwrap();
+    let third = util::user(&mut srv, "third").await;
+    assert_eq!(
+        join(&mut srv, &third, &team.invite_code).await,
+        Err(Code::FailedPrecondition)
+    );
+
+    // a spot opens up when somebody leaves
+    leave(&mut srv, &second).await.unwrap();
+    join(&mut srv, &third, &team.invite_code).await.unwrap();
+}
+
+#[tokio::test]
+async fn team_names_are_unique() {
+    let mut srv = util::spawn().await;
+    let alice = util::user(&mut srv, "alice").await;
+    let bob = util::user(&mut srv, "bob").await;
+    create_team(&mut srv, "alpha", &alice).await;
+
+    let err = srv
+        .client
+        .create_team(CreateTeamRequest {
+            team: Some(Team {
+                name: "alpha".to_string(),
+                captain_id: bob,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::AlreadyExists);
+
+    // captains can only lead one team
+    let err = srv
+        .client
+        .create_team(CreateTeamRequest {
+            team: Some(Team {
+                name: "bravo".to_string(),
+                captain_id: alice,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    let teams = srv
+        .client
+        .list_teams(ListTeamsRequest::default())
+        .await
+        .unwrap()
+        .into_inner()
+        .teams;
+    assert_eq!(teams.len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_teams_are_rejected() {
+    let mut srv = util::spawn().await;
+    let challenge_id = srv
+        .client
+        .create_challenge(CreateChallengeRequest {
+            challenge: Some(Challenge {
+                name: "web".to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id;
+
+    let err = srv
+        .client
+        .start_instance(StartInstanceRequest {
+            challenge_id: challenge_id.clone(),
+            team_id: "nobody".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+
+    let err = srv
+        .client
+        .submit_flag(SubmitFlagRequest {
+            challenge_id,
+            team_id: "nobody".to_string(),
+            flag: "flag{x}".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
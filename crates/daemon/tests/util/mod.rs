@@ -0,0 +1,190 @@
+use std::{net::SocketAddr, sync::Arc, time::SystemTime};
+
+use ctfjx::client::{self, BearerToken, Client};
+use ctfjx_common::identity::Keypair;
+use ctfjx_proto::grpc::{
+    AgentCredential, CreateJoinTokenRequest, CreateTeamRequest, CreateTokenRequest,
+    CreateUserRequest, EnrollAgentRequest, Team, User, user::Role,
+};
+use ctfjx_storage::MemoryStorage;
+use ctfjxd::{secret::Secret, server, service::CtfjxService};
+use tokio::{net::TcpListener, sync::oneshot};
+
+/// The admin token test daemons accept
+pub const ADMIN_TOKEN: &str = "admin token of the test daemon";
+
+pub struct TestServer {
+    /// Authenticated with [`ADMIN_TOKEN`]
+    pub client: Client,
+    addr: SocketAddr,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl TestServer {
+    /// Another client, sending `token` or nothing
+    #[allow(dead_code)]
+    pub async fn connect(&self, token: Option<&str>) -> Client {
+        client::connect(
+            format!("http://{}", self.addr),
+            BearerToken::new(token).unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+}
+
+/// Spawns a daemon backed by in-memory storage on an ephemeral port
+#[allow(dead_code)]
+pub async fn spawn() -> TestServer {
+    spawn_with(|svc| svc).await
+}
+
+/// [`spawn`], letting `configure` adjust the service first
+pub async fn spawn_with(configure: impl FnOnce(CtfjxService) -> CtfjxService) -> TestServer {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let service = configure(
+        CtfjxService::new(Arc::new(MemoryStorage::default()), &Secret::random())
+            .with_admin_token(ADMIN_TOKEN.parse().unwrap()),
+    );
+    tokio::spawn(server::serve(listener, service, async {
+        let _ = shutdown_rx.await;
+    }));
+
+    let client = client::connect(
+        format!("http://{addr}"),
+        BearerToken::new(Some(ADMIN_TOKEN)).unwrap(),
+    )
+    .await
+    .unwrap();
+    TestServer {
+        client,
+        addr,
+        _shutdown: shutdown_tx,
+    }
+}
+
+/// Creates a user and returns its id
+#[allow(dead_code)]
+pub async fn user(srv: &mut TestServer, name: &str) -> String {
+    srv.client
+        .create_user(CreateUserRequest {
+            user: Some(User {
+                name: name.to_string(),
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .id
+}
+
+/// Creates teams with the given ids, each around a captain of its own
+#[allow(dead_code)]
+pub async fn teams(srv: &mut TestServer, ids: &[&str]) {
+    for id in ids {
+        let captain_id = user(srv, &format!("{id} captain")).await;
+        srv.client
+            .create_team(CreateTeamRequest {
+                team: Some(Team {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    captain_id,
+                    ..Default::default()
+                }),
+            })
+            .await
+            .unwrap();
+    }
+}
+
+/// Creates an API token for `user_id` and returns its bearer token
+#[allow(dead_code)]
+pub async fn token(srv: &mut TestServer, user_id: &str, scope: &str) -> String {
+    srv.client
+        .create_token(CreateTokenRequest {
+            name: format!("{scope} token"),
+            user_id: user_id.to_string(),
+            scopes: vec![scope.to_string()],
+            expires_at: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .secret
+}
+
+/// Creates a user with `role` and returns its id along with a client
+/// holding the broadest token the role allows
+#[allow(dead_code)]
+pub async fn login(srv: &mut TestServer, name: &str, role: Role) -> (String, Client) {
+    let user = srv
+        .client
+        .create_user(CreateUserRequest {
+            user: Some(User {
+                id: name.to_string(),
+                name: name.to_string(),
+                role: role as i32,
+                ..Default::default()
+            }),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let scope = if role == Role::Admin {
+        "admin"
+    } else {
+        "write"
+    };
+    let token = token(srv, &user.id, scope).await;
+    let client = srv.connect(Some(&token)).await;
+    (user.id, client)
+}
+
+/// An agent enrolled through a join token
+#[allow(dead_code)]
+pub struct EnrolledAgent {
+    pub key: Keypair,
+    pub credential: AgentCredential,
+    pub daemon_public_key: Vec<u8>,
+    /// Signs in with a token asserting `key`
+    pub client: Client,
+}
+
+/// Enrolls `agent_id` with a fresh keypair
+#[allow(dead_code)]
+pub async fn enroll(srv: &mut TestServer, agent_id: &str) -> EnrolledAgent {
+    let join = srv
+        .client
+        .create_join_token(CreateJoinTokenRequest {
+            agent_id: agent_id.to_string(),
+            ttl_seconds: 0,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let key = Keypair::generate();
+    let enrolled = srv
+        .connect(None)
+        .await
+        .enroll_agent(EnrollAgentRequest {
+            join_token: join.token,
+            public_key: key.public_key().to_vec(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let credential = enrolled.credential.unwrap();
+    let client = srv
+        .connect(Some(&key.agent_token(&credential.id, SystemTime::now())))
+        .await;
+    EnrolledAgent {
+        key,
+        credential,
+        daemon_public_key: enrolled.daemon_public_key,
+        client,
+    }
+}
@@ -7,8 +7,9 @@ pub(crate) const VERSION_0: Version = 0x0;
 
 pub(crate) type StreamId = u16;
 
-#[derive(Clone)]
-pub(crate) enum MultiplexerMode {
+/// Which side of a session a [`Multiplexer`](crate::Multiplexer) is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiplexerMode {
     Client,
     Server,
 }
@@ -30,3 +31,6 @@ impl Display for MultiplexerMode {
 }
 
 pub(crate) const FRAME_BUFFER_SIZE: usize = 1 << 10; // 1kB
+
+/// Largest payload a single [`Cmd::Push`](crate::frame::Cmd::Push) frame can carry
+pub(crate) const MAX_PAYLOAD_SIZE: usize = u16::MAX as usize;
@@ -1,5 +1,29 @@
+//! Errors returned by the multiplexer and its streams.
+//!
+//! Every [`Error`] belongs to an [`ErrorClass`] telling the caller what to do
+//! about it, and converts to an [`io::Error`] with a stable [`io::ErrorKind`].
+//! Errors that went through [`AsyncRead`](tokio::io::AsyncRead) or
+//! [`AsyncWrite`](tokio::io::AsyncWrite) can be recovered with `Error::from`.
+
+use std::io;
+
 use thiserror::Error;
 
+use crate::{MultiplexerMode, StreamId};
+
+/// Where an error comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The peer does not speak our protocol, drop the session
+    Protocol,
+    /// The peer refused or forgot about a stream, reset the stream
+    Peer,
+    /// Misuse or a local limit, fix the call or retry later
+    Local,
+    /// The underlying connection is gone or stuck, reconnect
+    Transport,
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("invalid cmd: {0}")]
@@ -25,10 +49,211 @@ pub enum Error {
     StreamNotFound(u16),
     #[error("send frame failed for stream {0}")]
     SendFrameFailed(u16),
+    #[error("stream {0} is closed for reading")]
+    ReadClosed(u16),
+    #[error("stream {0} is closed for writing")]
+    WriteClosed(u16),
 
-    #[error("internal: ")]
+    #[error("internal: {0}")]
     Internal(String),
 
+    #[error("encode: {0}")]
+    Encode(#[from] prost::EncodeError),
+    #[error("decode: {0}")]
+    Decode(#[from] prost::DecodeError),
+    #[cfg(feature = "json")]
+    #[error("json: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Displays the inner error rather than exposing it as the source
     #[error("io: {0}")]
-    Io(#[from] std::io::Error),
+    Io(io::Error),
+
+    /// Any of the above, with the session and stream it happened on
+    #[error("{session} session{}: {source}", stream_id.map(|id| format!(", stream {id}")).unwrap_or_default())]
+    Context {
+        session: MultiplexerMode,
+        stream_id: Option<StreamId>,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attaches the session and stream, keeps the innermost context if already set
+    pub(crate) fn context(self, session: MultiplexerMode, stream_id: Option<StreamId>) -> Self {
+        match self {
+            e @ Error::Context { .. } => e,
+            e => Error::Context {
+                session,
+                stream_id: stream_id.or(e.own_stream_id()),
+                source: Box::new(e),
+            },
+        }
+    }
+
+    /// The error without any [`Error::Context`] around it
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    pub fn class(&self) -> ErrorClass {
+        match self.root() {
+            Error::InvalidCmd(_)
+            | Error::InvalidVersion(_)
+            | Error::DuplicateStream(_)
+            | Error::Decode(_) => ErrorClass::Protocol,
+            #[cfg(feature = "json")]
+            Error::Json(e) if !e.is_io() => ErrorClass::Protocol,
+            Error::StreamNotFound(_) => ErrorClass::Peer,
+            Error::PayloadTooLong()
+            | Error::StreamLimitExceeded
+            | Error::SendFrameFailed(_)
+            | Error::ReadClosed(_)
+            | Error::WriteClosed(_)
+            | Error::Internal(_)
+            | Error::Encode(_) => ErrorClass::Local,
+            _ => ErrorClass::Transport,
+        }
+    }
+
+    pub fn stream_id(&self) -> Option<StreamId> {
+        match self {
+            Error::Context { stream_id, .. } => *stream_id,
+            e => e.own_stream_id(),
+        }
+    }
+
+    /// The side of the session the error was raised on, if known
+    pub fn session(&self) -> Option<MultiplexerMode> {
+        match self {
+            Error::Context { session, .. } => Some(*session),
+            _ => None,
+        }
+    }
+
+    /// Whether the whole session is unusable, as opposed to a single stream or call
+    pub fn is_session_fatal(&self) -> bool {
+        matches!(self.class(), ErrorClass::Protocol | ErrorClass::Transport)
+    }
+
+    pub fn io_kind(&self) -> io::ErrorKind {
+        match self.root() {
+            Error::InvalidCmd(_) | Error::InvalidVersion(_) | Error::Decode(_) => {
+                io::ErrorKind::InvalidData
+            }
+            #[cfg(feature = "json")]
+            Error::Json(e) if e.is_io() => io::ErrorKind::Other,
+            #[cfg(feature = "json")]
+            Error::Json(_) => io::ErrorKind::InvalidData,
+            Error::PayloadTooLong() | Error::Encode(_) => io::ErrorKind::InvalidInput,
+            Error::MessageSendFail | Error::SendFrameFailed(_) => io::ErrorKind::BrokenPipe,
+            Error::ReadClosed(_) | Error::WriteClosed(_) => io::ErrorKind::BrokenPipe,
+            Error::MessageSendTooLong => io::ErrorKind::TimedOut,
+            Error::ConnectionClosed => io::ErrorKind::ConnectionAborted,
+            Error::StreamLimitExceeded => io::ErrorKind::QuotaExceeded,
+            Error::DuplicateStream(_) => io::ErrorKind::AlreadyExists,
+            Error::StreamNotFound(_) => io::ErrorKind::NotFound,
+            Error::Internal(_) => io::ErrorKind::Other,
+            Error::Io(e) => e.kind(),
+            Error::Context { .. } => unreachable!("root is never a context"),
+        }
+    }
+
+    fn own_stream_id(&self) -> Option<StreamId> {
+        match self {
+            Error::DuplicateStream(id)
+            | Error::StreamNotFound(id)
+            | Error::SendFrameFailed(id)
+            | Error::ReadClosed(id)
+            | Error::WriteClosed(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e,
+            e => io::Error::new(e.io_kind(), e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    /// Unwraps errors that were ours before going through [`io::Error`]
+    fn from(e: io::Error) -> Self {
+        if e.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = e.into_inner().expect("checked above");
+            return *inner.downcast::<Error>().expect("checked above");
+        }
+        Error::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn internal_displays_message() {
+        assert_eq!(
+            Error::Internal("peer ack not found".to_string()).to_string(),
+            "internal: peer ack not found"
+        );
+    }
+
+    #[test]
+    fn context_keeps_class_and_stream() {
+        let e = Error::StreamNotFound(7).context(MultiplexerMode::Server, None);
+        assert_eq!(e.class(), ErrorClass::Peer);
+        assert_eq!(e.stream_id(), Some(7));
+        assert_eq!(e.session(), Some(MultiplexerMode::Server));
+        assert_eq!(
+            e.to_string(),
+            "Server session, stream 7: stream not found 7"
+        );
+        assert_eq!(e.source().unwrap().to_string(), "stream not found 7");
+
+        // the innermost context wins
+        let e = e.context(MultiplexerMode::Client, Some(9));
+        assert_eq!(e.stream_id(), Some(7));
+        assert_eq!(e.session(), Some(MultiplexerMode::Server));
+    }
+
+    #[test]
+    fn io_roundtrip_preserves_error() {
+        let e = Error::WriteClosed(3).context(MultiplexerMode::Client, None);
+        let io_err = io::Error::from(e);
+        assert_eq!(io_err.kind(), io::ErrorKind::BrokenPipe);
+
+        let e = Error::from(io_err);
+        assert_eq!(e.stream_id(), Some(3));
+        assert!(matches!(e.root(), Error::WriteClosed(3)));
+    }
+
+    #[test]
+    fn foreign_io_errors_are_transport() {
+        let e = Error::from(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+        assert_eq!(e.class(), ErrorClass::Transport);
+        // printed once, whether or not the printer walks the source chain
+        assert_eq!(e.to_string(), "io: reset");
+        assert!(e.source().is_none());
+        assert!(e.is_session_fatal());
+        assert_eq!(io::Error::from(e).kind(), io::ErrorKind::ConnectionReset);
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(Error::InvalidCmd(9).class(), ErrorClass::Protocol);
+        assert_eq!(Error::StreamLimitExceeded.class(), ErrorClass::Local);
+        assert_eq!(Error::ConnectionClosed.class(), ErrorClass::Transport);
+        assert_eq!(Error::MessageSendTooLong.io_kind(), io::ErrorKind::TimedOut);
+        assert!(!Error::StreamNotFound(1).is_session_fatal());
+    }
 }
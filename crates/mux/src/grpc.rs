@@ -0,0 +1,123 @@
+//! Serve and call tonic gRPC services over a [`Multiplexer`].
+//!
+//! Each HTTP/2 connection tonic asks for is a new mux stream, so either side
+//! of a session can be the gRPC client, regardless of who dialed the
+//! underlying connection.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use mux::{Multiplexer, grpc};
+//!
+//! // side that serves
+//! let mux = Arc::new(Multiplexer::client(conn));
+//! tonic::transport::Server::builder()
+//!     .add_service(ServiceCtfjxServer::new(svc))
+//!     .serve_with_incoming(grpc::incoming(mux))
+//!     .await?;
+//!
+//! // side that calls
+//! let mux = Arc::new(Multiplexer::server(conn));
+//! let mut client = ServiceCtfjxClient::new(grpc::channel(mux).await?);
+//! ```
+
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures_util::{Stream as FuturesStream, stream};
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tonic::transport::{Channel, Endpoint, Uri, server::Connected};
+use tower_service::Service;
+
+use crate::{Multiplexer, Stream, error::Error};
+
+/// Placeholder authority, requests never leave the multiplexer
+const MUX_URI: &str = "http://mux";
+
+type ConnectFuture = Pin<Box<dyn Future<Output = Result<TokioIo<Stream>, Error>> + Send>>;
+
+/// Connection info attached to requests served over a mux stream
+#[derive(Debug, Clone, Copy)]
+pub struct StreamInfo {
+    pub stream_id: u16,
+}
+
+impl Connected for Stream {
+    type ConnectInfo = StreamInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        StreamInfo {
+            stream_id: self.id(),
+        }
+    }
+}
+
+/// A tonic connector that opens a new stream per connection
+pub struct Connector<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> {
+    mux: Arc<Multiplexer<T>>,
+}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Connector<T> {
+    pub fn new(mux: Arc<Multiplexer<T>>) -> Self {
+        Self { mux }
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Clone for Connector<T> {
+    fn clone(&self) -> Self {
+        Self {
+            mux: self.mux.clone(),
+        }
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Service<Uri> for Connector<T> {
+    type Response = TokioIo<Stream>;
+    type Error = Error;
+    type Future = ConnectFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let mux = self.mux.clone();
+        Box::pin(async move { mux.open().await.map(TokioIo::new) })
+    }
+}
+
+/// Creates a gRPC channel whose connections are streams opened on `mux`
+pub async fn channel<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
+    mux: Arc<Multiplexer<T>>,
+) -> Result<Channel, tonic::transport::Error> {
+    Endpoint::from_static(MUX_URI)
+        .connect_with_connector(Connector::new(mux))
+        .await
+}
+
+/// Like [`channel`] but only opens a stream on first use
+pub fn channel_lazy<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
+    mux: Arc<Multiplexer<T>>,
+) -> Channel {
+    Endpoint::from_static(MUX_URI).connect_with_connector_lazy(Connector::new(mux))
+}
+
+/// Streams accepted on `mux`, for [`tonic::transport::server::Router::serve_with_incoming`]
+///
+/// Ends once the multiplexer is closed.
+pub fn incoming<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(
+    mux: Arc<Multiplexer<T>>,
+) -> impl FuturesStream<Item = Result<Stream, Error>> + Send + 'static {
+    stream::unfold(mux, |mux| async move {
+        match mux.accept().await {
+            Ok(stream) => Some((Ok(stream), mux)),
+            Err(e) if matches!(e.root(), Error::ConnectionClosed) => None,
+            Err(e) => Some((Err(e), mux)),
+        }
+    })
+}
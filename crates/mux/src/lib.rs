@@ -4,8 +4,14 @@ pub(crate) mod multiplexer;
 pub(crate) mod poll;
 pub(crate) mod stream;
 
+pub use consts::MultiplexerMode;
 pub(crate) use consts::*;
 
 pub mod error;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod typed;
+#[cfg(feature = "ws")]
+pub mod ws;
 pub use multiplexer::Multiplexer;
-pub use stream::Stream;
+pub use stream::{Stream, StreamPerms};
@@ -20,6 +20,7 @@ use crate::{
 };
 
 pub struct Multiplexer<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> {
+    mode: MultiplexerMode,
     id_ca: Arc<StreamIdAllocator>,
     stream_manager: Arc<StreamManager>,
 
@@ -31,7 +32,7 @@ pub struct Multiplexer<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> {
 
     msg_tx: mpsc::UnboundedSender<Message>,
     close_tx: mpsc::UnboundedSender<StreamId>,
-    _phantom: PhantomData<T>,
+    _phantom: PhantomData<fn() -> T>,
 }
 
 impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Multiplexer<T> {
@@ -46,6 +47,7 @@ impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Multiplexer<T> {
         let shutdown_rx3 = shutdown_tx.subscribe();
 
         let session = Self {
+            mode,
             id_ca: Arc::new(StreamIdAllocator::new(&mode)),
             stream_manager: Arc::new(StreamManager::new(stream_creation_tx)),
             create_stream_rx: tokio::sync::Mutex::new(stream_creation_rx),
@@ -84,30 +86,44 @@ impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Multiplexer<T> {
         Self::new(conn, MultiplexerMode::Client)
     }
 
+    pub fn mode(&self) -> MultiplexerMode {
+        self.mode
+    }
+
     pub async fn open(&self) -> Result<Stream, Error> {
         if self.is_shutdown.load(Ordering::SeqCst) {
-            return Err(Error::ConnectionClosed);
+            return Err(Error::ConnectionClosed.context(self.mode, None));
         }
 
+        let stream_id = self.id_ca.alloc().map_err(|e| e.context(self.mode, None))?;
+        self.open_stream(stream_id)
+            .await
+            .map_err(|e| e.context(self.mode, Some(stream_id)))
+    }
+
+    async fn open_stream(&self, stream_id: StreamId) -> Result<Stream, Error> {
         let close_tx = self.close_tx.clone();
         let msg_tx = self.msg_tx.clone();
-        let shutdown_rx = self.shutdown_tx.subscribe();
-        let stream_id = self.id_ca.alloc()?;
         let (in_tx, in_rx) = mpsc::channel(consts::FRAME_BUFFER_SIZE);
         let (peer_close_tx, peer_close_rx) = oneshot::channel();
 
         let stream = Stream::new(
             stream_id,
+            self.mode,
             in_rx,
             msg_tx.clone(),
-            shutdown_rx,
             close_tx,
             peer_close_rx,
         );
 
         let (peer_ack_tx, peer_ack_rx) = oneshot::channel();
-        self.stream_manager
-            .add_stream(stream_id, in_tx, peer_close_tx, Some(peer_ack_tx))?;
+        self.stream_manager.add_stream(
+            stream_id,
+            stream.state(),
+            in_tx,
+            peer_close_tx,
+            Some(peer_ack_tx),
+        )?;
 
         stream::send_syn(msg_tx, stream_id).await?;
         peer_ack_rx
@@ -125,9 +141,14 @@ impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Multiplexer<T> {
             .await
             .recv()
             .await
-            .ok_or(Error::ConnectionClosed)?;
+            .ok_or(Error::ConnectionClosed.context(self.mode, None))?;
+
+        self.accept_stream(stream_id)
+            .await
+            .map_err(|e| e.context(self.mode, Some(stream_id)))
+    }
 
-        let shutdown_rx = self.shutdown_tx.subscribe();
+    async fn accept_stream(&self, stream_id: StreamId) -> Result<Stream, Error> {
         let close_tx = self.close_tx.clone();
         let msg_tx = self.msg_tx.clone();
         let (frame_tx, frame_rx) = mpsc::channel(consts::FRAME_BUFFER_SIZE);
@@ -135,14 +156,14 @@ impl<T: AsyncRead + AsyncWrite + Send + Unpin + 'static> Multiplexer<T> {
 
         let stream = Stream::new(
             stream_id,
+            self.mode,
             frame_rx,
             msg_tx,
-            shutdown_rx,
             close_tx,
             peer_close_rx,
         );
         self.stream_manager
-            .add_stream(stream_id, frame_tx, peer_close_tx, None)?;
+            .add_stream(stream_id, stream.state(), frame_tx, peer_close_tx, None)?;
         stream::send_ack(self.msg_tx.clone(), stream_id).await?;
 
         Ok(stream)
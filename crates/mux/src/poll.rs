@@ -58,20 +58,19 @@ pub(crate) async fn ingress_frame_dispatcher(
                     Some(Ok(frame)) => {
                         let _ = stream_manager.dispatch_frame(frame).await;
                     }
-                    None => {
-                        return;
-                    }
-                    Some(Err(_)) => {
-                        return;
+                    None | Some(Err(_)) => {
+                        break;
                     }
                 }
             }
 
             _ = shutdown_rx.recv() => {
-                return;
+                break;
             }
         }
     }
+
+    stream_manager.close_all();
 }
 
 pub(crate) async fn stream_close_handle(
@@ -21,7 +21,7 @@ impl StreamIdAllocator {
     pub(crate) fn new(m: &MultiplexerMode) -> Self {
         let start = m.get_starting_id();
         Self {
-            mode: m.clone(),
+            mode: *m,
             curr: AtomicU16::new(start),
             free_list: Mutex::new(VecDeque::new()),
         }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use parking_lot::Mutex;
 use tokio::sync::{mpsc, oneshot};
@@ -7,14 +7,17 @@ use crate::{
     StreamId,
     error::Error,
     frame::{Cmd, Frame},
+    stream::{StreamPerms, StreamState},
 };
 
 pub(crate) struct StreamManager {
     streams: Mutex<HashMap<StreamId, StreamHandle>>,
-    stream_creation_tx: mpsc::UnboundedSender<StreamId>,
+    // taken once the session is gone so pending accepts return
+    stream_creation_tx: Mutex<Option<mpsc::UnboundedSender<StreamId>>>,
 }
 
 pub(crate) struct StreamHandle {
+    state: Arc<StreamState>,
     frame_tx: mpsc::Sender<Frame>,
     remote_fin_tx: Option<oneshot::Sender<()>>,
     remote_ack_tx: Option<oneshot::Sender<()>>,
@@ -24,18 +27,20 @@ impl StreamManager {
     pub fn new(stream_creation_tx: mpsc::UnboundedSender<StreamId>) -> Self {
         Self {
             streams: Mutex::new(HashMap::new()),
-            stream_creation_tx,
+            stream_creation_tx: Mutex::new(Some(stream_creation_tx)),
         }
     }
 
     pub fn add_stream(
         &self,
         stream_id: StreamId,
+        state: Arc<StreamState>,
         frame_tx: mpsc::Sender<Frame>,
         remote_fin_tx: oneshot::Sender<()>,
         remote_ack_tx: Option<oneshot::Sender<()>>,
     ) -> Result<(), Error> {
         let stream_handle = StreamHandle {
+            state,
             frame_tx,
             remote_fin_tx: Some(remote_fin_tx),
             remote_ack_tx,
@@ -59,11 +64,24 @@ impl StreamManager {
         Ok(())
     }
 
+    /// Closes every stream, called once the session is gone
+    ///
+    /// Readers wake up with an error and pending accepts return.
+    pub fn close_all(&self) {
+        self.stream_creation_tx.lock().take();
+        for (_, handle) in self.streams.lock().drain() {
+            handle.state.deny(StreamPerms::RW);
+        }
+    }
+
     pub async fn dispatch_frame(&self, frame: Frame) -> Result<(), Error> {
         let stream_id = frame.header.stream_id;
         match frame.header.cmd {
             Cmd::Syn => self
                 .stream_creation_tx
+                .lock()
+                .as_ref()
+                .ok_or(Error::ConnectionClosed)?
                 .send(stream_id)
                 .map_err(|_| Error::SendFrameFailed(stream_id)),
             Cmd::Ack => self
@@ -76,16 +94,24 @@ impl StreamManager {
                 .ok_or(Error::Internal("remote ack tx not found".to_string()))?
                 .send(())
                 .map_err(|_| Error::SendFrameFailed(stream_id)),
-            Cmd::Fin => self
-                .streams
-                .lock()
-                .get_mut(&stream_id)
-                .ok_or(Error::StreamNotFound(stream_id))?
-                .remote_fin_tx
-                .take()
-                .ok_or(Error::Internal("remote fin tx not found".to_string()))?
-                .send(())
-                .map_err(|_| Error::SendFrameFailed(stream_id)),
+            Cmd::Fin => {
+                let mut streams = self.streams.lock();
+                let handle = streams
+                    .get_mut(&stream_id)
+                    .ok_or(Error::StreamNotFound(stream_id))?;
+                let res = handle
+                    .remote_fin_tx
+                    .take()
+                    .ok_or(Error::Internal("remote fin tx not found".to_string()))?
+                    .send(())
+                    .map_err(|_| Error::SendFrameFailed(stream_id));
+                let state = handle.state.clone();
+                drop(streams);
+
+                // may be the last perm, which removes the stream
+                state.deny(StreamPerms::R);
+                res
+            }
             Cmd::Push => {
                 let frame_tx = self
                     .streams
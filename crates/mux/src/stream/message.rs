@@ -19,14 +19,24 @@ impl Message {
     }
 }
 
-async fn send_frame(tx: mpsc::UnboundedSender<Message>, frame: Frame) -> Result<usize, Error> {
+/// Queues `frame` right away, the returned future resolves once it has been written out
+pub(crate) fn queue_frame(
+    tx: &mpsc::UnboundedSender<Message>,
+    frame: Frame,
+) -> Result<impl Future<Output = Result<usize, Error>> + Send + Sync + 'static, Error> {
     let (msg, rx) = Message::new(frame);
     tx.send(msg).map_err(|_| Error::MessageSendFail)?;
 
-    timeout(Duration::from_secs(5), rx)
-        .await
-        .map_err(|_| Error::MessageSendTooLong)?
-        .map_err(|_| Error::MessageSendFail)?
+    Ok(async move {
+        timeout(Duration::from_secs(5), rx)
+            .await
+            .map_err(|_| Error::MessageSendTooLong)?
+            .map_err(|_| Error::MessageSendFail)?
+    })
+}
+
+async fn send_frame(tx: mpsc::UnboundedSender<Message>, frame: Frame) -> Result<usize, Error> {
+    queue_frame(&tx, frame)?.await
 }
 
 fn send_frame_sync(tx: mpsc::UnboundedSender<Message>, frame: Frame) -> Result<(), Error> {
@@ -1,15 +1,13 @@
-use crate::{StreamId, error::Error, frame::Frame};
-use bitflags::bitflags;
-use parking_lot::RwLock;
+use crate::{MAX_PAYLOAD_SIZE, MultiplexerMode, StreamId, error::Error, frame::Frame};
 use std::{
     cmp,
     pin::Pin,
-    sync::OnceLock,
+    sync::{Arc, OnceLock},
     task::{Context, Poll},
 };
 use tokio::{
     io::{AsyncRead, AsyncWrite, ReadBuf},
-    sync::{broadcast, mpsc, oneshot},
+    sync::{mpsc, oneshot},
 };
 use tokio_util::bytes::{Buf, Bytes};
 
@@ -19,38 +17,42 @@ pub(crate) mod message;
 pub(crate) use message::*;
 pub(crate) mod manager;
 pub(crate) use manager::*;
+pub(crate) mod state;
+pub use state::StreamPerms;
+pub(crate) use state::StreamState;
 
 type FrameWriteFuture = Pin<Box<dyn Future<Output = Result<usize, Error>> + Send + Sync>>;
 
-bitflags! {
-    pub struct StreamPerms: u8 {
-        const R = 1 << 0;
-        const W = 1 << 1;
-        const RW = Self::R.bits() | Self::W.bits();
-    }
+/// How the read half ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadHalf {
+    Open,
+    // peer sent FIN, reads return EOF
+    Eof,
+    // session went away, reads return an error
+    Aborted,
 }
 
 // closing:
 // A -> FIN -> peer
 // A denies w
-// peer denies rw
+// peer denies r
 // peer -> FIN -> A
-// A denies rw
+// A denies r, peer denies w
+// once a side has neither r nor w the stream is forgotten
 pub struct Stream {
     stream_id: StreamId,
-    perms: RwLock<StreamPerms>,
+    mode: MultiplexerMode,
+    state: Arc<StreamState>,
 
     in_rx: mpsc::Receiver<Frame>,
     read_buf: Bytes,
+    read_half: ReadHalf,
 
     out_tx: mpsc::UnboundedSender<Message>,
     current_write_future: Option<FrameWriteFuture>,
 
-    // for when parent multiplexer closes
-    shutdown_rx: broadcast::Receiver<()>,
-    // for stream to internally send out when we loose rw perms
-    trigger_close_tx: mpsc::UnboundedSender<StreamId>,
-    // listen into when peer sends FIN
+    // listen into when peer sends FIN, dropped when the session goes away
     peer_close_rx: oneshot::Receiver<()>,
     close_once: OnceLock<()>,
 }
@@ -58,47 +60,97 @@ pub struct Stream {
 impl Stream {
     pub(crate) fn new(
         stream_id: StreamId,
+        mode: MultiplexerMode,
         in_rx: mpsc::Receiver<Frame>,
         out_tx: mpsc::UnboundedSender<Message>,
-        shutdown_rx: broadcast::Receiver<()>,
         trigger_close_tx: mpsc::UnboundedSender<StreamId>,
         peer_close_rx: oneshot::Receiver<()>,
     ) -> Self {
         Self {
             stream_id,
-            perms: RwLock::new(StreamPerms::RW),
+            mode,
+            state: Arc::new(StreamState::new(stream_id, trigger_close_tx)),
             read_buf: Bytes::new(),
+            read_half: ReadHalf::Open,
             current_write_future: None,
             in_rx,
             out_tx,
-            shutdown_rx,
-            trigger_close_tx,
             peer_close_rx,
             close_once: OnceLock::new(),
         }
     }
 
-    // Should be used to send out a FIN
-    pub fn close(&self) {
+    pub(crate) fn state(&self) -> Arc<StreamState> {
+        self.state.clone()
+    }
+
+    pub fn id(&self) -> StreamId {
+        self.stream_id
+    }
+
+    /// Sends a FIN to the peer, after which nothing more can be written
+    ///
+    /// Data written before is still delivered and the read half stays open
+    /// until the peer sends its own FIN.
+    pub fn shutdown_write(&self) {
         self.close_once.get_or_init(|| {
             self.deny_perm(StreamPerms::W);
             let _ = message::send_fin_sync(self.out_tx.clone(), self.stream_id);
         });
     }
 
+    /// Same as [`Stream::shutdown_write`]
+    pub fn close(&self) {
+        self.shutdown_write();
+    }
+
     pub fn deny_perm(&self, perm: StreamPerms) {
-        let mut p = self.perms.write();
-        *p -= perm & StreamPerms::RW;
+        self.state.deny(perm);
+    }
+
+    pub fn perms(&self) -> StreamPerms {
+        self.state.perms()
+    }
 
-        if !p.contains(StreamPerms::RW) {
-            let _ = self.trigger_close_tx.send(self.stream_id);
+    /// Peer sent FIN, the session closed, or reads were denied locally
+    pub fn is_read_closed(&self) -> bool {
+        !self.perms().contains(StreamPerms::R)
+    }
+
+    /// FIN was sent, the session closed, or writes were denied locally
+    pub fn is_write_closed(&self) -> bool {
+        !self.perms().contains(StreamPerms::W)
+    }
+
+    /// Resolves once both halves are closed
+    ///
+    /// The future does not borrow the stream, so it can be awaited while the
+    /// stream is being read from or written to elsewhere.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut perms_rx = self.state.subscribe();
+        async move {
+            let _ = perms_rx.wait_for(|p| p.is_empty()).await;
         }
     }
+
+    fn io_err(&self, e: Error) -> std::io::Error {
+        e.context(self.mode, Some(self.stream_id)).into()
+    }
+}
+
+impl std::fmt::Debug for Stream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Stream")
+            .field("stream_id", &self.stream_id)
+            .field("mode", &self.mode)
+            .field("perms", &self.perms())
+            .finish()
+    }
 }
 
 impl Drop for Stream {
     fn drop(&mut self) {
-        if self.perms.read().contains(StreamPerms::W) {
+        if self.perms().contains(StreamPerms::W) {
             let _ = message::send_fin_sync(self.out_tx.clone(), self.stream_id);
         }
         self.deny_perm(StreamPerms::RW);
@@ -106,6 +158,9 @@ impl Drop for Stream {
 }
 
 impl AsyncRead for Stream {
+    /// Returns EOF once the peer has sent FIN and everything before it was
+    /// read, and [`ConnectionAborted`](std::io::ErrorKind::ConnectionAborted)
+    /// if the session shuts down first.
     fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
@@ -114,13 +169,6 @@ impl AsyncRead for Stream {
         let self_mut = self.get_mut();
 
         loop {
-            if !self_mut.perms.read().contains(StreamPerms::R) {
-                return Poll::Ready(Err(std::io::Error::new(
-                    std::io::ErrorKind::BrokenPipe,
-                    "stream is closed",
-                )));
-            }
-
             // continue read op
             if !self_mut.read_buf.is_empty() {
                 let cpy = cmp::min(self_mut.read_buf.len(), buf.remaining());
@@ -129,35 +177,37 @@ impl AsyncRead for Stream {
                 return Poll::Ready(Ok(()));
             }
 
-            match Pin::new(&mut self_mut.in_rx).poll_recv(cx) {
+            match self_mut.read_half {
+                ReadHalf::Eof => return Poll::Ready(Ok(())),
+                ReadHalf::Aborted => {
+                    return Poll::Ready(Err(self_mut.io_err(Error::ConnectionClosed)));
+                }
+                ReadHalf::Open => (),
+            }
+
+            // frames before a FIN are always queued ahead of it
+            let in_closed = match Pin::new(&mut self_mut.in_rx).poll_recv(cx) {
                 Poll::Ready(Some(frame)) => {
                     self_mut.read_buf = Bytes::from(frame.payload);
                     continue;
                 }
-                Poll::Ready(None) => {
-                    return Poll::Ready(Ok(()));
-                }
-                Poll::Pending => {
-                    match Pin::new(&mut self_mut.peer_close_rx).poll(cx) {
-                        Poll::Ready(_) => {
-                            self_mut.deny_perm(StreamPerms::R);
-                            return Poll::Ready(Ok(()));
-                        }
-                        Poll::Pending => (),
-                    }
-                    match self_mut.shutdown_rx.try_recv() {
-                        Ok(_)
-                        | Err(broadcast::error::TryRecvError::Closed)
-                        | Err(broadcast::error::TryRecvError::Lagged(_)) => {
-                            self_mut.deny_perm(StreamPerms::R);
-                            return Poll::Ready(Ok(()));
-                        }
-                        _ => (),
-                    }
+                Poll::Ready(None) => true,
+                Poll::Pending => false,
+            };
 
-                    return Poll::Pending;
+            // a FIN can be what closed `in_rx`, so it is checked first
+            self_mut.read_half = match Pin::new(&mut self_mut.peer_close_rx).poll(cx) {
+                Poll::Ready(Ok(())) => ReadHalf::Eof,
+                Poll::Ready(Err(_)) => ReadHalf::Aborted,
+                Poll::Pending if in_closed => ReadHalf::Aborted,
+                Poll::Pending if self_mut.is_read_closed() => {
+                    return Poll::Ready(
+                        Err(self_mut.io_err(Error::ReadClosed(self_mut.stream_id))),
+                    );
                 }
-            }
+                Poll::Pending => return Poll::Pending,
+            };
+            self_mut.deny_perm(StreamPerms::R);
         }
     }
 }
@@ -168,48 +218,27 @@ impl AsyncWrite for Stream {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<Result<usize, std::io::Error>> {
-        if !self.perms.read().contains(StreamPerms::W) {
-            return Poll::Ready(Err(std::io::Error::new(
-                std::io::ErrorKind::BrokenPipe,
-                "stream is closed for writing",
-            )));
+        if self.is_write_closed() {
+            return Poll::Ready(Err(self.io_err(Error::WriteClosed(self.stream_id))));
         }
 
-        if self.current_write_future.is_none() {
-            let out_tx = self.out_tx.clone();
-            let stream_id = self.stream_id;
-            let data = buf.to_vec();
-
-            self.current_write_future =
-                Some(
-                    Box::pin(async move { message::send_push(out_tx, stream_id, &data).await })
-                        as FrameWriteFuture,
-                );
-            return Poll::Ready(Ok(buf.len()));
+        // one push in flight at a time
+        if let Some(fut) = self.current_write_future.as_mut() {
+            let res = std::task::ready!(fut.as_mut().poll(cx));
+            self.current_write_future = None;
+            if let Err(e) = res {
+                return Poll::Ready(Err(self.io_err(e)));
+            }
         }
 
-        match self
-            .current_write_future
-            .as_mut()
-            .unwrap()
-            .as_mut()
-            .poll(cx)
-        {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Ok(_)) => {
-                let out_tx = self.out_tx.clone();
-                let stream_id = self.stream_id;
-                let data = buf.to_vec();
+        // a push frame can only carry so much, the caller will come back for the rest
+        let buf = &buf[..cmp::min(buf.len(), MAX_PAYLOAD_SIZE)];
 
-                self.current_write_future =
-                    Some(
-                        Box::pin(async move { message::send_push(out_tx, stream_id, &data).await })
-                            as FrameWriteFuture,
-                    );
-                Poll::Ready(Ok(buf.len()))
-            }
-            Poll::Ready(Err(e)) => Poll::Ready(Err(std::io::Error::other(e.to_string()))),
-        }
+        // queued now so that a later FIN can never overtake it
+        let fut = message::queue_frame(&self.out_tx, Frame::new_push(self.stream_id, buf))
+            .map_err(|e| self.io_err(e))?;
+        self.current_write_future = Some(Box::pin(fut));
+        Poll::Ready(Ok(buf.len()))
     }
 
     fn poll_flush(
@@ -224,7 +253,7 @@ impl AsyncWrite for Stream {
                 }
                 Poll::Ready(Err(e)) => {
                     self.current_write_future = None;
-                    Poll::Ready(Err(std::io::Error::other(e.to_string())))
+                    Poll::Ready(Err(self.io_err(e)))
                 }
                 Poll::Pending => Poll::Pending,
             },
@@ -236,22 +265,9 @@ impl AsyncWrite for Stream {
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Result<(), std::io::Error>> {
-        let res = match &mut self.current_write_future {
-            Some(fut) => match fut.as_mut().poll(cx) {
-                Poll::Pending => Poll::Pending,
-                Poll::Ready(Ok(_)) => {
-                    self.current_write_future = None;
-                    Poll::Ready(Ok(()))
-                }
-                Poll::Ready(Err(e)) => {
-                    self.current_write_future = None;
-                    Poll::Ready(Err(std::io::Error::other(e.to_string())))
-                }
-            },
-            None => Poll::Ready(Ok(())),
-        };
+        let res = self.as_mut().poll_flush(cx);
         if !res.is_pending() {
-            self.close();
+            self.shutdown_write();
         }
         res
     }
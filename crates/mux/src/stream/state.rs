@@ -0,0 +1,54 @@
+use bitflags::bitflags;
+use tokio::sync::{mpsc, watch};
+
+use crate::StreamId;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct StreamPerms: u8 {
+        const R = 1 << 0;
+        const W = 1 << 1;
+        const RW = Self::R.bits() | Self::W.bits();
+    }
+}
+
+/// Half-close state shared between a [`Stream`](super::Stream) and the [`StreamManager`](super::StreamManager)
+pub(crate) struct StreamState {
+    stream_id: StreamId,
+    perms: watch::Sender<StreamPerms>,
+    // tells the session to forget about the stream once both halves are closed
+    trigger_close_tx: mpsc::UnboundedSender<StreamId>,
+}
+
+impl StreamState {
+    pub fn new(stream_id: StreamId, trigger_close_tx: mpsc::UnboundedSender<StreamId>) -> Self {
+        Self {
+            stream_id,
+            perms: watch::Sender::new(StreamPerms::RW),
+            trigger_close_tx,
+        }
+    }
+
+    pub fn perms(&self) -> StreamPerms {
+        *self.perms.borrow()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<StreamPerms> {
+        self.perms.subscribe()
+    }
+
+    /// Removes `perm`, triggers a close when the last one goes
+    pub fn deny(&self, perm: StreamPerms) {
+        let mut emptied = false;
+        self.perms.send_if_modified(|p| {
+            let before = *p;
+            *p -= perm & StreamPerms::RW;
+            emptied = !before.is_empty() && p.is_empty();
+            before != *p
+        });
+
+        if emptied {
+            let _ = self.trigger_close_tx.send(self.stream_id);
+        }
+    }
+}
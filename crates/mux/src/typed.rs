@@ -0,0 +1,161 @@
+//! Typed message channels on top of a [`Stream`].
+//!
+//! Each message is length-delimited on the wire and encoded with a [`Codec`],
+//! protobuf via [`ProstCodec`] by default and JSON via [`JsonCodec`] behind the
+//! `json` feature.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use futures_util::{SinkExt, StreamExt};
+//! use mux::typed::Channel;
+//!
+//! let stream = mux.open().await?;
+//! let mut ch: Channel<AgentFrame, AgentFrame> = Channel::new(stream);
+//!
+//! ch.send(frame).await?;
+//! let reply = ch.next().await.transpose()?;
+//! ```
+
+use std::{
+    io,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{Sink, Stream as FuturesStream, ready};
+use tokio_util::{
+    bytes::{Bytes, BytesMut},
+    codec::{Framed, LengthDelimitedCodec},
+};
+
+use crate::{Stream, error::Error};
+
+/// Encodes and decodes a single message type `T`
+pub trait Codec<T> {
+    fn encode(&self, item: &T, buf: &mut BytesMut) -> Result<(), Error>;
+    fn decode(&self, buf: &[u8]) -> Result<T, Error>;
+}
+
+/// Protobuf encoding through [`prost::Message`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProstCodec;
+
+impl<T: prost::Message + Default> Codec<T> for ProstCodec {
+    fn encode(&self, item: &T, buf: &mut BytesMut) -> Result<(), Error> {
+        item.encode(buf)?;
+        Ok(())
+    }
+
+    fn decode(&self, buf: &[u8]) -> Result<T, Error> {
+        Ok(T::decode(buf)?)
+    }
+}
+
+/// JSON encoding through [`serde_json`]
+#[cfg(feature = "json")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonCodec;
+
+#[cfg(feature = "json")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Codec<T> for JsonCodec {
+    fn encode(&self, item: &T, buf: &mut BytesMut) -> Result<(), Error> {
+        use tokio_util::bytes::BufMut;
+
+        serde_json::to_writer(buf.writer(), item)?;
+        Ok(())
+    }
+
+    fn decode(&self, buf: &[u8]) -> Result<T, Error> {
+        Ok(serde_json::from_slice(buf)?)
+    }
+}
+
+#[cfg(feature = "json")]
+pub type JsonChannel<Req, Resp> = Channel<Req, Resp, JsonCodec>;
+
+/// Length-delimited frames over a [`Stream`]
+///
+/// `LengthDelimitedCodec` encodes more than one buffer type, so its [`Sink`]
+/// methods are called through this alias with `Bytes` spelled out.
+type Framing = Framed<Stream, LengthDelimitedCodec>;
+
+/// Sends `Req` and receives `Resp` messages over a [`Stream`]
+///
+/// Implements [`Sink<Req>`] and [`futures_util::Stream`] yielding `Result<Resp, Error>`.
+pub struct Channel<Req, Resp, C = ProstCodec> {
+    framed: Framing,
+    codec: C,
+    _phantom: PhantomData<fn(Req) -> Resp>,
+}
+
+impl<Req, Resp> Channel<Req, Resp, ProstCodec> {
+    pub fn new(stream: Stream) -> Self {
+        Self::with_codec(stream, ProstCodec)
+    }
+}
+
+impl<Req, Resp, C> Channel<Req, Resp, C> {
+    pub fn with_codec(stream: Stream, codec: C) -> Self {
+        Self {
+            framed: Framed::new(stream, LengthDelimitedCodec::new()),
+            codec,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn get_ref(&self) -> &Stream {
+        self.framed.get_ref()
+    }
+
+    /// Returns the underlying stream
+    ///
+    /// Any buffered but unread messages are lost.
+    pub fn into_inner(self) -> Stream {
+        self.framed.into_inner()
+    }
+}
+
+impl<Req, Resp, C> Unpin for Channel<Req, Resp, C> {}
+
+impl<Req, Resp, C: Codec<Req>> Sink<Req> for Channel<Req, Resp, C> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        <Framing as Sink<Bytes>>::poll_ready(Pin::new(&mut self.get_mut().framed), cx)
+            .map_err(|e: io::Error| Error::from(e))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Req) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        let mut buf = BytesMut::new();
+        this.codec.encode(&item, &mut buf)?;
+        <Framing as Sink<Bytes>>::start_send(Pin::new(&mut this.framed), buf.freeze())
+            .map_err(|e: io::Error| Error::from(e))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        <Framing as Sink<Bytes>>::poll_flush(Pin::new(&mut self.get_mut().framed), cx)
+            .map_err(|e: io::Error| Error::from(e))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        <Framing as Sink<Bytes>>::poll_close(Pin::new(&mut self.get_mut().framed), cx)
+            .map_err(|e: io::Error| Error::from(e))
+    }
+}
+
+impl<Req, Resp, C: Codec<Resp>> FuturesStream for Channel<Req, Resp, C> {
+    type Item = Result<Resp, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let item = match ready!(Pin::new(&mut this.framed).poll_next(cx)) {
+            Some(Ok(buf)) => Some(this.codec.decode(&buf)),
+            Some(Err(e)) => Some(Err(Error::from(e))),
+            None => None,
+        };
+        Poll::Ready(item)
+    }
+}
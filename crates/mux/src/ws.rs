@@ -0,0 +1,127 @@
+//! Run a [`Multiplexer`](crate::Multiplexer) over a WebSocket.
+//!
+//! Mux bytes travel as binary messages; message boundaries carry no meaning.
+//! Useful where only outbound HTTP(S) is allowed.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! use mux::{Multiplexer, ws::WsStream};
+//!
+//! let (ws, _) = tokio_tungstenite::connect_async("wss://ctfjx.example/mux").await?;
+//! let mux = Multiplexer::client(WsStream::new(ws));
+//! ```
+
+use std::{
+    cmp, io,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use futures_util::{Sink, Stream as FuturesStream};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_util::bytes::{Buf, Bytes};
+
+/// Adapts a WebSocket into `AsyncRead + AsyncWrite`
+///
+/// Works with any `S` shaped like [`tokio_tungstenite::WebSocketStream`].
+pub struct WsStream<S> {
+    inner: S,
+    read_buf: Bytes,
+}
+
+impl<S> WsStream<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            read_buf: Bytes::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+fn to_io_error(e: WsError) -> io::Error {
+    match e {
+        WsError::Io(e) => e,
+        WsError::ConnectionClosed | WsError::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::BrokenPipe, e)
+        }
+        e => io::Error::other(e),
+    }
+}
+
+impl<S> AsyncRead for WsStream<S>
+where
+    S: FuturesStream<Item = Result<Message, WsError>> + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let self_mut = self.get_mut();
+
+        loop {
+            if !self_mut.read_buf.is_empty() {
+                let cpy = cmp::min(self_mut.read_buf.len(), buf.remaining());
+                buf.put_slice(&self_mut.read_buf[..cpy]);
+                self_mut.read_buf.advance(cpy);
+                return Poll::Ready(Ok(()));
+            }
+
+            match ready!(Pin::new(&mut self_mut.inner).poll_next(cx)) {
+                Some(Ok(Message::Binary(data))) => self_mut.read_buf = data,
+                // ping/pong are answered by tungstenite itself
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => (),
+                Some(Ok(Message::Text(_))) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected text message",
+                    )));
+                }
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                Some(Err(WsError::ConnectionClosed)) => return Poll::Ready(Ok(())),
+                Some(Err(e)) => return Poll::Ready(Err(to_io_error(e))),
+            }
+        }
+    }
+}
+
+impl<S> AsyncWrite for WsStream<S>
+where
+    S: Sink<Message, Error = WsError> + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        let inner = &mut self.get_mut().inner;
+        ready!(Pin::new(&mut *inner).poll_ready(cx)).map_err(to_io_error)?;
+        Pin::new(inner)
+            .start_send(Message::binary(buf.to_vec()))
+            .map_err(to_io_error)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.get_mut().inner)
+            .poll_flush(cx)
+            .map_err(to_io_error)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        match ready!(Pin::new(&mut self.get_mut().inner).poll_close(cx)) {
+            Ok(()) | Err(WsError::ConnectionClosed | WsError::AlreadyClosed) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(Err(to_io_error(e))),
+        }
+    }
+}
@@ -0,0 +1,356 @@
+#![cfg(feature = "grpc")]
+
+mod util;
+
+use std::{pin::Pin, sync::Arc};
+
+use ctfjx_proto::grpc::{
+    service_ctfjx_client::ServiceCtfjxClient,
+    service_ctfjx_server::{ServiceCtfjx, ServiceCtfjxServer},
+    *,
+};
+use futures_util::Stream;
+use mux::grpc;
+use tonic::{Request, Response, Status, Streaming, transport::Server};
+
+/// Answers pings with the name of the side serving them
+struct PingOnly(&'static str);
+
+type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl ServiceCtfjx for PingOnly {
+    async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PingResponse>, Status> {
+        let stream_id = request
+            .extensions()
+            .get::<grpc::StreamInfo>()
+            .map(|info| info.stream_id)
+            .ok_or(Status::internal("missing stream info"))?;
+
+        Ok(Response::new(PingResponse {
+            message: format!(
+                "{} -> {} ({})",
+                request.into_inner().client,
+                self.0,
+                stream_id
+            ),
+            ..Default::default()
+        }))
+    }
+
+    async fn create_challenge(
+        &self,
+        _: Request<CreateChallengeRequest>,
+    ) -> Result<Response<CreateChallengeResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn get_challenge(
+        &self,
+        _: Request<GetChallengeRequest>,
+    ) -> Result<Response<Challenge>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn update_challenge(
+        &self,
+        _: Request<UpdateChallengeRequest>,
+    ) -> Result<Response<Challenge>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn delete_challenge(
+        &self,
+        _: Request<DeleteChallengeRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn list_challenges(
+        &self,
+        _: Request<ListChallengesRequest>,
+    ) -> Result<Response<ListChallengesResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn create_user(&self, _: Request<CreateUserRequest>) -> Result<Response<User>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn get_user(&self, _: Request<GetUserRequest>) -> Result<Response<User>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn create_team(&self, _: Request<CreateTeamRequest>) -> Result<Response<Team>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn get_team(&self, _: Request<GetTeamRequest>) -> Result<Response<Team>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn list_teams(
+        &self,
+        _: Request<ListTeamsRequest>,
+    ) -> Result<Response<ListTeamsResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn join_team(&self, _: Request<JoinTeamRequest>) -> Result<Response<Team>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn leave_team(&self, _: Request<LeaveTeamRequest>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn transfer_captain(
+        &self,
+        _: Request<TransferCaptainRequest>,
+    ) -> Result<Response<Team>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn reset_invite_code(
+        &self,
+        _: Request<ResetInviteCodeRequest>,
+    ) -> Result<Response<Team>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn create_token(
+        &self,
+        _: Request<CreateTokenRequest>,
+    ) -> Result<Response<CreateTokenResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn list_tokens(
+        &self,
+        _: Request<ListTokensRequest>,
+    ) -> Result<Response<ListTokensResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn revoke_token(&self, _: Request<RevokeTokenRequest>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn create_session(
+        &self,
+        _: Request<CreateSessionRequest>,
+    ) -> Result<Response<Session>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn submit_flag(
+        &self,
+        _: Request<SubmitFlagRequest>,
+    ) -> Result<Response<SubmitFlagResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn invalidate_solve(
+        &self,
+        _: Request<InvalidateSolveRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn get_scoreboard(
+        &self,
+        _: Request<GetScoreboardRequest>,
+    ) -> Result<Response<Scoreboard>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type WatchScoreboardStream = BoxStream<ScoreboardDelta>;
+    async fn watch_scoreboard(
+        &self,
+        _: Request<WatchScoreboardRequest>,
+    ) -> Result<Response<Self::WatchScoreboardStream>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn freeze_scoreboard(
+        &self,
+        _: Request<FreezeScoreboardRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn unfreeze_scoreboard(
+        &self,
+        _: Request<UnfreezeScoreboardRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn set_team_division(
+        &self,
+        _: Request<SetTeamDivisionRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn start_instance(
+        &self,
+        _: Request<StartInstanceRequest>,
+    ) -> Result<Response<StartInstanceResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn stop_instance(
+        &self,
+        _: Request<StopInstanceRequest>,
+    ) -> Result<Response<StopInstanceResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn get_instance_status(
+        &self,
+        _: Request<GetInstanceStatusRequest>,
+    ) -> Result<Response<InstanceStatus>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn extend_instance(
+        &self,
+        _: Request<ExtendInstanceRequest>,
+    ) -> Result<Response<InstanceStatus>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn create_join_token(
+        &self,
+        _: Request<CreateJoinTokenRequest>,
+    ) -> Result<Response<JoinToken>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn enroll_agent(
+        &self,
+        _: Request<EnrollAgentRequest>,
+    ) -> Result<Response<EnrollAgentResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn rotate_agent_key(
+        &self,
+        _: Request<RotateAgentKeyRequest>,
+    ) -> Result<Response<EnrollAgentResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn list_agent_credentials(
+        &self,
+        _: Request<ListAgentCredentialsRequest>,
+    ) -> Result<Response<ListAgentCredentialsResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn revoke_agent_credential(
+        &self,
+        _: Request<RevokeAgentCredentialRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn register_agent(
+        &self,
+        _: Request<RegisterAgentRequest>,
+    ) -> Result<Response<RegisterAgentResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn assign_job(
+        &self,
+        _: Request<AssignJobRequest>,
+    ) -> Result<Response<AssignJobResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn report_job_result(
+        &self,
+        _: Request<ReportJobResultRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn send_heartbeat(&self, _: Request<Heartbeat>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(""))
+    }
+    async fn list_dead_jobs(
+        &self,
+        _: Request<ListDeadJobsRequest>,
+    ) -> Result<Response<ListDeadJobsResponse>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type StreamEventsStream = BoxStream<Event>;
+    async fn stream_events(
+        &self,
+        _: Request<StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type AgentStreamStream = BoxStream<AgentFrame>;
+    async fn agent_stream(
+        &self,
+        _: Request<Streaming<AgentFrame>>,
+    ) -> Result<Response<Self::AgentStreamStream>, Status> {
+        Err(Status::unimplemented(""))
+    }
+}
+
+#[tokio::test]
+async fn unary_call_over_mux() {
+    let (client, server) = util::make_mux_pair();
+    let (client, server) = (Arc::new(client), Arc::new(server));
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(ServiceCtfjxServer::new(PingOnly("server")))
+            .serve_with_incoming(grpc::incoming(server)),
+    );
+
+    let mut rpc = ServiceCtfjxClient::new(grpc::channel(client).await.unwrap());
+    for _ in 0..3 {
+        let resp = rpc
+            .ping(PingRequest {
+                client: "client".to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(resp.into_inner().message.starts_with("client -> server"));
+    }
+}
+
+#[tokio::test]
+async fn both_sides_serve_and_call() {
+    let (agent, daemon) = util::make_mux_pair();
+    let (agent, daemon) = (Arc::new(agent), Arc::new(daemon));
+
+    // the agent dialed out but still receives calls initiated by the daemon
+    tokio::spawn(
+        Server::builder()
+            .add_service(ServiceCtfjxServer::new(PingOnly("agent")))
+            .serve_with_incoming(grpc::incoming(agent.clone())),
+    );
+    tokio::spawn(
+        Server::builder()
+            .add_service(ServiceCtfjxServer::new(PingOnly("daemon")))
+            .serve_with_incoming(grpc::incoming(daemon.clone())),
+    );
+
+    let mut to_agent = ServiceCtfjxClient::new(grpc::channel_lazy(daemon));
+    let mut to_daemon = ServiceCtfjxClient::new(grpc::channel_lazy(agent));
+
+    let (a, d) = tokio::join!(
+        to_agent.ping(PingRequest {
+            client: "daemon".to_string(),
+        }),
+        to_daemon.ping(PingRequest {
+            client: "agent".to_string(),
+        }),
+    );
+    assert!(
+        a.unwrap()
+            .into_inner()
+            .message
+            .starts_with("daemon -> agent")
+    );
+    assert!(
+        d.unwrap()
+            .into_inner()
+            .message
+            .starts_with("agent -> daemon")
+    );
+}
+
+#[tokio::test]
+async fn large_response_body() {
+    let (client, server) = util::make_mux_pair();
+    let (client, server) = (Arc::new(client), Arc::new(server));
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(ServiceCtfjxServer::new(PingOnly("server")))
+            .serve_with_incoming(grpc::incoming(server)),
+    );
+
+    let mut rpc = ServiceCtfjxClient::new(grpc::channel(client).await.unwrap());
+    let client_name = "c".repeat(512 * 1024);
+    let resp = rpc
+        .ping(PingRequest {
+            client: client_name.clone(),
+        })
+        .await
+        .unwrap();
+    assert!(resp.into_inner().message.starts_with(&client_name));
+}
@@ -0,0 +1,133 @@
+mod util;
+
+use std::{io::ErrorKind, time::Duration};
+
+use mux::{
+    MultiplexerMode,
+    error::{Error, ErrorClass},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    time::timeout,
+};
+
+#[tokio::test]
+async fn half_closed_stream_still_reads() {
+    let (client, server) = util::make_mux_pair();
+    let (a, b) = tokio::join!(client.open(), server.accept());
+    let (mut a, mut b) = (a.unwrap(), b.unwrap());
+
+    a.write_all(b"request").await.unwrap();
+    a.shutdown_write();
+    assert!(a.is_write_closed());
+    assert!(!a.is_read_closed());
+
+    let mut req = Vec::new();
+    b.read_to_end(&mut req).await.unwrap();
+    assert_eq!(req, b"request");
+    assert!(b.is_read_closed());
+    assert!(!b.is_write_closed());
+
+    b.write_all(b"response").await.unwrap();
+    b.shutdown().await.unwrap();
+
+    let mut resp = Vec::new();
+    a.read_to_end(&mut resp).await.unwrap();
+    assert_eq!(resp, b"response");
+}
+
+#[tokio::test]
+async fn peer_fin_is_eof_every_time() {
+    let (client, server) = util::make_mux_pair();
+    let (a, b) = tokio::join!(client.open(), server.accept());
+    let (a, mut b) = (a.unwrap(), b.unwrap());
+
+    a.shutdown_write();
+
+    let mut buf = [0u8; 8];
+    for _ in 0..3 {
+        assert_eq!(b.read(&mut buf).await.unwrap(), 0);
+    }
+}
+
+#[tokio::test]
+async fn write_after_shutdown_write_fails() {
+    let (client, server) = util::make_mux_pair();
+    let (a, _b) = tokio::join!(client.open(), server.accept());
+    let mut a = a.unwrap();
+
+    a.shutdown_write();
+    let err = a.write_all(b"late").await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+    let err = Error::from(err);
+    assert!(matches!(err.root(), Error::WriteClosed(_)));
+    assert_eq!(err.stream_id(), Some(a.id()));
+    assert_eq!(err.session(), Some(MultiplexerMode::Client));
+    assert_eq!(err.class(), ErrorClass::Local);
+}
+
+#[tokio::test]
+async fn closed_resolves_once_both_halves_are_done() {
+    let (client, server) = util::make_mux_pair();
+    let (a, b) = tokio::join!(client.open(), server.accept());
+    let (mut a, mut b) = (a.unwrap(), b.unwrap());
+
+    let a_closed = tokio::spawn(a.closed());
+    let b_closed = tokio::spawn(b.closed());
+
+    a.shutdown_write();
+    assert_eq!(b.read(&mut [0u8; 1]).await.unwrap(), 0);
+    assert!(
+        timeout(Duration::from_millis(50), b.closed())
+            .await
+            .is_err(),
+        "write half of b is still open"
+    );
+
+    b.shutdown_write();
+    assert_eq!(a.read(&mut [0u8; 1]).await.unwrap(), 0);
+
+    timeout(Duration::from_secs(1), a_closed)
+        .await
+        .unwrap()
+        .unwrap();
+    timeout(Duration::from_secs(1), b_closed)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn session_shutdown_is_an_error() {
+    let (client, server) = util::make_mux_pair();
+    let (a, b) = tokio::join!(client.open(), server.accept());
+    let (mut a, mut b) = (a.unwrap(), b.unwrap());
+
+    let reader = tokio::spawn(async move {
+        let mut buf = [0u8; 8];
+        let res = b.read(&mut buf).await;
+        (res, b)
+    });
+    let a_closed = a.closed();
+
+    client.close();
+
+    let (res, b) = timeout(Duration::from_secs(1), reader)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::ConnectionAborted);
+    assert!(b.is_read_closed() && b.is_write_closed());
+
+    let err = a.read(&mut [0u8; 1]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionAborted);
+    timeout(Duration::from_secs(1), a_closed).await.unwrap();
+
+    let err = timeout(Duration::from_secs(1), server.accept())
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(err.root(), Error::ConnectionClosed));
+    assert!(err.is_session_fatal());
+}
@@ -0,0 +1,89 @@
+mod util;
+
+use ctfjx_proto::grpc::{AgentFrame, Heartbeat, RegisterAgentRequest, agent_frame::Payload};
+use futures_util::{SinkExt, StreamExt};
+use mux::typed::Channel;
+
+#[tokio::test]
+async fn agent_frames_roundtrip() {
+    let (client, server) = util::make_mux_pair();
+    let (client_stream, server_stream) = tokio::join!(client.open(), server.accept());
+
+    let mut agent: Channel<AgentFrame, AgentFrame> = Channel::new(client_stream.unwrap());
+    let mut daemon: Channel<AgentFrame, AgentFrame> = Channel::new(server_stream.unwrap());
+
+    let register = AgentFrame {
+        payload: Some(Payload::Register(RegisterAgentRequest {
+            agent_id: "agent-1".to_string(),
+            version: "0.1.0".to_string(),
+            capabilities: vec!["docker".to_string()],
+            ..Default::default()
+        })),
+    };
+    agent.send(register.clone()).await.unwrap();
+    assert_eq!(daemon.next().await.unwrap().unwrap(), register);
+
+    let heartbeat = AgentFrame {
+        payload: Some(Payload::Heartbeat(Heartbeat {
+            agent_id: "agent-1".to_string(),
+            ..Default::default()
+        })),
+    };
+    daemon.send(heartbeat.clone()).await.unwrap();
+    assert_eq!(agent.next().await.unwrap().unwrap(), heartbeat);
+}
+
+#[tokio::test]
+async fn large_message_spans_frames() {
+    let (client, server) = util::make_mux_pair();
+    let (client_stream, server_stream) = tokio::join!(client.open(), server.accept());
+
+    let mut tx: Channel<RegisterAgentRequest, RegisterAgentRequest> =
+        Channel::new(client_stream.unwrap());
+    let mut rx: Channel<RegisterAgentRequest, RegisterAgentRequest> =
+        Channel::new(server_stream.unwrap());
+
+    let msg = RegisterAgentRequest {
+        agent_id: "x".repeat(200 * 1024),
+        ..Default::default()
+    };
+    let (sent, received) = tokio::join!(tx.send(msg.clone()), rx.next());
+    sent.unwrap();
+    assert_eq!(received.unwrap().unwrap(), msg);
+}
+
+#[tokio::test]
+async fn stream_ends_when_peer_closes() {
+    let (client, server) = util::make_mux_pair();
+    let (client_stream, server_stream) = tokio::join!(client.open(), server.accept());
+
+    let mut tx: Channel<Heartbeat, Heartbeat> = Channel::new(client_stream.unwrap());
+    let mut rx: Channel<Heartbeat, Heartbeat> = Channel::new(server_stream.unwrap());
+
+    tx.send(Heartbeat::default()).await.unwrap();
+    tx.close().await.unwrap();
+
+    assert_eq!(rx.next().await.unwrap().unwrap(), Heartbeat::default());
+    assert!(rx.next().await.is_none());
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn json_codec_roundtrip() {
+    use mux::typed::JsonChannel;
+
+    let (client, server) = util::make_mux_pair();
+    let (client_stream, server_stream) = tokio::join!(client.open(), server.accept());
+
+    let mut tx: JsonChannel<Heartbeat, Heartbeat> =
+        Channel::with_codec(client_stream.unwrap(), mux::typed::JsonCodec);
+    let mut rx: JsonChannel<Heartbeat, Heartbeat> =
+        Channel::with_codec(server_stream.unwrap(), mux::typed::JsonCodec);
+
+    let heartbeat = Heartbeat {
+        agent_id: "agent-1".to_string(),
+        ..Default::default()
+    };
+    tx.send(heartbeat.clone()).await.unwrap();
+    assert_eq!(rx.next().await.unwrap().unwrap(), heartbeat);
+}
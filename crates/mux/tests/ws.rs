@@ -0,0 +1,78 @@
+#![cfg(feature = "ws")]
+
+use ctfjx_proto::grpc::{AgentFrame, Heartbeat, agent_frame::Payload};
+use futures_util::{SinkExt, StreamExt};
+use mux::{Multiplexer, typed::Channel, ws::WsStream};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream, duplex};
+use tokio_tungstenite::{WebSocketStream, accept_async, client_async};
+
+type WsMux = Multiplexer<WsStream<WebSocketStream<DuplexStream>>>;
+
+/// returns (client, server) talking through an in-process websocket
+async fn make_ws_mux_pair() -> (WsMux, WsMux) {
+    let (client, server) = duplex(64 * 1024);
+    let (client, server) = tokio::join!(
+        client_async("ws://localhost/mux", client),
+        accept_async(server)
+    );
+    let (client, _) = client.unwrap();
+    let server = server.unwrap();
+
+    (
+        Multiplexer::client(WsStream::new(client)),
+        Multiplexer::server(WsStream::new(server)),
+    )
+}
+
+#[tokio::test]
+async fn bytes_roundtrip() {
+    let (client, server) = make_ws_mux_pair().await;
+    let (a, b) = tokio::join!(client.open(), server.accept());
+    let (mut a, mut b) = (a.unwrap(), b.unwrap());
+
+    a.write_all(b"hello over ws").await.unwrap();
+    a.flush().await.unwrap();
+
+    let mut buf = [0u8; 13];
+    b.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"hello over ws");
+}
+
+#[tokio::test]
+async fn many_streams_and_large_messages() {
+    let (client, server) = make_ws_mux_pair().await;
+
+    for i in 0..4 {
+        let (a, b) = tokio::join!(client.open(), server.accept());
+        let mut tx: Channel<AgentFrame, AgentFrame> = Channel::new(a.unwrap());
+        let mut rx: Channel<AgentFrame, AgentFrame> = Channel::new(b.unwrap());
+
+        let frame = AgentFrame {
+            payload: Some(Payload::Heartbeat(Heartbeat {
+                agent_id: format!("agent-{i}").repeat(20 * 1024),
+                ..Default::default()
+            })),
+        };
+        let (sent, received) = tokio::join!(tx.send(frame.clone()), rx.next());
+        sent.unwrap();
+        assert_eq!(received.unwrap().unwrap(), frame);
+    }
+}
+
+#[tokio::test]
+async fn read_ends_when_websocket_closes() {
+    let (client, server) = duplex(64 * 1024);
+    let (client, server) = tokio::join!(
+        client_async("ws://localhost/mux", client),
+        accept_async(server)
+    );
+    let mut client = WsStream::new(client.unwrap().0);
+    let mut server = WsStream::new(server.unwrap());
+
+    client.write_all(b"bye").await.unwrap();
+    client.shutdown().await.unwrap();
+
+    let mut buf = Vec::new();
+    server.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, b"bye");
+}
@@ -5,6 +5,8 @@ fn main() {
         .build_server(true)
         .build_transport(true)
         .type_attribute(".", "#[derive(serde::Serialize,serde::Deserialize)]")
+        // records stored as JSON predate fields added later
+        .message_attribute(".", "#[serde(default)]")
         .extern_path(".google.protobuf.Any", "::prost_wkt_types::Any")
         .extern_path(".google.protobuf.Timestamp", "::prost_wkt_types::Timestamp")
         .extern_path(".google.protobuf.Value", "::prost_wkt_types::Value")
@@ -1,11 +1,13 @@
 // This file is @generated by prost-build.
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct PingRequest {
     #[prost(string, tag = "1")]
     pub client: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct PingResponse {
     #[prost(string, tag = "1")]
@@ -16,6 +18,7 @@ pub struct PingResponse {
     pub version: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Challenge {
     #[prost(string, tag = "1")]
@@ -24,6 +27,7 @@ pub struct Challenge {
     pub name: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
     pub description: ::prost::alloc::string::String,
+    /// Id of the user owning the challenge, authors only manage their own
     #[prost(string, tag = "4")]
     pub owner: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "5")]
@@ -39,14 +43,57 @@ pub struct Challenge {
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    /// Accepted flags, a submission matching any of them solves the challenge.
+    /// Only returned to admins and the owner.
+    #[prost(message, repeated, tag = "10")]
+    pub flags: ::prost::alloc::vec::Vec<FlagSpec>,
+    /// Value of the challenge when `scoring` is not set
+    #[prost(int32, tag = "11")]
+    pub points: i32,
+    #[prost(message, optional, tag = "12")]
+    pub scoring: ::core::option::Option<Scoring>,
+    /// What an agent needs to run instances of the challenge. A requirement is
+    /// either a capability the agent registered, e.g. `docker` or `kvm`, or a
+    /// `key=value` label matched against its metadata, e.g. `arch=amd64` or
+    /// `region=eu-west`.
+    #[prost(string, repeated, tag = "13")]
+    pub requirements: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// What agents run for an instance. Only returned to admins and the owner.
+    #[prost(message, optional, tag = "14")]
+    pub runtime: ::core::option::Option<ChallengeRuntime>,
 }
+/// How an instance of a challenge is run. The instance is told the port to
+/// listen on in `PORT` and its team's flag in `FLAG` and a `flag.txt` file.
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChallengeRuntime {
+    /// Container image, for agents running containers
+    #[prost(string, tag = "1")]
+    pub image: ::prost::alloc::string::String,
+    /// Program and arguments. Agents running processes need it, containers
+    /// fall back to the image's entrypoint.
+    #[prost(string, repeated, tag = "2")]
+    pub command: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "3")]
+    pub env: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Port the challenge listens on inside a container, processes are given
+    /// a free one
+    #[prost(int32, tag = "4")]
+    pub port: i32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateChallengeRequest {
     #[prost(message, optional, tag = "1")]
     pub challenge: ::core::option::Option<Challenge>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct CreateChallengeResponse {
     #[prost(string, tag = "1")]
@@ -55,24 +102,32 @@ pub struct CreateChallengeResponse {
     pub message: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct GetChallengeRequest {
     #[prost(string, tag = "1")]
     pub id: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateChallengeRequest {
     #[prost(message, optional, tag = "1")]
     pub challenge: ::core::option::Option<Challenge>,
+    /// Fields of `challenge` to apply, e.g. `tags` or `metadata.author`. Empty
+    /// or `*` replaces every field but `flags`, which has to be named.
+    #[prost(message, optional, tag = "2")]
+    pub update_mask: ::core::option::Option<::prost_wkt_types::FieldMask>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct DeleteChallengeRequest {
     #[prost(string, tag = "1")]
     pub id: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct ListChallengesRequest {
     #[prost(int32, tag = "1")]
@@ -83,6 +138,7 @@ pub struct ListChallengesRequest {
     pub filter: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListChallengesResponse {
     #[prost(message, repeated, tag = "1")]
@@ -91,74 +147,123 @@ pub struct ListChallengesResponse {
     pub next_page_token: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct StartInstanceRequest {
-    #[prost(string, tag = "1")]
-    pub challenge_id: ::prost::alloc::string::String,
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct FlagSpec {
+    #[prost(enumeration = "flag_spec::Kind", tag = "1")]
+    pub kind: i32,
     #[prost(string, tag = "2")]
-    pub owner: ::prost::alloc::string::String,
-    #[prost(map = "string, string", tag = "3")]
-    pub overrides: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
-    #[prost(string, tag = "4")]
-    pub agent_id: ::prost::alloc::string::String,
+    pub value: ::prost::alloc::string::String,
+}
+/// Nested message and enum types in `FlagSpec`.
+pub mod flag_spec {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Kind {
+        Unspecified = 0,
+        /// `value` is the flag
+        Static = 1,
+        /// `value` is the flag, compared ignoring case
+        CaseInsensitive = 2,
+        /// `value` is a regular expression the whole flag has to match
+        Regex = 3,
+        /// Unique per team, derived by the daemon. `value` is the prefix wrapping
+        /// it, `flag` when empty, as in `flag{...}`
+        Dynamic = 4,
+    }
+    impl Kind {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Self::Unspecified => "KIND_UNSPECIFIED",
+                Self::Static => "KIND_STATIC",
+                Self::CaseInsensitive => "KIND_CASE_INSENSITIVE",
+                Self::Regex => "KIND_REGEX",
+                Self::Dynamic => "KIND_DYNAMIC",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "KIND_UNSPECIFIED" => Some(Self::Unspecified),
+                "KIND_STATIC" => Some(Self::Static),
+                "KIND_CASE_INSENSITIVE" => Some(Self::CaseInsensitive),
+                "KIND_REGEX" => Some(Self::Regex),
+                "KIND_DYNAMIC" => Some(Self::Dynamic),
+                _ => None,
+            }
+        }
+    }
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct StartInstanceResponse {
+pub struct SubmitFlagRequest {
     #[prost(string, tag = "1")]
-    pub instance_id: ::prost::alloc::string::String,
+    pub challenge_id: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
-    pub node: ::prost::alloc::string::String,
+    pub team_id: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
-    pub message: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "4")]
-    pub started_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    pub flag: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct StopInstanceRequest {
-    #[prost(string, tag = "1")]
-    pub instance_id: ::prost::alloc::string::String,
+pub struct SubmitFlagResponse {
+    #[prost(bool, tag = "1")]
+    pub correct: bool,
+    /// The team had solved the challenge before, no new solve was recorded
     #[prost(bool, tag = "2")]
-    pub force: bool,
-}
-#[derive(serde::Serialize, serde::Deserialize)]
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct StopInstanceResponse {
-    #[prost(string, tag = "1")]
-    pub instance_id: ::prost::alloc::string::String,
-    #[prost(string, tag = "2")]
+    pub already_solved: bool,
+    #[prost(string, tag = "3")]
     pub message: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "3")]
-    pub stopped_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(message, optional, tag = "4")]
+    pub solved_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct GetInstanceStatusRequest {
+pub struct InvalidateSolveRequest {
     #[prost(string, tag = "1")]
-    pub instance_id: ::prost::alloc::string::String,
+    pub challenge_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub team_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct InstanceStatus {
+pub struct User {
     #[prost(string, tag = "1")]
-    pub instance_id: ::prost::alloc::string::String,
+    pub id: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
-    pub challenge_id: ::prost::alloc::string::String,
-    #[prost(enumeration = "instance_status::State", tag = "3")]
-    pub state: i32,
-    #[prost(string, tag = "4")]
-    pub node: ::prost::alloc::string::String,
-    #[prost(string, tag = "5")]
-    pub message: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "6")]
-    pub last_heartbeat: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    pub name: ::prost::alloc::string::String,
+    /// Empty while the user is not in a team
+    #[prost(string, tag = "3")]
+    pub team_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub created_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(enumeration = "user::Role", tag = "5")]
+    pub role: i32,
 }
-/// Nested message and enum types in `InstanceStatus`.
-pub mod instance_status {
+/// Nested message and enum types in `User`.
+pub mod user {
+    /// What the user may do, see the daemon's permission table
     #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(
         Clone,
@@ -172,118 +277,258 @@ pub mod instance_status {
         ::prost::Enumeration
     )]
     #[repr(i32)]
-    pub enum State {
+    pub enum Role {
+        /// Treated as a player
         Unspecified = 0,
-        Starting = 1,
-        Running = 2,
-        Stopped = 3,
-        Failed = 4,
+        /// Plays in a team
+        Player = 1,
+        /// Writes challenges and manages the ones they own
+        Author = 2,
+        /// Runs the event
+        Admin = 3,
+        /// A runner agent, the user id being its agent_id
+        Agent = 4,
     }
-    impl State {
+    impl Role {
         /// String value of the enum field names used in the ProtoBuf definition.
         ///
         /// The values are not transformed in any way and thus are considered stable
         /// (if the ProtoBuf definition does not change) and safe for programmatic use.
         pub fn as_str_name(&self) -> &'static str {
             match self {
-                Self::Unspecified => "STATE_UNSPECIFIED",
-                Self::Starting => "STATE_STARTING",
-                Self::Running => "STATE_RUNNING",
-                Self::Stopped => "STATE_STOPPED",
-                Self::Failed => "STATE_FAILED",
+                Self::Unspecified => "ROLE_UNSPECIFIED",
+                Self::Player => "ROLE_PLAYER",
+                Self::Author => "ROLE_AUTHOR",
+                Self::Admin => "ROLE_ADMIN",
+                Self::Agent => "ROLE_AGENT",
             }
         }
         /// Creates an enum from field names used in the ProtoBuf definition.
         pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
             match value {
-                "STATE_UNSPECIFIED" => Some(Self::Unspecified),
-                "STATE_STARTING" => Some(Self::Starting),
-                "STATE_RUNNING" => Some(Self::Running),
-                "STATE_STOPPED" => Some(Self::Stopped),
-                "STATE_FAILED" => Some(Self::Failed),
+                "ROLE_UNSPECIFIED" => Some(Self::Unspecified),
+                "ROLE_PLAYER" => Some(Self::Player),
+                "ROLE_AUTHOR" => Some(Self::Author),
+                "ROLE_ADMIN" => Some(Self::Admin),
+                "ROLE_AGENT" => Some(Self::Agent),
                 _ => None,
             }
         }
     }
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Team {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub name: ::prost::alloc::string::String,
+    /// Empty once every member left
+    #[prost(string, tag = "3")]
+    pub captain_id: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "4")]
+    pub member_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "5")]
+    pub division: ::prost::alloc::string::String,
+    /// Lets users join the team, only returned when it is created or reset
+    #[prost(string, tag = "6")]
+    pub invite_code: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "7")]
+    pub created_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateUserRequest {
+    #[prost(message, optional, tag = "1")]
+    pub user: ::core::option::Option<User>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetUserRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Creates a team with `team.captain_id` as its captain and only member
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateTeamRequest {
+    #[prost(message, optional, tag = "1")]
+    pub team: ::core::option::Option<Team>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetTeamRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListTeamsRequest {
+    #[prost(int32, tag = "1")]
+    pub page_size: i32,
+    #[prost(string, tag = "2")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct RegisterAgentRequest {
+pub struct ListTeamsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub teams: ::prost::alloc::vec::Vec<Team>,
+    #[prost(string, tag = "2")]
+    pub next_page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct JoinTeamRequest {
     #[prost(string, tag = "1")]
-    pub agent_id: ::prost::alloc::string::String,
+    pub user_id: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
-    pub version: ::prost::alloc::string::String,
-    #[prost(string, repeated, tag = "3")]
-    pub capabilities: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
-    #[prost(map = "string, string", tag = "4")]
-    pub metadata: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
+    pub invite_code: ::prost::alloc::string::String,
 }
+/// Captains have to hand the team over before leaving it, unless they are
+/// the last member
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct RegisterAgentResponse {
-    #[prost(bool, tag = "1")]
-    pub accepted: bool,
+pub struct LeaveTeamRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct TransferCaptainRequest {
+    #[prost(string, tag = "1")]
+    pub team_id: ::prost::alloc::string::String,
+    /// A member of the team
     #[prost(string, tag = "2")]
-    pub message: ::prost::alloc::string::String,
+    pub user_id: ::prost::alloc::string::String,
 }
+/// Replaces the invite code, the old one stops working
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct Job {
+pub struct ResetInviteCodeRequest {
     #[prost(string, tag = "1")]
-    pub job_id: ::prost::alloc::string::String,
+    pub team_id: ::prost::alloc::string::String,
+}
+/// A long lived credential acting as a user. Only a hash of its secret is kept
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ApiToken {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
     #[prost(string, tag = "2")]
-    pub r#type: ::prost::alloc::string::String,
+    pub name: ::prost::alloc::string::String,
     #[prost(string, tag = "3")]
-    pub payload_json: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "4")]
+    pub user_id: ::prost::alloc::string::String,
+    /// What the token may do: `read`, `write` or `admin`, each implying the
+    /// ones before it
+    #[prost(string, repeated, tag = "4")]
+    pub scopes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "5")]
     pub created_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    /// Never expires when not set
+    #[prost(message, optional, tag = "6")]
+    pub expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(message, optional, tag = "7")]
+    pub revoked_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct AssignJobRequest {
+pub struct CreateTokenRequest {
     #[prost(string, tag = "1")]
-    pub agent_id: ::prost::alloc::string::String,
-    #[prost(int32, tag = "2")]
-    pub max_jobs: i32,
+    pub name: ::prost::alloc::string::String,
+    /// The caller when empty, only admins create tokens for others
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+    /// At most the caller's own scopes
+    #[prost(string, repeated, tag = "3")]
+    pub scopes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "4")]
+    pub expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateTokenResponse {
+    #[prost(message, optional, tag = "1")]
+    pub token: ::core::option::Option<ApiToken>,
+    /// The bearer token, it cannot be retrieved again
+    #[prost(string, tag = "2")]
+    pub secret: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListTokensRequest {
+    /// The caller when empty, only admins list the tokens of others
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct AssignJobResponse {
+pub struct ListTokensResponse {
     #[prost(message, repeated, tag = "1")]
-    pub jobs: ::prost::alloc::vec::Vec<Job>,
+    pub tokens: ::prost::alloc::vec::Vec<ApiToken>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct StreamEventsRequest {
+pub struct RevokeTokenRequest {
     #[prost(string, tag = "1")]
-    pub instance_id: ::prost::alloc::string::String,
-    #[prost(string, tag = "2")]
-    pub challenge_id: ::prost::alloc::string::String,
-    #[prost(string, tag = "3")]
-    pub level: ::prost::alloc::string::String,
+    pub id: ::prost::alloc::string::String,
 }
+/// Trades the caller's credential for a short lived session token with the
+/// same scopes, for browsers and other places a token should not outlive
 #[derive(serde::Serialize, serde::Deserialize)]
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Event {
-    #[prost(enumeration = "event::Type", tag = "1")]
-    pub r#type: i32,
-    #[prost(string, tag = "2")]
-    pub source: ::prost::alloc::string::String,
-    #[prost(string, tag = "3")]
-    pub message: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "4")]
-    pub time: ::core::option::Option<::prost_wkt_types::Timestamp>,
-    #[prost(map = "string, string", tag = "5")]
-    pub labels: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
+#[serde(default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateSessionRequest {
+    /// Capped to a day, an hour when 0
+    #[prost(int64, tag = "1")]
+    pub ttl_seconds: i64,
 }
-/// Nested message and enum types in `Event`.
-pub mod event {
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Session {
+    #[prost(string, tag = "1")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+/// How a challenge loses value as teams solve it. Every solver is awarded the
+/// current value, so earlier solvers lose points too.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Scoring {
+    #[prost(enumeration = "scoring::Function", tag = "1")]
+    pub function: i32,
+    #[prost(int32, tag = "2")]
+    pub initial: i32,
+    #[prost(int32, tag = "3")]
+    pub minimum: i32,
+    #[prost(int32, tag = "4")]
+    pub decay: i32,
+    /// Extra points for the first, second, ... solver, kept whatever the value
+    #[prost(int32, repeated, tag = "5")]
+    pub first_blood_bonus: ::prost::alloc::vec::Vec<i32>,
+}
+/// Nested message and enum types in `Scoring`.
+pub mod scoring {
     #[derive(serde::Serialize, serde::Deserialize)]
     #[derive(
         Clone,
@@ -297,239 +542,799 @@ pub mod event {
         ::prost::Enumeration
     )]
     #[repr(i32)]
-    pub enum Type {
+    pub enum Function {
+        /// The value is `Challenge.points`
         Unspecified = 0,
-        Log = 1,
-        Metric = 2,
-        State = 3,
-        Job = 4,
+        /// Loses `decay` points per solve
+        Linear = 1,
+        /// Drops along a parabola, reaching `minimum` after `decay` solves
+        Logarithmic = 2,
     }
-    impl Type {
+    impl Function {
         /// String value of the enum field names used in the ProtoBuf definition.
         ///
         /// The values are not transformed in any way and thus are considered stable
         /// (if the ProtoBuf definition does not change) and safe for programmatic use.
         pub fn as_str_name(&self) -> &'static str {
             match self {
-                Self::Unspecified => "TYPE_UNSPECIFIED",
-                Self::Log => "TYPE_LOG",
-                Self::Metric => "TYPE_METRIC",
-                Self::State => "TYPE_STATE",
-                Self::Job => "TYPE_JOB",
+                Self::Unspecified => "FUNCTION_UNSPECIFIED",
+                Self::Linear => "FUNCTION_LINEAR",
+                Self::Logarithmic => "FUNCTION_LOGARITHMIC",
             }
         }
         /// Creates an enum from field names used in the ProtoBuf definition.
         pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
             match value {
-                "TYPE_UNSPECIFIED" => Some(Self::Unspecified),
-                "TYPE_LOG" => Some(Self::Log),
-                "TYPE_METRIC" => Some(Self::Metric),
-                "TYPE_STATE" => Some(Self::State),
-                "TYPE_JOB" => Some(Self::Job),
+                "FUNCTION_UNSPECIFIED" => Some(Self::Unspecified),
+                "FUNCTION_LINEAR" => Some(Self::Linear),
+                "FUNCTION_LOGARITHMIC" => Some(Self::Logarithmic),
                 _ => None,
             }
         }
     }
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetScoreboardRequest {
+    /// Only rank teams of this division, every team when empty
+    #[prost(string, tag = "1")]
+    pub division: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub page_size: i32,
+    /// Pages are keyed by rank, teams moving between requests may be skipped
+    /// or seen twice
+    #[prost(string, tag = "3")]
+    pub page_token: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ScoreboardEntry {
+    #[prost(int32, tag = "1")]
+    pub rank: i32,
+    #[prost(string, tag = "2")]
+    pub team_id: ::prost::alloc::string::String,
+    #[prost(int64, tag = "3")]
+    pub score: i64,
+    #[prost(int32, tag = "4")]
+    pub solves: i32,
+    /// Ties on score go to the team that got there first
+    #[prost(message, optional, tag = "5")]
+    pub last_solve_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(string, tag = "6")]
+    pub division: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ChallengeValue {
+    #[prost(string, tag = "1")]
+    pub challenge_id: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub value: i64,
+    #[prost(int32, tag = "3")]
+    pub solves: i32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct Heartbeat {
+pub struct Scoreboard {
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<ScoreboardEntry>,
+    #[prost(message, repeated, tag = "2")]
+    pub challenges: ::prost::alloc::vec::Vec<ChallengeValue>,
+    #[prost(string, tag = "3")]
+    pub next_page_token: ::prost::alloc::string::String,
+    /// Set when the board is frozen, solves from then on are not shown
+    #[prost(message, optional, tag = "4")]
+    pub frozen_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct WatchScoreboardRequest {
     #[prost(string, tag = "1")]
-    pub agent_id: ::prost::alloc::string::String,
-    #[prost(message, optional, tag = "2")]
-    pub time: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    pub division: ::prost::alloc::string::String,
+}
+/// What changed since the previous delta, the first one of a stream holds the
+/// whole board
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScoreboardDelta {
+    /// Teams that are new to the board or whose entry changed
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<ScoreboardEntry>,
+    /// Teams that dropped off the board
+    #[prost(string, repeated, tag = "2")]
+    pub removed_team_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Challenges that are new or whose value changed
+    #[prost(message, repeated, tag = "3")]
+    pub challenges: ::prost::alloc::vec::Vec<ChallengeValue>,
+    #[prost(string, repeated, tag = "4")]
+    pub removed_challenge_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "5")]
+    pub frozen_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct FreezeScoreboardRequest {
+    /// Now when not set
+    #[prost(message, optional, tag = "1")]
+    pub freeze_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+/// Lifts the freeze, revealing the solves it hid
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct UnfreezeScoreboardRequest {}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct SetTeamDivisionRequest {
+    #[prost(string, tag = "1")]
+    pub team_id: ::prost::alloc::string::String,
+    /// Empty to take the team out of its division
+    #[prost(string, tag = "2")]
+    pub division: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StartInstanceRequest {
+    #[prost(string, tag = "1")]
+    pub challenge_id: ::prost::alloc::string::String,
+    /// The team the instance is started for
+    #[prost(string, tag = "2")]
+    pub team_id: ::prost::alloc::string::String,
     #[prost(map = "string, string", tag = "3")]
-    pub stats: ::std::collections::HashMap<
+    pub overrides: ::std::collections::HashMap<
         ::prost::alloc::string::String,
         ::prost::alloc::string::String,
     >,
+    /// Pins the instance to this agent, which still has to meet the
+    /// challenge's requirements. The least loaded eligible agent otherwise.
+    #[prost(string, tag = "4")]
+    pub agent_id: ::prost::alloc::string::String,
+    /// How long the instance runs before it is stopped, the daemon's default
+    /// when 0 and never longer than its maximum lifetime
+    #[prost(int64, tag = "5")]
+    pub ttl_seconds: i64,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
-pub struct JobResult {
+pub struct StartInstanceResponse {
     #[prost(string, tag = "1")]
-    pub job_id: ::prost::alloc::string::String,
-    #[prost(bool, tag = "2")]
-    pub success: bool,
+    pub instance_id: ::prost::alloc::string::String,
+    /// The agent the instance was placed on, empty while none is eligible
+    #[prost(string, tag = "2")]
+    pub node: ::prost::alloc::string::String,
+    /// Why no agent was eligible, when `node` is empty
     #[prost(string, tag = "3")]
     pub message: ::prost::alloc::string::String,
-    #[prost(string, tag = "4")]
-    pub result_json: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub started_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
     #[prost(message, optional, tag = "5")]
-    pub finished_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    pub expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
 }
 #[derive(serde::Serialize, serde::Deserialize)]
-#[derive(Clone, PartialEq, ::prost::Message)]
-pub struct AgentFrame {
-    #[prost(oneof = "agent_frame::Payload", tags = "1, 2, 3, 4, 5")]
-    pub payload: ::core::option::Option<agent_frame::Payload>,
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct StopInstanceRequest {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub force: bool,
 }
-/// Nested message and enum types in `AgentFrame`.
-pub mod agent_frame {
-    #[derive(serde::Serialize, serde::Deserialize)]
-    #[derive(Clone, PartialEq, ::prost::Oneof)]
-    pub enum Payload {
-        #[prost(message, tag = "1")]
-        Register(super::RegisterAgentRequest),
-        #[prost(message, tag = "2")]
-        Job(super::Job),
-        #[prost(message, tag = "3")]
-        JobResponse(super::AssignJobResponse),
-        #[prost(message, tag = "4")]
-        Heartbeat(super::Heartbeat),
-        #[prost(message, tag = "5")]
-        JobResult(super::JobResult),
-    }
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct StopInstanceResponse {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "3")]
+    pub stopped_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
 }
-/// Generated client implementations.
-pub mod service_ctfjx_client {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct GetInstanceStatusRequest {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct InstanceStatus {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub challenge_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "instance_status::State", tag = "3")]
+    pub state: i32,
+    #[prost(string, tag = "4")]
+    pub node: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "6")]
+    pub last_heartbeat: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    /// When the instance is stopped unless extended. A `level=warn` log event
+    /// labelled with the instance and its team is published a few minutes
+    /// before.
+    #[prost(message, optional, tag = "7")]
+    pub expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    /// Where the instance is reached on its node once running, 0 before or when
+    /// it serves nothing
+    #[prost(int32, tag = "8")]
+    pub port: i32,
+}
+/// Nested message and enum types in `InstanceStatus`.
+pub mod instance_status {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
     )]
-    use tonic::codegen::*;
-    use tonic::codegen::http::Uri;
-    #[derive(Debug, Clone)]
-    pub struct ServiceCtfjxClient<T> {
-        inner: tonic::client::Grpc<T>,
-    }
-    impl ServiceCtfjxClient<tonic::transport::Channel> {
-        /// Attempt to create a new client by connecting to a given endpoint.
-        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
-        where
-            D: TryInto<tonic::transport::Endpoint>,
-            D::Error: Into<StdError>,
-        {
-            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
-            Ok(Self::new(conn))
-        }
+    #[repr(i32)]
+    pub enum State {
+        Unspecified = 0,
+        Starting = 1,
+        Running = 2,
+        Stopped = 3,
+        Failed = 4,
     }
-    impl<T> ServiceCtfjxClient<T>
-    where
-        T: tonic::client::GrpcService<tonic::body::Body>,
-        T::Error: Into<StdError>,
-        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
-        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
-    {
-        pub fn new(inner: T) -> Self {
-            let inner = tonic::client::Grpc::new(inner);
-            Self { inner }
-        }
-        pub fn with_origin(inner: T, origin: Uri) -> Self {
-            let inner = tonic::client::Grpc::with_origin(inner, origin);
-            Self { inner }
-        }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> ServiceCtfjxClient<InterceptedService<T, F>>
-        where
-            F: tonic::service::Interceptor,
-            T::ResponseBody: Default,
-            T: tonic::codegen::Service<
-                http::Request<tonic::body::Body>,
-                Response = http::Response<
-                    <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
-                >,
-            >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::Body>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
-        {
-            ServiceCtfjxClient::new(InterceptedService::new(inner, interceptor))
-        }
-        /// Compress requests with the given encoding.
-        ///
-        /// This requires the server to support it otherwise it might respond with an
-        /// error.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.send_compressed(encoding);
-            self
-        }
-        /// Enable decompressing responses.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.accept_compressed(encoding);
-            self
-        }
-        /// Limits the maximum size of a decoded message.
+    impl State {
+        /// String value of the enum field names used in the ProtoBuf definition.
         ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_decoding_message_size(limit);
-            self
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Self::Unspecified => "STATE_UNSPECIFIED",
+                Self::Starting => "STATE_STARTING",
+                Self::Running => "STATE_RUNNING",
+                Self::Stopped => "STATE_STOPPED",
+                Self::Failed => "STATE_FAILED",
+            }
         }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_encoding_message_size(limit);
-            self
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "STATE_UNSPECIFIED" => Some(Self::Unspecified),
+                "STATE_STARTING" => Some(Self::Starting),
+                "STATE_RUNNING" => Some(Self::Running),
+                "STATE_STOPPED" => Some(Self::Stopped),
+                "STATE_FAILED" => Some(Self::Failed),
+                _ => None,
+            }
         }
-        pub async fn ping(
-            &mut self,
-            request: impl tonic::IntoRequest<super::PingRequest>,
-        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic_prost::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/Ping",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "Ping"));
-            self.inner.unary(req, path, codec).await
+    }
+}
+/// Pushes back when a running instance expires, up to the daemon's maximum
+/// lifetime counted from when the instance was started
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ExtendInstanceRequest {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    /// The daemon's default TTL when 0
+    #[prost(int64, tag = "2")]
+    pub seconds: i64,
+}
+/// A one-time token letting a single agent enroll
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct JoinToken {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// The agent enrolling with the token
+    #[prost(string, tag = "2")]
+    pub agent_id: ::prost::alloc::string::String,
+    /// The bearer join token, only returned on creation
+    #[prost(string, tag = "3")]
+    pub token: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub created_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    /// Set once an agent enrolled with it
+    #[prost(message, optional, tag = "6")]
+    pub used_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CreateJoinTokenRequest {
+    /// Generated when empty
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    /// An hour when 0
+    #[prost(int64, tag = "2")]
+    pub ttl_seconds: i64,
+}
+/// Binds an agent to a public key
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AgentCredential {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub agent_id: ::prost::alloc::string::String,
+    /// Raw ed25519 public key
+    #[prost(bytes = "vec", tag = "3")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "4")]
+    pub created_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub revoked_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EnrollAgentRequest {
+    #[prost(string, tag = "1")]
+    pub join_token: ::prost::alloc::string::String,
+    /// Raw ed25519 public key of the agent
+    #[prost(bytes = "vec", tag = "2")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct EnrollAgentResponse {
+    #[prost(message, optional, tag = "1")]
+    pub credential: ::core::option::Option<AgentCredential>,
+    /// Raw ed25519 public key of the daemon, to check `RegisterAgent` replies
+    #[prost(bytes = "vec", tag = "2")]
+    pub daemon_public_key: ::prost::alloc::vec::Vec<u8>,
+}
+/// Binds the calling agent to a new key, revoking the credential it called
+/// with
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RotateAgentKeyRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListAgentCredentialsRequest {
+    /// Every agent when empty
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAgentCredentialsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub credentials: ::prost::alloc::vec::Vec<AgentCredential>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RevokeAgentCredentialRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+/// Only accepted from the agent itself
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegisterAgentRequest {
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "3")]
+    pub capabilities: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(map = "string, string", tag = "4")]
+    pub metadata: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// Random bytes for the daemon to sign
+    #[prost(bytes = "vec", tag = "5")]
+    pub nonce: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RegisterAgentResponse {
+    #[prost(bool, tag = "1")]
+    pub accepted: bool,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    /// The daemon's ed25519 signature over the agent id and nonce
+    #[prost(bytes = "vec", tag = "3")]
+    pub daemon_signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Job {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub payload_json: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub created_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    /// Which attempt this is, starting at 1
+    #[prost(int32, tag = "5")]
+    pub attempt: i32,
+    /// When the job is handed out again unless a result was reported
+    #[prost(message, optional, tag = "6")]
+    pub lease_expires_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct AssignJobRequest {
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(int32, tag = "2")]
+    pub max_jobs: i32,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AssignJobResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub jobs: ::prost::alloc::vec::Vec<Job>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ReportJobResultRequest {
+    /// The agent holding the lease
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub result: ::core::option::Option<JobResult>,
+}
+/// A job that ran out of attempts
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct DeadJob {
+    #[prost(message, optional, tag = "1")]
+    pub job: ::core::option::Option<Job>,
+    /// The agent the job was meant for, empty for any
+    #[prost(string, tag = "2")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    pub attempts: i32,
+    /// Why the last attempt failed
+    #[prost(string, tag = "4")]
+    pub last_error: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "5")]
+    pub died_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ListDeadJobsRequest {
+    /// Every agent when empty
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListDeadJobsResponse {
+    /// Oldest first
+    #[prost(message, repeated, tag = "1")]
+    pub jobs: ::prost::alloc::vec::Vec<DeadJob>,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct StreamEventsRequest {
+    #[prost(string, tag = "1")]
+    pub instance_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub challenge_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub level: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Event {
+    #[prost(enumeration = "event::Type", tag = "1")]
+    pub r#type: i32,
+    #[prost(string, tag = "2")]
+    pub source: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub time: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    #[prost(map = "string, string", tag = "5")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+}
+/// Nested message and enum types in `Event`.
+pub mod event {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Type {
+        Unspecified = 0,
+        Log = 1,
+        Metric = 2,
+        State = 3,
+        Job = 4,
+    }
+    impl Type {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Self::Unspecified => "TYPE_UNSPECIFIED",
+                Self::Log => "TYPE_LOG",
+                Self::Metric => "TYPE_METRIC",
+                Self::State => "TYPE_STATE",
+                Self::Job => "TYPE_JOB",
+            }
         }
-        pub async fn create_challenge(
-            &mut self,
-            request: impl tonic::IntoRequest<super::CreateChallengeRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::CreateChallengeResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic_prost::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/CreateChallenge",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateChallenge"));
-            self.inner.unary(req, path, codec).await
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "TYPE_UNSPECIFIED" => Some(Self::Unspecified),
+                "TYPE_LOG" => Some(Self::Log),
+                "TYPE_METRIC" => Some(Self::Metric),
+                "TYPE_STATE" => Some(Self::State),
+                "TYPE_JOB" => Some(Self::Job),
+                _ => None,
+            }
         }
-        pub async fn get_challenge(
-            &mut self,
-            request: impl tonic::IntoRequest<super::GetChallengeRequest>,
-        ) -> std::result::Result<tonic::Response<super::Challenge>, tonic::Status> {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic_prost::ProstCodec::default();
+    }
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Heartbeat {
+    #[prost(string, tag = "1")]
+    pub agent_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub time: ::core::option::Option<::prost_wkt_types::Timestamp>,
+    /// `load`, a number, is what instances are balanced by, an agent not
+    /// reporting it counts as idle. Ties go to the agent running the fewest
+    /// instances.
+    #[prost(map = "string, string", tag = "3")]
+    pub stats: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+}
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct JobResult {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub success: bool,
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub result_json: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "5")]
+    pub finished_at: ::core::option::Option<::prost_wkt_types::Timestamp>,
+}
+/// On `AgentStream` the agent sends `register` first and is answered with
+/// `registered`. The daemon then pushes each `job` as soon as the agent may
+/// take it, and the agent sends `heartbeat`s and `job_result`s. Once the
+/// stream drops the agent counts as offline and its leased jobs are queued
+/// again.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AgentFrame {
+    #[prost(oneof = "agent_frame::Payload", tags = "1, 2, 3, 4, 5, 6")]
+    pub payload: ::core::option::Option<agent_frame::Payload>,
+}
+/// Nested message and enum types in `AgentFrame`.
+pub mod agent_frame {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Payload {
+        #[prost(message, tag = "1")]
+        Register(super::RegisterAgentRequest),
+        #[prost(message, tag = "2")]
+        Job(super::Job),
+        #[prost(message, tag = "3")]
+        JobResponse(super::AssignJobResponse),
+        #[prost(message, tag = "4")]
+        Heartbeat(super::Heartbeat),
+        #[prost(message, tag = "5")]
+        JobResult(super::JobResult),
+        #[prost(message, tag = "6")]
+        Registered(super::RegisterAgentResponse),
+    }
+}
+/// Generated client implementations.
+pub mod service_ctfjx_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct ServiceCtfjxClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl ServiceCtfjxClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> ServiceCtfjxClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::Body>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> ServiceCtfjxClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::Body>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::Body>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            ServiceCtfjxClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn ping(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PingRequest>,
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/Ping",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "Ping"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_challenge(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateChallengeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateChallengeResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/CreateChallenge",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateChallenge"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_challenge(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetChallengeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Challenge>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
                 "/ctfjx.v1.ServiceCtfjx/GetChallenge",
             );
@@ -604,13 +1409,10 @@ pub mod service_ctfjx_client {
                 .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ListChallenges"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn start_instance(
+        pub async fn create_user(
             &mut self,
-            request: impl tonic::IntoRequest<super::StartInstanceRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::StartInstanceResponse>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::CreateUserRequest>,
+        ) -> std::result::Result<tonic::Response<super::User>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -621,20 +1423,17 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/StartInstance",
+                "/ctfjx.v1.ServiceCtfjx/CreateUser",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "StartInstance"));
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateUser"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn stop_instance(
+        pub async fn get_user(
             &mut self,
-            request: impl tonic::IntoRequest<super::StopInstanceRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::StopInstanceResponse>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::GetUserRequest>,
+        ) -> std::result::Result<tonic::Response<super::User>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -645,17 +1444,17 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/StopInstance",
+                "/ctfjx.v1.ServiceCtfjx/GetUser",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "StopInstance"));
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "GetUser"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn get_instance_status(
+        pub async fn create_team(
             &mut self,
-            request: impl tonic::IntoRequest<super::GetInstanceStatusRequest>,
-        ) -> std::result::Result<tonic::Response<super::InstanceStatus>, tonic::Status> {
+            request: impl tonic::IntoRequest<super::CreateTeamRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -666,20 +1465,17 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/GetInstanceStatus",
+                "/ctfjx.v1.ServiceCtfjx/CreateTeam",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "GetInstanceStatus"));
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateTeam"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn register_agent(
+        pub async fn get_team(
             &mut self,
-            request: impl tonic::IntoRequest<super::RegisterAgentRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::RegisterAgentResponse>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::GetTeamRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -690,18 +1486,18 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/RegisterAgent",
+                "/ctfjx.v1.ServiceCtfjx/GetTeam",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "RegisterAgent"));
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "GetTeam"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn assign_job(
+        pub async fn list_teams(
             &mut self,
-            request: impl tonic::IntoRequest<super::AssignJobRequest>,
+            request: impl tonic::IntoRequest<super::ListTeamsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::AssignJobResponse>,
+            tonic::Response<super::ListTeamsResponse>,
             tonic::Status,
         > {
             self.inner
@@ -714,20 +1510,17 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/AssignJob",
+                "/ctfjx.v1.ServiceCtfjx/ListTeams",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "AssignJob"));
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ListTeams"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn stream_events(
+        pub async fn join_team(
             &mut self,
-            request: impl tonic::IntoRequest<super::StreamEventsRequest>,
-        ) -> std::result::Result<
-            tonic::Response<tonic::codec::Streaming<super::Event>>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::JoinTeamRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -738,20 +1531,17 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/StreamEvents",
+                "/ctfjx.v1.ServiceCtfjx/JoinTeam",
             );
             let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "StreamEvents"));
-            self.inner.server_streaming(req, path, codec).await
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "JoinTeam"));
+            self.inner.unary(req, path, codec).await
         }
-        pub async fn agent_stream(
+        pub async fn leave_team(
             &mut self,
-            request: impl tonic::IntoStreamingRequest<Message = super::AgentFrame>,
-        ) -> std::result::Result<
-            tonic::Response<tonic::codec::Streaming<super::AgentFrame>>,
-            tonic::Status,
-        > {
+            request: impl tonic::IntoRequest<super::LeaveTeamRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
             self.inner
                 .ready()
                 .await
@@ -762,210 +1552,2145 @@ pub mod service_ctfjx_client {
                 })?;
             let codec = tonic_prost::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/ctfjx.v1.ServiceCtfjx/AgentStream",
+                "/ctfjx.v1.ServiceCtfjx/LeaveTeam",
             );
-            let mut req = request.into_streaming_request();
+            let mut req = request.into_request();
             req.extensions_mut()
-                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "AgentStream"));
-            self.inner.streaming(req, path, codec).await
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "LeaveTeam"));
+            self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod service_ctfjx_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with ServiceCtfjxServer.
-    #[async_trait]
-    pub trait ServiceCtfjx: std::marker::Send + std::marker::Sync + 'static {
-        async fn ping(
-            &self,
-            request: tonic::Request<super::PingRequest>,
-        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
-        async fn create_challenge(
-            &self,
-            request: tonic::Request<super::CreateChallengeRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::CreateChallengeResponse>,
-            tonic::Status,
-        >;
-        async fn get_challenge(
-            &self,
-            request: tonic::Request<super::GetChallengeRequest>,
-        ) -> std::result::Result<tonic::Response<super::Challenge>, tonic::Status>;
-        async fn update_challenge(
-            &self,
-            request: tonic::Request<super::UpdateChallengeRequest>,
-        ) -> std::result::Result<tonic::Response<super::Challenge>, tonic::Status>;
-        async fn delete_challenge(
-            &self,
-            request: tonic::Request<super::DeleteChallengeRequest>,
-        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
-        async fn list_challenges(
-            &self,
-            request: tonic::Request<super::ListChallengesRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::ListChallengesResponse>,
-            tonic::Status,
-        >;
-        async fn start_instance(
-            &self,
-            request: tonic::Request<super::StartInstanceRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::StartInstanceResponse>,
-            tonic::Status,
-        >;
-        async fn stop_instance(
-            &self,
-            request: tonic::Request<super::StopInstanceRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::StopInstanceResponse>,
-            tonic::Status,
-        >;
-        async fn get_instance_status(
-            &self,
-            request: tonic::Request<super::GetInstanceStatusRequest>,
-        ) -> std::result::Result<tonic::Response<super::InstanceStatus>, tonic::Status>;
-        async fn register_agent(
-            &self,
-            request: tonic::Request<super::RegisterAgentRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::RegisterAgentResponse>,
-            tonic::Status,
-        >;
-        async fn assign_job(
-            &self,
-            request: tonic::Request<super::AssignJobRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::AssignJobResponse>,
-            tonic::Status,
-        >;
-        /// Server streaming response type for the StreamEvents method.
-        type StreamEventsStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::Event, tonic::Status>,
-            >
-            + std::marker::Send
-            + 'static;
-        async fn stream_events(
-            &self,
-            request: tonic::Request<super::StreamEventsRequest>,
+        pub async fn transfer_captain(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TransferCaptainRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/TransferCaptain",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "TransferCaptain"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn reset_invite_code(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ResetInviteCodeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/ResetInviteCode",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ResetInviteCode"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_token(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateTokenRequest>,
         ) -> std::result::Result<
-            tonic::Response<Self::StreamEventsStream>,
+            tonic::Response<super::CreateTokenResponse>,
             tonic::Status,
-        >;
-        /// Server streaming response type for the AgentStream method.
-        type AgentStreamStream: tonic::codegen::tokio_stream::Stream<
-                Item = std::result::Result<super::AgentFrame, tonic::Status>,
-            >
-            + std::marker::Send
-            + 'static;
-        async fn agent_stream(
-            &self,
-            request: tonic::Request<tonic::Streaming<super::AgentFrame>>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/CreateToken",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateToken"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_tokens(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTokensRequest>,
         ) -> std::result::Result<
-            tonic::Response<Self::AgentStreamStream>,
+            tonic::Response<super::ListTokensResponse>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct ServiceCtfjxServer<T> {
-        inner: Arc<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    impl<T> ServiceCtfjxServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
-        }
-        pub fn from_arc(inner: Arc<T>) -> Self {
-            Self {
-                inner,
-                accept_compression_encodings: Default::default(),
-                send_compression_encodings: Default::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            }
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/ListTokens",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ListTokens"));
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
-        where
-            F: tonic::service::Interceptor,
-        {
-            InterceptedService::new(Self::new(inner), interceptor)
+        pub async fn revoke_token(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeTokenRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/RevokeToken",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "RevokeToken"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Enable decompressing requests with the given encoding.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.accept_compression_encodings.enable(encoding);
-            self
+        pub async fn create_session(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateSessionRequest>,
+        ) -> std::result::Result<tonic::Response<super::Session>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/CreateSession",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateSession"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Compress responses with the given encoding, if the client supports it.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.send_compression_encodings.enable(encoding);
-            self
+        pub async fn submit_flag(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SubmitFlagRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SubmitFlagResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/SubmitFlag",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "SubmitFlag"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.max_decoding_message_size = Some(limit);
-            self
+        pub async fn invalidate_solve(
+            &mut self,
+            request: impl tonic::IntoRequest<super::InvalidateSolveRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/InvalidateSolve",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "InvalidateSolve"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.max_encoding_message_size = Some(limit);
-            self
+        pub async fn get_scoreboard(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetScoreboardRequest>,
+        ) -> std::result::Result<tonic::Response<super::Scoreboard>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/GetScoreboard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "GetScoreboard"));
+            self.inner.unary(req, path, codec).await
         }
-    }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for ServiceCtfjxServer<T>
-    where
-        T: ServiceCtfjx,
-        B: Body + std::marker::Send + 'static,
-        B::Error: Into<StdError> + std::marker::Send + 'static,
-    {
-        type Response = http::Response<tonic::body::Body>;
-        type Error = std::convert::Infallible;
-        type Future = BoxFuture<Self::Response, Self::Error>;
-        fn poll_ready(
+        pub async fn watch_scoreboard(
             &mut self,
-            _cx: &mut Context<'_>,
-        ) -> Poll<std::result::Result<(), Self::Error>> {
-            Poll::Ready(Ok(()))
+            request: impl tonic::IntoRequest<super::WatchScoreboardRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ScoreboardDelta>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/WatchScoreboard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "WatchScoreboard"));
+            self.inner.server_streaming(req, path, codec).await
         }
-        fn call(&mut self, req: http::Request<B>) -> Self::Future {
-            match req.uri().path() {
-                "/ctfjx.v1.ServiceCtfjx/Ping" => {
+        pub async fn freeze_scoreboard(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FreezeScoreboardRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/FreezeScoreboard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "FreezeScoreboard"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn unfreeze_scoreboard(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UnfreezeScoreboardRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/UnfreezeScoreboard",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "UnfreezeScoreboard"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn set_team_division(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetTeamDivisionRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/SetTeamDivision",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "SetTeamDivision"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn start_instance(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StartInstanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StartInstanceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/StartInstance",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "StartInstance"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stop_instance(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StopInstanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StopInstanceResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/StopInstance",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "StopInstance"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_instance_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetInstanceStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::InstanceStatus>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/GetInstanceStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "GetInstanceStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn extend_instance(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExtendInstanceRequest>,
+        ) -> std::result::Result<tonic::Response<super::InstanceStatus>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/ExtendInstance",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ExtendInstance"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_join_token(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateJoinTokenRequest>,
+        ) -> std::result::Result<tonic::Response<super::JoinToken>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/CreateJoinToken",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "CreateJoinToken"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn enroll_agent(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EnrollAgentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EnrollAgentResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/EnrollAgent",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "EnrollAgent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn rotate_agent_key(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RotateAgentKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EnrollAgentResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/RotateAgentKey",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "RotateAgentKey"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_agent_credentials(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListAgentCredentialsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListAgentCredentialsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/ListAgentCredentials",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ListAgentCredentials"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn revoke_agent_credential(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RevokeAgentCredentialRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/RevokeAgentCredential",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "RevokeAgentCredential"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn register_agent(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RegisterAgentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterAgentResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/RegisterAgent",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "RegisterAgent"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn assign_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AssignJobRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AssignJobResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/AssignJob",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "AssignJob"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn report_job_result(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReportJobResultRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/ReportJobResult",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ReportJobResult"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn send_heartbeat(
+            &mut self,
+            request: impl tonic::IntoRequest<super::Heartbeat>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/SendHeartbeat",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "SendHeartbeat"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn list_dead_jobs(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListDeadJobsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListDeadJobsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/ListDeadJobs",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "ListDeadJobs"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn stream_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::StreamEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::Event>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/StreamEvents",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "StreamEvents"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn agent_stream(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::AgentFrame>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::AgentFrame>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/ctfjx.v1.ServiceCtfjx/AgentStream",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("ctfjx.v1.ServiceCtfjx", "AgentStream"));
+            self.inner.streaming(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod service_ctfjx_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ServiceCtfjxServer.
+    #[async_trait]
+    pub trait ServiceCtfjx: std::marker::Send + std::marker::Sync + 'static {
+        async fn ping(
+            &self,
+            request: tonic::Request<super::PingRequest>,
+        ) -> std::result::Result<tonic::Response<super::PingResponse>, tonic::Status>;
+        async fn create_challenge(
+            &self,
+            request: tonic::Request<super::CreateChallengeRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateChallengeResponse>,
+            tonic::Status,
+        >;
+        async fn get_challenge(
+            &self,
+            request: tonic::Request<super::GetChallengeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Challenge>, tonic::Status>;
+        async fn update_challenge(
+            &self,
+            request: tonic::Request<super::UpdateChallengeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Challenge>, tonic::Status>;
+        async fn delete_challenge(
+            &self,
+            request: tonic::Request<super::DeleteChallengeRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn list_challenges(
+            &self,
+            request: tonic::Request<super::ListChallengesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListChallengesResponse>,
+            tonic::Status,
+        >;
+        async fn create_user(
+            &self,
+            request: tonic::Request<super::CreateUserRequest>,
+        ) -> std::result::Result<tonic::Response<super::User>, tonic::Status>;
+        async fn get_user(
+            &self,
+            request: tonic::Request<super::GetUserRequest>,
+        ) -> std::result::Result<tonic::Response<super::User>, tonic::Status>;
+        async fn create_team(
+            &self,
+            request: tonic::Request<super::CreateTeamRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status>;
+        async fn get_team(
+            &self,
+            request: tonic::Request<super::GetTeamRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status>;
+        async fn list_teams(
+            &self,
+            request: tonic::Request<super::ListTeamsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTeamsResponse>,
+            tonic::Status,
+        >;
+        async fn join_team(
+            &self,
+            request: tonic::Request<super::JoinTeamRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status>;
+        async fn leave_team(
+            &self,
+            request: tonic::Request<super::LeaveTeamRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn transfer_captain(
+            &self,
+            request: tonic::Request<super::TransferCaptainRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status>;
+        async fn reset_invite_code(
+            &self,
+            request: tonic::Request<super::ResetInviteCodeRequest>,
+        ) -> std::result::Result<tonic::Response<super::Team>, tonic::Status>;
+        async fn create_token(
+            &self,
+            request: tonic::Request<super::CreateTokenRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateTokenResponse>,
+            tonic::Status,
+        >;
+        async fn list_tokens(
+            &self,
+            request: tonic::Request<super::ListTokensRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTokensResponse>,
+            tonic::Status,
+        >;
+        async fn revoke_token(
+            &self,
+            request: tonic::Request<super::RevokeTokenRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn create_session(
+            &self,
+            request: tonic::Request<super::CreateSessionRequest>,
+        ) -> std::result::Result<tonic::Response<super::Session>, tonic::Status>;
+        async fn submit_flag(
+            &self,
+            request: tonic::Request<super::SubmitFlagRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::SubmitFlagResponse>,
+            tonic::Status,
+        >;
+        async fn invalidate_solve(
+            &self,
+            request: tonic::Request<super::InvalidateSolveRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn get_scoreboard(
+            &self,
+            request: tonic::Request<super::GetScoreboardRequest>,
+        ) -> std::result::Result<tonic::Response<super::Scoreboard>, tonic::Status>;
+        /// Server streaming response type for the WatchScoreboard method.
+        type WatchScoreboardStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ScoreboardDelta, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn watch_scoreboard(
+            &self,
+            request: tonic::Request<super::WatchScoreboardRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::WatchScoreboardStream>,
+            tonic::Status,
+        >;
+        async fn freeze_scoreboard(
+            &self,
+            request: tonic::Request<super::FreezeScoreboardRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn unfreeze_scoreboard(
+            &self,
+            request: tonic::Request<super::UnfreezeScoreboardRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn set_team_division(
+            &self,
+            request: tonic::Request<super::SetTeamDivisionRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn start_instance(
+            &self,
+            request: tonic::Request<super::StartInstanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StartInstanceResponse>,
+            tonic::Status,
+        >;
+        async fn stop_instance(
+            &self,
+            request: tonic::Request<super::StopInstanceRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::StopInstanceResponse>,
+            tonic::Status,
+        >;
+        async fn get_instance_status(
+            &self,
+            request: tonic::Request<super::GetInstanceStatusRequest>,
+        ) -> std::result::Result<tonic::Response<super::InstanceStatus>, tonic::Status>;
+        async fn extend_instance(
+            &self,
+            request: tonic::Request<super::ExtendInstanceRequest>,
+        ) -> std::result::Result<tonic::Response<super::InstanceStatus>, tonic::Status>;
+        async fn create_join_token(
+            &self,
+            request: tonic::Request<super::CreateJoinTokenRequest>,
+        ) -> std::result::Result<tonic::Response<super::JoinToken>, tonic::Status>;
+        async fn enroll_agent(
+            &self,
+            request: tonic::Request<super::EnrollAgentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EnrollAgentResponse>,
+            tonic::Status,
+        >;
+        async fn rotate_agent_key(
+            &self,
+            request: tonic::Request<super::RotateAgentKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EnrollAgentResponse>,
+            tonic::Status,
+        >;
+        async fn list_agent_credentials(
+            &self,
+            request: tonic::Request<super::ListAgentCredentialsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListAgentCredentialsResponse>,
+            tonic::Status,
+        >;
+        async fn revoke_agent_credential(
+            &self,
+            request: tonic::Request<super::RevokeAgentCredentialRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn register_agent(
+            &self,
+            request: tonic::Request<super::RegisterAgentRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RegisterAgentResponse>,
+            tonic::Status,
+        >;
+        async fn assign_job(
+            &self,
+            request: tonic::Request<super::AssignJobRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AssignJobResponse>,
+            tonic::Status,
+        >;
+        async fn report_job_result(
+            &self,
+            request: tonic::Request<super::ReportJobResultRequest>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn send_heartbeat(
+            &self,
+            request: tonic::Request<super::Heartbeat>,
+        ) -> std::result::Result<tonic::Response<()>, tonic::Status>;
+        async fn list_dead_jobs(
+            &self,
+            request: tonic::Request<super::ListDeadJobsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListDeadJobsResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the StreamEvents method.
+        type StreamEventsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::Event, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn stream_events(
+            &self,
+            request: tonic::Request<super::StreamEventsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::StreamEventsStream>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the AgentStream method.
+        type AgentStreamStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::AgentFrame, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn agent_stream(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::AgentFrame>>,
+        ) -> std::result::Result<
+            tonic::Response<Self::AgentStreamStream>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct ServiceCtfjxServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> ServiceCtfjxServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for ServiceCtfjxServer<T>
+    where
+        T: ServiceCtfjx,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/ctfjx.v1.ServiceCtfjx/Ping" => {
+                    #[allow(non_camel_case_types)]
+                    struct PingSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<T: ServiceCtfjx> tonic::server::UnaryService<super::PingRequest>
+                    for PingSvc<T> {
+                        type Response = super::PingResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PingRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::ping(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PingSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/CreateChallenge" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::CreateChallengeRequest>
+                    for CreateChallengeSvc<T> {
+                        type Response = super::CreateChallengeResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateChallengeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::create_challenge(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateChallengeSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/GetChallenge" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::GetChallengeRequest>
+                    for GetChallengeSvc<T> {
+                        type Response = super::Challenge;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetChallengeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::get_challenge(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetChallengeSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/UpdateChallenge" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::UpdateChallengeRequest>
+                    for UpdateChallengeSvc<T> {
+                        type Response = super::Challenge;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateChallengeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::update_challenge(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = UpdateChallengeSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/DeleteChallenge" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::DeleteChallengeRequest>
+                    for DeleteChallengeSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteChallengeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::delete_challenge(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = DeleteChallengeSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/ListChallenges" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListChallengesSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::ListChallengesRequest>
+                    for ListChallengesSvc<T> {
+                        type Response = super::ListChallengesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListChallengesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::list_challenges(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListChallengesSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/CreateUser" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateUserSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::CreateUserRequest>
+                    for CreateUserSvc<T> {
+                        type Response = super::User;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateUserRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::create_user(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateUserSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/GetUser" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetUserSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::GetUserRequest>
+                    for GetUserSvc<T> {
+                        type Response = super::User;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetUserRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::get_user(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetUserSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/CreateTeam" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateTeamSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::CreateTeamRequest>
+                    for CreateTeamSvc<T> {
+                        type Response = super::Team;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateTeamRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::create_team(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateTeamSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/GetTeam" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetTeamSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::GetTeamRequest>
+                    for GetTeamSvc<T> {
+                        type Response = super::Team;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetTeamRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::get_team(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetTeamSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/ListTeams" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListTeamsSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::ListTeamsRequest>
+                    for ListTeamsSvc<T> {
+                        type Response = super::ListTeamsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListTeamsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::list_teams(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListTeamsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/JoinTeam" => {
+                    #[allow(non_camel_case_types)]
+                    struct JoinTeamSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::JoinTeamRequest>
+                    for JoinTeamSvc<T> {
+                        type Response = super::Team;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JoinTeamRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::join_team(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = JoinTeamSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/LeaveTeam" => {
+                    #[allow(non_camel_case_types)]
+                    struct LeaveTeamSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::LeaveTeamRequest>
+                    for LeaveTeamSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LeaveTeamRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::leave_team(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LeaveTeamSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/TransferCaptain" => {
+                    #[allow(non_camel_case_types)]
+                    struct TransferCaptainSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::TransferCaptainRequest>
+                    for TransferCaptainSvc<T> {
+                        type Response = super::Team;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TransferCaptainRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::transfer_captain(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = TransferCaptainSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/ResetInviteCode" => {
+                    #[allow(non_camel_case_types)]
+                    struct ResetInviteCodeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::ResetInviteCodeRequest>
+                    for ResetInviteCodeSvc<T> {
+                        type Response = super::Team;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResetInviteCodeRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::reset_invite_code(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ResetInviteCodeSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/CreateToken" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateTokenSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::CreateTokenRequest>
+                    for CreateTokenSvc<T> {
+                        type Response = super::CreateTokenResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateTokenRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::create_token(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateTokenSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/ListTokens" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListTokensSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::ListTokensRequest>
+                    for ListTokensSvc<T> {
+                        type Response = super::ListTokensResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListTokensRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::list_tokens(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListTokensSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/RevokeToken" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeTokenSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::RevokeTokenRequest>
+                    for RevokeTokenSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeTokenRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::revoke_token(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeTokenSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/CreateSession" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateSessionSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::CreateSessionRequest>
+                    for CreateSessionSvc<T> {
+                        type Response = super::Session;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateSessionRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::create_session(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateSessionSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/SubmitFlag" => {
+                    #[allow(non_camel_case_types)]
+                    struct SubmitFlagSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::SubmitFlagRequest>
+                    for SubmitFlagSvc<T> {
+                        type Response = super::SubmitFlagResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SubmitFlagRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::submit_flag(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SubmitFlagSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/InvalidateSolve" => {
+                    #[allow(non_camel_case_types)]
+                    struct InvalidateSolveSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::InvalidateSolveRequest>
+                    for InvalidateSolveSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::InvalidateSolveRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::invalidate_solve(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = InvalidateSolveSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/GetScoreboard" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetScoreboardSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::GetScoreboardRequest>
+                    for GetScoreboardSvc<T> {
+                        type Response = super::Scoreboard;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetScoreboardRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::get_scoreboard(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetScoreboardSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/WatchScoreboard" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchScoreboardSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::ServerStreamingService<
+                        super::WatchScoreboardRequest,
+                    > for WatchScoreboardSvc<T> {
+                        type Response = super::ScoreboardDelta;
+                        type ResponseStream = T::WatchScoreboardStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchScoreboardRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::watch_scoreboard(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchScoreboardSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/FreezeScoreboard" => {
+                    #[allow(non_camel_case_types)]
+                    struct FreezeScoreboardSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::FreezeScoreboardRequest>
+                    for FreezeScoreboardSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FreezeScoreboardRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::freeze_scoreboard(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = FreezeScoreboardSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/UnfreezeScoreboard" => {
+                    #[allow(non_camel_case_types)]
+                    struct UnfreezeScoreboardSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::UnfreezeScoreboardRequest>
+                    for UnfreezeScoreboardSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UnfreezeScoreboardRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::unfreeze_scoreboard(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = UnfreezeScoreboardSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/SetTeamDivision" => {
                     #[allow(non_camel_case_types)]
-                    struct PingSvc<T: ServiceCtfjx>(pub Arc<T>);
-                    impl<T: ServiceCtfjx> tonic::server::UnaryService<super::PingRequest>
-                    for PingSvc<T> {
-                        type Response = super::PingResponse;
+                    struct SetTeamDivisionSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::SetTeamDivisionRequest>
+                    for SetTeamDivisionSvc<T> {
+                        type Response = ();
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::PingRequest>,
+                            request: tonic::Request<super::SetTeamDivisionRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::ping(&inner, request).await
+                                <T as ServiceCtfjx>::set_team_division(&inner, request)
+                                    .await
                             };
                             Box::pin(fut)
                         }
@@ -976,7 +3701,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = PingSvc(inner);
+                        let method = SetTeamDivisionSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -992,25 +3717,25 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/CreateChallenge" => {
+                "/ctfjx.v1.ServiceCtfjx/StartInstance" => {
                     #[allow(non_camel_case_types)]
-                    struct CreateChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct StartInstanceSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::CreateChallengeRequest>
-                    for CreateChallengeSvc<T> {
-                        type Response = super::CreateChallengeResponse;
+                    > tonic::server::UnaryService<super::StartInstanceRequest>
+                    for StartInstanceSvc<T> {
+                        type Response = super::StartInstanceResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CreateChallengeRequest>,
+                            request: tonic::Request<super::StartInstanceRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::create_challenge(&inner, request).await
+                                <T as ServiceCtfjx>::start_instance(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1021,7 +3746,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = CreateChallengeSvc(inner);
+                        let method = StartInstanceSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1037,25 +3762,25 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/GetChallenge" => {
+                "/ctfjx.v1.ServiceCtfjx/StopInstance" => {
                     #[allow(non_camel_case_types)]
-                    struct GetChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct StopInstanceSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::GetChallengeRequest>
-                    for GetChallengeSvc<T> {
-                        type Response = super::Challenge;
+                    > tonic::server::UnaryService<super::StopInstanceRequest>
+                    for StopInstanceSvc<T> {
+                        type Response = super::StopInstanceResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetChallengeRequest>,
+                            request: tonic::Request<super::StopInstanceRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::get_challenge(&inner, request).await
+                                <T as ServiceCtfjx>::stop_instance(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1066,7 +3791,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetChallengeSvc(inner);
+                        let method = StopInstanceSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1082,25 +3807,26 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/UpdateChallenge" => {
+                "/ctfjx.v1.ServiceCtfjx/GetInstanceStatus" => {
                     #[allow(non_camel_case_types)]
-                    struct UpdateChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct GetInstanceStatusSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::UpdateChallengeRequest>
-                    for UpdateChallengeSvc<T> {
-                        type Response = super::Challenge;
+                    > tonic::server::UnaryService<super::GetInstanceStatusRequest>
+                    for GetInstanceStatusSvc<T> {
+                        type Response = super::InstanceStatus;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::UpdateChallengeRequest>,
+                            request: tonic::Request<super::GetInstanceStatusRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::update_challenge(&inner, request).await
+                                <T as ServiceCtfjx>::get_instance_status(&inner, request)
+                                    .await
                             };
                             Box::pin(fut)
                         }
@@ -1111,7 +3837,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = UpdateChallengeSvc(inner);
+                        let method = GetInstanceStatusSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1127,25 +3853,25 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/DeleteChallenge" => {
+                "/ctfjx.v1.ServiceCtfjx/ExtendInstance" => {
                     #[allow(non_camel_case_types)]
-                    struct DeleteChallengeSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct ExtendInstanceSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::DeleteChallengeRequest>
-                    for DeleteChallengeSvc<T> {
-                        type Response = ();
+                    > tonic::server::UnaryService<super::ExtendInstanceRequest>
+                    for ExtendInstanceSvc<T> {
+                        type Response = super::InstanceStatus;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteChallengeRequest>,
+                            request: tonic::Request<super::ExtendInstanceRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::delete_challenge(&inner, request).await
+                                <T as ServiceCtfjx>::extend_instance(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1156,7 +3882,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = DeleteChallengeSvc(inner);
+                        let method = ExtendInstanceSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1172,25 +3898,26 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/ListChallenges" => {
+                "/ctfjx.v1.ServiceCtfjx/CreateJoinToken" => {
                     #[allow(non_camel_case_types)]
-                    struct ListChallengesSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct CreateJoinTokenSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::ListChallengesRequest>
-                    for ListChallengesSvc<T> {
-                        type Response = super::ListChallengesResponse;
+                    > tonic::server::UnaryService<super::CreateJoinTokenRequest>
+                    for CreateJoinTokenSvc<T> {
+                        type Response = super::JoinToken;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListChallengesRequest>,
+                            request: tonic::Request<super::CreateJoinTokenRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::list_challenges(&inner, request).await
+                                <T as ServiceCtfjx>::create_join_token(&inner, request)
+                                    .await
                             };
                             Box::pin(fut)
                         }
@@ -1201,7 +3928,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = ListChallengesSvc(inner);
+                        let method = CreateJoinTokenSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1217,25 +3944,25 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/StartInstance" => {
+                "/ctfjx.v1.ServiceCtfjx/EnrollAgent" => {
                     #[allow(non_camel_case_types)]
-                    struct StartInstanceSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct EnrollAgentSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::StartInstanceRequest>
-                    for StartInstanceSvc<T> {
-                        type Response = super::StartInstanceResponse;
+                    > tonic::server::UnaryService<super::EnrollAgentRequest>
+                    for EnrollAgentSvc<T> {
+                        type Response = super::EnrollAgentResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::StartInstanceRequest>,
+                            request: tonic::Request<super::EnrollAgentRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::start_instance(&inner, request).await
+                                <T as ServiceCtfjx>::enroll_agent(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1246,7 +3973,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StartInstanceSvc(inner);
+                        let method = EnrollAgentSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1262,25 +3989,25 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/StopInstance" => {
+                "/ctfjx.v1.ServiceCtfjx/RotateAgentKey" => {
                     #[allow(non_camel_case_types)]
-                    struct StopInstanceSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct RotateAgentKeySvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::StopInstanceRequest>
-                    for StopInstanceSvc<T> {
-                        type Response = super::StopInstanceResponse;
+                    > tonic::server::UnaryService<super::RotateAgentKeyRequest>
+                    for RotateAgentKeySvc<T> {
+                        type Response = super::EnrollAgentResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::StopInstanceRequest>,
+                            request: tonic::Request<super::RotateAgentKeyRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::stop_instance(&inner, request).await
+                                <T as ServiceCtfjx>::rotate_agent_key(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -1291,7 +4018,7 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = StopInstanceSvc(inner);
+                        let method = RotateAgentKeySvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1307,25 +4034,25 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
-                "/ctfjx.v1.ServiceCtfjx/GetInstanceStatus" => {
+                "/ctfjx.v1.ServiceCtfjx/ListAgentCredentials" => {
                     #[allow(non_camel_case_types)]
-                    struct GetInstanceStatusSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    struct ListAgentCredentialsSvc<T: ServiceCtfjx>(pub Arc<T>);
                     impl<
                         T: ServiceCtfjx,
-                    > tonic::server::UnaryService<super::GetInstanceStatusRequest>
-                    for GetInstanceStatusSvc<T> {
-                        type Response = super::InstanceStatus;
+                    > tonic::server::UnaryService<super::ListAgentCredentialsRequest>
+                    for ListAgentCredentialsSvc<T> {
+                        type Response = super::ListAgentCredentialsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetInstanceStatusRequest>,
+                            request: tonic::Request<super::ListAgentCredentialsRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as ServiceCtfjx>::get_instance_status(&inner, request)
+                                <T as ServiceCtfjx>::list_agent_credentials(&inner, request)
                                     .await
                             };
                             Box::pin(fut)
@@ -1337,7 +4064,56 @@ pub mod service_ctfjx_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetInstanceStatusSvc(inner);
+                        let method = ListAgentCredentialsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/RevokeAgentCredential" => {
+                    #[allow(non_camel_case_types)]
+                    struct RevokeAgentCredentialSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::RevokeAgentCredentialRequest>
+                    for RevokeAgentCredentialSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RevokeAgentCredentialRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::revoke_agent_credential(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RevokeAgentCredentialSvc(inner);
                         let codec = tonic_prost::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1443,6 +4219,140 @@ pub mod service_ctfjx_server {
                     };
                     Box::pin(fut)
                 }
+                "/ctfjx.v1.ServiceCtfjx/ReportJobResult" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReportJobResultSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::ReportJobResultRequest>
+                    for ReportJobResultSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReportJobResultRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::report_job_result(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ReportJobResultSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/SendHeartbeat" => {
+                    #[allow(non_camel_case_types)]
+                    struct SendHeartbeatSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<T: ServiceCtfjx> tonic::server::UnaryService<super::Heartbeat>
+                    for SendHeartbeatSvc<T> {
+                        type Response = ();
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::Heartbeat>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::send_heartbeat(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = SendHeartbeatSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/ctfjx.v1.ServiceCtfjx/ListDeadJobs" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListDeadJobsSvc<T: ServiceCtfjx>(pub Arc<T>);
+                    impl<
+                        T: ServiceCtfjx,
+                    > tonic::server::UnaryService<super::ListDeadJobsRequest>
+                    for ListDeadJobsSvc<T> {
+                        type Response = super::ListDeadJobsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListDeadJobsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ServiceCtfjx>::list_dead_jobs(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListDeadJobsSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/ctfjx.v1.ServiceCtfjx/StreamEvents" => {
                     #[allow(non_camel_case_types)]
                     struct StreamEventsSvc<T: ServiceCtfjx>(pub Arc<T>);
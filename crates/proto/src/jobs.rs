@@ -0,0 +1,48 @@
+//! Jobs the daemon hands to agents, carried as JSON in `Job.payload_json`,
+//! and the stats agents report back with their heartbeats.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grpc::ChallengeRuntime;
+
+pub const JOB_INSTANCE_START: &str = "instance.start";
+pub const JOB_INSTANCE_STOP: &str = "instance.stop";
+
+/// Heartbeat stat the daemon balances instances by, the 1 minute load
+/// average per cpu
+pub const STAT_LOAD: &str = "load";
+pub const STAT_CPUS: &str = "cpus";
+/// Jobs the agent is executing
+pub const STAT_JOBS: &str = "jobs";
+pub const STAT_MEM_AVAILABLE: &str = "mem_available_bytes";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartInstancePayload {
+    pub instance_id: String,
+    pub challenge_id: String,
+    pub team_id: String,
+    pub overrides: HashMap<String, String>,
+    /// The team's dynamic flag, for the runtime to hand to the instance as
+    /// the `FLAG` environment variable and a `flag.txt` file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flag: Option<String>,
+    /// What to run, the challenge's runtime when the job was queued
+    #[serde(default)]
+    pub runtime: ChallengeRuntime,
+}
+
+/// What a successful start job reports back as its result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartInstanceResult {
+    /// Where the instance is reached on the agent's host, 0 when it serves
+    /// nothing
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StopInstancePayload {
+    pub instance_id: String,
+    pub force: bool,
+}
@@ -1,3 +1,5 @@
 pub mod grpc {
     include!("ctfjx.v1.rs");
 }
+
+pub mod jobs;
@@ -0,0 +1,38 @@
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("{0} `{1}` not found")]
+    NotFound(&'static str, String),
+    #[error("{0} `{1}` already exists")]
+    AlreadyExists(&'static str, String),
+    /// The record changed since the caller read it
+    #[error("{0} `{1}` was modified concurrently")]
+    Conflict(&'static str, String),
+    /// A transaction body failed with an error of its own
+    #[error("transaction aborted")]
+    Aborted,
+    #[error("sqlite: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("serde: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("migration {version}: {source}")]
+    Migration {
+        version: usize,
+        source: rusqlite::Error,
+    },
+}
+
+#[cfg(feature = "tonic")]
+impl From<StorageError> for tonic::Status {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::NotFound(..) => tonic::Status::not_found(e.to_string()),
+            StorageError::AlreadyExists(..) => tonic::Status::already_exists(e.to_string()),
+            StorageError::Conflict(..) => tonic::Status::aborted(e.to_string()),
+            _ => tonic::Status::internal(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! Filter expressions for listing challenges.
+//!
+//! ```text
+//! expr       = or
+//! or         = and { "OR" and }
+//! and        = unary { "AND" unary }
+//! unary      = "NOT" unary | primary
+//! primary    = "(" expr ")" | comparison
+//! comparison = field ( "=" | "!=" | ":" ) value
+//! field      = "id" | "name" | "description" | "owner" | "difficulty"
+//!            | "tags" | "metadata" | "metadata." key
+//! value      = '"' { char | '\"' | '\\' } '"' | word
+//! ```
+//!
+//! `=` and `!=` compare exactly. `:` is a case insensitive "has": a substring
+//! for text fields, a tag for `tags`, a key for `metadata`, e.g.
+//! `difficulty = "hard" AND tags:"web" AND metadata.author = "x"`.
+
+use std::{fmt, iter::Peekable, str::CharIndices};
+
+use ctfjx_proto::grpc::Challenge;
+use thiserror::Error;
+
+/// How deep parentheses and `NOT`s may nest, deeper filters are rejected
+/// rather than recursed into
+const MAX_DEPTH: usize = 64;
+
+/// Why a filter did not parse
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("column {column}: {message}")]
+pub struct FilterError {
+    /// Byte offset into the input it failed at
+    pub offset: usize,
+    /// 1-based position of `offset` in characters, as a user counts it
+    pub column: usize,
+    pub message: String,
+}
+
+impl FilterError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            column: 0,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Compare { field: Field, op: Op, value: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Name,
+    Description,
+    Owner,
+    Difficulty,
+    Tags,
+    /// All of `metadata`, or a single key of it
+    Metadata(Option<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Has,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Has => ":",
+        })
+    }
+}
+
+impl Filter {
+    /// Parses `input`, an empty or blank input is no filter at all
+    pub fn parse(input: &str) -> Result<Option<Filter>, FilterError> {
+        Self::parse_tokens(input).map_err(|mut e| {
+            e.column = input[..e.offset].chars().count() + 1;
+            e
+        })
+    }
+
+    fn parse_tokens(input: &str) -> Result<Option<Filter>, FilterError> {
+        let tokens = lex(input)?;
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: input.len(),
+        };
+        let filter = parser.or(0)?;
+        match parser.peek() {
+            None => Ok(Some(filter)),
+            Some(tok) => Err(FilterError::new(
+                tok.offset,
+                format!("unexpected {}", tok.kind),
+            )),
+        }
+    }
+
+    pub fn matches(&self, challenge: &Challenge) -> bool {
+        match self {
+            Filter::And(a, b) => a.matches(challenge) && b.matches(challenge),
+            Filter::Or(a, b) => a.matches(challenge) || b.matches(challenge),
+            Filter::Not(f) => !f.matches(challenge),
+            Filter::Compare { field, op, value } => compare(challenge, field, *op, value),
+        }
+    }
+}
+
+fn has(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn compare(challenge: &Challenge, field: &Field, op: Op, value: &str) -> bool {
+    let text = match field {
+        Field::Id => &challenge.id,
+        Field::Name => &challenge.name,
+        Field::Description => &challenge.description,
+        Field::Owner => &challenge.owner,
+        Field::Difficulty => &challenge.difficulty,
+        Field::Tags => {
+            let tagged = match op {
+                Op::Has => challenge.tags.iter().any(|t| t.eq_ignore_ascii_case(value)),
+                Op::Eq | Op::Ne => challenge.tags.iter().any(|t| t == value),
+            };
+            return tagged != (op == Op::Ne);
+        }
+        Field::Metadata(None) => {
+            let present = challenge
+                .metadata
+                .keys()
+                .any(|k| k.eq_ignore_ascii_case(value));
+            return present != (op == Op::Ne);
+        }
+        Field::Metadata(Some(key)) => match challenge.metadata.get(key) {
+            Some(v) => v,
+            // a missing key equals nothing, so it is always `!=`
+            None => return op == Op::Ne,
+        },
+    };
+
+    match op {
+        Op::Eq => text == value,
+        Op::Ne => text != value,
+        Op::Has => has(text, value),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Word(String),
+    Str(String),
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::LParen => f.write_str("`(`"),
+            TokenKind::RParen => f.write_str("`)`"),
+            TokenKind::Op(op) => write!(f, "`{op}`"),
+            TokenKind::And => f.write_str("`AND`"),
+            TokenKind::Or => f.write_str("`OR`"),
+            TokenKind::Not => f.write_str("`NOT`"),
+            TokenKind::Word(w) => write!(f, "`{w}`"),
+            TokenKind::Str(s) => write!(f, "{s:?}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    offset: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '*' | '/')
+}
+
+fn lex(input: &str) -> Result<Vec<Token>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        let kind = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                TokenKind::LParen
+            }
+            ')' => {
+                chars.next();
+                TokenKind::RParen
+            }
+            '=' => {
+                chars.next();
+                TokenKind::Op(Op::Eq)
+            }
+            ':' => {
+                chars.next();
+                TokenKind::Op(Op::Has)
+            }
+            '!' => {
+                chars.next();
+                match chars.next() {
+                    Some((_, '=')) => TokenKind::Op(Op::Ne),
+                    _ => return Err(FilterError::new(offset, "expected `!=`")),
+                }
+            }
+            '"' => TokenKind::Str(lex_string(&mut chars, offset)?),
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek().filter(|(_, c)| is_word_char(*c)) {
+                    word.push(c);
+                    chars.next();
+                }
+                match word.as_str() {
+                    "AND" => TokenKind::And,
+                    "OR" => TokenKind::Or,
+                    "NOT" => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                }
+            }
+            c => return Err(FilterError::new(offset, format!("unexpected `{c}`"))),
+        };
+        tokens.push(Token { kind, offset });
+    }
+
+    Ok(tokens)
+}
+
+/// Lexes a quoted string, the opening quote being at `start`
+fn lex_string(chars: &mut Peekable<CharIndices<'_>>, start: usize) -> Result<String, FilterError> {
+    chars.next();
+    let mut out = String::new();
+    loop {
+        match chars.next() {
+            Some((_, '"')) => return Ok(out),
+            Some((_, '\\')) => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => out.push(c),
+                Some((offset, c)) => {
+                    return Err(FilterError::new(offset, format!("unknown escape `\\{c}`")));
+                }
+                None => break,
+            },
+            Some((_, c)) => out.push(c),
+            None => break,
+        }
+    }
+    Err(FilterError::new(start, "unterminated string"))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Offset reported for errors at the end of input
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, FilterError> {
+        let tok = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(FilterError::new(self.end, "unexpected end of filter"))?;
+        self.pos += 1;
+        Ok(tok)
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        let found = self.peek().is_some_and(|t| &t.kind == kind);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Fails once `depth` levels of nesting are exceeded, at the next token
+    fn nest(&self, depth: usize) -> Result<usize, FilterError> {
+        if depth >= MAX_DEPTH {
+            let offset = self.peek().map_or(self.end, |t| t.offset);
+            return Err(FilterError::new(offset, "filter nests too deeply"));
+        }
+        Ok(depth + 1)
+    }
+
+    fn or(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut lhs = self.and(depth)?;
+        while self.eat(&TokenKind::Or) {
+            lhs = Filter::Or(Box::new(lhs), Box::new(self.and(depth)?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let mut lhs = self.unary(depth)?;
+        while self.eat(&TokenKind::And) {
+            lhs = Filter::And(Box::new(lhs), Box::new(self.unary(depth)?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        if self.eat(&TokenKind::Not) {
+            let depth = self.nest(depth)?;
+            return Ok(Filter::Not(Box::new(self.unary(depth)?)));
+        }
+        self.primary(depth)
+    }
+
+    fn primary(&mut self, depth: usize) -> Result<Filter, FilterError> {
+        let tok = self.next()?;
+        match tok.kind {
+            TokenKind::LParen => {
+                let inner = self.or(self.nest(depth)?)?;
+                let close = self.next()?;
+                match close.kind {
+                    TokenKind::RParen => Ok(inner),
+                    kind => Err(FilterError::new(
+                        close.offset,
+                        format!("expected `)`, found {kind}"),
+                    )),
+                }
+            }
+            TokenKind::Word(name) => {
+                let field = parse_field(&name, tok.offset)?;
+                let op = self.next()?;
+                let TokenKind::Op(op) = op.kind else {
+                    return Err(FilterError::new(
+                        op.offset,
+                        format!("expected `=`, `!=` or `:`, found {}", op.kind),
+                    ));
+                };
+                let value = self.next()?;
+                match value.kind {
+                    TokenKind::Word(value) | TokenKind::Str(value) => {
+                        Ok(Filter::Compare { field, op, value })
+                    }
+                    kind => Err(FilterError::new(
+                        value.offset,
+                        format!("expected a value, found {kind}"),
+                    )),
+                }
+            }
+            kind => Err(FilterError::new(
+                tok.offset,
+                format!("expected a field, found {kind}"),
+            )),
+        }
+    }
+}
+
+fn parse_field(name: &str, offset: usize) -> Result<Field, FilterError> {
+    let field = match name.split_once('.') {
+        None => match name {
+            "id" => Field::Id,
+            "name" => Field::Name,
+            "description" => Field::Description,
+            "owner" => Field::Owner,
+            "difficulty" => Field::Difficulty,
+            "tags" => Field::Tags,
+            "metadata" => Field::Metadata(None),
+            _ => return Err(FilterError::new(offset, format!("unknown field `{name}`"))),
+        },
+        Some(("metadata", key)) if !key.is_empty() => Field::Metadata(Some(key.to_string())),
+        Some(_) => return Err(FilterError::new(offset, format!("unknown field `{name}`"))),
+    };
+    Ok(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn challenge() -> Challenge {
+        Challenge {
+            id: "c1".to_string(),
+            name: "Baby Heap".to_string(),
+            difficulty: "hard".to_string(),
+            tags: vec!["pwn".to_string(), "web".to_string()],
+            metadata: HashMap::from([("author".to_string(), "x".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn eval(filter: &str) -> bool {
+        Filter::parse(filter)
+            .unwrap()
+            .unwrap()
+            .matches(&challenge())
+    }
+
+    #[test]
+    fn empty_is_no_filter() {
+        assert_eq!(Filter::parse("").unwrap(), None);
+        assert_eq!(Filter::parse("  \t").unwrap(), None);
+    }
+
+    #[test]
+    fn evaluates() {
+        assert!(eval(
+            r#"difficulty = "hard" AND tags:"web" AND metadata.author = "x""#
+        ));
+        assert!(eval("name:heap"));
+        assert!(eval("NOT name = heap"));
+        assert!(eval("tags:WEB AND tags != crypto"));
+        assert!(eval("metadata:author AND NOT metadata:license"));
+        assert!(eval("metadata.license != mit"));
+        assert!(!eval("metadata.license = mit"));
+        assert!(eval("difficulty = easy OR (tags = pwn AND id = c1)"));
+        assert!(!eval("difficulty = easy OR tags = pwn AND id = c2"));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let parsed = Filter::parse("id = a OR id = b AND id = c")
+            .unwrap()
+            .unwrap();
+        assert!(matches!(parsed, Filter::Or(_, rhs) if matches!(*rhs, Filter::And(..))));
+    }
+
+    #[test]
+    fn strings_unescape() {
+        let parsed = Filter::parse(r#"name = "say \"hi\" \\o/""#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            parsed,
+            Filter::Compare {
+                field: Field::Name,
+                op: Op::Eq,
+                value: r#"say "hi" \o/"#.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let err = |input: &str| Filter::parse(input).unwrap_err();
+
+        assert_eq!(err("points = 5").offset, 0);
+        assert_eq!(err("name = a AND").offset, 12);
+        assert_eq!(err("name a").offset, 5);
+        assert_eq!(err(r#"name = "open"#).offset, 7);
+        assert_eq!(err("(name = a").offset, 9);
+        assert_eq!(err("name = a)").offset, 8);
+        assert_eq!(err("name ! a").offset, 5);
+        assert_eq!(err("name = a & b").offset, 9);
+        assert_eq!(
+            err("points = 5").to_string(),
+            "column 1: unknown field `points`"
+        );
+
+        // columns count characters, offsets count bytes
+        let e = err(r#"name = "é" AND"#);
+        assert_eq!((e.offset, e.column), (15, 15));
+        assert_eq!(e.to_string(), "column 15: unexpected end of filter");
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let nested = |depth: usize| format!("{}name = x{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Filter::parse(&nested(MAX_DEPTH)).is_ok());
+        let err = Filter::parse(&nested(MAX_DEPTH + 1)).unwrap_err();
+        assert_eq!(err.message, "filter nests too deeply");
+        assert!(Filter::parse(&nested(100_000)).is_err());
+        assert!(Filter::parse(&format!("{}name = x", "NOT ".repeat(100_000))).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! Where ctfjx keeps challenges, instances, agents and jobs.
+//!
+//! Callers only talk to the [`Storage`] trait, backends are picked through
+//! [`StorageConfig`]. [`MemoryStorage`] is meant for tests, [`SqliteStorage`]
+//! persists to a single file and survives restarts.
+
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use ctfjx_proto::grpc::{AgentCredential, Challenge, DeadJob, Team, User};
+use prost_wkt_types::Timestamp;
+
+mod error;
+pub mod filter;
+pub mod memory;
+pub mod model;
+pub mod sqlite;
+
+pub use error::{Result, StorageError};
+pub use filter::{Filter, FilterError};
+pub use memory::MemoryStorage;
+pub use model::*;
+pub use sqlite::SqliteStorage;
+
+/// Operations available both directly on a [`Storage`] and inside one of its
+/// transactions
+pub trait Store {
+    fn insert_challenge(&self, challenge: &Challenge) -> Result<()>;
+    fn get_challenge(&self, id: &str) -> Result<Challenge>;
+    /// Replaces the challenge and returns it as stored.
+    ///
+    /// When `challenge.updated_at` is set it has to match the stored one, so
+    /// concurrent writers fail with [`StorageError::Conflict`] instead of
+    /// overwriting each other. `created_at` is kept and `updated_at` bumped.
+    fn update_challenge(&self, challenge: &Challenge) -> Result<Challenge>;
+    fn delete_challenge(&self, id: &str) -> Result<()>;
+    /// Challenges matching `filter`, all of them without one, ordered by id
+    fn list_challenges(&self, filter: Option<&Filter>, page: &Page) -> Result<Vec<Challenge>>;
+
+    fn insert_user(&self, user: &User) -> Result<()>;
+    fn get_user(&self, id: &str) -> Result<User>;
+    fn update_user(&self, user: &User) -> Result<()>;
+
+    /// Inserts a team, whose id, name and invite code all have to be unique
+    fn insert_team(&self, team: &Team) -> Result<()>;
+    fn get_team(&self, id: &str) -> Result<Team>;
+    fn get_team_by_invite(&self, invite_code: &str) -> Result<Team>;
+    fn update_team(&self, team: &Team) -> Result<()>;
+    /// Teams ordered by id
+    fn list_teams(&self, page: &Page) -> Result<Vec<Team>>;
+
+    fn insert_token(&self, token: &TokenRecord) -> Result<()>;
+    fn get_token(&self, id: &str) -> Result<TokenRecord>;
+    fn update_token(&self, token: &TokenRecord) -> Result<()>;
+    /// Tokens, of one user or all of them, ordered by id
+    fn list_tokens(&self, user_id: Option<&str>) -> Result<Vec<TokenRecord>>;
+
+    fn insert_instance(&self, instance: &Instance) -> Result<()>;
+    fn get_instance(&self, id: &str) -> Result<Instance>;
+    fn update_instance(&self, instance: &Instance) -> Result<()>;
+    /// Instances, of one challenge or all of them, ordered by id
+    fn list_instances(&self, challenge_id: Option<&str>) -> Result<Vec<Instance>>;
+
+    /// Inserts or replaces the agent
+    fn put_agent(&self, agent: &Agent) -> Result<()>;
+    fn get_agent(&self, id: &str) -> Result<Agent>;
+    /// Agents ordered by id
+    fn list_agents(&self) -> Result<Vec<Agent>>;
+
+    fn insert_join_token(&self, token: &JoinTokenRecord) -> Result<()>;
+    fn get_join_token(&self, id: &str) -> Result<JoinTokenRecord>;
+    fn update_join_token(&self, token: &JoinTokenRecord) -> Result<()>;
+
+    fn insert_agent_credential(&self, credential: &AgentCredential) -> Result<()>;
+    fn get_agent_credential(&self, id: &str) -> Result<AgentCredential>;
+    fn update_agent_credential(&self, credential: &AgentCredential) -> Result<()>;
+    /// Credentials, of one agent or all of them, ordered by id
+    fn list_agent_credentials(&self, agent_id: Option<&str>) -> Result<Vec<AgentCredential>>;
+
+    fn insert_submission(&self, submission: &Submission) -> Result<()>;
+    /// Submissions, for one challenge or all of them, oldest first
+    fn list_submissions(&self, challenge_id: Option<&str>) -> Result<Vec<Submission>>;
+
+    /// Records a solve, a team solves each challenge at most once
+    fn insert_solve(&self, solve: &Solve) -> Result<()>;
+    fn get_solve(&self, challenge_id: &str, team_id: &str) -> Result<Solve>;
+    fn delete_solve(&self, challenge_id: &str, team_id: &str) -> Result<()>;
+    /// Solves, for one challenge or all of them, oldest first
+    fn list_solves(&self, challenge_id: Option<&str>) -> Result<Vec<Solve>>;
+
+    /// The stored settings, defaults before any were put
+    fn get_scoreboard_settings(&self) -> Result<ScoreboardSettings>;
+    fn put_scoreboard_settings(&self, settings: &ScoreboardSettings) -> Result<()>;
+
+    fn enqueue_job(&self, job: &QueuedJob) -> Result<()>;
+    fn get_job(&self, job_id: &str) -> Result<QueuedJob>;
+    /// Every queued job, leased or not, in the order they were enqueued
+    fn list_jobs(&self) -> Result<Vec<QueuedJob>>;
+    fn update_job(&self, job: &QueuedJob) -> Result<()>;
+    fn delete_job(&self, job_id: &str) -> Result<()>;
+
+    fn insert_dead_job(&self, job: &DeadJob) -> Result<()>;
+    /// Dead jobs, meant for one agent or all of them, oldest first
+    fn list_dead_jobs(&self, agent_id: Option<&str>) -> Result<Vec<DeadJob>>;
+}
+
+/// A window over records ordered by their key, for keyset pagination
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page {
+    /// Only records whose key sorts after this one
+    pub after: Option<String>,
+    pub limit: usize,
+}
+
+impl Page {
+    pub const ALL: Page = Page {
+        after: None,
+        limit: usize::MAX,
+    };
+}
+
+pub trait Storage: Store + Send + Sync + 'static {
+    /// Runs `f` atomically, nothing it wrote is kept when it fails
+    fn transaction(&self, f: &mut dyn FnMut(&dyn Store) -> Result<()>) -> Result<()>;
+}
+
+impl dyn Storage {
+    /// [`Storage::transaction`] for closures returning a value or any error
+    /// a [`StorageError`] converts into
+    pub fn atomically<T, E>(
+        &self,
+        f: impl FnOnce(&dyn Store) -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E>
+    where
+        E: From<StorageError>,
+    {
+        let mut f = Some(f);
+        let mut out = None;
+        let res = self.transaction(&mut |store| {
+            let f = f.take().expect("transaction body runs once");
+            match f(store) {
+                Ok(v) => {
+                    out = Some(Ok(v));
+                    Ok(())
+                }
+                Err(e) => {
+                    out = Some(Err(e));
+                    Err(StorageError::Aborted)
+                }
+            }
+        });
+
+        match (res, out) {
+            (_, Some(Err(e))) => Err(e),
+            (Err(e), _) => Err(e.into()),
+            (Ok(()), Some(Ok(v))) => Ok(v),
+            (Ok(()), None) => unreachable!("transaction committed without running"),
+        }
+    }
+}
+
+/// Checks `update` against the stored challenge, returning what to store
+pub(crate) fn apply_challenge_update(stored: &Challenge, update: &Challenge) -> Result<Challenge> {
+    if update.updated_at.is_some() && update.updated_at != stored.updated_at {
+        return Err(StorageError::Conflict("challenge", stored.id.clone()));
+    }
+
+    let mut next = update.clone();
+    next.created_at = stored.created_at;
+    next.updated_at = Some(next_version(stored.updated_at));
+    Ok(next)
+}
+
+/// A timestamp strictly after `prev`, even when the clock did not move
+fn next_version(prev: Option<Timestamp>) -> Timestamp {
+    let now = SystemTime::now();
+    let Some(prev) = prev.and_then(|ts| SystemTime::try_from(ts).ok()) else {
+        return now.into();
+    };
+    now.max(prev + Duration::from_nanos(1)).into()
+}
+
+/// Orders timestamps, which do not implement `Ord` themselves
+pub fn ts_key(ts: &Timestamp) -> (i64, i32) {
+    (ts.seconds, ts.nanos)
+}
+
+/// Which backend to use, parsed from `memory` or `sqlite:<path>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageConfig {
+    Memory,
+    Sqlite(PathBuf),
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig::Sqlite(PathBuf::from("ctfjxd.db"))
+    }
+}
+
+impl FromStr for StorageConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "memory" => Ok(StorageConfig::Memory),
+            Some(("sqlite", path)) if !path.is_empty() => {
+                Ok(StorageConfig::Sqlite(PathBuf::from(path)))
+            }
+            _ => Err(format!("unknown storage `{s}`")),
+        }
+    }
+}
+
+pub fn open(config: &StorageConfig) -> Result<Arc<dyn Storage>> {
+    match config {
+        StorageConfig::Memory => Ok(Arc::new(MemoryStorage::default())),
+        StorageConfig::Sqlite(path) => Ok(Arc::new(SqliteStorage::open(path)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        assert_eq!("memory".parse(), Ok(StorageConfig::Memory));
+        assert_eq!(
+            "sqlite:/var/lib/ctfjx/db.sqlite".parse(),
+            Ok(StorageConfig::Sqlite("/var/lib/ctfjx/db.sqlite".into()))
+        );
+        assert!("sqlite:".parse::<StorageConfig>().is_err());
+        assert!("postgres://localhost".parse::<StorageConfig>().is_err());
+    }
+
+    #[test]
+    fn versions_always_move_forward() {
+        let far_future: Timestamp = (SystemTime::now() + Duration::from_secs(3600)).into();
+        let next = next_version(Some(far_future));
+        assert!(SystemTime::try_from(next).unwrap() > SystemTime::try_from(far_future).unwrap());
+    }
+}
@@ -0,0 +1,547 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    ops::Bound,
+};
+
+use ctfjx_proto::grpc::{AgentCredential, Challenge, DeadJob, Team, User};
+use parking_lot::Mutex;
+
+use crate::{
+    Agent, Filter, Instance, JoinTokenRecord, Page, QueuedJob, Result, ScoreboardSettings, Solve,
+    Storage, StorageError, Store, Submission, TokenRecord, apply_challenge_update, ts_key,
+};
+
+/// Keeps everything in memory, lost on restart
+#[derive(Default)]
+pub struct MemoryStorage {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default, Clone)]
+struct Inner {
+    challenges: BTreeMap<String, Challenge>,
+    users: HashMap<String, User>,
+    teams: BTreeMap<String, Team>,
+    tokens: BTreeMap<String, TokenRecord>,
+    instances: HashMap<String, Instance>,
+    agents: HashMap<String, Agent>,
+    join_tokens: HashMap<String, JoinTokenRecord>,
+    credentials: BTreeMap<String, AgentCredential>,
+    submissions: Vec<Submission>,
+    /// Keyed by challenge, then team
+    solves: BTreeMap<(String, String), Solve>,
+    scoreboard: ScoreboardSettings,
+    jobs: VecDeque<QueuedJob>,
+    dead_jobs: Vec<DeadJob>,
+}
+
+impl Storage for MemoryStorage {
+    fn transaction(&self, f: &mut dyn FnMut(&dyn Store) -> Result<()>) -> Result<()> {
+        // writers queue up behind the lock, the body works on a copy that only
+        // replaces the real state once it succeeds
+        let mut inner = self.inner.lock();
+        let tx = MemoryStorage {
+            inner: Mutex::new(inner.clone()),
+        };
+        f(&tx)?;
+        *inner = tx.inner.into_inner();
+        Ok(())
+    }
+}
+
+impl Store for MemoryStorage {
+    fn insert_challenge(&self, challenge: &Challenge) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.challenges.contains_key(&challenge.id) {
+            return Err(StorageError::AlreadyExists(
+                "challenge",
+                challenge.id.clone(),
+            ));
+        }
+        inner
+            .challenges
+            .insert(challenge.id.clone(), challenge.clone());
+        Ok(())
+    }
+
+    fn get_challenge(&self, id: &str) -> Result<Challenge> {
+        self.inner
+            .lock()
+            .challenges
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("challenge", id.to_string()))
+    }
+
+    fn update_challenge(&self, challenge: &Challenge) -> Result<Challenge> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .challenges
+            .get_mut(&challenge.id)
+            .ok_or(StorageError::NotFound("challenge", challenge.id.clone()))?;
+        *existing = apply_challenge_update(existing, challenge)?;
+        Ok(existing.clone())
+    }
+
+    fn delete_challenge(&self, id: &str) -> Result<()> {
+        self.inner
+            .lock()
+            .challenges
+            .remove(id)
+            .map(|_| ())
+            .ok_or(StorageError::NotFound("challenge", id.to_string()))
+    }
+
+    fn list_challenges(&self, filter: Option<&Filter>, page: &Page) -> Result<Vec<Challenge>> {
+        let inner = self.inner.lock();
+        let start = match &page.after {
+            Some(after) => Bound::Excluded(after.clone()),
+            None => Bound::Unbounded,
+        };
+        Ok(inner
+            .challenges
+            .range((start, Bound::Unbounded))
+            .map(|(_, c)| c)
+            .filter(|c| filter.is_none_or(|f| f.matches(c)))
+            .take(page.limit)
+            .cloned()
+            .collect())
+    }
+
+    fn insert_user(&self, user: &User) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.users.contains_key(&user.id) {
+            return Err(StorageError::AlreadyExists("user", user.id.clone()));
+        }
+        inner.users.insert(user.id.clone(), user.clone());
+        Ok(())
+    }
+
+    fn get_user(&self, id: &str) -> Result<User> {
+        self.inner
+            .lock()
+            .users
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("user", id.to_string()))
+    }
+
+    fn update_user(&self, user: &User) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .users
+            .get_mut(&user.id)
+            .ok_or(StorageError::NotFound("user", user.id.clone()))?;
+        *existing = user.clone();
+        Ok(())
+    }
+
+    fn insert_team(&self, team: &Team) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.teams.contains_key(&team.id) {
+            return Err(StorageError::AlreadyExists("team", team.id.clone()));
+        }
+        for other in inner.teams.values() {
+            if other.name == team.name {
+                return Err(StorageError::AlreadyExists("team", team.name.clone()));
+            }
+            if other.invite_code == team.invite_code {
+                return Err(StorageError::AlreadyExists(
+                    "invite code",
+                    team.invite_code.clone(),
+                ));
+            }
+        }
+        inner.teams.insert(team.id.clone(), team.clone());
+        Ok(())
+    }
+
+    fn get_team(&self, id: &str) -> Result<Team> {
+        self.inner
+            .lock()
+            .teams
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("team", id.to_string()))
+    }
+
+    fn get_team_by_invite(&self, invite_code: &str) -> Result<Team> {
+        self.inner
+            .lock()
+            .teams
+            .values()
+            .find(|t| t.invite_code == invite_code)
+            .cloned()
+            .ok_or(StorageError::NotFound(
+                "invite code",
+                invite_code.to_string(),
+            ))
+    }
+
+    fn update_team(&self, team: &Team) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .teams
+            .get_mut(&team.id)
+            .ok_or(StorageError::NotFound("team", team.id.clone()))?;
+        *existing = team.clone();
+        Ok(())
+    }
+
+    fn list_teams(&self, page: &Page) -> Result<Vec<Team>> {
+        let start = match &page.after {
+            Some(after) => Bound::Excluded(after.clone()),
+            None => Bound::Unbounded,
+        };
+        Ok(self
+            .inner
+            .lock()
+            .teams
+            .range((start, Bound::Unbounded))
+            .map(|(_, t)| t.clone())
+            .take(page.limit)
+            .collect())
+    }
+
+    fn insert_token(&self, token: &TokenRecord) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let id = &token.token.id;
+        if inner.tokens.contains_key(id) {
+            return Err(StorageError::AlreadyExists("token", id.clone()));
+        }
+        inner.tokens.insert(id.clone(), token.clone());
+        Ok(())
+    }
+
+    fn get_token(&self, id: &str) -> Result<TokenRecord> {
+        self.inner
+            .lock()
+            .tokens
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("token", id.to_string()))
+    }
+
+    fn update_token(&self, token: &TokenRecord) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .tokens
+            .get_mut(&token.token.id)
+            .ok_or(StorageError::NotFound("token", token.token.id.clone()))?;
+        *existing = token.clone();
+        Ok(())
+    }
+
+    fn list_tokens(&self, user_id: Option<&str>) -> Result<Vec<TokenRecord>> {
+        Ok(self
+            .inner
+            .lock()
+            .tokens
+            .values()
+            .filter(|t| user_id.is_none_or(|id| t.token.user_id == id))
+            .cloned()
+            .collect())
+    }
+
+    fn insert_instance(&self, instance: &Instance) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let id = &instance.status.instance_id;
+        if inner.instances.contains_key(id) {
+            return Err(StorageError::AlreadyExists("instance", id.clone()));
+        }
+        inner.instances.insert(id.clone(), instance.clone());
+        Ok(())
+    }
+
+    fn get_instance(&self, id: &str) -> Result<Instance> {
+        self.inner
+            .lock()
+            .instances
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("instance", id.to_string()))
+    }
+
+    fn update_instance(&self, instance: &Instance) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let id = &instance.status.instance_id;
+        let existing = inner
+            .instances
+            .get_mut(id)
+            .ok_or(StorageError::NotFound("instance", id.clone()))?;
+        *existing = instance.clone();
+        Ok(())
+    }
+
+    fn list_instances(&self, challenge_id: Option<&str>) -> Result<Vec<Instance>> {
+        let mut instances: Vec<_> = self
+            .inner
+            .lock()
+            .instances
+            .values()
+            .filter(|i| challenge_id.is_none_or(|id| i.status.challenge_id == id))
+            .cloned()
+            .collect();
+        instances.sort_by(|a, b| a.status.instance_id.cmp(&b.status.instance_id));
+        Ok(instances)
+    }
+
+    fn put_agent(&self, agent: &Agent) -> Result<()> {
+        self.inner
+            .lock()
+            .agents
+            .insert(agent.agent_id.clone(), agent.clone());
+        Ok(())
+    }
+
+    fn get_agent(&self, id: &str) -> Result<Agent> {
+        self.inner
+            .lock()
+            .agents
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("agent", id.to_string()))
+    }
+
+    fn list_agents(&self) -> Result<Vec<Agent>> {
+        let mut agents: Vec<_> = self.inner.lock().agents.values().cloned().collect();
+        agents.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+        Ok(agents)
+    }
+
+    fn insert_join_token(&self, token: &JoinTokenRecord) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let id = &token.token.id;
+        if inner.join_tokens.contains_key(id) {
+            return Err(StorageError::AlreadyExists("join token", id.clone()));
+        }
+        inner.join_tokens.insert(id.clone(), token.clone());
+        Ok(())
+    }
+
+    fn get_join_token(&self, id: &str) -> Result<JoinTokenRecord> {
+        self.inner
+            .lock()
+            .join_tokens
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("join token", id.to_string()))
+    }
+
+    fn update_join_token(&self, token: &JoinTokenRecord) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .join_tokens
+            .get_mut(&token.token.id)
+            .ok_or(StorageError::NotFound("join token", token.token.id.clone()))?;
+        *existing = token.clone();
+        Ok(())
+    }
+
+    fn insert_agent_credential(&self, credential: &AgentCredential) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.credentials.contains_key(&credential.id) {
+            return Err(StorageError::AlreadyExists(
+                "agent credential",
+                credential.id.clone(),
+            ));
+        }
+        inner
+            .credentials
+            .insert(credential.id.clone(), credential.clone());
+        Ok(())
+    }
+
+    fn get_agent_credential(&self, id: &str) -> Result<AgentCredential> {
+        self.inner
+            .lock()
+            .credentials
+            .get(id)
+            .cloned()
+            .ok_or(StorageError::NotFound("agent credential", id.to_string()))
+    }
+
+    fn update_agent_credential(&self, credential: &AgentCredential) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .credentials
+            .get_mut(&credential.id)
+            .ok_or(StorageError::NotFound(
+                "agent credential",
+                credential.id.clone(),
+            ))?;
+        *existing = credential.clone();
+        Ok(())
+    }
+
+    fn list_agent_credentials(&self, agent_id: Option<&str>) -> Result<Vec<AgentCredential>> {
+        Ok(self
+            .inner
+            .lock()
+            .credentials
+            .values()
+            .filter(|c| agent_id.is_none_or(|id| c.agent_id == id))
+            .cloned()
+            .collect())
+    }
+
+    fn insert_submission(&self, submission: &Submission) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.submissions.iter().any(|s| s.id == submission.id) {
+            return Err(StorageError::AlreadyExists(
+                "submission",
+                submission.id.clone(),
+            ));
+        }
+        inner.submissions.push(submission.clone());
+        Ok(())
+    }
+
+    fn list_submissions(&self, challenge_id: Option<&str>) -> Result<Vec<Submission>> {
+        let mut submissions: Vec<_> = self
+            .inner
+            .lock()
+            .submissions
+            .iter()
+            .filter(|s| challenge_id.is_none_or(|id| s.challenge_id == id))
+            .cloned()
+            .collect();
+        submissions.sort_by_key(|s| ts_key(&s.submitted_at));
+        Ok(submissions)
+    }
+
+    fn insert_solve(&self, solve: &Solve) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let key = (solve.challenge_id.clone(), solve.team_id.clone());
+        if inner.solves.contains_key(&key) {
+            return Err(StorageError::AlreadyExists(
+                "solve",
+                format!("{}/{}", solve.challenge_id, solve.team_id),
+            ));
+        }
+        inner.solves.insert(key, solve.clone());
+        Ok(())
+    }
+
+    fn get_solve(&self, challenge_id: &str, team_id: &str) -> Result<Solve> {
+        self.inner
+            .lock()
+            .solves
+            .get(&(challenge_id.to_string(), team_id.to_string()))
+            .cloned()
+            .ok_or(StorageError::NotFound(
+                "solve",
+                format!("{challenge_id}/{team_id}"),
+            ))
+    }
+
+    fn delete_solve(&self, challenge_id: &str, team_id: &str) -> Result<()> {
+        self.inner
+            .lock()
+            .solves
+            .remove(&(challenge_id.to_string(), team_id.to_string()))
+            .map(|_| ())
+            .ok_or(StorageError::NotFound(
+                "solve",
+                format!("{challenge_id}/{team_id}"),
+            ))
+    }
+
+    fn list_solves(&self, challenge_id: Option<&str>) -> Result<Vec<Solve>> {
+        let mut solves: Vec<_> = self
+            .inner
+            .lock()
+            .solves
+            .values()
+            .filter(|s| challenge_id.is_none_or(|id| s.challenge_id == id))
+            .cloned()
+            .collect();
+        solves.sort_by_key(|s| ts_key(&s.solved_at));
+        Ok(solves)
+    }
+
+    fn get_scoreboard_settings(&self) -> Result<ScoreboardSettings> {
+        Ok(self.inner.lock().scoreboard.clone())
+    }
+
+    fn put_scoreboard_settings(&self, settings: &ScoreboardSettings) -> Result<()> {
+        self.inner.lock().scoreboard = settings.clone();
+        Ok(())
+    }
+
+    fn enqueue_job(&self, job: &QueuedJob) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.jobs.iter().any(|j| j.job.job_id == job.job.job_id) {
+            return Err(StorageError::AlreadyExists("job", job.job.job_id.clone()));
+        }
+        inner.jobs.push_back(job.clone());
+        Ok(())
+    }
+
+    fn get_job(&self, job_id: &str) -> Result<QueuedJob> {
+        self.inner
+            .lock()
+            .jobs
+            .iter()
+            .find(|j| j.job.job_id == job_id)
+            .cloned()
+            .ok_or(StorageError::NotFound("job", job_id.to_string()))
+    }
+
+    fn list_jobs(&self) -> Result<Vec<QueuedJob>> {
+        Ok(self.inner.lock().jobs.iter().cloned().collect())
+    }
+
+    fn update_job(&self, job: &QueuedJob) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let existing = inner
+            .jobs
+            .iter_mut()
+            .find(|j| j.job.job_id == job.job.job_id)
+            .ok_or(StorageError::NotFound("job", job.job.job_id.clone()))?;
+        *existing = job.clone();
+        Ok(())
+    }
+
+    fn delete_job(&self, job_id: &str) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let pos = inner
+            .jobs
+            .iter()
+            .position(|j| j.job.job_id == job_id)
+            .ok_or(StorageError::NotFound("job", job_id.to_string()))?;
+        inner.jobs.remove(pos);
+        Ok(())
+    }
+
+    fn insert_dead_job(&self, job: &DeadJob) -> Result<()> {
+        let mut inner = self.inner.lock();
+        let id = job
+            .job
+            .as_ref()
+            .map(|j| j.job_id.as_str())
+            .unwrap_or_default();
+        if inner
+            .dead_jobs
+            .iter()
+            .any(|d| d.job.as_ref().is_some_and(|j| j.job_id == id))
+        {
+            return Err(StorageError::AlreadyExists("dead job", id.to_string()));
+        }
+        inner.dead_jobs.push(job.clone());
+        Ok(())
+    }
+
+    fn list_dead_jobs(&self, agent_id: Option<&str>) -> Result<Vec<DeadJob>> {
+        let mut jobs: Vec<_> = self
+            .inner
+            .lock()
+            .dead_jobs
+            .iter()
+            .filter(|d| agent_id.is_none_or(|id| d.agent_id == id))
+            .cloned()
+            .collect();
+        jobs.sort_by_key(|d| d.died_at.as_ref().map(ts_key));
+        Ok(jobs)
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use ctfjx_proto::grpc::{ApiToken, InstanceStatus, Job, JoinToken};
+use prost_wkt_types::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// A challenge instance as tracked by the daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance {
+    pub status: InstanceStatus,
+    /// The team the instance was started for
+    #[serde(alias = "owner")]
+    pub team_id: String,
+    pub overrides: HashMap<String, String>,
+    pub started_at: Option<Timestamp>,
+    pub stopped_at: Option<Timestamp>,
+    /// The `status.expires_at` the team was warned about, extending the
+    /// instance warrants another warning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry_warned: Option<Timestamp>,
+}
+
+/// An agent that registered with the daemon
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Agent {
+    pub agent_id: String,
+    pub version: String,
+    pub capabilities: Vec<String>,
+    pub metadata: HashMap<String, String>,
+    pub registered_at: Option<Timestamp>,
+    pub last_seen: Option<Timestamp>,
+    /// Stats from the agent's latest heartbeat
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub stats: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_heartbeat: Option<Timestamp>,
+    /// Registered and, when it holds an `AgentStream`, still connected
+    #[serde(default)]
+    pub online: bool,
+    #[serde(default)]
+    pub liveness: Liveness,
+}
+
+/// Whether an agent was heard from recently enough to place instances on,
+/// ordered from best to worst
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liveness {
+    #[default]
+    Healthy,
+    /// Missed some heartbeats
+    Suspect,
+    /// Missed so many its instances were failed
+    Dead,
+}
+
+impl Liveness {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Liveness::Healthy => "healthy",
+            Liveness::Suspect => "suspect",
+            Liveness::Dead => "dead",
+        }
+    }
+}
+
+/// A job waiting to be handed out, or leased to an agent until it reports
+/// back
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueuedJob {
+    /// Carries the current attempt and lease expiry
+    pub job: Job,
+    /// Only this agent may take the job, any agent if `None`
+    pub agent_id: Option<String>,
+    /// The agent holding the lease
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leased_to: Option<String>,
+    /// Not handed out again before this time, after a failed attempt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<Timestamp>,
+    /// Why the previous attempt failed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub last_error: String,
+    /// What an agent needs to take the job, see `Challenge.requirements`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requirements: Vec<String>,
+}
+
+/// An API token along with what checks its secret
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenRecord {
+    pub token: ApiToken,
+    /// Hex SHA-256 of the secret, the secret itself is never stored
+    pub secret_hash: String,
+}
+
+/// A join token along with what checks its secret
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinTokenRecord {
+    /// Stored without its `token`
+    pub token: JoinToken,
+    /// Hex SHA-256 of the secret
+    pub secret_hash: String,
+}
+
+/// A flag a team submitted, right or wrong
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Submission {
+    pub id: String,
+    pub challenge_id: String,
+    pub team_id: String,
+    pub flag: String,
+    pub correct: bool,
+    pub submitted_at: Timestamp,
+    /// The team whose dynamic flag this was, when it was not the submitter's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_from: Option<String>,
+}
+
+/// Scoreboard wide settings, a single record
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreboardSettings {
+    /// Public views leave out solves from this time on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freeze_at: Option<Timestamp>,
+}
+
+/// A team's first correct submission for a challenge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Solve {
+    pub challenge_id: String,
+    pub team_id: String,
+    pub submission_id: String,
+    pub solved_at: Timestamp,
+}
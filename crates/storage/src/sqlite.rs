@@ -0,0 +1,899 @@
+//! Embedded SQLite backend.
+//!
+//! Records are stored as JSON next to the columns needed to look them up, so
+//! new proto fields do not need a migration. Schema changes are appended to
+//! [`MIGRATIONS`] and tracked through `PRAGMA user_version`.
+
+use std::{path::Path, time::Duration};
+
+use ctfjx_proto::grpc::{AgentCredential, Challenge, DeadJob, Team, User};
+use parking_lot::Mutex;
+use prost_wkt_types::Timestamp;
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+use crate::{
+    Agent, Filter, Instance, JoinTokenRecord, Page, QueuedJob, Result, ScoreboardSettings, Solve,
+    Storage, StorageError, Store, Submission, TokenRecord, apply_challenge_update,
+};
+
+/// Schema changes, applied in order. Never edit a released entry, append one
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE challenges (
+        id TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE instances (
+        id TEXT PRIMARY KEY,
+        challenge_id TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX instances_challenge_id ON instances (challenge_id);
+    CREATE TABLE agents (
+        id TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE jobs (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        job_id TEXT NOT NULL UNIQUE,
+        agent_id TEXT,
+        data TEXT NOT NULL
+    );",
+    // 2: flag submissions and solves
+    "CREATE TABLE submissions (
+        id TEXT PRIMARY KEY,
+        challenge_id TEXT NOT NULL,
+        team_id TEXT NOT NULL,
+        submitted_at INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX submissions_challenge_id ON submissions (challenge_id, submitted_at);
+    CREATE TABLE solves (
+        challenge_id TEXT NOT NULL,
+        team_id TEXT NOT NULL,
+        solved_at INTEGER NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (challenge_id, team_id)
+    );
+    CREATE INDEX solves_solved_at ON solves (solved_at);",
+    // 3: singleton records such as the scoreboard settings
+    "CREATE TABLE settings (
+        name TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );",
+    // 4: users and teams
+    "CREATE TABLE users (
+        id TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE teams (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        invite_code TEXT NOT NULL UNIQUE,
+        data TEXT NOT NULL
+    );",
+    // 5: API tokens
+    "CREATE TABLE tokens (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX tokens_user_id ON tokens (user_id);",
+    // 6: agent enrollment
+    "CREATE TABLE join_tokens (
+        id TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE TABLE agent_credentials (
+        id TEXT PRIMARY KEY,
+        agent_id TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX agent_credentials_agent_id ON agent_credentials (agent_id);",
+    // 7: job leases, `data` holds the whole queued job from now on
+    "UPDATE jobs SET data = json_object('job', json(data), 'agent_id', agent_id);
+    CREATE TABLE dead_jobs (
+        job_id TEXT PRIMARY KEY,
+        agent_id TEXT NOT NULL,
+        died_at INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX dead_jobs_died_at ON dead_jobs (died_at);",
+];
+
+/// Names the scoreboard settings row of `settings`
+const SCOREBOARD_SETTINGS: &str = "scoreboard";
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Opens or creates the database at `path`, migrating it to the latest schema
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::with_connection(conn)
+    }
+
+    /// A fresh database that only lives as long as the returned storage
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(mut conn: Connection) -> Result<Self> {
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Version of the schema the database is at
+    pub fn schema_version(&self) -> Result<usize> {
+        Ok(self.with_conn(|conn| user_version(conn))?)
+    }
+
+    /// Runs `f` on the locked connection, see [`blocking`]
+    fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> T) -> T {
+        blocking(|| f(&mut self.conn.lock()))
+    }
+}
+
+/// Runs `f`, which waits on the connection lock and the disk
+///
+/// On a multi-threaded tokio runtime the worker hands its other tasks to
+/// another thread first, so a slow write does not stall unrelated requests.
+/// Elsewhere `f` just runs in place.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(f)
+        }
+        _ => f(),
+    }
+}
+
+fn user_version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let current = user_version(conn)?;
+    for (i, sql) in MIGRATIONS.iter().enumerate().skip(current) {
+        let version = i + 1;
+        apply_migration(conn, version, sql)
+            .map_err(|source| StorageError::Migration { version, source })?;
+    }
+    Ok(())
+}
+
+fn apply_migration(conn: &mut Connection, version: usize, sql: &str) -> rusqlite::Result<()> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+    tx.execute_batch(sql)?;
+    tx.pragma_update(None, "user_version", version)?;
+    tx.commit()
+}
+
+impl Storage for SqliteStorage {
+    fn transaction(&self, f: &mut dyn FnMut(&dyn Store) -> Result<()>) -> Result<()> {
+        self.with_conn(|conn| {
+            let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            // dropping `tx` on error rolls back
+            f(&Conn(&tx))?;
+            tx.commit()?;
+            Ok(())
+        })
+    }
+}
+
+impl Store for SqliteStorage {
+    fn insert_challenge(&self, challenge: &Challenge) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_challenge(challenge))
+    }
+
+    fn get_challenge(&self, id: &str) -> Result<Challenge> {
+        self.with_conn(|conn| Conn(conn).get_challenge(id))
+    }
+
+    fn update_challenge(&self, challenge: &Challenge) -> Result<Challenge> {
+        self.with_conn(|conn| Conn(conn).update_challenge(challenge))
+    }
+
+    fn delete_challenge(&self, id: &str) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).delete_challenge(id))
+    }
+
+    fn list_challenges(&self, filter: Option<&Filter>, page: &Page) -> Result<Vec<Challenge>> {
+        self.with_conn(|conn| Conn(conn).list_challenges(filter, page))
+    }
+
+    fn insert_user(&self, user: &User) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_user(user))
+    }
+
+    fn get_user(&self, id: &str) -> Result<User> {
+        self.with_conn(|conn| Conn(conn).get_user(id))
+    }
+
+    fn update_user(&self, user: &User) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_user(user))
+    }
+
+    fn insert_team(&self, team: &Team) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_team(team))
+    }
+
+    fn get_team(&self, id: &str) -> Result<Team> {
+        self.with_conn(|conn| Conn(conn).get_team(id))
+    }
+
+    fn get_team_by_invite(&self, invite_code: &str) -> Result<Team> {
+        self.with_conn(|conn| Conn(conn).get_team_by_invite(invite_code))
+    }
+
+    fn update_team(&self, team: &Team) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_team(team))
+    }
+
+    fn list_teams(&self, page: &Page) -> Result<Vec<Team>> {
+        self.with_conn(|conn| Conn(conn).list_teams(page))
+    }
+
+    fn insert_token(&self, token: &TokenRecord) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_token(token))
+    }
+
+    fn get_token(&self, id: &str) -> Result<TokenRecord> {
+        self.with_conn(|conn| Conn(conn).get_token(id))
+    }
+
+    fn update_token(&self, token: &TokenRecord) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_token(token))
+    }
+
+    fn list_tokens(&self, user_id: Option<&str>) -> Result<Vec<TokenRecord>> {
+        self.with_conn(|conn| Conn(conn).list_tokens(user_id))
+    }
+
+    fn insert_instance(&self, instance: &Instance) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_instance(instance))
+    }
+
+    fn get_instance(&self, id: &str) -> Result<Instance> {
+        self.with_conn(|conn| Conn(conn).get_instance(id))
+    }
+
+    fn update_instance(&self, instance: &Instance) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_instance(instance))
+    }
+
+    fn list_instances(&self, challenge_id: Option<&str>) -> Result<Vec<Instance>> {
+        self.with_conn(|conn| Conn(conn).list_instances(challenge_id))
+    }
+
+    fn put_agent(&self, agent: &Agent) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).put_agent(agent))
+    }
+
+    fn get_agent(&self, id: &str) -> Result<Agent> {
+        self.with_conn(|conn| Conn(conn).get_agent(id))
+    }
+
+    fn list_agents(&self) -> Result<Vec<Agent>> {
+        self.with_conn(|conn| Conn(conn).list_agents())
+    }
+
+    fn insert_join_token(&self, token: &JoinTokenRecord) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_join_token(token))
+    }
+
+    fn get_join_token(&self, id: &str) -> Result<JoinTokenRecord> {
+        self.with_conn(|conn| Conn(conn).get_join_token(id))
+    }
+
+    fn update_join_token(&self, token: &JoinTokenRecord) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_join_token(token))
+    }
+
+    fn insert_agent_credential(&self, credential: &AgentCredential) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_agent_credential(credential))
+    }
+
+    fn get_agent_credential(&self, id: &str) -> Result<AgentCredential> {
+        self.with_conn(|conn| Conn(conn).get_agent_credential(id))
+    }
+
+    fn update_agent_credential(&self, credential: &AgentCredential) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_agent_credential(credential))
+    }
+
+    fn list_agent_credentials(&self, agent_id: Option<&str>) -> Result<Vec<AgentCredential>> {
+        self.with_conn(|conn| Conn(conn).list_agent_credentials(agent_id))
+    }
+
+    fn insert_submission(&self, submission: &Submission) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_submission(submission))
+    }
+
+    fn list_submissions(&self, challenge_id: Option<&str>) -> Result<Vec<Submission>> {
+        self.with_conn(|conn| Conn(conn).list_submissions(challenge_id))
+    }
+
+    fn insert_solve(&self, solve: &Solve) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_solve(solve))
+    }
+
+    fn get_solve(&self, challenge_id: &str, team_id: &str) -> Result<Solve> {
+        self.with_conn(|conn| Conn(conn).get_solve(challenge_id, team_id))
+    }
+
+    fn delete_solve(&self, challenge_id: &str, team_id: &str) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).delete_solve(challenge_id, team_id))
+    }
+
+    fn list_solves(&self, challenge_id: Option<&str>) -> Result<Vec<Solve>> {
+        self.with_conn(|conn| Conn(conn).list_solves(challenge_id))
+    }
+
+    fn get_scoreboard_settings(&self) -> Result<ScoreboardSettings> {
+        self.with_conn(|conn| Conn(conn).get_scoreboard_settings())
+    }
+
+    fn put_scoreboard_settings(&self, settings: &ScoreboardSettings) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).put_scoreboard_settings(settings))
+    }
+
+    fn enqueue_job(&self, job: &QueuedJob) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).enqueue_job(job))
+    }
+
+    fn get_job(&self, job_id: &str) -> Result<QueuedJob> {
+        self.with_conn(|conn| Conn(conn).get_job(job_id))
+    }
+
+    fn list_jobs(&self) -> Result<Vec<QueuedJob>> {
+        self.with_conn(|conn| Conn(conn).list_jobs())
+    }
+
+    fn update_job(&self, job: &QueuedJob) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).update_job(job))
+    }
+
+    fn delete_job(&self, job_id: &str) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).delete_job(job_id))
+    }
+
+    fn insert_dead_job(&self, job: &DeadJob) -> Result<()> {
+        self.with_conn(|conn| Conn(conn).insert_dead_job(job))
+    }
+
+    fn list_dead_jobs(&self, agent_id: Option<&str>) -> Result<Vec<DeadJob>> {
+        self.with_conn(|conn| Conn(conn).list_dead_jobs(agent_id))
+    }
+}
+
+/// A connection, or a transaction dereferenced to one
+struct Conn<'a>(&'a Connection);
+
+fn to_json(value: &impl Serialize) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+fn from_json<T: DeserializeOwned>(data: &str) -> Result<T> {
+    Ok(serde_json::from_str(data)?)
+}
+
+/// Nanoseconds since the epoch, for columns records are sorted by
+fn ts_nanos(ts: &Timestamp) -> i64 {
+    ts.seconds
+        .saturating_mul(1_000_000_000)
+        .saturating_add(i64::from(ts.nanos))
+}
+
+/// Whether `e` is a primary key or unique constraint violation
+fn is_unique_violation(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::ConstraintViolation)
+    )
+}
+
+impl Conn<'_> {
+    /// Decodes the `data` column of every row `sql` returns
+    fn list_data<T: DeserializeOwned>(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<T>> {
+        let mut stmt = self.0.prepare_cached(sql)?;
+        let rows = stmt.query_map(params, |row| row.get::<_, String>(0))?;
+        rows.map(|data| from_json(&data?)).collect()
+    }
+
+    fn get_data(&self, sql: &str, kind: &'static str, id: &str) -> Result<String> {
+        self.0
+            .query_row(sql, [id], |row| row.get(0))
+            .optional()?
+            .ok_or(StorageError::NotFound(kind, id.to_string()))
+    }
+
+    /// Runs an insert, mapping key collisions to [`StorageError::AlreadyExists`]
+    fn insert(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+        kind: &'static str,
+        id: &str,
+    ) -> Result<()> {
+        match self.0.execute(sql, params) {
+            Ok(_) => Ok(()),
+            Err(e) if is_unique_violation(&e) => {
+                Err(StorageError::AlreadyExists(kind, id.to_string()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Runs an update, erroring when no row was touched
+    fn update(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+        kind: &'static str,
+        id: &str,
+    ) -> Result<()> {
+        match self.0.execute(sql, params)? {
+            0 => Err(StorageError::NotFound(kind, id.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Store for Conn<'_> {
+    fn insert_challenge(&self, challenge: &Challenge) -> Result<()> {
+        self.insert(
+            "INSERT INTO challenges (id, data) VALUES (?1, ?2)",
+            params![challenge.id, to_json(challenge)?],
+            "challenge",
+            &challenge.id,
+        )
+    }
+
+    fn get_challenge(&self, id: &str) -> Result<Challenge> {
+        from_json(&self.get_data("SELECT data FROM challenges WHERE id = ?1", "challenge", id)?)
+    }
+
+    fn update_challenge(&self, challenge: &Challenge) -> Result<Challenge> {
+        let stored = self.get_challenge(&challenge.id)?;
+        let next = apply_challenge_update(&stored, challenge)?;
+        self.update(
+            "UPDATE challenges SET data = ?2 WHERE id = ?1",
+            params![next.id, to_json(&next)?],
+            "challenge",
+            &next.id,
+        )?;
+        Ok(next)
+    }
+
+    fn delete_challenge(&self, id: &str) -> Result<()> {
+        self.update(
+            "DELETE FROM challenges WHERE id = ?1",
+            [id],
+            "challenge",
+            id,
+        )
+    }
+
+    fn list_challenges(&self, filter: Option<&Filter>, page: &Page) -> Result<Vec<Challenge>> {
+        let mut stmt = self.0.prepare_cached(
+            "SELECT data FROM challenges WHERE ?1 IS NULL OR id > ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map([&page.after], |row| row.get::<_, String>(0))?;
+
+        // filters run on the decoded records, fields live inside `data`, so
+        // rows are pulled until the page is full
+        let mut challenges = Vec::new();
+        for data in rows {
+            if challenges.len() >= page.limit {
+                break;
+            }
+            let challenge: Challenge = from_json(&data?)?;
+            if filter.is_none_or(|f| f.matches(&challenge)) {
+                challenges.push(challenge);
+            }
+        }
+        Ok(challenges)
+    }
+
+    fn insert_user(&self, user: &User) -> Result<()> {
+        self.insert(
+            "INSERT INTO users (id, data) VALUES (?1, ?2)",
+            params![user.id, to_json(user)?],
+            "user",
+            &user.id,
+        )
+    }
+
+    fn get_user(&self, id: &str) -> Result<User> {
+        from_json(&self.get_data("SELECT data FROM users WHERE id = ?1", "user", id)?)
+    }
+
+    fn update_user(&self, user: &User) -> Result<()> {
+        self.update(
+            "UPDATE users SET data = ?2 WHERE id = ?1",
+            params![user.id, to_json(user)?],
+            "user",
+            &user.id,
+        )
+    }
+
+    fn insert_team(&self, team: &Team) -> Result<()> {
+        self.insert(
+            "INSERT INTO teams (id, name, invite_code, data) VALUES (?1, ?2, ?3, ?4)",
+            params![team.id, team.name, team.invite_code, to_json(team)?],
+            "team",
+            &team.id,
+        )
+    }
+
+    fn get_team(&self, id: &str) -> Result<Team> {
+        from_json(&self.get_data("SELECT data FROM teams WHERE id = ?1", "team", id)?)
+    }
+
+    fn get_team_by_invite(&self, invite_code: &str) -> Result<Team> {
+        from_json(&self.get_data(
+            "SELECT data FROM teams WHERE invite_code = ?1",
+            "invite code",
+            invite_code,
+        )?)
+    }
+
+    fn update_team(&self, team: &Team) -> Result<()> {
+        self.update(
+            "UPDATE teams SET name = ?2, invite_code = ?3, data = ?4 WHERE id = ?1",
+            params![team.id, team.name, team.invite_code, to_json(team)?],
+            "team",
+            &team.id,
+        )
+    }
+
+    fn list_teams(&self, page: &Page) -> Result<Vec<Team>> {
+        self.list_data(
+            "SELECT data FROM teams WHERE ?1 IS NULL OR id > ?1 ORDER BY id LIMIT ?2",
+            params![page.after, i64::try_from(page.limit).unwrap_or(i64::MAX)],
+        )
+    }
+
+    fn insert_token(&self, token: &TokenRecord) -> Result<()> {
+        self.insert(
+            "INSERT INTO tokens (id, user_id, data) VALUES (?1, ?2, ?3)",
+            params![token.token.id, token.token.user_id, to_json(token)?],
+            "token",
+            &token.token.id,
+        )
+    }
+
+    fn get_token(&self, id: &str) -> Result<TokenRecord> {
+        from_json(&self.get_data("SELECT data FROM tokens WHERE id = ?1", "token", id)?)
+    }
+
+    fn update_token(&self, token: &TokenRecord) -> Result<()> {
+        self.update(
+            "UPDATE tokens SET data = ?2 WHERE id = ?1",
+            params![token.token.id, to_json(token)?],
+            "token",
+            &token.token.id,
+        )
+    }
+
+    fn list_tokens(&self, user_id: Option<&str>) -> Result<Vec<TokenRecord>> {
+        self.list_data(
+            "SELECT data FROM tokens WHERE ?1 IS NULL OR user_id = ?1 ORDER BY id",
+            [user_id],
+        )
+    }
+
+    fn insert_instance(&self, instance: &Instance) -> Result<()> {
+        let id = &instance.status.instance_id;
+        self.insert(
+            "INSERT INTO instances (id, challenge_id, data) VALUES (?1, ?2, ?3)",
+            params![id, instance.status.challenge_id, to_json(instance)?],
+            "instance",
+            id,
+        )
+    }
+
+    fn get_instance(&self, id: &str) -> Result<Instance> {
+        from_json(&self.get_data("SELECT data FROM instances WHERE id = ?1", "instance", id)?)
+    }
+
+    fn update_instance(&self, instance: &Instance) -> Result<()> {
+        let id = &instance.status.instance_id;
+        self.update(
+            "UPDATE instances SET challenge_id = ?2, data = ?3 WHERE id = ?1",
+            params![id, instance.status.challenge_id, to_json(instance)?],
+            "instance",
+            id,
+        )
+    }
+
+    fn list_instances(&self, challenge_id: Option<&str>) -> Result<Vec<Instance>> {
+        self.list_data(
+            "SELECT data FROM instances WHERE ?1 IS NULL OR challenge_id = ?1 ORDER BY id",
+            [challenge_id],
+        )
+    }
+
+    fn put_agent(&self, agent: &Agent) -> Result<()> {
+        self.0.execute(
+            "INSERT INTO agents (id, data) VALUES (?1, ?2)
+             ON CONFLICT (id) DO UPDATE SET data = excluded.data",
+            params![agent.agent_id, to_json(agent)?],
+        )?;
+        Ok(())
+    }
+
+    fn get_agent(&self, id: &str) -> Result<Agent> {
+        from_json(&self.get_data("SELECT data FROM agents WHERE id = ?1", "agent", id)?)
+    }
+
+    fn list_agents(&self) -> Result<Vec<Agent>> {
+        self.list_data("SELECT data FROM agents ORDER BY id", [])
+    }
+
+    fn insert_join_token(&self, token: &JoinTokenRecord) -> Result<()> {
+        self.insert(
+            "INSERT INTO join_tokens (id, data) VALUES (?1, ?2)",
+            params![token.token.id, to_json(token)?],
+            "join token",
+            &token.token.id,
+        )
+    }
+
+    fn get_join_token(&self, id: &str) -> Result<JoinTokenRecord> {
+        from_json(&self.get_data(
+            "SELECT data FROM join_tokens WHERE id = ?1",
+            "join token",
+            id,
+        )?)
+    }
+
+    fn update_join_token(&self, token: &JoinTokenRecord) -> Result<()> {
+        self.update(
+            "UPDATE join_tokens SET data = ?2 WHERE id = ?1",
+            params![token.token.id, to_json(token)?],
+            "join token",
+            &token.token.id,
+        )
+    }
+
+    fn insert_agent_credential(&self, credential: &AgentCredential) -> Result<()> {
+        self.insert(
+            "INSERT INTO agent_credentials (id, agent_id, data) VALUES (?1, ?2, ?3)",
+            params![credential.id, credential.agent_id, to_json(credential)?],
+            "agent credential",
+            &credential.id,
+        )
+    }
+
+    fn get_agent_credential(&self, id: &str) -> Result<AgentCredential> {
+        from_json(&self.get_data(
+            "SELECT data FROM agent_credentials WHERE id = ?1",
+            "agent credential",
+            id,
+        )?)
+    }
+
+    fn update_agent_credential(&self, credential: &AgentCredential) -> Result<()> {
+        self.update(
+            "UPDATE agent_credentials SET data = ?2 WHERE id = ?1",
+            params![credential.id, to_json(credential)?],
+            "agent credential",
+            &credential.id,
+        )
+    }
+
+    fn list_agent_credentials(&self, agent_id: Option<&str>) -> Result<Vec<AgentCredential>> {
+        self.list_data(
+            "SELECT data FROM agent_credentials WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY id",
+            [agent_id],
+        )
+    }
+
+    fn insert_submission(&self, submission: &Submission) -> Result<()> {
+        self.insert(
+            "INSERT INTO submissions (id, challenge_id, team_id, submitted_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                submission.id,
+                submission.challenge_id,
+                submission.team_id,
+                ts_nanos(&submission.submitted_at),
+                to_json(submission)?
+            ],
+            "submission",
+            &submission.id,
+        )
+    }
+
+    fn list_submissions(&self, challenge_id: Option<&str>) -> Result<Vec<Submission>> {
+        self.list_data(
+            "SELECT data FROM submissions WHERE ?1 IS NULL OR challenge_id = ?1
+             ORDER BY submitted_at",
+            [challenge_id],
+        )
+    }
+
+    fn insert_solve(&self, solve: &Solve) -> Result<()> {
+        self.insert(
+            "INSERT INTO solves (challenge_id, team_id, solved_at, data) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                solve.challenge_id,
+                solve.team_id,
+                ts_nanos(&solve.solved_at),
+                to_json(solve)?
+            ],
+            "solve",
+            &format!("{}/{}", solve.challenge_id, solve.team_id),
+        )
+    }
+
+    fn get_solve(&self, challenge_id: &str, team_id: &str) -> Result<Solve> {
+        let data = self
+            .0
+            .query_row(
+                "SELECT data FROM solves WHERE challenge_id = ?1 AND team_id = ?2",
+                [challenge_id, team_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .ok_or(StorageError::NotFound(
+                "solve",
+                format!("{challenge_id}/{team_id}"),
+            ))?;
+        from_json(&data)
+    }
+
+    fn delete_solve(&self, challenge_id: &str, team_id: &str) -> Result<()> {
+        self.update(
+            "DELETE FROM solves WHERE challenge_id = ?1 AND team_id = ?2",
+            [challenge_id, team_id],
+            "solve",
+            &format!("{challenge_id}/{team_id}"),
+        )
+    }
+
+    fn list_solves(&self, challenge_id: Option<&str>) -> Result<Vec<Solve>> {
+        self.list_data(
+            "SELECT data FROM solves WHERE ?1 IS NULL OR challenge_id = ?1 ORDER BY solved_at",
+            [challenge_id],
+        )
+    }
+
+    fn get_scoreboard_settings(&self) -> Result<ScoreboardSettings> {
+        match self.get_data(
+            "SELECT data FROM settings WHERE name = ?1",
+            "settings",
+            SCOREBOARD_SETTINGS,
+        ) {
+            Ok(data) => from_json(&data),
+            Err(StorageError::NotFound(..)) => Ok(ScoreboardSettings::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn put_scoreboard_settings(&self, settings: &ScoreboardSettings) -> Result<()> {
+        self.0.execute(
+            "INSERT INTO settings (name, data) VALUES (?1, ?2)
+             ON CONFLICT (name) DO UPDATE SET data = excluded.data",
+            params![SCOREBOARD_SETTINGS, to_json(settings)?],
+        )?;
+        Ok(())
+    }
+
+    fn enqueue_job(&self, job: &QueuedJob) -> Result<()> {
+        self.insert(
+            "INSERT INTO jobs (job_id, agent_id, data) VALUES (?1, ?2, ?3)",
+            params![job.job.job_id, job.agent_id, to_json(job)?],
+            "job",
+            &job.job.job_id,
+        )
+    }
+
+    fn get_job(&self, job_id: &str) -> Result<QueuedJob> {
+        from_json(&self.get_data("SELECT data FROM jobs WHERE job_id = ?1", "job", job_id)?)
+    }
+
+    fn list_jobs(&self) -> Result<Vec<QueuedJob>> {
+        self.list_data("SELECT data FROM jobs ORDER BY seq", [])
+    }
+
+    fn update_job(&self, job: &QueuedJob) -> Result<()> {
+        self.update(
+            "UPDATE jobs SET agent_id = ?2, data = ?3 WHERE job_id = ?1",
+            params![job.job.job_id, job.agent_id, to_json(job)?],
+            "job",
+            &job.job.job_id,
+        )
+    }
+
+    fn delete_job(&self, job_id: &str) -> Result<()> {
+        self.update(
+            "DELETE FROM jobs WHERE job_id = ?1",
+            [job_id],
+            "job",
+            job_id,
+        )
+    }
+
+    fn insert_dead_job(&self, job: &DeadJob) -> Result<()> {
+        let id = job
+            .job
+            .as_ref()
+            .map(|j| j.job_id.as_str())
+            .unwrap_or_default();
+        self.insert(
+            "INSERT INTO dead_jobs (job_id, agent_id, died_at, data) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                job.agent_id,
+                job.died_at.as_ref().map(ts_nanos).unwrap_or_default(),
+                to_json(job)?
+            ],
+            "dead job",
+            id,
+        )
+    }
+
+    fn list_dead_jobs(&self, agent_id: Option<&str>) -> Result<Vec<DeadJob>> {
+        self.list_data(
+            "SELECT data FROM dead_jobs WHERE ?1 IS NULL OR agent_id = ?1 ORDER BY died_at",
+            [agent_id],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_applied_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ctfjx.db");
+
+        let store = SqliteStorage::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+        drop(store);
+
+        // reopening an up to date database is a no-op
+        let store = SqliteStorage::open(&path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len());
+    }
+
+    #[test]
+    fn queued_jobs_keep_their_agent() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        for (i, sql) in MIGRATIONS[..6].iter().enumerate() {
+            apply_migration(&mut conn, i + 1, sql).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO jobs (job_id, agent_id, data) VALUES ('j1', 'a1', ?1)",
+            [r#"{"job_id":"j1","type":"test","payload_json":"{}","created_at":null}"#],
+        )
+        .unwrap();
+
+        let store = SqliteStorage::with_connection(conn).unwrap();
+        let jobs = store.list_jobs().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].job.job_id, "j1");
+        assert_eq!(jobs[0].agent_id.as_deref(), Some("a1"));
+        assert_eq!(jobs[0].leased_to, None);
+    }
+}
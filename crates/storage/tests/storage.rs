@@ -0,0 +1,607 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use ctfjx_proto::grpc::{
+    AgentCredential, ApiToken, Challenge, DeadJob, InstanceStatus, Job, JoinToken, Team, User,
+};
+use ctfjx_storage::{
+    Agent, Filter, Instance, JoinTokenRecord, Liveness, MemoryStorage, Page, QueuedJob,
+    ScoreboardSettings, Solve, SqliteStorage, Storage, StorageError, Store, Submission,
+    TokenRecord,
+};
+use prost_wkt_types::Timestamp;
+
+fn backends() -> Vec<(&'static str, Arc<dyn Storage>)> {
+    vec![
+        ("memory", Arc::new(MemoryStorage::default())),
+        ("sqlite", Arc::new(SqliteStorage::open_in_memory().unwrap())),
+    ]
+}
+
+fn challenge(id: &str) -> Challenge {
+    Challenge {
+        id: id.to_string(),
+        name: format!("challenge {id}"),
+        tags: vec!["web".to_string()],
+        created_at: Some(std::time::SystemTime::now().into()),
+        updated_at: Some(std::time::SystemTime::now().into()),
+        ..Default::default()
+    }
+}
+
+fn queued(job_id: &str, agent_id: Option<&str>) -> QueuedJob {
+    QueuedJob {
+        job: Job {
+            job_id: job_id.to_string(),
+            r#type: "test".to_string(),
+            ..Default::default()
+        },
+        agent_id: agent_id.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn challenge_crud() {
+    for (name, store) in backends() {
+        store.insert_challenge(&challenge("b")).unwrap();
+        store.insert_challenge(&challenge("a")).unwrap();
+        assert!(
+            matches!(
+                store.insert_challenge(&challenge("a")),
+                Err(StorageError::AlreadyExists(..))
+            ),
+            "{name}"
+        );
+
+        let ids: Vec<_> = store
+            .list_challenges(None, &Page::ALL)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, ["a", "b"], "{name}");
+
+        store.delete_challenge("a").unwrap();
+        assert!(
+            matches!(store.get_challenge("a"), Err(StorageError::NotFound(..))),
+            "{name}"
+        );
+        assert!(
+            matches!(store.delete_challenge("a"), Err(StorageError::NotFound(..))),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn challenges_are_filtered() {
+    for (name, store) in backends() {
+        let mut web = challenge("a");
+        web.difficulty = "hard".to_string();
+        store.insert_challenge(&web).unwrap();
+        let mut pwn = challenge("b");
+        pwn.tags = vec!["pwn".to_string()];
+        store.insert_challenge(&pwn).unwrap();
+
+        let filter = Filter::parse(r#"difficulty = "hard" AND tags:"web""#)
+            .unwrap()
+            .unwrap();
+        let ids: Vec<_> = store
+            .list_challenges(Some(&filter), &Page::ALL)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, ["a"], "{name}");
+    }
+}
+
+#[test]
+fn challenges_are_paged_by_id() {
+    for (name, store) in backends() {
+        for id in ["d", "a", "c", "b", "e"] {
+            store.insert_challenge(&challenge(id)).unwrap();
+        }
+        let filter = Filter::parse("id != c").unwrap();
+
+        let mut seen = Vec::new();
+        let mut page = Page {
+            after: None,
+            limit: 2,
+        };
+        loop {
+            let batch = store.list_challenges(filter.as_ref(), &page).unwrap();
+            let Some(last) = batch.last() else {
+                break;
+            };
+            page.after = Some(last.id.clone());
+            seen.extend(batch.into_iter().map(|c| c.id));
+        }
+        assert_eq!(seen, ["a", "b", "d", "e"], "{name}");
+    }
+}
+
+#[test]
+fn challenge_updates_are_optimistic() {
+    for (name, store) in backends() {
+        store.insert_challenge(&challenge("a")).unwrap();
+        let read = store.get_challenge("a").unwrap();
+
+        let mut first = read.clone();
+        first.description = "first".to_string();
+        let stored = store.update_challenge(&first).unwrap();
+        assert_eq!(stored.created_at, read.created_at, "{name}");
+        assert_ne!(stored.updated_at, read.updated_at, "{name}");
+
+        // a writer still holding the old version loses
+        let mut second = read.clone();
+        second.description = "second".to_string();
+        assert!(
+            matches!(
+                store.update_challenge(&second),
+                Err(StorageError::Conflict(..))
+            ),
+            "{name}"
+        );
+
+        // leaving `updated_at` out skips the check
+        second.updated_at = None;
+        let stored = store.update_challenge(&second).unwrap();
+        assert_eq!(store.get_challenge("a").unwrap(), stored, "{name}");
+    }
+}
+
+#[test]
+fn failed_transactions_roll_back() {
+    for (name, store) in backends() {
+        let res: Result<(), StorageError> = store.atomically(|tx| {
+            tx.insert_challenge(&challenge("a"))?;
+            tx.insert_challenge(&challenge("a"))
+        });
+        assert!(
+            matches!(res, Err(StorageError::AlreadyExists(..))),
+            "{name}"
+        );
+        assert!(
+            store.list_challenges(None, &Page::ALL).unwrap().is_empty(),
+            "{name}"
+        );
+
+        let n = store
+            .atomically(|tx| {
+                tx.insert_challenge(&challenge("a"))?;
+                tx.insert_challenge(&challenge("b"))?;
+                Ok::<_, StorageError>(tx.list_challenges(None, &Page::ALL)?.len())
+            })
+            .unwrap();
+        assert_eq!(n, 2, "{name}");
+        assert_eq!(
+            store.list_challenges(None, &Page::ALL).unwrap().len(),
+            2,
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn instances_and_agents() {
+    for (name, store) in backends() {
+        let mut instance = Instance {
+            status: InstanceStatus {
+                instance_id: "i1".to_string(),
+                challenge_id: "a".to_string(),
+                ..Default::default()
+            },
+            team_id: "team".to_string(),
+            overrides: HashMap::from([("PORT".to_string(), "1337".to_string())]),
+            started_at: None,
+            stopped_at: None,
+            expiry_warned: None,
+        };
+        store.insert_instance(&instance).unwrap();
+        instance.status.message = "running".to_string();
+        instance.expiry_warned = Some(SystemTime::UNIX_EPOCH.into());
+        store.update_instance(&instance).unwrap();
+        assert_eq!(store.get_instance("i1").unwrap(), instance, "{name}");
+        assert_eq!(
+            store.list_instances(Some("a")).unwrap(),
+            [instance.clone()],
+            "{name}"
+        );
+        assert!(
+            store.list_instances(Some("b")).unwrap().is_empty(),
+            "{name}"
+        );
+
+        let mut agent = Agent {
+            agent_id: "agent".to_string(),
+            version: "0.1.0".to_string(),
+            ..Default::default()
+        };
+        store.put_agent(&agent).unwrap();
+        agent.version = "0.2.0".to_string();
+        agent.stats = HashMap::from([("load".to_string(), "0.5".to_string())]);
+        agent.liveness = Liveness::Suspect;
+        store.put_agent(&agent).unwrap();
+        assert_eq!(store.get_agent("agent").unwrap(), agent, "{name}");
+
+        let other = Agent {
+            agent_id: "a-first".to_string(),
+            ..Default::default()
+        };
+        store.put_agent(&other).unwrap();
+        assert_eq!(store.list_agents().unwrap(), [other, agent], "{name}");
+    }
+}
+
+fn team(id: &str, name: &str, invite_code: &str) -> Team {
+    Team {
+        id: id.to_string(),
+        name: name.to_string(),
+        invite_code: invite_code.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn users_and_teams() {
+    for (name, store) in backends() {
+        let mut user = User {
+            id: "u1".to_string(),
+            name: "alice".to_string(),
+            ..Default::default()
+        };
+        store.insert_user(&user).unwrap();
+        user.team_id = "t1".to_string();
+        store.update_user(&user).unwrap();
+        assert_eq!(store.get_user("u1").unwrap(), user, "{name}");
+
+        store.insert_team(&team("t2", "bravo", "code-2")).unwrap();
+        let mut alpha = team("t1", "alpha", "code-1");
+        store.insert_team(&alpha).unwrap();
+        for dup in [
+            team("t1", "other", "code-3"),
+            team("t3", "alpha", "code-3"),
+            team("t3", "other", "code-1"),
+        ] {
+            assert!(
+                matches!(
+                    store.insert_team(&dup),
+                    Err(StorageError::AlreadyExists(..))
+                ),
+                "{name}: {dup:?}"
+            );
+        }
+
+        alpha.invite_code = "code-4".to_string();
+        alpha.member_ids = vec!["u1".to_string()];
+        store.update_team(&alpha).unwrap();
+        assert_eq!(store.get_team_by_invite("code-4").unwrap(), alpha, "{name}");
+        assert!(
+            matches!(
+                store.get_team_by_invite("code-1"),
+                Err(StorageError::NotFound(..))
+            ),
+            "{name}"
+        );
+
+        let page = Page {
+            after: Some("t1".to_string()),
+            limit: 10,
+        };
+        let ids: Vec<_> = store
+            .list_teams(&page)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(ids, ["t2"], "{name}");
+    }
+}
+
+#[test]
+fn tokens() {
+    for (name, store) in backends() {
+        let record = |id: &str, user_id: &str| TokenRecord {
+            token: ApiToken {
+                id: id.to_string(),
+                user_id: user_id.to_string(),
+                scopes: vec!["read".to_string()],
+                ..Default::default()
+            },
+            secret_hash: format!("hash of {id}"),
+        };
+        store.insert_token(&record("b", "u1")).unwrap();
+        store.insert_token(&record("a", "u1")).unwrap();
+        store.insert_token(&record("c", "u2")).unwrap();
+        assert!(
+            matches!(
+                store.insert_token(&record("a", "u2")),
+                Err(StorageError::AlreadyExists(..))
+            ),
+            "{name}"
+        );
+
+        let mut revoked = store.get_token("a").unwrap();
+        revoked.token.revoked_at = Some(Timestamp::default());
+        store.update_token(&revoked).unwrap();
+
+        let tokens = store.list_tokens(Some("u1")).unwrap();
+        assert_eq!(tokens, [revoked, record("b", "u1")], "{name}");
+        assert_eq!(store.list_tokens(None).unwrap().len(), 3, "{name}");
+    }
+}
+
+#[test]
+fn join_tokens_and_agent_credentials() {
+    for (name, store) in backends() {
+        let mut join = JoinTokenRecord {
+            token: JoinToken {
+                id: "j1".to_string(),
+                agent_id: "runner".to_string(),
+                ..Default::default()
+            },
+            secret_hash: "hash of j1".to_string(),
+        };
+        store.insert_join_token(&join).unwrap();
+        assert!(
+            matches!(
+                store.insert_join_token(&join),
+                Err(StorageError::AlreadyExists(..))
+            ),
+            "{name}"
+        );
+        join.token.used_at = Some(Timestamp::default());
+        store.update_join_token(&join).unwrap();
+        assert_eq!(store.get_join_token("j1").unwrap(), join, "{name}");
+
+        let credential = |id: &str, agent_id: &str| AgentCredential {
+            id: id.to_string(),
+            agent_id: agent_id.to_string(),
+            public_key: vec![7; 32],
+            ..Default::default()
+        };
+        store
+            .insert_agent_credential(&credential("b", "runner"))
+            .unwrap();
+        store
+            .insert_agent_credential(&credential("a", "runner"))
+            .unwrap();
+        store
+            .insert_agent_credential(&credential("c", "other"))
+            .unwrap();
+
+        let mut revoked = store.get_agent_credential("a").unwrap();
+        revoked.revoked_at = Some(Timestamp::default());
+        store.update_agent_credential(&revoked).unwrap();
+        assert_eq!(
+            store.list_agent_credentials(Some("runner")).unwrap(),
+            [revoked, credential("b", "runner")],
+            "{name}"
+        );
+        assert_eq!(
+            store.list_agent_credentials(None).unwrap().len(),
+            3,
+            "{name}"
+        );
+        assert!(
+            matches!(
+                store.update_agent_credential(&credential("d", "runner")),
+                Err(StorageError::NotFound(..))
+            ),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn submissions_and_solves() {
+    let at = |secs| Timestamp {
+        seconds: secs,
+        nanos: 0,
+    };
+    for (name, store) in backends() {
+        for (i, (challenge_id, team_id, secs)) in
+            [("c1", "t1", 30), ("c1", "t2", 10), ("c2", "t1", 20)]
+                .into_iter()
+                .enumerate()
+        {
+            let submission = Submission {
+                id: format!("s{i}"),
+                challenge_id: challenge_id.to_string(),
+                team_id: team_id.to_string(),
+                flag: "flag{x}".to_string(),
+                correct: true,
+                submitted_at: at(secs),
+                shared_from: None,
+            };
+            store.insert_submission(&submission).unwrap();
+            store
+                .insert_solve(&Solve {
+                    challenge_id: challenge_id.to_string(),
+                    team_id: team_id.to_string(),
+                    submission_id: submission.id,
+                    solved_at: at(secs),
+                })
+                .unwrap();
+        }
+
+        let dup = store.get_solve("c1", "t1").unwrap();
+        assert!(
+            matches!(
+                store.insert_solve(&dup),
+                Err(StorageError::AlreadyExists(..))
+            ),
+            "{name}"
+        );
+        assert!(
+            matches!(store.get_solve("c2", "t2"), Err(StorageError::NotFound(..))),
+            "{name}"
+        );
+
+        let teams = |solves: Vec<Solve>| {
+            solves
+                .into_iter()
+                .map(|s| format!("{}/{}", s.challenge_id, s.team_id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            teams(store.list_solves(None).unwrap()),
+            ["c1/t2", "c2/t1", "c1/t1"],
+            "{name}"
+        );
+        assert_eq!(
+            teams(store.list_solves(Some("c1")).unwrap()),
+            ["c1/t2", "c1/t1"],
+            "{name}"
+        );
+        store.delete_solve("c1", "t2").unwrap();
+        assert_eq!(
+            teams(store.list_solves(Some("c1")).unwrap()),
+            ["c1/t1"],
+            "{name}"
+        );
+        assert!(
+            matches!(
+                store.delete_solve("c1", "t2"),
+                Err(StorageError::NotFound(..))
+            ),
+            "{name}"
+        );
+
+        let ids: Vec<_> = store
+            .list_submissions(Some("c1"))
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, ["s1", "s0"], "{name}");
+    }
+}
+
+#[test]
+fn scoreboard_settings() {
+    for (name, store) in backends() {
+        assert_eq!(
+            store.get_scoreboard_settings().unwrap(),
+            ScoreboardSettings::default(),
+            "{name}"
+        );
+        let settings = ScoreboardSettings {
+            freeze_at: Some(Timestamp {
+                seconds: 1,
+                nanos: 0,
+            }),
+        };
+        store.put_scoreboard_settings(&settings).unwrap();
+        store.put_scoreboard_settings(&settings).unwrap();
+        assert_eq!(store.get_scoreboard_settings().unwrap(), settings, "{name}");
+    }
+}
+
+#[test]
+fn jobs_keep_their_order() {
+    for (name, store) in backends() {
+        store.enqueue_job(&queued("1", None)).unwrap();
+        store.enqueue_job(&queued("2", Some("other"))).unwrap();
+        store.enqueue_job(&queued("3", Some("me"))).unwrap();
+        assert!(
+            matches!(
+                store.enqueue_job(&queued("3", None)),
+                Err(StorageError::AlreadyExists(..))
+            ),
+            "{name}"
+        );
+
+        let mut leased = queued("1", None);
+        leased.leased_to = Some("me".to_string());
+        leased.job.attempt = 1;
+        store.update_job(&leased).unwrap();
+        assert_eq!(store.get_job("1").unwrap(), leased, "{name}");
+        store.delete_job("2").unwrap();
+        assert!(
+            matches!(store.delete_job("2"), Err(StorageError::NotFound(..))),
+            "{name}"
+        );
+        assert_eq!(
+            store.list_jobs().unwrap(),
+            [leased, queued("3", Some("me"))],
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn dead_jobs() {
+    let dead = |job_id: &str, agent_id: &str, secs| DeadJob {
+        job: Some(queued(job_id, None).job),
+        agent_id: agent_id.to_string(),
+        attempts: 3,
+        last_error: "boom".to_string(),
+        died_at: Some(Timestamp {
+            seconds: secs,
+            nanos: 0,
+        }),
+    };
+    for (name, store) in backends() {
+        store.insert_dead_job(&dead("b", "me", 2)).unwrap();
+        store.insert_dead_job(&dead("a", "", 1)).unwrap();
+        assert!(
+            matches!(
+                store.insert_dead_job(&dead("a", "", 3)),
+                Err(StorageError::AlreadyExists(..))
+            ),
+            "{name}"
+        );
+        assert_eq!(
+            store.list_dead_jobs(None).unwrap(),
+            [dead("a", "", 1), dead("b", "me", 2)],
+            "{name}"
+        );
+        assert_eq!(
+            store.list_dead_jobs(Some("me")).unwrap(),
+            [dead("b", "me", 2)],
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn sqlite_survives_restarts() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ctfjx.db");
+
+    let store = SqliteStorage::open(&path).unwrap();
+    store.insert_challenge(&challenge("a")).unwrap();
+    store.enqueue_job(&queued("1", None)).unwrap();
+    drop(store);
+
+    let store = SqliteStorage::open(&path).unwrap();
+    assert_eq!(store.get_challenge("a").unwrap().name, "challenge a");
+    assert_eq!(store.list_jobs().unwrap(), [queued("1", None)]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn slow_sqlite_calls_do_not_stall_other_tasks() {
+    let store: Arc<dyn Storage> = Arc::new(SqliteStorage::open_in_memory().unwrap());
+    let started = std::time::Instant::now();
+
+    let slow = tokio::spawn(async move {
+        store
+            .transaction(&mut |_| {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok(())
+            })
+            .unwrap();
+    });
+    // give the only worker time to enter the transaction
+    std::thread::sleep(Duration::from_millis(50));
+    let ticked = tokio::spawn(async move { started.elapsed() }).await.unwrap();
+
+    assert!(ticked < Duration::from_millis(400), "{ticked:?}");
+    slow.await.unwrap();
+}